@@ -18,19 +18,27 @@
 //!   through `Phase::Compaction`.
 //!
 //! Pure decisions live in [`crate::core::transitions`]; all async I/O
-//! lives here.
+//! lives here — including writes to the agent's
+//! [`History`](tau_history::History): whenever a turn settles (the
+//! next one is about to start, the queues are being drained, or the
+//! prompt is done) the messages it added are committed as one commit,
+//! stamped with the time, the agent id, and the model and usage of the
+//! assistant messages in it.
 
 mod approval;
 mod drain;
 mod executing;
+mod rewind;
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
 
 use futures::StreamExt;
 use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
-use tau_ai::Message;
+use tau_ai::{Message, Usage};
+use tau_history::{CommitKind, CommitMeta, HistoryError};
 use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
@@ -38,9 +46,9 @@ use serde_json::Value as JsonValue;
 
 use crate::core::approval::{ApprovalDecision, ToolRisk};
 use crate::core::command::{Command, PromptResult};
-use crate::core::compaction::estimate_total_tokens;
+use crate::core::compaction::{CompactionOutcome, estimate_total_tokens};
 use crate::core::interaction::InteractionResponse;
-use crate::core::state::{State, ToolCall};
+use crate::core::state::{HistoryResync, State, ToolCall};
 use crate::core::stream::{StreamOutcome, StreamReducer};
 use crate::core::tool::{ToolResult, send_event};
 use crate::core::transitions as t;
@@ -197,10 +205,10 @@ pub(crate) async fn run_actor(
                 tokio::select! {
                     biased;
                     Some(cmd) = urgent_rx.recv() => {
-                        handle_idle_command(&mut state, cmd, &mut prompt_reply, &mut turn_number, &mut prompt_cancel)
+                        handle_idle_command(&mut state, cmd, &mut prompt_reply, &mut turn_number, &mut prompt_cancel).await
                     }
                     Some(cmd) = normal_rx.recv() => {
-                        handle_idle_command(&mut state, cmd, &mut prompt_reply, &mut turn_number, &mut prompt_cancel)
+                        handle_idle_command(&mut state, cmd, &mut prompt_reply, &mut turn_number, &mut prompt_cancel).await
                     }
                     else => break,
                 }
@@ -223,9 +231,113 @@ pub(crate) async fn run_actor(
                 emit_end_and_idle(&mut state, result, &mut prompt_reply, &mut turn_number)
             }
         };
+        let settled = matches!(
+            phase,
+            Phase::Idle
+                | Phase::Done(_)
+                | Phase::Turn(Turn {
+                    sub: TurnSub::Prepare { .. } | TurnSub::Drain(_),
+                    ..
+                })
+        );
+        if settled {
+            sync_history(&mut state).await;
+        }
     }
 }
 
+/// Commit the messages added since the last commit, first recording
+/// any compaction history missed. A failed append is logged and
+/// retried at the next settle point; the working copy is unaffected.
+async fn sync_history(state: &mut State) {
+    if let Some(resync) = state.conv.history_resync {
+        if let Err(e) = resync_history(state, resync).await {
+            tracing::warn!("could not record compaction in history: {e}");
+            // History would get the turns without their summary.
+            if resync == HistoryResync::Summarized {
+                return;
+            }
+        }
+    }
+    let messages = &state.conv.conversation.messages;
+    if state.conv.history_len >= messages.len() {
+        return;
+    }
+    let len = messages.len();
+    let batch = messages[state.conv.history_len..].to_vec();
+    let meta = turn_meta(state, &batch);
+    match state.frame.history.append_with(batch, meta).await {
+        Ok(()) => t::apply_history_synced(&mut state.conv, len),
+        Err(e) => tracing::warn!("could not commit turn to history: {e}"),
+    }
+}
+
+/// Bring history in line with a compaction it missed: prune its
+/// messages again, or cut all of them down to the working copy's
+/// summary.
+async fn resync_history(state: &mut State, resync: HistoryResync) -> Result<(), HistoryError> {
+    let history = Arc::clone(&state.frame.history);
+    let meta = commit_meta(state, CommitKind::Compaction);
+    let messages = &state.conv.conversation.messages;
+    match resync {
+        HistoryResync::Pruned => {
+            let synced = state.conv.history_len.min(messages.len());
+            if synced > 0 {
+                history.prune(messages[..synced].to_vec(), meta).await?;
+            }
+        }
+        HistoryResync::Summarized => {
+            let Some(summary_message) = messages.first().cloned() else {
+                t::apply_history_resynced(&mut state.conv);
+                return Ok(());
+            };
+            let summary_text = state.conv.conversation.previous_summary.clone();
+            let end = history.messages().await?.len();
+            history
+                .compact_prefix_with(end, summary_message, summary_text.unwrap_or_default(), meta)
+                .await?;
+            t::apply_history_synced(&mut state.conv, 1);
+        }
+    }
+    t::apply_history_resynced(&mut state.conv);
+    Ok(())
+}
+
+/// Metadata every commit the actor makes carries: why, when, and
+/// which agent.
+fn commit_meta(state: &State, kind: CommitKind) -> CommitMeta {
+    let meta = CommitMeta::new()
+        .with_kind(kind)
+        .with_timestamp(chrono::Utc::now().timestamp_millis());
+    match state.shared.agent_id.get() {
+        Some(id) => meta.with_author(id.clone()),
+        None => meta,
+    }
+}
+
+fn turn_meta(state: &State, batch: &[Message]) -> CommitMeta {
+    let kind = if state.conv.reprompt {
+        CommitKind::Reprompt
+    } else {
+        CommitKind::Turn
+    };
+    let mut meta = commit_meta(state, kind);
+    let mut usage: Option<Usage> = None;
+    for msg in batch {
+        let Message::Assistant { metadata, .. } = msg else {
+            continue;
+        };
+        if let Some(model) = &metadata.model {
+            meta.model = Some(model.clone());
+        }
+        usage
+            .get_or_insert_with(Usage::default)
+            .accumulate(&metadata.usage);
+    }
+    meta.usage = usage.map(Into::into);
+    meta
+}
+
 fn emit_end_and_idle(
     state: &mut State,
     result: Result<(), crate::types::error::Error>,
@@ -561,11 +673,14 @@ fn finish_cancelled_batch(
 
 // ─── Compaction ─────────────────────────────────────────────────────
 
-/// Run one compaction pass: emit `CompactionStart`, summarize via
-/// [`compaction::compact`](crate::core::compaction::compact), and on
-/// success emit `CompactionEnd` and commit the result to the
-/// conversation. On failure nothing is committed and no event beyond
-/// `CompactionStart` is emitted — what the failure *means* is the
+/// Run one compaction pass: emit `CompactionStart`, prune and/or
+/// summarize via [`compaction::compact`](crate::core::compaction::compact),
+/// and on success emit `CompactionEnd` and commit the result to the
+/// conversation. History failing to record it doesn't fail the pass:
+/// the working copy is compacted anyway and the next
+/// [`sync_history`] tries again. On failure nothing is committed and
+/// no event beyond `CompactionStart` is emitted — what the failure
+/// *means* is the
 /// caller's decision: fatal for forced compaction (`step_compaction`,
 /// overflow/manual), logged-and-ignored for the proactive threshold
 /// pass (`run_proactive_compaction`).
@@ -579,31 +694,95 @@ async fn run_compaction_pass(
         &state.frame.event_tx,
         AgentEvent::CompactionStart { reason },
     );
-    let cr = crate::core::compaction::compact(
+    let outcome = crate::core::compaction::compact(
         &state.conv.conversation.messages,
         &state.frame.config.compaction,
         &state.frame.config,
         &state.frame.transport,
+        reason,
         state.conv.conversation.previous_summary.as_deref(),
         custom_instructions,
         cancel,
     )
     .await?;
-    let tokens_after = crate::core::compaction::estimate_total_tokens(
-        &state.conv.conversation.messages[cr.first_kept_index..],
-    );
-    send_event(
-        &state.frame.event_tx,
-        AgentEvent::CompactionEnd {
-            tokens_before: cr.tokens_before,
-            tokens_after,
-        },
-    );
-    crate::core::compaction::apply_compaction_result(
-        &mut state.conv.conversation.messages,
-        &mut state.conv.conversation.previous_summary,
-        cr,
-    );
+    match outcome {
+        CompactionOutcome::Pruned {
+            messages,
+            pruned,
+            tokens_before,
+        } => {
+            let tokens_after = estimate_total_tokens(&messages);
+            tracing::debug!("Compaction pruned {pruned} tool outputs; summary skipped");
+            send_event(
+                &state.frame.event_tx,
+                AgentEvent::CompactionEnd {
+                    tokens_before,
+                    tokens_after,
+                },
+            );
+            // Same message count; what isn't in history yet goes in,
+            // pruned, with the next sync.
+            let synced = state.conv.history_len.min(messages.len());
+            if synced > 0 {
+                let meta = commit_meta(state, CommitKind::Compaction);
+                let pruned = messages[..synced].to_vec();
+                if let Err(e) = state.frame.history.prune(pruned, meta).await {
+                    tracing::warn!("could not record compaction in history: {e}");
+                    t::apply_history_diverged(&mut state.conv, HistoryResync::Pruned);
+                }
+            }
+            state.conv.conversation.messages = messages;
+        }
+        CompactionOutcome::Summarized(cr) => {
+            let end = cr.first_kept_index;
+            let tokens_before = cr.tokens_before;
+            let meta = commit_meta(state, CommitKind::Compaction).with_usage(&cr.usage);
+            let tokens_after = estimate_total_tokens(&state.conv.conversation.messages[end..]);
+            let mut messages = state.conv.conversation.messages.clone();
+            let mut previous_summary = None;
+            crate::core::compaction::apply_compaction_result(
+                &mut messages,
+                &mut previous_summary,
+                cr,
+            );
+            // The summarized prefix has to be in history before it can
+            // be cut; usually it already is.
+            if end > state.conv.history_len || state.conv.history_resync.is_some() {
+                sync_history(state).await;
+            }
+            let recorded = if state.conv.history_resync == Some(HistoryResync::Summarized) {
+                false
+            } else {
+                let summary_text = previous_summary.clone().unwrap_or_default();
+                let history = &state.frame.history;
+                match history
+                    .compact_prefix_with(end, messages[0].clone(), summary_text, meta)
+                    .await
+                {
+                    Ok(()) => true,
+                    Err(e) => {
+                        tracing::warn!("could not record compaction in history: {e}");
+                        false
+                    }
+                }
+            };
+            send_event(
+                &state.frame.event_tx,
+                AgentEvent::CompactionEnd {
+                    tokens_before,
+                    tokens_after,
+                },
+            );
+            state.conv.conversation.messages = messages;
+            state.conv.conversation.previous_summary = previous_summary;
+            if recorded {
+                let history_len = state.conv.history_len.saturating_sub(end) + 1;
+                t::apply_history_synced(&mut state.conv, history_len);
+            } else {
+                t::apply_history_diverged(&mut state.conv, HistoryResync::Summarized);
+            }
+        }
+    }
     Ok(())
 }
 
@@ -681,7 +860,7 @@ async fn run_proactive_compaction(state: &mut State, cancel: &CancellationToken)
 
 // ─── Command handling ────────────────────────────────────────────────
 
-fn handle_idle_command(
+async fn handle_idle_command(
     state: &mut State,
     cmd: Command,
     prompt_reply: &mut Option<oneshot::Sender<PromptResult>>,
@@ -689,7 +868,11 @@ fn handle_idle_command(
     prompt_cancel: &mut CancellationToken,
 ) -> Phase {
     match cmd {
-        Command::Prompt { content, reply } => {
+        Command::Prompt {
+            content,
+            reprompt,
+            reply,
+        } => {
             *prompt_reply = Some(reply);
             state.shared.prompt_in_flight.store(true, Ordering::Release);
             // Clear any stale graceful-interrupt request so it does
@@ -709,6 +892,7 @@ fn handle_idle_command(
             }
             *turn_number = 0;
             t::apply_clear_error(&mut state.conv);
+            t::apply_prompt_source(&mut state.conv, reprompt);
             send_event(&state.frame.event_tx, AgentEvent::AgentStart);
 
             let user_message = Message::User {
@@ -729,6 +913,18 @@ fn handle_idle_command(
             custom_instructions,
             reply,
         }),
+        Command::UserTurns(reply) => {
+            let _ = reply.send(rewind::user_turns(state).await);
+            Phase::Idle
+        }
+        Command::Rewind { to, fork, reply } => {
+            let _ = reply.send(rewind::rewind(state, to, fork).await);
+            Phase::Idle
+        }
+        Command::SwitchHistory { history, reply } => {
+            let _ = reply.send(rewind::switch_history(state, history).await);
+            Phase::Idle
+        }
         other => {
             handle_busy_command(state, other);
             Phase::Idle
@@ -805,6 +1001,13 @@ fn handle_busy_command(state: &mut State, cmd: Command) {
                 result: Err(crate::types::error::Error::Busy),
             });
         }
+        // The conversation can't move under an in-flight prompt.
+        Command::UserTurns(reply) => {
+            let _ = reply.send(Err(crate::types::error::Error::Busy));
+        }
+        Command::Rewind { reply, .. } | Command::SwitchHistory { reply, .. } => {
+            let _ = reply.send(Err(crate::types::error::Error::Busy));
+        }
     }
 }
 
@@ -83,6 +83,11 @@ pub(super) fn classify_and_enter_approval(
     let mut pre_results: HashMap<usize, (String, String, ToolResult)> = HashMap::new();
     let mut dispatch: HashSet<usize> = HashSet::new();
     let pending_gates: FuturesUnordered<GateFuture> = FuturesUnordered::new();
+    let cwd = state
+        .conv
+        .cwd
+        .clone()
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
     for (idx, tc) in tool_calls.iter().enumerate() {
         let tool = state
@@ -99,11 +104,15 @@ pub(super) fn classify_and_enter_approval(
             .as_ref()
             .map(|t| t.activity_description(&tc.args))
             .unwrap_or_else(|| format!("Running {}", tc.name));
+        let risk_reasons = tool
+            .as_ref()
+            .map(|t| t.risk_reasons(&tc.args))
+            .unwrap_or_default();
 
         match state
             .frame
             .approval_policy
-            .classify(&tc.name, &tc.args, risk)
+            .classify_in(&tc.name, &tc.args, risk, &cwd)
         {
             ApprovalDecision::Auto => {
                 emit_resolved(
@@ -134,6 +143,7 @@ pub(super) fn classify_and_enter_approval(
                             "arguments": tc.args.clone(),
                             "activity": activity,
                             "risk": risk,
+                            "risk_reasons": risk_reasons,
                         }),
                     },
                     response_tx,
@@ -8,6 +8,7 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::core::command::Command;
+use crate::core::spill::{ToolOutputSpill, spill_oversized};
 use crate::core::state::{State, ToolCall};
 use crate::core::tool::{ExecutionContext, ProgressSender, ToolResult, send_event};
 use crate::core::transitions as t;
@@ -135,6 +136,7 @@ pub(super) fn spawn_group(
         let cwd = cwd.clone();
         let agent_id = agent_id.clone();
         let subagent_depth = state.frame.subagent_depth;
+        let spill = state.frame.config.tool_output_spill.clone();
 
         let id = tc.id.clone();
         let name = tc.name.clone();
@@ -150,6 +152,7 @@ pub(super) fn spawn_group(
             file_access,
             agent_id,
             subagent_depth,
+            spill: spill.clone(),
         };
 
         join_set.spawn(async move {
@@ -159,6 +162,7 @@ pub(super) fn spawn_group(
                 name.clone(),
                 args,
                 validator_and_schema,
+                spill,
                 event_tx,
                 ctx,
             )
@@ -170,6 +174,7 @@ pub(super) fn spawn_group(
     join_set
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_single_tool(
     tool: Option<crate::core::tool::BoxedTool>,
     id: String,
@@ -179,6 +184,7 @@ async fn run_single_tool(
         std::sync::Arc<jsonschema::Validator>,
         std::sync::Arc<serde_json::Value>,
     )>,
+    spill: Option<ToolOutputSpill>,
     event_tx: tokio::sync::broadcast::Sender<AgentEvent>,
     ctx: ExecutionContext,
 ) -> ToolResult {
@@ -207,6 +213,12 @@ async fn run_single_tool(
     } else {
         ToolResult::error(format!("Tool not found: {}", name))
     };
+    // The UI's `ToolExecutionEnd` gets the same capped text the model
+    // sees; the full output lives in the spill file.
+    let result = match &spill {
+        Some(spill) => spill_oversized(result, &id, spill),
+        None => result,
+    };
 
     send_event(
         &event_tx,
@@ -0,0 +1,109 @@
+//! Going back in the conversation: listing user turns, and rewinding
+//! or forking the agent's [`History`](tau_history::History) to an
+//! earlier point, or moving onto a history the host forked itself.
+//! Idle-only; the actor rejects all of them mid-prompt.
+
+use std::sync::Arc;
+
+use tau_history::{History, HistoryError, ObjectHash, UserTurn};
+
+use crate::core::state::State;
+use crate::core::transitions as t;
+use crate::types::error::{Error, Result};
+use crate::types::rewind::{RewindTarget, Rewound};
+
+use super::sync_history;
+
+fn history_error(e: HistoryError) -> Error {
+    Error::History {
+        reason: e.to_string(),
+    }
+}
+
+pub(super) async fn user_turns(state: &State) -> Result<Vec<UserTurn>> {
+    state
+        .frame
+        .history
+        .user_turns()
+        .await
+        .map_err(history_error)
+}
+
+/// Move the live conversation to `to`: in place when `fork` is
+/// `false`, onto a fresh history forked there when it's `true`. Either
+/// way the working copy and the file-access tracker are rebuilt from
+/// the history, so a write to a file only read in the abandoned turns
+/// has to read it again.
+pub(super) async fn rewind(state: &mut State, to: RewindTarget, fork: bool) -> Result<Rewound> {
+    // Whatever a failed append left uncommitted belongs to the line
+    // being abandoned.
+    sync_history(state).await;
+
+    let point = match to {
+        RewindTarget::Commit(hash) => Some(hash),
+        RewindTarget::Turn(n) => {
+            let turns = user_turns(state).await?;
+            match n.checked_sub(1).and_then(|i| turns.get(i)) {
+                Some(turn) => turn.before,
+                None => {
+                    return Err(Error::NoSuchTurn {
+                        turn: n,
+                        turns: turns.len(),
+                    });
+                }
+            }
+        }
+    };
+
+    let abandoned = if fork {
+        let abandoned = state.frame.history.head();
+        state.frame.history = state
+            .frame
+            .history
+            .fork_at(point)
+            .await
+            .map_err(history_error)?;
+        abandoned
+    } else {
+        state
+            .frame
+            .history
+            .rewind(point)
+            .await
+            .map_err(history_error)?
+    };
+    reload(state, abandoned).await
+}
+
+/// Continue on `history` — typically a named branch the host forked
+/// at a rewind point — leaving the current one untouched.
+pub(super) async fn switch_history(
+    state: &mut State,
+    history: Arc<dyn History>,
+) -> Result<Rewound> {
+    sync_history(state).await;
+    let abandoned = state.frame.history.head();
+    state.frame.history = history;
+    reload(state, abandoned).await
+}
+
+/// Rebuild the working copy and the file-access tracker from the
+/// history the actor is now on.
+async fn reload(state: &mut State, abandoned: Option<ObjectHash>) -> Result<Rewound> {
+    let history = &state.frame.history;
+    let messages = history.messages().await.map_err(history_error)?;
+    let previous_summary = history.previous_summary().await.map_err(history_error)?;
+    state
+        .frame
+        .file_access
+        .lock()
+        .rebuild_from_messages(&messages, &state.conv.cwd);
+    let count = messages.len();
+    t::apply_rewound(&mut state.conv, messages, previous_summary);
+
+    Ok(Rewound {
+        head: state.frame.history.head(),
+        abandoned,
+        messages: count,
+    })
+}
@@ -11,6 +11,9 @@
 //!
 //! Tools self-report their inherent risk via [`Tool::risk`](crate::core::tool::Tool::risk);
 //! policies combine that with the tool name and arguments to make the call.
+//! [`RulePolicy`] does so from allow / ask / deny lists of [`ToolRule`]s,
+//! whose [`ArgPattern`]s range from substrings to regexes, globs and
+//! cwd-relative path scopes.
 //!
 //! After the gate resolves, the actor emits
 //! [`ToolApprovalOutcome`](crate::ToolApprovalOutcome) on the
@@ -19,8 +22,11 @@
 //! distinction is deliberate: `ApprovalDecision` is the policy's input to
 //! the actor; `ToolApprovalOutcome` is the actor's report to the world.
 
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -48,6 +54,19 @@ pub enum ApprovalDecision {
 
 pub trait ApprovalPolicy: Send + Sync {
     fn classify(&self, tool: &str, arguments: &Value, risk: ToolRisk) -> ApprovalDecision;
+
+    /// [`Self::classify`] for a call made from `cwd`, the agent's
+    /// working directory. The actor calls this one; policies that
+    /// resolve relative paths override it. Defaults to ignoring `cwd`.
+    fn classify_in(
+        &self,
+        tool: &str,
+        arguments: &Value,
+        risk: ToolRisk,
+        _cwd: &Path,
+    ) -> ApprovalDecision {
+        self.classify(tool, arguments, risk)
+    }
 }
 
 /// Default: gate `Elevated`, auto-approve everything else.
@@ -85,6 +104,12 @@ impl ApprovalPolicy for AutoAcceptAll {
 ///   **nothing** (it would otherwise match every value).
 /// - [`Equals`](Self::Equals) trims both ends, so surrounding padding is
 ///   insignificant.
+/// - [`Regex`](Self::Regex) searches the collapsed value as-is, so
+///   anchors see any surrounding space; [`Glob`](Self::Glob) matches
+///   the whole collapsed value with its ends trimmed.
+///
+/// [`PathWithin`](Self::PathWithin) treats the value as a path instead
+/// and isn't normalized.
 #[derive(Debug, Clone)]
 pub enum ArgPattern {
     /// Substring match: the (normalized) needle appears anywhere in
@@ -92,10 +117,39 @@ pub enum ArgPattern {
     Contains(String),
     /// Exact string equality after normalization.
     Equals(String),
+    /// Regex search (unanchored unless the regex says otherwise).
+    Regex(Regex),
+    /// Shell-style glob over the whole value. `*` also matches `/`.
+    Glob(glob::Pattern),
+    /// The value is a path inside this directory (or the directory
+    /// itself). Both sides are resolved against the agent's cwd,
+    /// with `~/` expanded and symlinks followed as far as the path
+    /// exists, so `src/../secrets` and a `src/link -> /etc` symlink
+    /// don't count as inside `src`.
+    PathWithin(PathBuf),
+    /// Inverts another pattern — `Not(PathWithin("src"))` matches
+    /// paths outside `src`.
+    Not(Box<ArgPattern>),
+    /// The value is a command line: `split` breaks it into simple
+    /// commands and every one must match `each`. A line `split`
+    /// returns `None` for never matches. The host supplies the
+    /// splitter, since this crate has no shell parser.
+    EachCommand {
+        each: Box<ArgPattern>,
+        split: fn(&str) -> Option<Vec<String>>,
+    },
 }
 
 impl ArgPattern {
-    fn matches_value(&self, value: &str) -> bool {
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self::Regex)
+    }
+
+    pub fn glob(pattern: &str) -> Result<Self, glob::PatternError> {
+        glob::Pattern::new(pattern).map(Self::Glob)
+    }
+
+    fn matches_value(&self, value: &str, cwd: &Path) -> bool {
         match self {
             ArgPattern::Contains(needle) => {
                 let needle = collapse_ws(needle);
@@ -110,6 +164,30 @@ impl ArgPattern {
             ArgPattern::Equals(needle) => {
                 collapse_ws(value).trim() == collapse_ws(needle).trim()
             }
+            ArgPattern::Regex(re) => re.is_match(&collapse_ws(value)),
+            ArgPattern::Glob(glob) => glob.matches(collapse_ws(value).trim()),
+            ArgPattern::PathWithin(dir) => {
+                !value.trim().is_empty()
+                    && resolve_real(value.trim(), cwd).starts_with(resolve_real(dir, cwd))
+            }
+            ArgPattern::Not(inner) => !inner.matches_value(value, cwd),
+            ArgPattern::EachCommand { each, split } => split(value).is_some_and(|commands| {
+                !commands.is_empty() && commands.iter().all(|c| each.matches_value(c, cwd))
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ArgPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgPattern::Contains(needle) => write!(f, "contains {needle:?}"),
+            ArgPattern::Equals(value) => write!(f, "equals {value:?}"),
+            ArgPattern::Regex(re) => write!(f, "matches /{re}/"),
+            ArgPattern::Glob(glob) => write!(f, "matches glob {:?}", glob.as_str()),
+            ArgPattern::PathWithin(dir) => write!(f, "is within {:?}", dir.display().to_string()),
+            ArgPattern::Not(inner) => write!(f, "not ({inner})"),
+            ArgPattern::EachCommand { each, .. } => write!(f, "has every command {each}"),
         }
     }
 }
@@ -134,13 +212,29 @@ pub struct ArgMatch {
 }
 
 impl ArgMatch {
+    /// [`Self::matches_in`] the process's current directory.
     pub fn matches(&self, arguments: &Value) -> bool {
+        self.matches_in(arguments, &std::env::current_dir().unwrap_or_default())
+    }
+
+    /// Relative paths are resolved against `cwd` for
+    /// [`ArgPattern::PathWithin`]; other patterns ignore it.
+    pub fn matches_in(&self, arguments: &Value, cwd: &Path) -> bool {
         match &self.path {
             Some(path) => match resolve_path(arguments, path) {
-                Some(Value::String(s)) => self.pattern.matches_value(s),
+                Some(Value::String(s)) => self.pattern.matches_value(s, cwd),
                 _ => false,
             },
-            None => any_string_leaf(arguments, &mut |leaf| self.pattern.matches_value(leaf)),
+            None => any_string_leaf(arguments, &mut |leaf| self.pattern.matches_value(leaf, cwd)),
+        }
+    }
+}
+
+impl fmt::Display for ArgMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{path} {}", self.pattern),
+            None => write!(f, "any argument {}", self.pattern),
         }
     }
 }
@@ -156,10 +250,16 @@ impl ArgMatch {
 ///   2. either `matches` is empty (any invocation matches), OR any
 ///      single [`ArgMatch`] in `matches` matches the arguments (OR
 ///      semantics — combine via separate rules for AND).
+///
+/// `Display` renders the rule for explanations, e.g.
+/// `bash where command matches /^rm/`.
 #[derive(Debug, Clone)]
 pub struct ToolRule {
     pub tool: String,
     pub matches: Vec<ArgMatch>,
+    /// Why the rule exists, appended to the rejection a deny rule
+    /// produces.
+    pub reason: Option<String>,
 }
 
 impl ToolRule {
@@ -168,9 +268,29 @@ impl ToolRule {
         Self {
             tool: tool.into(),
             matches: vec![],
+            reason: None,
+        }
+    }
+
+    /// Match `pattern` against the string at a JSON path (see
+    /// [`ArgMatch::path`]).
+    pub fn at(tool: impl Into<String>, path: impl Into<String>, pattern: ArgPattern) -> Self {
+        Self {
+            tool: tool.into(),
+            matches: vec![ArgMatch {
+                path: Some(path.into()),
+                pattern,
+            }],
+            reason: None,
         }
     }
 
+    /// Attach the explanation shown when this rule denies a call.
+    pub fn because(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
     /// Convenience: substring match against any string-typed argument
     /// leaf, with whitespace normalization. Equivalent to the legacy
     /// `arg_substrings: vec![needle]` shape, minus the brittle
@@ -182,6 +302,7 @@ impl ToolRule {
                 path: None,
                 pattern: ArgPattern::Contains(needle.into()),
             }],
+            reason: None,
         }
     }
 
@@ -198,6 +319,7 @@ impl ToolRule {
                 path: Some(path.into()),
                 pattern: ArgPattern::Contains(needle.into()),
             }],
+            reason: None,
         }
     }
 
@@ -213,17 +335,38 @@ impl ToolRule {
                 path: Some(path.into()),
                 pattern: ArgPattern::Equals(value.into()),
             }],
+            reason: None,
         }
     }
 
+    /// [`Self::matches_in`] the process's current directory.
     pub fn matches(&self, tool: &str, arguments: &Value) -> bool {
+        self.matches_in(
+            tool,
+            arguments,
+            &std::env::current_dir().unwrap_or_default(),
+        )
+    }
+
+    pub fn matches_in(&self, tool: &str, arguments: &Value, cwd: &Path) -> bool {
         if self.tool != tool {
             return false;
         }
         if self.matches.is_empty() {
             return true;
         }
-        self.matches.iter().any(|m| m.matches(arguments))
+        self.matches.iter().any(|m| m.matches_in(arguments, cwd))
+    }
+}
+
+impl fmt::Display for ToolRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tool)?;
+        for (i, m) in self.matches.iter().enumerate() {
+            f.write_str(if i == 0 { " where " } else { " or " })?;
+            write!(f, "{m}")?;
+        }
+        Ok(())
     }
 }
 
@@ -290,10 +433,61 @@ fn resolve_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
     Some(current)
 }
 
-/// Allow / deny lists with a fallback policy. Deny wins over allow;
-/// allow wins over fallback.
+/// Resolve a path argument to where it really points: `~/` expanded,
+/// relative paths joined onto `cwd`, the longest existing prefix
+/// canonicalized (following symlinks), and `.` / `..` in the
+/// not-yet-existing rest applied lexically. Nothing past that prefix
+/// exists, so it can't be a symlink.
+fn resolve_real(path: impl AsRef<Path>, cwd: &Path) -> PathBuf {
+    let path = path.as_ref();
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        Err(_) => cwd.join(path),
+    };
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    let mut out = loop {
+        if let Ok(real) = std::fs::canonicalize(existing) {
+            break real;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_owned());
+                existing = parent;
+            }
+            // Dangling `..` or a root that won't canonicalize: fall
+            // back to lexical resolution of the whole path.
+            _ => {
+                rest.clear();
+                break PathBuf::new();
+            }
+        }
+    };
+    let tail: Vec<Component> = if out.as_os_str().is_empty() {
+        path.components().collect()
+    } else {
+        rest.iter().rev().map(|n| Component::Normal(n)).collect()
+    };
+    for component in tail {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Allow / ask / deny lists with a fallback policy. The first list
+/// with a matching rule decides: deny rejects (naming the rule and
+/// its reason), ask gates, allow auto-approves. Deny wins over ask,
+/// ask over allow, and allow over the fallback — so a narrow ask or
+/// deny rule carves an exception out of a broad allow.
 pub struct RulePolicy {
     pub allow: Vec<ToolRule>,
+    pub ask: Vec<ToolRule>,
     pub deny: Vec<ToolRule>,
     pub fallback: Arc<dyn ApprovalPolicy>,
 }
@@ -302,6 +496,7 @@ impl RulePolicy {
     pub fn new(fallback: Arc<dyn ApprovalPolicy>) -> Self {
         Self {
             allow: vec![],
+            ask: vec![],
             deny: vec![],
             fallback,
         }
@@ -312,6 +507,11 @@ impl RulePolicy {
         self
     }
 
+    pub fn ask(mut self, rule: ToolRule) -> Self {
+        self.ask.push(rule);
+        self
+    }
+
     pub fn deny(mut self, rule: ToolRule) -> Self {
         self.deny.push(rule);
         self
@@ -320,13 +520,42 @@ impl RulePolicy {
 
 impl ApprovalPolicy for RulePolicy {
     fn classify(&self, tool: &str, arguments: &Value, risk: ToolRisk) -> ApprovalDecision {
-        if self.deny.iter().any(|r| r.matches(tool, arguments)) {
-            return ApprovalDecision::Reject(format!("denied by policy: {tool}"));
+        self.classify_in(
+            tool,
+            arguments,
+            risk,
+            &std::env::current_dir().unwrap_or_default(),
+        )
+    }
+
+    fn classify_in(
+        &self,
+        tool: &str,
+        arguments: &Value,
+        risk: ToolRisk,
+        cwd: &Path,
+    ) -> ApprovalDecision {
+        if let Some(rule) = self
+            .deny
+            .iter()
+            .find(|r| r.matches_in(tool, arguments, cwd))
+        {
+            return ApprovalDecision::Reject(match &rule.reason {
+                Some(reason) => format!("denied by policy: {rule} ({reason})"),
+                None => format!("denied by policy: {rule}"),
+            });
         }
-        if self.allow.iter().any(|r| r.matches(tool, arguments)) {
+        if self.ask.iter().any(|r| r.matches_in(tool, arguments, cwd)) {
+            return ApprovalDecision::Gate;
+        }
+        if self
+            .allow
+            .iter()
+            .any(|r| r.matches_in(tool, arguments, cwd))
+        {
             return ApprovalDecision::Auto;
         }
-        self.fallback.classify(tool, arguments, risk)
+        self.fallback.classify_in(tool, arguments, risk, cwd)
     }
 }
 
@@ -500,6 +729,7 @@ mod tests {
                     pattern: ArgPattern::Contains("dd if=".into()),
                 },
             ],
+            reason: None,
         };
         let rm = serde_json::json!({"command": "rm -rf /"});
         let dd = serde_json::json!({"command": "dd if=/dev/zero of=/dev/sda"});
@@ -508,4 +738,110 @@ mod tests {
         assert!(rule.matches("bash", &dd));
         assert!(!rule.matches("bash", &safe));
     }
+
+    #[test]
+    fn regex_and_glob_patterns() {
+        let re = ToolRule::at(
+            "bash",
+            "command",
+            ArgPattern::regex("^cargo (check|test)( |$)").unwrap(),
+        );
+        assert!(re.matches("bash", &serde_json::json!({"command": "cargo test -p tau"})));
+        assert!(re.matches("bash", &serde_json::json!({"command": "cargo  check"})));
+        assert!(!re.matches("bash", &serde_json::json!({"command": "cargo testify"})));
+        assert!(!re.matches("bash", &serde_json::json!({"command": "echo; cargo test"})));
+
+        let glob = ToolRule::at("write", "path", ArgPattern::glob("*.lock").unwrap());
+        assert!(glob.matches("write", &serde_json::json!({"path": "deps/Cargo.lock"})));
+        assert!(!glob.matches("write", &serde_json::json!({"path": "Cargo.lock.bak"})));
+    }
+
+    #[test]
+    fn each_command_needs_every_command_to_match() {
+        fn split(line: &str) -> Option<Vec<String>> {
+            match line.contains(';') {
+                true => None,
+                false => Some(line.split("&&").map(|c| c.trim().to_string()).collect()),
+            }
+        }
+        let rule = ToolRule::at(
+            "bash",
+            "command",
+            ArgPattern::EachCommand {
+                each: Box::new(ArgPattern::regex("^cargo (check|test)( |$)").unwrap()),
+                split,
+            },
+        );
+        let call = |c: &str| serde_json::json!({ "command": c });
+        assert!(rule.matches("bash", &call("cargo check && cargo test")));
+        assert!(!rule.matches("bash", &call("cargo test && rm -rf ~")));
+        assert!(!rule.matches("bash", &call("cargo test; cargo check")));
+        assert_eq!(
+            rule.to_string(),
+            "bash where command has every command matches /^cargo (check|test)( |$)/"
+        );
+    }
+
+    #[test]
+    fn path_within_resolves_against_cwd_and_symlinks() {
+        let root = tempfile::tempdir().unwrap();
+        let cwd = root.path();
+        std::fs::create_dir_all(cwd.join("src")).unwrap();
+        std::fs::create_dir_all(cwd.join("secrets")).unwrap();
+        let within = ArgPattern::PathWithin("src".into());
+        let rule = ToolRule::at("write", "path", within);
+        let call = |p: &str| serde_json::json!({ "path": p });
+
+        assert!(rule.matches_in("write", &call("src/new/mod.rs"), cwd));
+        assert!(rule.matches_in("write", &call("./src"), cwd));
+        let absolute = cwd.join("src/lib.rs").display().to_string();
+        assert!(rule.matches_in("write", &call(&absolute), cwd));
+        assert!(!rule.matches_in("write", &call("src/../secrets/key"), cwd));
+        assert!(!rule.matches_in("write", &call("srcfoo/x"), cwd));
+        assert!(!rule.matches_in("write", &call(""), cwd));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(cwd.join("secrets"), cwd.join("src/escape")).unwrap();
+            assert!(!rule.matches_in("write", &call("src/escape/key"), cwd));
+        }
+    }
+
+    #[test]
+    fn deny_beats_ask_beats_allow_with_explanation() {
+        let p = RulePolicy::new(Arc::new(DefaultPolicy))
+            .allow(ToolRule::any("write"))
+            .ask(ToolRule::at(
+                "write",
+                "path",
+                ArgPattern::glob("*.toml").unwrap(),
+            ))
+            .deny(
+                ToolRule::at(
+                    "write",
+                    "path",
+                    ArgPattern::Not(Box::new(ArgPattern::PathWithin("/work/src".into()))),
+                )
+                .because("writes stay in src/"),
+            );
+        let cwd = Path::new("/work");
+        let classify = |path: &str| {
+            p.classify_in(
+                "write",
+                &serde_json::json!({ "path": path }),
+                ToolRisk::Local,
+                cwd,
+            )
+        };
+        assert!(matches!(classify("src/lib.rs"), ApprovalDecision::Auto));
+        assert!(matches!(classify("src/Cargo.toml"), ApprovalDecision::Gate));
+        match classify("Cargo.toml") {
+            ApprovalDecision::Reject(reason) => assert_eq!(
+                reason,
+                "denied by policy: write where path not (is within \"/work/src\") \
+                 (writes stay in src/)"
+            ),
+            other => panic!("expected reject, got {other:?}"),
+        }
+    }
 }
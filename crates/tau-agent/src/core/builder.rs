@@ -19,6 +19,7 @@ use std::time::Duration;
 use futures::FutureExt;
 use parking_lot::Mutex as ParkingMutex;
 use tau_ai::{Message, ServerTool};
+use tau_history::{CommitKind, CommitMeta, History, Repository};
 use tokio::sync::{broadcast, mpsc};
 
 use crate::core::approval::{ApprovalPolicy, DefaultPolicy};
@@ -30,6 +31,7 @@ use crate::core::state::{Conv, Frame, Shared, State, TransformContextFn};
 use crate::core::tool::{BoxedTool, FileAccessTracker};
 use crate::core::transport::Transport;
 use crate::types::conversation::Conversation;
+use crate::types::error::Error;
 use crate::types::events::AgentEvent;
 
 /// Default capacity of the urgent (Steer / FollowUp) channel. Sized
@@ -79,6 +81,7 @@ pub struct AgentBuilder {
     transform_context: Option<Arc<TransformContextFn>>,
     initial_messages: Vec<Message>,
     previous_summary: Option<String>,
+    history: Option<Arc<dyn History>>,
     subagent_depth: u32,
 
     // Pre-created shared primitives.
@@ -128,6 +131,7 @@ impl AgentBuilder {
             transform_context: None,
             initial_messages: vec![],
             previous_summary: None,
+            history: None,
             subagent_depth: 0,
             event_tx,
             urgent_tx,
@@ -230,6 +234,21 @@ impl AgentBuilder {
         self
     }
 
+    /// Back the agent with `history` instead of a private in-memory
+    /// [`Branch`](tau_history::Branch). The actor commits each turn to
+    /// it and routes summarizing compactions through
+    /// [`History::compact_prefix`], so a host holding the same branch
+    /// can fork, merge or tag it.
+    ///
+    /// An existing history is resumed: its messages and previous
+    /// summary become the agent's conversation and any
+    /// [`seed`](Self::seed) is ignored. An empty one receives the seed
+    /// messages as its first commit.
+    pub fn set_history(&mut self, history: Arc<dyn History>) -> &mut Self {
+        self.history = Some(history);
+        self
+    }
+
     /// Set this agent's depth in the subagent spawn tree. `0` for the
     /// host's root; the fleet's spawn paths increment by one for each
     /// descendant. Tools read this back via
@@ -306,12 +325,10 @@ impl AgentBuilder {
     /// Consume the builder, spawn the actor task, and wait for the
     /// actor to signal readiness before returning the handle.
     ///
-    /// Returns `Err(Error::ActorPanic)` if the actor task panicked
-    /// before signalling — i.e. before reaching `Idle` for the first
-    /// time. The actor itself currently has no fallible async setup,
-    /// but the contract leaves room for future startup checks
-    /// (transport pre-warm, tool init) to surface their errors here
-    /// rather than via a side-channel.
+    /// Returns `Err(Error::History)` if the history can't be read or
+    /// the seed can't be committed to it, and `Err(Error::ActorPanic)`
+    /// if the actor task panicked before signalling — i.e. before
+    /// reaching `Idle` for the first time.
     ///
     /// The returned handle is interchangeable with one from
     /// [`Self::handle`] — same channels, same shared atomics.
@@ -319,11 +336,17 @@ impl AgentBuilder {
         let urgent_rx = self.urgent_rx.take().expect("spawn() consumes self");
         let normal_rx = self.normal_rx.take().expect("spawn() consumes self");
 
-        let conversation = Conversation {
-            messages: self.initial_messages,
-            previous_summary: self.previous_summary,
-            ..Default::default()
-        };
+        let history = self
+            .history
+            .take()
+            .unwrap_or_else(|| Repository::new().new_branch());
+        let conversation = load_history(
+            history.as_ref(),
+            std::mem::take(&mut self.initial_messages),
+            self.previous_summary.take(),
+        )
+        .await?;
+        let history_len = conversation.messages.len();
 
         let schema_cache: HashMap<String, (Arc<jsonschema::Validator>, Arc<serde_json::Value>)> =
             self.tools
@@ -357,12 +380,19 @@ impl AgentBuilder {
             interaction_tx: self.interaction_tx,
             interaction_timeout: self.interaction_timeout,
             approval_policy: self.approval_policy,
+            history,
             transform_context: self.transform_context,
             file_access: Arc::new(ParkingMutex::new(FileAccessTracker::default())),
             subagent_depth: self.subagent_depth,
         };
+        if let Some(cwd) = &self.cwd {
+            let _ = self.shared.cwd.set(cwd.clone());
+        }
         let conv = Conv {
             conversation,
+            history_len,
+            history_resync: None,
+            reprompt: false,
             steering_queue: Vec::new(),
             follow_up_queue: Vec::new(),
             cwd: self.cwd,
@@ -459,3 +489,59 @@ impl AgentBuilder {
         }
     }
 }
+
+/// Build the starting conversation from `history`, committing the seed
+/// first if the history is empty.
+async fn load_history(
+    history: &dyn History,
+    seed: Vec<Message>,
+    seed_summary: Option<String>,
+) -> crate::types::error::Result<Conversation> {
+    let err = |e: tau_history::HistoryError| Error::History {
+        reason: e.to_string(),
+    };
+    let existing = history.messages().await.map_err(err)?;
+    if existing.is_empty() {
+        // A compacted seed leads with its summary message; committing
+        // that as a compaction records the summary too.
+        match (seed.split_first(), &seed_summary) {
+            (Some((summary_message, rest)), Some(summary)) => {
+                history
+                    .compact_prefix(0, summary_message.clone(), summary.clone())
+                    .await
+                    .map_err(err)?;
+                if !rest.is_empty() {
+                    history
+                        .append_with(rest.to_vec(), seed_meta())
+                        .await
+                        .map_err(err)?;
+                }
+            }
+            (Some(_), None) => history
+                .append_with(seed.clone(), seed_meta())
+                .await
+                .map_err(err)?,
+            (None, _) => {}
+        }
+        return Ok(Conversation {
+            messages: seed,
+            previous_summary: seed_summary,
+            ..Default::default()
+        });
+    }
+    if !seed.is_empty() {
+        tracing::warn!("agent seed ignored: its history already has messages");
+    }
+    Ok(Conversation {
+        messages: existing,
+        previous_summary: history.previous_summary().await.map_err(err)?,
+        ..Default::default()
+    })
+}
+
+/// Seed commits aren't turns: [`History::user_turns`] skips them.
+fn seed_meta() -> CommitMeta {
+    CommitMeta::new()
+        .with_kind(CommitKind::Seed)
+        .with_timestamp(chrono::Utc::now().timestamp_millis())
+}
@@ -9,7 +9,8 @@
 //!   [`DEFAULT_URGENT_CAPACITY`](crate::core::builder::DEFAULT_URGENT_CAPACITY)
 //!   (256), sized for bursts of background-subagent completions.
 //! - **normal** — everything else: prompts, queries (`GetConfig`,
-//!   `GetMessages`, …), config setters, manual compaction. Capacity
+//!   `GetMessages`, …), config setters, manual compaction, rewinds.
+//!   Capacity
 //!   [`DEFAULT_NORMAL_CAPACITY`](crate::core::builder::DEFAULT_NORMAL_CAPACITY)
 //!   (64).
 //!
@@ -39,6 +40,7 @@
 use std::sync::Arc;
 
 use tau_ai::{Content, Message, Model, ReasoningLevel};
+use tau_history::{History, UserTurn};
 use tokio::sync::oneshot;
 
 use crate::core::approval::ApprovalPolicy;
@@ -46,6 +48,7 @@ use crate::core::compaction::CompactionConfig;
 use crate::core::config::AgentConfig;
 use crate::types::conversation::Conversation;
 use crate::types::info::{ContextStats, ToolInfo};
+use crate::types::rewind::{RewindTarget, Rewound};
 
 /// Result of a `Prompt` or `Compact` operation, returned via the
 /// embedded oneshot.
@@ -57,6 +60,8 @@ pub enum Command {
     // Prompt lifecycle
     Prompt {
         content: Vec<Content>,
+        /// Generated by the host, not typed by the user.
+        reprompt: bool,
         reply: oneshot::Sender<PromptResult>,
     },
     Steer(Message),
@@ -82,6 +87,20 @@ pub enum Command {
         custom_instructions: Option<String>,
         reply: oneshot::Sender<PromptResult>,
     },
+
+    // Going back in the conversation. Idle only: mid-prompt both reply
+    // `Error::Busy`.
+    UserTurns(oneshot::Sender<crate::types::error::Result<Vec<UserTurn>>>),
+    Rewind {
+        to: RewindTarget,
+        /// Move onto a fork instead of rewinding the history in place.
+        fork: bool,
+        reply: oneshot::Sender<crate::types::error::Result<Rewound>>,
+    },
+    SwitchHistory {
+        history: Arc<dyn History>,
+        reply: oneshot::Sender<crate::types::error::Result<Rewound>>,
+    },
 }
 
 impl Command {
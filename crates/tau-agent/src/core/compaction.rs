@@ -11,6 +11,7 @@
 //! - [`cut_point`] — pure split-turn / cut-point detection;
 //! - [`prompts`] — prompt templates and prompt assembly;
 //! - [`file_ops`] — read/write file-operation extraction;
+//! - [`prune`] — first-stage tool-output pruning;
 //! - this root — orchestration ([`compact`]), result application,
 //!   token estimation, and the [`Summarizer`] seam that decouples the
 //!   algorithm from the transport.
@@ -18,11 +19,12 @@
 mod cut_point;
 mod file_ops;
 mod prompts;
+mod prune;
 
 use std::sync::Arc;
 
 use futures::StreamExt;
-use tau_ai::{Content, Message};
+use tau_ai::{Content, Message, Model, Usage};
 use tokio_util::sync::CancellationToken;
 
 use crate::core::config::AgentConfig;
@@ -95,6 +97,22 @@ pub struct CompactionConfig {
     /// accumulated at least this much, then continues to the next
     /// message boundary.
     pub keep_recent: CompactionThreshold,
+    /// Cheap first stage: before summarizing, replace the bodies of
+    /// `ToolResult`s older than the `keep_recent` window with a short
+    /// "output elided" stub. The stubbed prefix is also what the
+    /// summarizer sees, so this shrinks the summarization prompt too.
+    pub prune_tool_outputs: bool,
+    /// Only tool results larger than this many characters are pruned.
+    pub prune_min_chars: usize,
+    /// Pruning counts as sufficient — and the LLM summarization is
+    /// skipped — when the pruned conversation's estimated size is at
+    /// or below this. Manual compaction always summarizes.
+    pub prune_target: CompactionThreshold,
+    /// Model that writes compaction summaries. `None` falls back to the
+    /// agent's [`utility_model`](AgentConfig::utility_model). Either
+    /// way, the main model is used instead when the chosen model's
+    /// context window can't fit the summarization prompt.
+    pub summarizer_model: Option<Model>,
 }
 
 impl Default for CompactionConfig {
@@ -110,10 +128,33 @@ impl Default for CompactionConfig {
             // 0.10 of 200K = 20_000 tokens — matches the old absolute
             // default. Scales sensibly on smaller models.
             keep_recent: CompactionThreshold::Fraction(0.10),
+            prune_tool_outputs: true,
+            // ~500 tokens: short results (edits, small greps) carry
+            // their meaning in-line and are cheap to keep.
+            prune_min_chars: 2_000,
+            // Half the window leaves plenty of room to keep working
+            // before the threshold trips again.
+            prune_target: CompactionThreshold::Fraction(0.5),
+            summarizer_model: None,
         }
     }
 }
 
+/// What a compaction pass produced.
+pub enum CompactionOutcome {
+    /// Tool-output pruning alone brought the conversation under
+    /// [`CompactionConfig::prune_target`]; no summary was written.
+    /// `messages` is the full replacement conversation.
+    Pruned {
+        messages: Vec<Message>,
+        /// Number of tool results that were elided.
+        pruned: usize,
+        tokens_before: u64,
+    },
+    /// The prefix was summarized.
+    Summarized(CompactionResult),
+}
+
 pub struct CompactionResult {
     /// Summary text. Wrapped in `<context-summary>` markers and
     /// prepended to the kept messages by [`apply_compaction_result`].
@@ -124,6 +165,8 @@ pub struct CompactionResult {
     /// Total estimated tokens before compaction (for the
     /// `CompactionEnd` event).
     pub tokens_before: u64,
+    /// Tokens the summarizer spent writing `summary`.
+    pub usage: Usage,
 }
 
 // ─── Token estimation (char/4 heuristic) ─────────────────────────────
@@ -174,32 +217,54 @@ fn content_char_count(content: &[Content]) -> usize {
 /// required. The production impl is [`TransportSummarizer`].
 #[async_trait::async_trait]
 trait Summarizer: Send + Sync {
-    async fn summarize(&self, prompt: &str, cancel: &CancellationToken)
-    -> Result<String, String>;
+    /// The summary for `prompt`, and the tokens spent writing it.
+    async fn summarize(
+        &self,
+        prompt: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(String, Usage), String>;
 }
 
+/// Output budget for one summarization call.
+const SUMMARY_MAX_TOKENS: u32 = 4096;
+
 /// Production [`Summarizer`]: a one-shot, tool-less `transport.run()`
-/// call against the agent's configured model.
+/// call against the cheapest model that fits the prompt.
 struct TransportSummarizer<'a> {
-    agent_config: &'a AgentConfig,
+    /// Configured summarizer / utility model, if it differs from `main`.
+    preferred: &'a Model,
+    /// The agent's own model — the fallback for oversized prompts.
+    main: &'a Model,
     transport: &'a Arc<dyn Transport>,
 }
 
+/// Pick the model for a summarization prompt: `preferred` when its
+/// context window holds the prompt plus the summary budget, else
+/// `main`.
+fn pick_summarizer_model<'a>(preferred: &'a Model, main: &'a Model, prompt: &str) -> &'a Model {
+    let needed = (prompt.len() / 4) as u64 + u64::from(SUMMARY_MAX_TOKENS);
+    if u64::from(preferred.context_window) >= needed {
+        preferred
+    } else {
+        main
+    }
+}
+
 #[async_trait::async_trait]
 impl Summarizer for TransportSummarizer<'_> {
     async fn summarize(
         &self,
         prompt: &str,
         cancel: &CancellationToken,
-    ) -> Result<String, String> {
+    ) -> Result<(String, Usage), String> {
         let run_config = AgentRunConfig {
             system_prompt: Some(prompts::SUMMARIZATION_SYSTEM_PROMPT.into()),
             tools: vec![],
             server_tools: vec![],
-            model: self.agent_config.model.clone(),
+            model: pick_summarizer_model(self.preferred, self.main, prompt).clone(),
             reasoning: None,
             thinking_adaptive: false,
-            max_tokens: Some(4096),
+            max_tokens: Some(SUMMARY_MAX_TOKENS),
             temperature: None,
             // Summarization is a one-shot call, not part of any turn loop.
             turn_number: 0,
@@ -216,9 +281,11 @@ impl Summarizer for TransportSummarizer<'_> {
             .map_err(|e| format!("Compaction LLM call failed: {e}"))?;
 
         let mut result_text = String::new();
+        let mut usage = Usage::default();
         while let Some(event) = stream.next().await {
             match event {
                 AgentEvent::MessageEnd { message } => result_text = message.text(),
+                AgentEvent::TurnEnd { usage: u, .. } => usage.accumulate(&u),
                 AgentEvent::Error { message } => {
                     return Err(format!("Compaction LLM error: {message}"));
                 }
@@ -229,14 +296,30 @@ impl Summarizer for TransportSummarizer<'_> {
         if result_text.is_empty() {
             return Err("Compaction LLM returned empty response".into());
         }
-        Ok(result_text)
+        Ok((result_text, usage))
     }
 }
 
 // ─── Entry point ─────────────────────────────────────────────────────
 
+/// Tool-output pruning parameters, resolved against the model's
+/// context window.
+#[derive(Debug, Clone, Copy)]
+struct PruneBudget {
+    min_chars: usize,
+    /// Skip summarization when the pruned estimate is at or below
+    /// this. `None` means always summarize (manual compaction).
+    target_tokens: Option<u64>,
+}
+
 /// Run compaction on the given messages.
 ///
+/// When [`CompactionConfig::prune_tool_outputs`] is on, large tool
+/// results before the cut point are elided first. For `Threshold` and
+/// `Overflow` passes that alone may be enough
+/// ([`CompactionOutcome::Pruned`]); otherwise — and always for
+/// `Manual` — the pruned prefix is summarized.
+///
 /// `custom_instructions`, when present and non-empty after trimming, is
 /// appended as a `## User instructions` section to the main summarization
 /// prompt (both the initial and the update variants). The split-turn
@@ -254,25 +337,36 @@ impl Summarizer for TransportSummarizer<'_> {
 ///   `run_proactive_compaction` in the actor) is best-effort: the error
 ///   is logged with `tracing::warn` and the conversation continues
 ///   uncompacted until the next opportunity.
+#[allow(clippy::too_many_arguments)]
 pub async fn compact(
     messages: &[Message],
     config: &CompactionConfig,
     agent_config: &AgentConfig,
     transport: &Arc<dyn Transport>,
+    reason: CompactionReason,
     previous_summary: Option<&str>,
     custom_instructions: Option<&str>,
     cancel: &CancellationToken,
-) -> Result<CompactionResult, String> {
-    let keep_recent_tokens = config
-        .keep_recent
-        .resolve(agent_config.model.context_window as u64);
+) -> Result<CompactionOutcome, String> {
+    let context_window = agent_config.model.context_window as u64;
+    let keep_recent_tokens = config.keep_recent.resolve(context_window);
+    let prune = config.prune_tool_outputs.then(|| PruneBudget {
+        min_chars: config.prune_min_chars,
+        target_tokens: (reason != CompactionReason::Manual)
+            .then(|| config.prune_target.resolve(context_window)),
+    });
     let summarizer = TransportSummarizer {
-        agent_config,
+        preferred: config
+            .summarizer_model
+            .as_ref()
+            .unwrap_or_else(|| agent_config.utility_model()),
+        main: &agent_config.model,
         transport,
     };
     compact_with_summarizer(
         messages,
         keep_recent_tokens,
+        prune,
         previous_summary,
         custom_instructions,
         &summarizer,
@@ -281,18 +375,19 @@ pub async fn compact(
     .await
 }
 
-/// Transport-free compaction orchestration: find the cut point,
+/// Transport-free compaction orchestration: find the cut point, prune,
 /// assemble the prompt(s), and stitch the summarizer's output into a
 /// [`CompactionResult`]. [`compact`] wraps this with the production
 /// [`TransportSummarizer`]; tests drive it with a stub.
 async fn compact_with_summarizer(
     messages: &[Message],
     keep_recent_tokens: u64,
+    prune: Option<PruneBudget>,
     previous_summary: Option<&str>,
     custom_instructions: Option<&str>,
     summarizer: &dyn Summarizer,
     cancel: &CancellationToken,
-) -> Result<CompactionResult, String> {
+) -> Result<CompactionOutcome, String> {
     let tokens_before = estimate_total_tokens(messages);
 
     if cancel.is_cancelled() {
@@ -306,7 +401,27 @@ async fn compact_with_summarizer(
         }
     })?;
 
-    let messages_to_summarize = &messages[..cut.first_kept_index];
+    // Stage one: elide stale tool output in the prefix. The kept
+    // suffix is never touched — the model is likely still using it.
+    let mut prefix = messages[..cut.first_kept_index].to_vec();
+    if let Some(budget) = prune {
+        let pruned = prune::prune_tool_outputs(&mut prefix, budget.min_chars);
+        if pruned > 0 {
+            let tokens_after = estimate_total_tokens(&prefix)
+                + estimate_total_tokens(&messages[cut.first_kept_index..]);
+            if budget.target_tokens.is_some_and(|t| tokens_after <= t) {
+                prefix.extend_from_slice(&messages[cut.first_kept_index..]);
+                return Ok(CompactionOutcome::Pruned {
+                    messages: prefix,
+                    pruned,
+                    tokens_before,
+                });
+            }
+        }
+    }
+
+    // Stage two: summarize the (pruned) prefix.
+    let messages_to_summarize = &prefix[..];
     let (read_files, modified_files) = file_ops::extract_file_operations(messages_to_summarize);
     let conversation_text = prompts::serialize_messages_for_summary(messages_to_summarize);
 
@@ -319,13 +434,15 @@ async fn compact_with_summarizer(
     );
 
     let mut full_summary = String::new();
+    let mut usage = Usage::default();
 
     if cut.is_split_turn {
         if let Some(turn_start) = cut.turn_start_index {
-            let turn_prefix = &messages[turn_start..cut.first_kept_index];
+            let turn_prefix = &prefix[turn_start..];
             let turn_prefix_text = prompts::serialize_messages_for_summary(turn_prefix);
             let turn_prompt = prompts::build_turn_prefix_prompt(&turn_prefix_text);
-            let turn_summary = summarizer.summarize(&turn_prompt, cancel).await?;
+            let (turn_summary, turn_usage) = summarizer.summarize(&turn_prompt, cancel).await?;
+            usage.accumulate(&turn_usage);
             full_summary.push_str("## Split Turn Context\n");
             full_summary.push_str(&turn_summary);
             full_summary.push_str("\n\n");
@@ -335,14 +452,16 @@ async fn compact_with_summarizer(
     if cancel.is_cancelled() {
         return Err("Compaction cancelled".into());
     }
-    let main_summary = summarizer.summarize(&prompt, cancel).await?;
+    let (main_summary, main_usage) = summarizer.summarize(&prompt, cancel).await?;
     full_summary.push_str(&main_summary);
+    usage.accumulate(&main_usage);
 
-    Ok(CompactionResult {
+    Ok(CompactionOutcome::Summarized(CompactionResult {
         summary: full_summary,
         first_kept_index: cut.first_kept_index,
         tokens_before,
-    })
+        usage,
+    }))
 }
 
 /// Apply a successful compaction result to a conversation: splice off
@@ -458,6 +577,28 @@ mod tests {
         assert_eq!(keep, 20_000);
     }
 
+    #[test]
+    fn summarizer_model_falls_back_when_window_too_small() {
+        let main = crate::test_utils::make_test_model();
+        let mut small = main.clone();
+        small.id = "small".into();
+        small.context_window = 8_000;
+
+        let short_prompt = "x".repeat(4 * 1_000);
+        assert_eq!(
+            pick_summarizer_model(&small, &main, &short_prompt).id,
+            "small"
+        );
+
+        // 8K window minus the 4096-token summary budget can't hold a
+        // 5K-token prompt.
+        let long_prompt = "x".repeat(4 * 5_000);
+        assert_eq!(
+            pick_summarizer_model(&small, &main, &long_prompt).id,
+            main.id
+        );
+    }
+
     #[test]
     fn apply_compaction_replaces_prefix() {
         let mut messages = vec![
@@ -474,6 +615,7 @@ mod tests {
                 summary: "Summary of old conversation".into(),
                 first_kept_index: 2,
                 tokens_before: 1000,
+                usage: Usage::default(),
             },
         );
         // summary + 2 recent
@@ -508,13 +650,27 @@ mod tests {
             &self,
             prompt: &str,
             _cancel: &CancellationToken,
-        ) -> Result<String, String> {
+        ) -> Result<(String, Usage), String> {
             self.prompts.lock().unwrap().push(prompt.to_string());
-            self.replies
+            let reply = self
+                .replies
                 .lock()
                 .unwrap()
                 .pop()
-                .ok_or_else(|| "stub exhausted".to_string())
+                .ok_or_else(|| "stub exhausted".to_string())?;
+            let usage = Usage {
+                input: prompt.len() as u64,
+                output: reply.len() as u64,
+                ..Usage::default()
+            };
+            Ok((reply, usage))
+        }
+    }
+
+    fn summarized(outcome: CompactionOutcome) -> CompactionResult {
+        match outcome {
+            CompactionOutcome::Summarized(r) => r,
+            CompactionOutcome::Pruned { .. } => panic!("expected a summary, got prune-only"),
         }
     }
 
@@ -536,16 +692,19 @@ mod tests {
 
         // keep_recent = 1 token → the rev-walk overshoots immediately
         // and the fallback keeps the last two messages.
-        let result = compact_with_summarizer(
-            &messages,
-            1,
-            None,
-            Some("Focus on file paths."),
-            &stub,
-            &cancel,
-        )
-        .await
-        .expect("compaction succeeds with stub");
+        let result = summarized(
+            compact_with_summarizer(
+                &messages,
+                1,
+                None,
+                None,
+                Some("Focus on file paths."),
+                &stub,
+                &cancel,
+            )
+            .await
+            .expect("compaction succeeds with stub"),
+        );
 
         assert_eq!(result.summary, "STUB SUMMARY");
         assert_eq!(result.first_kept_index, 2);
@@ -583,9 +742,11 @@ mod tests {
         let stub = StubSummarizer::new(&["TURN PREFIX", "MAIN SUMMARY"]);
         let cancel = CancellationToken::new();
 
-        let result = compact_with_summarizer(&messages, keep_recent, None, None, &stub, &cancel)
-            .await
-            .expect("split-turn compaction succeeds with stub");
+        let result = summarized(
+            compact_with_summarizer(&messages, keep_recent, None, None, None, &stub, &cancel)
+                .await
+                .expect("split-turn compaction succeeds with stub"),
+        );
 
         assert_eq!(result.first_kept_index, 5);
         assert_eq!(
@@ -597,5 +758,116 @@ mod tests {
         assert_eq!(prompts.len(), 2, "turn-prefix call then main call");
         assert!(prompts[0].contains("<partial-turn>"));
         assert!(prompts[1].contains("[User]: first task"));
+        assert_eq!(
+            result.usage.input,
+            (prompts[0].len() + prompts[1].len()) as u64,
+            "both calls' usage is counted"
+        );
+    }
+
+    fn big_tool_exchange(id: &str, body_chars: usize) -> [Message; 2] {
+        [
+            Message::Assistant {
+                content: vec![Content::tool_call(id, "bash", serde_json::json!({}))],
+                metadata: AssistantMetadata::default(),
+            },
+            Message::ToolResult {
+                tool_call_id: id.into(),
+                tool_name: "bash".into(),
+                content: vec![Content::text("x".repeat(body_chars))],
+                is_error: false,
+                timestamp: 0,
+            },
+        ]
+    }
+
+    /// Stale bulky tool output that pruning can clear: no summarizer
+    /// call, the kept suffix is untouched, and the old result becomes
+    /// a stub that still answers its tool call.
+    #[tokio::test]
+    async fn pruning_alone_skips_summarization() {
+        let mut messages = vec![user("run the tests")];
+        messages.extend(big_tool_exchange("old", 40_000));
+        messages.push(user("recent question"));
+        messages.push(assistant("recent answer"));
+        let stub = StubSummarizer::new(&[]);
+        let cancel = CancellationToken::new();
+        let budget = PruneBudget {
+            min_chars: 1_000,
+            target_tokens: Some(1_000),
+        };
+
+        let outcome =
+            compact_with_summarizer(&messages, 1, Some(budget), None, None, &stub, &cancel)
+                .await
+                .expect("prune-only compaction succeeds");
+
+        let CompactionOutcome::Pruned {
+            messages: pruned_messages,
+            pruned,
+            tokens_before,
+        } = outcome
+        else {
+            panic!("expected prune-only outcome");
+        };
+        assert_eq!(pruned, 1);
+        assert_eq!(tokens_before, estimate_total_tokens(&messages));
+        assert_eq!(pruned_messages.len(), messages.len());
+        assert!(pruned_messages[2].text().contains("output elided"));
+        assert_eq!(pruned_messages[4].text(), "recent answer");
+        assert!(stub.prompts.lock().unwrap().is_empty(), "no LLM call");
+    }
+
+    /// When pruning can't reach the target the pass falls back to
+    /// summarization — and the summarizer sees the stub, not the bulk.
+    #[tokio::test]
+    async fn insufficient_pruning_falls_back_to_summary_of_pruned_prefix() {
+        let mut messages = vec![user("run the tests")];
+        messages.extend(big_tool_exchange("old", 40_000));
+        messages.push(user("recent question"));
+        messages.push(assistant("recent answer"));
+        let stub = StubSummarizer::new(&["SUMMARY"]);
+        let cancel = CancellationToken::new();
+        let budget = PruneBudget {
+            min_chars: 1_000,
+            target_tokens: Some(1),
+        };
+
+        let result = summarized(
+            compact_with_summarizer(&messages, 1, Some(budget), None, None, &stub, &cancel)
+                .await
+                .expect("fallback summarization succeeds"),
+        );
+
+        assert_eq!(result.summary, "SUMMARY");
+        let prompts = stub.prompts.lock().unwrap();
+        assert!(prompts[0].contains("output elided"));
+        assert!(
+            prompts[0].len() < 10_000,
+            "bulky output must not reach the prompt"
+        );
+    }
+
+    /// A `None` target (manual compaction) always summarizes, even when
+    /// pruning alone would have been enough.
+    #[tokio::test]
+    async fn manual_budget_always_summarizes() {
+        let mut messages = vec![user("run the tests")];
+        messages.extend(big_tool_exchange("old", 40_000));
+        messages.push(user("recent question"));
+        messages.push(assistant("recent answer"));
+        let stub = StubSummarizer::new(&["SUMMARY"]);
+        let cancel = CancellationToken::new();
+        let budget = PruneBudget {
+            min_chars: 1_000,
+            target_tokens: None,
+        };
+
+        let result = summarized(
+            compact_with_summarizer(&messages, 1, Some(budget), None, None, &stub, &cancel)
+                .await
+                .expect("manual compaction succeeds"),
+        );
+        assert_eq!(result.summary, "SUMMARY");
     }
 }
@@ -0,0 +1,85 @@
+//! Tool-output pruning — the cheap first stage of compaction.
+//!
+//! Pure logic — no transport. Replaces the bodies of large, stale
+//! `ToolResult` messages with a short stub. The message itself stays
+//! in place with its `tool_call_id`, so every `tool_use` keeps its
+//! `tool_result` partner and `ensure_tool_result_pairing` has nothing
+//! to repair.
+
+use tau_ai::{Content, Message};
+
+/// Elide every `ToolResult` in `messages` whose content is larger than
+/// `min_chars`. Returns how many results were pruned.
+pub(super) fn prune_tool_outputs(messages: &mut [Message], min_chars: usize) -> usize {
+    let mut pruned = 0;
+    for message in messages.iter_mut() {
+        let Message::ToolResult {
+            tool_name, content, ..
+        } = message
+        else {
+            continue;
+        };
+        let chars = super::content_char_count(content);
+        if chars <= min_chars {
+            continue;
+        }
+        *content = vec![Content::text(stub_text(tool_name, chars))];
+        pruned += 1;
+    }
+    pruned
+}
+
+fn stub_text(tool_name: &str, chars: usize) -> String {
+    format!("[{tool_name} output elided during compaction ({chars} chars); re-run to see]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tau_ai::AssistantMetadata;
+
+    fn tool_result(id: &str, name: &str, body: &str) -> Message {
+        Message::ToolResult {
+            tool_call_id: id.into(),
+            tool_name: name.into(),
+            content: vec![Content::text(body)],
+            is_error: false,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn prunes_only_results_over_threshold() {
+        let mut messages = vec![
+            tool_result("a", "bash", &"x".repeat(100)),
+            tool_result("b", "read", "short"),
+        ];
+        assert_eq!(prune_tool_outputs(&mut messages, 50), 1);
+        assert_eq!(
+            messages[0].text(),
+            "[bash output elided during compaction (100 chars); re-run to see]"
+        );
+        assert_eq!(messages[1].text(), "short");
+    }
+
+    #[test]
+    fn pruning_keeps_tool_call_pairing_intact() {
+        let mut messages = vec![
+            Message::user("go"),
+            Message::Assistant {
+                content: vec![Content::tool_call("call-1", "grep", serde_json::json!({}))],
+                metadata: AssistantMetadata::default(),
+            },
+            tool_result("call-1", "grep", &"match\n".repeat(1000)),
+        ];
+        prune_tool_outputs(&mut messages, 10);
+
+        let mut repaired = messages.clone();
+        tau_ai::messages::ensure_tool_result_pairing(&mut repaired);
+        assert_eq!(repaired.len(), messages.len(), "no synthetic results added");
+        let Message::ToolResult { tool_call_id, .. } = &messages[2] else {
+            panic!("tool result must stay in place");
+        };
+        assert_eq!(tool_call_id, "call-1");
+    }
+}
@@ -7,6 +7,7 @@
 use tau_ai::{Model, ReasoningLevel};
 
 use crate::core::compaction::CompactionConfig;
+use crate::core::spill::ToolOutputSpill;
 
 /// Drain mode for the steering / follow-up queues.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,6 +26,9 @@ pub enum DequeueMode {
 pub struct AgentConfig {
     pub(crate) system_prompt: Option<String>,
     pub(crate) model: Model,
+    /// Cheaper model for compaction summaries, the only model call
+    /// the runtime makes on its own behalf. `None` = use `model`.
+    pub(crate) utility_model: Option<Model>,
     pub(crate) reasoning: ReasoningLevel,
     pub(crate) thinking_adaptive: bool,
     pub(crate) max_tokens: Option<u32>,
@@ -32,6 +36,9 @@ pub struct AgentConfig {
     /// when the limit is hit on a tool-call boundary.
     pub(crate) max_turns: Option<u32>,
     pub(crate) compaction: CompactionConfig,
+    /// `None` = tool results go into context at whatever size the tool
+    /// returned.
+    pub(crate) tool_output_spill: Option<ToolOutputSpill>,
     pub(crate) steering_mode: DequeueMode,
     pub(crate) follow_up_mode: DequeueMode,
     pub(crate) cache_scope: Option<String>,
@@ -59,6 +66,12 @@ impl AgentConfig {
     pub fn model(&self) -> &Model {
         &self.model
     }
+    /// Model for compaction summaries (unless
+    /// [`CompactionConfig::summarizer_model`] overrides it), falling
+    /// back to the main model when no utility model is configured.
+    pub fn utility_model(&self) -> &Model {
+        self.utility_model.as_ref().unwrap_or(&self.model)
+    }
     pub fn reasoning(&self) -> ReasoningLevel {
         self.reasoning
     }
@@ -74,6 +87,9 @@ impl AgentConfig {
     pub fn compaction(&self) -> &CompactionConfig {
         &self.compaction
     }
+    pub fn tool_output_spill(&self) -> Option<&ToolOutputSpill> {
+        self.tool_output_spill.as_ref()
+    }
     pub fn steering_mode(&self) -> DequeueMode {
         self.steering_mode
     }
@@ -106,11 +122,13 @@ impl AgentConfigBuilder {
             inner: AgentConfig {
                 system_prompt: None,
                 model,
+                utility_model: None,
                 reasoning: ReasoningLevel::default(),
                 thinking_adaptive: false,
                 max_tokens: None,
                 max_turns: None,
                 compaction: CompactionConfig::default(),
+                tool_output_spill: None,
                 steering_mode: DequeueMode::All,
                 follow_up_mode: DequeueMode::All,
                 cache_scope: None,
@@ -130,6 +148,16 @@ impl AgentConfigBuilder {
         self
     }
 
+    /// Cheaper model for compaction summaries. Nothing else calls a
+    /// model on the agent's behalf: the plan-mode context digest is
+    /// built from the messages without one, and sessions have no
+    /// generated titles. Should be served by the same transport as the
+    /// main model.
+    pub fn utility_model(mut self, model: Model) -> Self {
+        self.inner.utility_model = Some(model);
+        self
+    }
+
     pub fn reasoning(mut self, level: ReasoningLevel) -> Self {
         self.inner.reasoning = level;
         self
@@ -157,6 +185,13 @@ impl AgentConfigBuilder {
         self
     }
 
+    /// Spill tool results over the configured size to disk, leaving a
+    /// preview and a path the model can page through with `read`.
+    pub fn tool_output_spill(mut self, spill: ToolOutputSpill) -> Self {
+        self.inner.tool_output_spill = Some(spill);
+        self
+    }
+
     pub fn steering_mode(mut self, mode: DequeueMode) -> Self {
         self.inner.steering_mode = mode;
         self
@@ -0,0 +1,268 @@
+//! Goal mode: keep prompting until a check command passes.
+//!
+//! [`AgentHandle::prompt_until`](crate::AgentHandle::prompt_until)
+//! sends the initial prompt, and each time the agent finishes (a turn
+//! with no tool calls) runs [`GoalConfig::check_command`]. A failing
+//! check's output is fed back as the next prompt; the loop stops on
+//! success, after [`GoalConfig::max_iterations`], once the token
+//! budget is spent, or when the agent is aborted. Progress is reported
+//! on the agent's own event channel as
+//! [`AgentEvent::GoalCheck`](crate::AgentEvent::GoalCheck) and
+//! [`AgentEvent::GoalEnd`](crate::AgentEvent::GoalEnd).
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Characters of check output kept — from the tail, where test
+/// runners and compilers put their verdicts.
+const CHECK_OUTPUT_TAIL_CHARS: usize = 8_000;
+
+/// Bytes of each output stream held while the check runs; older
+/// output is dropped as more arrives.
+const CHECK_STREAM_TAIL_BYTES: usize = 64 * 1024;
+
+/// Goal-mode settings for [`AgentHandle::prompt_until`](crate::AgentHandle::prompt_until).
+#[derive(Debug, Clone)]
+pub struct GoalConfig {
+    /// Shell command whose zero exit status means "done", e.g.
+    /// `cargo test -p foo`. Run with `sh -c`.
+    pub check_command: String,
+    /// Working directory for the check. `None` = the agent's cwd.
+    pub cwd: Option<PathBuf>,
+    /// Maximum number of prompt → check rounds, including the first.
+    pub max_iterations: u32,
+    /// Stop once the prompts in this goal have used this many tokens
+    /// (input + output). `None` = unlimited.
+    pub token_budget: Option<u64>,
+    /// Kill the check command after this long and count it as a failure.
+    pub check_timeout: Duration,
+}
+
+impl GoalConfig {
+    pub fn new(check_command: impl Into<String>) -> Self {
+        Self {
+            check_command: check_command.into(),
+            cwd: None,
+            max_iterations: 5,
+            token_budget: None,
+            check_timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Result of one run of the check command.
+#[derive(Debug, Clone)]
+pub(crate) struct CheckRun {
+    pub(crate) passed: bool,
+    pub(crate) exit_code: Option<i32>,
+    /// Tail of combined stdout + stderr.
+    pub(crate) output: String,
+}
+
+/// Run the check in its own process group, so that on timeout — or
+/// when the goal is abandoned mid-check — everything it started is
+/// killed, not just the shell.
+pub(crate) async fn run_check(goal: &GoalConfig) -> CheckRun {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c")
+        .arg(&goal.check_command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if let Some(cwd) = &goal.cwd {
+        cmd.current_dir(cwd);
+    }
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            return CheckRun {
+                passed: false,
+                exit_code: None,
+                output: format!("Failed to spawn check command: {e}"),
+            };
+        }
+    };
+    let group = ProcessGroup(child.id());
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let run = async {
+        let (stdout, stderr) = tokio::join!(read_tail(stdout), read_tail(stderr));
+        child.wait().await.map(|status| (status, stdout, stderr))
+    };
+    let outcome = tokio::time::timeout(goal.check_timeout, run).await;
+    drop(group);
+    match outcome {
+        Ok(Ok((status, stdout, stderr))) => {
+            let mut text = String::from_utf8_lossy(&stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&stderr));
+            CheckRun {
+                passed: status.success(),
+                exit_code: status.code(),
+                output: tail(&text, CHECK_OUTPUT_TAIL_CHARS),
+            }
+        }
+        Ok(Err(e)) => CheckRun {
+            passed: false,
+            exit_code: None,
+            output: format!("Failed to run check command: {e}"),
+        },
+        Err(_) => CheckRun {
+            passed: false,
+            exit_code: None,
+            output: format!(
+                "Check command timed out after {} seconds",
+                goal.check_timeout.as_secs()
+            ),
+        },
+    }
+}
+
+/// Kills the check's process group — the shell and everything it
+/// started — when dropped.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.0.and_then(|id| libc::pid_t::try_from(id).ok()) {
+            // SAFETY: kill(2) takes no pointers. A negative pid names
+            // the group; its id isn't reused while any member lives.
+            unsafe {
+                libc::kill(-pgid, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Everything `stream` yields until it closes, keeping only the last
+/// [`CHECK_STREAM_TAIL_BYTES`] or so.
+async fn read_tail(mut stream: impl AsyncRead + Unpin) -> Vec<u8> {
+    let mut kept = Vec::new();
+    let mut buf = [0; 8 * 1024];
+    while let Ok(n @ 1..) = stream.read(&mut buf).await {
+        kept.extend_from_slice(&buf[..n]);
+        if kept.len() > 2 * CHECK_STREAM_TAIL_BYTES {
+            kept.drain(..kept.len() - CHECK_STREAM_TAIL_BYTES);
+        }
+    }
+    kept
+}
+
+/// The follow-up prompt for a failed check.
+pub(crate) fn feedback_prompt(goal: &GoalConfig, check: &CheckRun) -> String {
+    let status = match check.exit_code {
+        Some(code) => format!("exited with code {code}"),
+        None => "did not complete".to_string(),
+    };
+    format!(
+        "The check `{}` {status}. Keep working until it passes.\n\n<check-output>\n{}\n</check-output>",
+        goal.check_command,
+        check.output.trim_end()
+    )
+}
+
+fn tail(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(total - max_chars).collect();
+    format!("... [{} chars omitted]\n{kept}", total - max_chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn check_reports_exit_status_and_output() {
+        let pass = run_check(&GoalConfig::new("echo ok")).await;
+        assert!(pass.passed);
+        assert_eq!(pass.exit_code, Some(0));
+        assert_eq!(pass.output, "ok\n");
+
+        let fail = run_check(&GoalConfig::new("echo broken >&2; exit 3")).await;
+        assert!(!fail.passed);
+        assert_eq!(fail.exit_code, Some(3));
+        assert_eq!(fail.output, "broken\n");
+    }
+
+    #[tokio::test]
+    async fn slow_check_times_out_as_failure() {
+        let mut goal = GoalConfig::new("sleep 5");
+        goal.check_timeout = Duration::from_millis(50);
+        let run = run_check(&goal).await;
+        assert!(!run.passed);
+        assert_eq!(run.exit_code, None);
+        assert!(run.output.contains("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn timeout_kills_what_the_check_started() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let mut goal =
+            GoalConfig::new(format!("sleep 30 & echo $! > {}; wait", pid_file.display()));
+        goal.check_timeout = Duration::from_millis(200);
+        let run = run_check(&goal).await;
+        assert!(run.output.contains("timed out"));
+
+        let pid: libc::pid_t = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        // Killed, though maybe not yet reaped by init.
+        let mut gone = false;
+        for _ in 0..50 {
+            let status = std::fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+            if status.is_empty() || status.contains(") Z ") {
+                gone = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(gone, "background sleep {pid} survived");
+    }
+
+    #[tokio::test]
+    async fn huge_output_keeps_only_its_tail() {
+        let run = run_check(&GoalConfig::new(
+            "head -c 5000000 /dev/zero | tr '\\0' x; echo END",
+        ))
+        .await;
+        assert!(run.passed);
+        assert!(
+            run.output.ends_with("xEND\n"),
+            "{}",
+            &run.output[run.output.len() - 20..]
+        );
+        assert!(run.output.chars().count() < CHECK_OUTPUT_TAIL_CHARS + 100);
+    }
+
+    #[test]
+    fn feedback_includes_command_and_output_tail() {
+        let goal = GoalConfig::new("cargo test");
+        let check = CheckRun {
+            passed: false,
+            exit_code: Some(101),
+            output: "test foo ... FAILED\n".into(),
+        };
+        let prompt = feedback_prompt(&goal, &check);
+        assert!(prompt.starts_with("The check `cargo test` exited with code 101."));
+        assert!(prompt.contains("<check-output>\ntest foo ... FAILED\n</check-output>"));
+    }
+
+    #[test]
+    fn tail_keeps_the_end() {
+        assert_eq!(tail("abcdef", 10), "abcdef");
+        assert_eq!(tail("abcdef", 2), "... [4 chars omitted]\nef");
+    }
+}
@@ -17,6 +17,7 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use parking_lot::Mutex;
+use tau_history::History;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
@@ -24,11 +25,13 @@ use crate::core::approval::ApprovalPolicy;
 use crate::core::command::{Command, PromptResult};
 use crate::core::compaction::CompactionConfig;
 use crate::core::config::AgentConfig;
+use crate::core::goal::{self, GoalConfig};
 use crate::core::state::Shared;
 use crate::types::conversation::Conversation;
 use crate::types::error::{Error, Result};
-use crate::types::events::AgentEvent;
+use crate::types::events::{AgentEvent, GoalOutcome};
 use crate::types::info::{ContextStats, ToolInfo};
+use crate::types::rewind::{RewindTarget, Rewound};
 
 /// Channel-based handle into a running agent. **Pure-core**: this type
 /// does not know about the fleet. Spec transitions (`respec` /
@@ -46,12 +49,21 @@ impl AgentHandle {
     // ─── Prompts ─────────────────────────────────────────────────────
 
     pub async fn prompt(&self, input: &str) -> Result<oneshot::Receiver<PromptResult>> {
+        self.send_prompt(input, false).await
+    }
+
+    async fn send_prompt(
+        &self,
+        input: &str,
+        reprompt: bool,
+    ) -> Result<oneshot::Receiver<PromptResult>> {
         let (reply_tx, reply_rx) = oneshot::channel();
         let content = vec![tau_ai::Content::text(input)];
         if self
             .normal_tx
             .send(Command::Prompt {
                 content,
+                reprompt,
                 reply: reply_tx,
             })
             .await
@@ -65,6 +77,19 @@ impl AgentHandle {
     /// Convenience: send prompt and block until completion.
     pub async fn prompt_and_wait(&self, input: &str) -> Result<()> {
         let rx = self.prompt(input).await?;
+        self.wait_prompt(rx).await
+    }
+
+    /// [`prompt_and_wait`](Self::prompt_and_wait) for a prompt the
+    /// host generated rather than the user typed. Its turn is
+    /// committed as a [`CommitKind::Reprompt`](tau_history::CommitKind::Reprompt),
+    /// so [`user_turns`](Self::user_turns) doesn't offer it.
+    pub(crate) async fn reprompt_and_wait(&self, input: &str) -> Result<()> {
+        let rx = self.send_prompt(input, true).await?;
+        self.wait_prompt(rx).await
+    }
+
+    async fn wait_prompt(&self, rx: oneshot::Receiver<PromptResult>) -> Result<()> {
         match rx.await {
             Ok(r) => r.result,
             Err(_) => Err(self
@@ -73,6 +98,92 @@ impl AgentHandle {
         }
     }
 
+    /// Goal mode: send `input`, then after each completed round run
+    /// `goal.check_command`, feeding failing output back as the next
+    /// prompt until the check passes, `goal.max_iterations` rounds have
+    /// run, or `goal.token_budget` is spent. Emits
+    /// [`AgentEvent::GoalCheck`] per round and one
+    /// [`AgentEvent::GoalEnd`]. An [`abort`](Self::abort) or
+    /// [`interrupt`](Self::interrupt) — during a round or while the
+    /// check runs — ends the goal with [`GoalOutcome::Aborted`]; other
+    /// prompt errors are returned as-is. The check runs in the agent's
+    /// cwd unless `goal.cwd` says otherwise.
+    pub async fn prompt_until(&self, input: &str, goal: &GoalConfig) -> Result<GoalOutcome> {
+        let mut goal = goal.clone();
+        if goal.cwd.is_none() {
+            goal.cwd = self.shared.cwd.get().cloned();
+        }
+        let tokens_at_start = self.tokens_used().await;
+        let mut prompt = input.to_string();
+        let mut iteration = 0;
+        let outcome = loop {
+            iteration += 1;
+            let mut events = self.subscribe();
+            let result = if iteration == 1 {
+                self.prompt_and_wait(&prompt).await
+            } else {
+                self.reprompt_and_wait(&prompt).await
+            };
+            // The prompt's token stays current until the next prompt
+            // starts, so an abort between rounds still lands on it.
+            let cancel = self.shared.cancel.lock().clone();
+            if cancel.is_cancelled() || ended_interrupted(&mut events) {
+                break GoalOutcome::Aborted {
+                    iterations: iteration - 1,
+                };
+            }
+            result?;
+
+            let check = tokio::select! {
+                check = goal::run_check(&goal) => check,
+                () = cancel.cancelled() => {
+                    break GoalOutcome::Aborted {
+                        iterations: iteration - 1,
+                    };
+                }
+            };
+            let _ = self.event_tx.send(AgentEvent::GoalCheck {
+                iteration,
+                max_iterations: goal.max_iterations,
+                passed: check.passed,
+                exit_code: check.exit_code,
+                output: check.output.clone(),
+            });
+            if check.passed {
+                break GoalOutcome::Passed {
+                    iterations: iteration,
+                };
+            }
+            if iteration >= goal.max_iterations {
+                break GoalOutcome::MaxIterations {
+                    iterations: iteration,
+                };
+            }
+            let tokens_used = self.tokens_used().await.saturating_sub(tokens_at_start);
+            if goal
+                .token_budget
+                .is_some_and(|budget| tokens_used >= budget)
+            {
+                break GoalOutcome::BudgetExhausted {
+                    iterations: iteration,
+                    tokens_used,
+                };
+            }
+            prompt = goal::feedback_prompt(&goal, &check);
+        };
+        let _ = self.event_tx.send(AgentEvent::GoalEnd {
+            outcome: outcome.clone(),
+        });
+        Ok(outcome)
+    }
+
+    async fn tokens_used(&self) -> u64 {
+        self.state()
+            .await
+            .map(|c| c.total_usage.input + c.total_usage.output)
+            .unwrap_or(0)
+    }
+
     // ─── Steer / follow-up (urgent) ──────────────────────────────────
 
     pub fn try_steer(&self, message: tau_ai::Message) -> Result<()> {
@@ -166,6 +277,85 @@ impl AgentHandle {
         Ok(reply_rx)
     }
 
+    // ─── Going back ──────────────────────────────────────────────────
+
+    /// The conversation's user prompts, oldest first, read from its
+    /// [`History`](tau_history::History). Turn `n` in
+    /// [`rewind`](Self::rewind) and [`fork_at`](Self::fork_at) is the
+    /// `n`th entry, counting from 1. Empty if the history can't go
+    /// back. Fails with [`Error::Busy`] while a prompt is in flight.
+    pub async fn user_turns(&self) -> Result<Vec<tau_history::UserTurn>> {
+        let (tx, rx) = oneshot::channel();
+        if self.normal_tx.send(Command::UserTurns(tx)).await.is_err() {
+            return Err(self.dead_actor_error("Agent task has shut down").await);
+        }
+        match rx.await {
+            Ok(turns) => turns,
+            Err(_) => Err(self
+                .dead_actor_error("Agent task dropped without responding")
+                .await),
+        }
+    }
+
+    /// Reset the conversation to `to` — just before a turn's prompt,
+    /// or an exact commit — so the next prompt continues from there.
+    /// The history moves back in place; the line it leaves stays
+    /// reachable as [`Rewound::abandoned`], to compare against or
+    /// rewind to again. The file-access tracker is rebuilt from the
+    /// remaining messages. Fails with [`Error::Busy`] while a prompt
+    /// is in flight and [`Error::NoSuchTurn`] for a turn out of range.
+    pub async fn rewind(&self, to: impl Into<RewindTarget>) -> Result<Rewound> {
+        self.go_back(to.into(), false).await
+    }
+
+    /// Like [`rewind`](Self::rewind), but continue on a new history
+    /// forked at `at`, leaving the current one untouched — a host
+    /// sharing the old history keeps seeing it unchanged.
+    pub async fn fork_at(&self, at: impl Into<RewindTarget>) -> Result<Rewound> {
+        self.go_back(at.into(), true).await
+    }
+
+    /// Continue on `history` instead — typically a branch the host
+    /// forked itself at a point from [`user_turns`](Self::user_turns),
+    /// so it can name it. The current history is left untouched and
+    /// its head reported as [`Rewound::abandoned`]. Fails with
+    /// [`Error::Busy`] while a prompt is in flight.
+    pub async fn switch_history(&self, history: Arc<dyn History>) -> Result<Rewound> {
+        let (reply, rx) = oneshot::channel();
+        if self
+            .normal_tx
+            .send(Command::SwitchHistory { history, reply })
+            .await
+            .is_err()
+        {
+            return Err(self.dead_actor_error("Agent task has shut down").await);
+        }
+        match rx.await {
+            Ok(rewound) => rewound,
+            Err(_) => Err(self
+                .dead_actor_error("Agent task dropped without responding")
+                .await),
+        }
+    }
+
+    async fn go_back(&self, to: RewindTarget, fork: bool) -> Result<Rewound> {
+        let (reply, rx) = oneshot::channel();
+        if self
+            .normal_tx
+            .send(Command::Rewind { to, fork, reply })
+            .await
+            .is_err()
+        {
+            return Err(self.dead_actor_error("Agent task has shut down").await);
+        }
+        match rx.await {
+            Ok(rewound) => rewound,
+            Err(_) => Err(self
+                .dead_actor_error("Agent task dropped without responding")
+                .await),
+        }
+    }
+
     // ─── Subscribe / read ────────────────────────────────────────────
 
     pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
@@ -320,3 +510,17 @@ impl AgentHandle {
         }
     }
 }
+
+/// Whether the prompt that just finished was stopped by
+/// [`AgentHandle::interrupt`]. `events` was subscribed before the prompt
+/// was sent; its `AgentEnd` is the newest event, so lagging cannot drop it.
+fn ended_interrupted(events: &mut broadcast::Receiver<AgentEvent>) -> bool {
+    let mut interrupted = false;
+    loop {
+        match events.try_recv() {
+            Ok(AgentEvent::AgentEnd { interrupted: i, .. }) => interrupted = i,
+            Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+            Err(_) => return interrupted,
+        }
+    }
+}
@@ -11,9 +11,12 @@ pub mod builder;
 pub mod command;
 pub mod compaction;
 pub mod config;
+pub mod goal;
 pub mod handle;
 pub mod interaction;
 pub mod overflow;
+pub mod replay;
+pub mod spill;
 pub mod state;
 pub mod stream;
 pub mod tool;
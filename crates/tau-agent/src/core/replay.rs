@@ -0,0 +1,456 @@
+//! Deterministic replay of a recorded conversation.
+//!
+//! [`Replay`] re-drives a fresh actor through a stored message log up
+//! to turn N, then lets it continue live. Turn `k` is the `k`-th
+//! assistant message in the log (one model call each). Turns before N
+//! come from the recording:
+//!
+//! - [`Replay::transport`] wraps the live transport and serves each
+//!   recorded assistant message instead of calling the model. Replayed
+//!   turns report zero usage — no call was made — so they never trip
+//!   proactive compaction.
+//! - [`Replay::wrap_tools`] wraps every tool so a call the recording
+//!   answered returns the recorded result without executing. Unrecorded
+//!   calls are refused until the run goes live; nothing has side
+//!   effects before turn N.
+//! - User messages, steering and subagent notifications that arrived
+//!   mid-run are steered back in when the transport serves the turn
+//!   they followed. The actor drains steering ahead of stream events,
+//!   so they land at the same point in history every time.
+//!
+//! The whole replay runs as one prompt ([`Replay::prompt`]); from turn
+//! N the transport falls through to the live one, optionally after a
+//! replacement prompt. Prompts are replayed as text — image blocks in
+//! the first prompt are dropped.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use futures::stream as fstream;
+use parking_lot::Mutex;
+use serde_json::Value;
+use tau_ai::{Message, Usage};
+use tokio_util::sync::CancellationToken;
+
+use crate::core::approval::ToolRisk;
+use crate::core::builder::AgentSeed;
+use crate::core::handle::AgentHandle;
+use crate::core::tool::{BoxedTool, Concurrency, ExecutionContext, Tool, ToolCategory, ToolResult};
+use crate::core::transport::{AgentEventStream, AgentRunConfig, Transport};
+use crate::types::error::{Error, Result};
+use crate::types::events::AgentEvent;
+
+/// One recorded model call and whatever was injected after its tool
+/// results, before the next call.
+struct RecordedTurn {
+    message: Message,
+    inject_after: Vec<Message>,
+}
+
+struct RecordedResult {
+    tool_name: String,
+    arguments: Value,
+    result: ToolResult,
+}
+
+/// State shared by the replay transport and tool wrappers.
+struct Script {
+    turns: Vec<RecordedTurn>,
+    /// `None` once a tool call has consumed it.
+    results: Mutex<Vec<Option<RecordedResult>>>,
+    cursor: AtomicUsize,
+    live: AtomicBool,
+    /// Steers injections into the actor. Released once the last
+    /// recorded turn is served, so the transport (which the actor
+    /// owns) doesn't keep the actor's channels open.
+    handle: Mutex<Option<AgentHandle>>,
+}
+
+impl Script {
+    fn has_recorded(&self, tool_name: &str, arguments: &Value) -> bool {
+        self.results
+            .lock()
+            .iter()
+            .flatten()
+            .any(|r| r.tool_name == tool_name && r.arguments == *arguments)
+    }
+
+    fn take_recorded(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let mut results = self.results.lock();
+        let slot = results.iter_mut().find(|slot| {
+            slot.as_ref()
+                .is_some_and(|r| r.tool_name == tool_name && r.arguments == *arguments)
+        })?;
+        slot.take().map(|r| r.result)
+    }
+}
+
+/// A recorded conversation prepared for replay. See the module docs.
+pub struct Replay {
+    script: Arc<Script>,
+    seed: Vec<Message>,
+    prompt: String,
+    recorded_turns: usize,
+}
+
+impl Replay {
+    /// Prepare to replay `messages` up to (not including) turn
+    /// `from_turn`, which runs live. `prompt` replaces the user
+    /// message that preceded turn `from_turn`, or is steered in before
+    /// it when that turn continued a run.
+    ///
+    /// `from_turn` ranges over `1..=recorded turns + 1`; the upper
+    /// bound replays everything and continues after the last turn.
+    pub fn new(messages: Vec<Message>, from_turn: usize, prompt: Option<String>) -> Result<Self> {
+        let (leading, mut turns, results) = split_turns(messages);
+        let recorded_turns = turns.len();
+        if from_turn == 0 || from_turn > recorded_turns + 1 {
+            return Err(Error::ReplayFailed {
+                reason: format!(
+                    "turn {from_turn} is out of range (the log has {recorded_turns} turns)"
+                ),
+            });
+        }
+
+        let Some(prompt_index) = leading
+            .iter()
+            .rposition(|m| matches!(m, Message::User { .. }))
+        else {
+            return Err(Error::ReplayFailed {
+                reason: "the log has no user prompt before its first turn".into(),
+            });
+        };
+        let mut seed = leading;
+        let first_prompt = seed.remove(prompt_index).text();
+
+        turns.truncate(from_turn - 1);
+        let prompt = match (prompt, turns.last_mut()) {
+            (Some(p), None) => p,
+            (Some(p), Some(last)) => {
+                let replacement = Message::user(p);
+                match last
+                    .inject_after
+                    .iter()
+                    .rposition(|m| matches!(m, Message::User { .. }))
+                {
+                    Some(i) => last.inject_after[i] = replacement,
+                    None => last.inject_after.push(replacement),
+                }
+                first_prompt
+            }
+            (None, _) => first_prompt,
+        };
+
+        // Only the replayed turns' tool results are substitutable.
+        let results = results
+            .into_iter()
+            .filter(|(turn, _)| *turn < turns.len())
+            .map(|(_, r)| Some(r))
+            .collect();
+        Ok(Self {
+            script: Arc::new(Script {
+                turns,
+                results: Mutex::new(results),
+                cursor: AtomicUsize::new(0),
+                live: AtomicBool::new(false),
+                handle: Mutex::new(None),
+            }),
+            seed,
+            prompt,
+            recorded_turns,
+        })
+    }
+
+    /// History before the first prompt (e.g. a compaction summary).
+    pub fn seed(&self) -> AgentSeed {
+        if self.seed.is_empty() {
+            AgentSeed::Empty
+        } else {
+            AgentSeed::Messages {
+                messages: self.seed.clone(),
+                previous_summary: None,
+            }
+        }
+    }
+
+    /// The prompt that starts the replay.
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Turns that will be served from the recording.
+    pub fn replayed_turns(&self) -> usize {
+        self.script.turns.len()
+    }
+
+    /// Turns in the whole recording.
+    pub fn recorded_turns(&self) -> usize {
+        self.recorded_turns
+    }
+
+    /// Whether the transport has made its first live model call.
+    pub fn is_live(&self) -> bool {
+        self.script.live.load(Ordering::Acquire)
+    }
+
+    /// Wrap `live` so recorded turns are served first. Pass the result
+    /// to [`AgentBuilder::new`](crate::AgentBuilder::new).
+    pub fn transport(&self, live: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(ReplayTransport {
+            script: Arc::clone(&self.script),
+            live,
+        })
+    }
+
+    /// Wrap `tools` so recorded calls return their recorded results.
+    pub fn wrap_tools(&self, tools: &[BoxedTool]) -> Vec<BoxedTool> {
+        tools
+            .iter()
+            .map(|inner| {
+                Arc::new(ReplayTool {
+                    inner: Arc::clone(inner),
+                    script: Arc::clone(&self.script),
+                }) as BoxedTool
+            })
+            .collect()
+    }
+
+    /// Give the transport the handle it steers injections through.
+    /// Call after spawning and before sending [`Replay::prompt`].
+    pub fn attach(&self, handle: &AgentHandle) {
+        if !self.script.turns.is_empty() {
+            *self.script.handle.lock() = Some(handle.clone());
+        }
+    }
+}
+
+/// Split a log into the messages before the first assistant turn, the
+/// turns, and each turn's tool results tagged with its turn index.
+fn split_turns(
+    messages: Vec<Message>,
+) -> (
+    Vec<Message>,
+    Vec<RecordedTurn>,
+    Vec<(usize, RecordedResult)>,
+) {
+    let mut leading = Vec::new();
+    let mut turns: Vec<RecordedTurn> = Vec::new();
+    let mut results = Vec::new();
+    for message in messages {
+        match message {
+            Message::Assistant { .. } => turns.push(RecordedTurn {
+                message,
+                inject_after: Vec::new(),
+            }),
+            Message::ToolResult {
+                tool_call_id,
+                tool_name,
+                content,
+                is_error,
+                ..
+            } => {
+                let Some(turn) = turns.len().checked_sub(1) else {
+                    continue;
+                };
+                let arguments = turns[turn]
+                    .message
+                    .tool_calls()
+                    .into_iter()
+                    .find(|(id, _, _)| *id == tool_call_id)
+                    .map(|(_, _, args)| args.clone());
+                if let Some(arguments) = arguments {
+                    results.push((
+                        turn,
+                        RecordedResult {
+                            tool_name,
+                            arguments,
+                            result: ToolResult {
+                                content,
+                                is_error,
+                                details: None,
+                            },
+                        },
+                    ));
+                }
+            }
+            Message::User { .. } | Message::SystemInjection { .. } => match turns.last_mut() {
+                Some(turn) => turn.inject_after.push(message),
+                None => leading.push(message),
+            },
+        }
+    }
+    (leading, turns, results)
+}
+
+struct ReplayTransport {
+    script: Arc<Script>,
+    live: Arc<dyn Transport>,
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn run(
+        &self,
+        messages: Vec<Message>,
+        config: &AgentRunConfig,
+        cancel: CancellationToken,
+    ) -> tau_ai::Result<AgentEventStream> {
+        let index = self.script.cursor.fetch_add(1, Ordering::AcqRel);
+        let Some(turn) = self.script.turns.get(index) else {
+            self.script.live.store(true, Ordering::Release);
+            return self.live.run(messages, config, cancel).await;
+        };
+
+        // Queued before the stream is polled, so the actor enqueues
+        // them during this turn and drains them right after it.
+        {
+            let mut handle = self.script.handle.lock();
+            if let Some(h) = handle.as_ref() {
+                for message in &turn.inject_after {
+                    if let Err(e) = h.try_steer(message.clone()) {
+                        tracing::warn!("replay: could not inject recorded message: {e}");
+                    }
+                }
+            }
+            if index + 1 == self.script.turns.len() {
+                handle.take();
+            }
+        }
+
+        let turn_number = config.turn_number;
+        let events = vec![
+            AgentEvent::TurnStart { turn_number },
+            AgentEvent::MessageEnd {
+                message: turn.message.clone(),
+            },
+            AgentEvent::TurnEnd {
+                turn_number,
+                message: turn.message.clone(),
+                usage: Usage::default(),
+            },
+        ];
+        Ok(Box::pin(fstream::iter(events)))
+    }
+}
+
+struct ReplayTool {
+    inner: BoxedTool,
+    script: Arc<Script>,
+}
+
+#[async_trait]
+impl Tool for ReplayTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn label(&self) -> &str {
+        self.inner.label()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.inner.parameters_schema()
+    }
+
+    fn concurrency(&self) -> Concurrency {
+        self.inner.concurrency()
+    }
+
+    fn activity_description(&self, arguments: &Value) -> String {
+        self.inner.activity_description(arguments)
+    }
+
+    /// Recorded calls never execute, so they never need approval.
+    fn risk(&self, arguments: &Value) -> ToolRisk {
+        if self.script.has_recorded(self.inner.name(), arguments) {
+            ToolRisk::Safe
+        } else {
+            self.inner.risk(arguments)
+        }
+    }
+
+    fn risk_reasons(&self, arguments: &Value) -> Vec<String> {
+        if self.script.has_recorded(self.inner.name(), arguments) {
+            Vec::new()
+        } else {
+            self.inner.risk_reasons(arguments)
+        }
+    }
+
+    fn category(&self) -> ToolCategory {
+        self.inner.category()
+    }
+
+    async fn execute(&self, arguments: Value, ctx: ExecutionContext) -> ToolResult {
+        if let Some(result) = self.script.take_recorded(self.inner.name(), &arguments) {
+            return result;
+        }
+        if !self.script.live.load(Ordering::Acquire) {
+            return ToolResult::error("Not executed: replay has no recorded result for this call");
+        }
+        self.inner.execute(arguments, ctx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_assistant_message, make_tool_call_message};
+
+    fn tool_result(id: &str, text: &str) -> Message {
+        Message::ToolResult {
+            tool_call_id: id.into(),
+            tool_name: "bash".into(),
+            content: vec![tau_ai::Content::text(text)],
+            is_error: false,
+            timestamp: 0,
+        }
+    }
+
+    fn log() -> Vec<Message> {
+        vec![
+            Message::user("list files"),
+            make_tool_call_message("bash", "c1", serde_json::json!({ "command": "ls" })),
+            tool_result("c1", "a.rs"),
+            Message::user("only rust files"),
+            make_assistant_message("a.rs"),
+            Message::user("thanks"),
+            make_assistant_message("welcome"),
+        ]
+    }
+
+    #[test]
+    fn override_replaces_the_prompt_before_the_live_turn() {
+        let replay = Replay::new(log(), 3, Some("and tests?".into())).unwrap();
+        assert_eq!(replay.prompt(), "list files");
+        assert_eq!(replay.replayed_turns(), 2);
+        assert_eq!(replay.recorded_turns(), 3);
+        let injected = &replay.script.turns[1].inject_after;
+        assert_eq!(injected.len(), 1);
+        assert_eq!(injected[0].text(), "and tests?");
+    }
+
+    #[test]
+    fn only_replayed_turns_keep_recorded_results() {
+        let replay = Replay::new(log(), 1, Some("start over".into())).unwrap();
+        assert_eq!(replay.prompt(), "start over");
+        assert!(
+            !replay
+                .script
+                .has_recorded("bash", &serde_json::json!({ "command": "ls" }))
+        );
+
+        let replay = Replay::new(log(), 2, None).unwrap();
+        assert!(
+            replay
+                .script
+                .has_recorded("bash", &serde_json::json!({ "command": "ls" }))
+        );
+        assert!(Replay::new(log(), 5, None).is_err());
+    }
+}
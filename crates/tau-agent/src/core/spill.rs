@@ -0,0 +1,203 @@
+//! Oversized tool-result spilling.
+//!
+//! When a [`ToolResult`]'s text exceeds [`ToolOutputSpill::max_chars`],
+//! the runtime writes the full text to the spill directory and hands
+//! the model a head+tail preview plus the file's path. The model pages
+//! through the rest with the `read` tool's `byte_offset` — by bytes
+//! rather than lines, since `read` cuts long lines and a minified blob
+//! can be one line — in pages that stay under the limit, so long test
+//! logs and build outputs stay recoverable without landing in context
+//! wholesale.
+
+use std::path::{Path, PathBuf};
+
+use tau_ai::Content;
+
+use crate::core::tool::ToolResult;
+
+/// The `read` tool cuts lines at this many characters.
+const READ_MAX_LINE_CHARS: usize = 2_000;
+
+/// Room left on a `read` page for its line-number column and notices.
+const READ_PAGE_OVERHEAD: usize = 500;
+
+/// Where and when to spill oversized tool results. Set on the agent via
+/// [`AgentConfigBuilder::tool_output_spill`](crate::AgentConfigBuilder::tool_output_spill).
+#[derive(Debug, Clone)]
+pub struct ToolOutputSpill {
+    /// Session-scoped directory for spilled outputs. Created on first
+    /// spill; one file per tool call.
+    pub dir: PathBuf,
+    /// Results whose text is longer than this many characters are
+    /// spilled.
+    pub max_chars: usize,
+    /// Characters of preview kept in context, split evenly between the
+    /// head and the tail of the output.
+    pub preview_chars: usize,
+}
+
+impl ToolOutputSpill {
+    /// Spill into `dir` with the default size limits.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            // Matches the bash tool's historical 30KB in-tool cap.
+            max_chars: 30_000,
+            preview_chars: 4_000,
+        }
+    }
+
+    /// Lines per `read` page of `text` that keep the page itself under
+    /// `max_chars`, so reading a spill file back never spills again.
+    pub fn page_lines(&self, text: &str) -> usize {
+        let lines = text.lines().count();
+        let widest = text
+            .lines()
+            .map(|l| l.chars().count().min(READ_MAX_LINE_CHARS))
+            .max()
+            .unwrap_or(0);
+        // Line-number column (at least 6 wide), tab and newline.
+        let per_line = widest + lines.to_string().len().max(6) + 2;
+        (self.max_chars.saturating_sub(READ_PAGE_OVERHEAD) / per_line).max(1)
+    }
+
+    /// Bytes per `read` page of a spill file with `byte_offset`. A page
+    /// has no more characters than bytes, so it stays under
+    /// `max_chars` however long its lines are.
+    pub fn page_bytes(&self) -> usize {
+        self.max_chars.saturating_sub(READ_PAGE_OVERHEAD).max(1)
+    }
+}
+
+/// Replace an oversized result's text with a preview and a handle to
+/// the full output on disk. Results under the limit pass through
+/// untouched; non-text content (images) is always kept.
+pub(crate) fn spill_oversized(
+    result: ToolResult,
+    tool_call_id: &str,
+    spill: &ToolOutputSpill,
+) -> ToolResult {
+    let text = result.text_content();
+    let total_chars = text.chars().count();
+    if total_chars <= spill.max_chars {
+        return result;
+    }
+
+    let path = spill_path(&spill.dir, tool_call_id);
+    let notice = match write_spill(&path, &text) {
+        Ok(()) => format!(
+            "[Output too large for context: {total_chars} chars, {} bytes. Full output saved to {} — page through it with the read tool's byte_offset, {} bytes a page.]",
+            text.len(),
+            path.display(),
+            spill.page_bytes(),
+        ),
+        Err(e) => format!(
+            "[Output truncated: {total_chars} chars. Saving the full output failed ({e}); re-run with narrower output.]"
+        ),
+    };
+
+    let mut content = vec![Content::text(format!(
+        "{}\n\n{notice}",
+        preview(&text, spill.preview_chars)
+    ))];
+    content.extend(
+        result
+            .content
+            .into_iter()
+            .filter(|c| !matches!(c, Content::Text { .. })),
+    );
+    ToolResult {
+        content,
+        is_error: result.is_error,
+        details: result.details,
+    }
+}
+
+fn write_spill(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text)
+}
+
+/// The file a spill named `name` (a tool-call id, optionally with a
+/// suffix) is written to in `dir`.
+pub fn spill_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.txt", sanitize(name)))
+}
+
+/// First and last `budget / 2` characters, with an elision marker.
+fn preview(text: &str, budget: usize) -> String {
+    let half = budget / 2;
+    let head: String = text.chars().take(half).collect();
+    let total = text.chars().count();
+    let tail: String = text.chars().skip(total.saturating_sub(half)).collect();
+    format!(
+        "{head}\n\n... [{} chars omitted] ...\n\n{tail}",
+        total - head.chars().count() - tail.chars().count()
+    )
+}
+
+/// Tool-call ids come from the provider; keep only filename-safe chars.
+fn sanitize(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spill_in(dir: &Path) -> ToolOutputSpill {
+        ToolOutputSpill {
+            dir: dir.to_path_buf(),
+            max_chars: 100,
+            preview_chars: 40,
+        }
+    }
+
+    #[test]
+    fn small_results_pass_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = spill_oversized(ToolResult::text("short"), "call-1", &spill_in(dir.path()));
+        assert_eq!(out.text_content(), "short");
+        assert!(!dir.path().join("call-1.txt").exists());
+    }
+
+    #[test]
+    fn oversized_result_is_written_and_previewed() {
+        let dir = tempfile::tempdir().unwrap();
+        let full: String = (0..100).map(|i| format!("line {i}\n")).collect();
+        let out = spill_oversized(
+            ToolResult::error(full.clone()),
+            "toolu/01",
+            &spill_in(dir.path()),
+        );
+
+        let path = dir.path().join("toolu_01.txt");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), full);
+        let text = out.text_content();
+        assert!(text.starts_with("line 0\nline 1"));
+        assert!(text.contains("chars omitted"));
+        assert!(text.contains(&path.display().to_string()));
+        assert!(text.contains("byte_offset, 1 bytes a page"));
+        assert!(out.is_error, "error flag survives spilling");
+    }
+
+    #[test]
+    fn read_pages_fit_under_the_limit() {
+        let spill = ToolOutputSpill::new("/unused");
+        let long_lines = "x".repeat(5_000) + "\n";
+        let page = spill.page_lines(&long_lines.repeat(100));
+        assert_eq!(page, 14);
+        assert!(page * (READ_MAX_LINE_CHARS + 8) + READ_PAGE_OVERHEAD <= spill.max_chars);
+        assert_eq!(spill.page_lines("short\n"), 2_269);
+    }
+}
@@ -33,6 +33,7 @@ use std::time::Duration;
 
 use parking_lot::Mutex;
 use tau_ai::Message;
+use tau_history::History;
 use tokio::sync::{broadcast, mpsc};
 use tokio_util::sync::CancellationToken;
 
@@ -66,7 +67,9 @@ pub struct ToolCall {
 ///
 /// 1. **`handle_busy_command` mutates `Frame` fields directly** while
 ///    servicing `SetModel` / `SetReasoning` / `SetCompactionConfig` /
-///    `SetApprovalPolicy`. Changes take effect for the next turn.
+///    `SetApprovalPolicy`, and an idle `Rewind` onto a fork or
+///    `SwitchHistory` swaps `history`. Changes take effect for the
+///    next turn.
 /// 2. **Interior-mutable Arcs reachable from `Frame` may be mutated
 ///    elsewhere.** `file_access`'s `Arc<Mutex<...>>` is written by
 ///    tool tasks via `ExecutionContext`. That's not a mutation
@@ -96,6 +99,11 @@ pub struct Frame {
     /// so tool-initiated interactions can self-apply the same deadline.
     pub interaction_timeout: Option<Duration>,
     pub approval_policy: Arc<dyn ApprovalPolicy>,
+    /// Durable record of the conversation. The actor appends one
+    /// commit per turn and routes summarizing compactions through
+    /// [`History::compact_prefix`]; `Conv::conversation` is the
+    /// working copy it reads from between commits.
+    pub history: Arc<dyn History>,
     pub transform_context: Option<Arc<TransformContextFn>>,
     /// File-access tracker. Logically "shared mutable" but it's owned
     /// by the actor and reached by tools per-call via
@@ -122,11 +130,35 @@ pub struct Frame {
 /// `transitions.rs` is a review flag).
 pub struct Conv {
     pub conversation: Conversation,
+    /// How many leading messages of `conversation` are already
+    /// committed to [`Frame::history`]. Everything after is the
+    /// in-progress turn.
+    pub history_len: usize,
+    /// A compaction the working copy has but [`Frame::history`]
+    /// couldn't record; the next sync tries again.
+    pub history_resync: Option<HistoryResync>,
+    /// The prompt starting the in-progress turn came from the host
+    /// (see [`AgentHandle::reprompt_and_wait`]), so its commit is a
+    /// [`CommitKind::Reprompt`](tau_history::CommitKind::Reprompt).
+    ///
+    /// [`AgentHandle::reprompt_and_wait`]: crate::core::handle::AgentHandle::reprompt_and_wait
+    pub reprompt: bool,
     pub steering_queue: Vec<Message>,
     pub follow_up_queue: Vec<Message>,
     pub cwd: Option<PathBuf>,
 }
 
+/// Which compaction [`Conv::history_resync`] still has to record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryResync {
+    /// Tool outputs were pruned from the first `history_len`
+    /// messages. Later turns can still be appended meanwhile.
+    Pruned,
+    /// The working copy starts with a summary history hasn't got;
+    /// `history_len` is `0` until it has, and nothing is appended.
+    Summarized,
+}
+
 // ─── Shared: atomics shared with the handle ──────────────────────────
 
 /// Atomics and shared cells visible to both the actor and any
@@ -159,6 +191,10 @@ pub struct Shared {
     /// `adopt`). Surfaced to tools via
     /// [`ExecutionContext::agent_id`](crate::core::tool::ExecutionContext::agent_id).
     pub agent_id: Arc<OnceLock<String>>,
+    /// The working directory given to
+    /// [`AgentBuilder::set_cwd`](crate::core::builder::AgentBuilder::set_cwd),
+    /// stamped at spawn. Unset when the agent runs in the process's cwd.
+    pub cwd: Arc<OnceLock<PathBuf>>,
     /// Recorded by the actor's `catch_unwind` wrapper if the actor
     /// task panics. `None` while the actor is alive or after a clean
     /// shutdown.
@@ -178,6 +214,7 @@ impl Default for Shared {
             cancel: Arc::new(Mutex::new(CancellationToken::new())),
             interrupt_requested: Arc::new(AtomicBool::new(false)),
             agent_id: Arc::new(OnceLock::new()),
+            cwd: Arc::new(OnceLock::new()),
             panic_reason: Arc::new(Mutex::new(None)),
             shutdown_signaled: Arc::new(tokio::sync::Notify::new()),
         }
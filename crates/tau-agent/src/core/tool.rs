@@ -6,9 +6,11 @@
 //! There is **no** post-construction binding hook — anything a tool
 //! needs to know about its caller arrives via this struct.
 
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -19,6 +21,7 @@ use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
 use crate::core::approval::ToolRisk;
+use crate::core::spill::ToolOutputSpill;
 use crate::types::events::{AgentEvent, ConsoleLevel, ConsoleLine};
 
 /// Helper: send an event on a broadcast channel; ignore "no subscribers".
@@ -26,27 +29,150 @@ pub(crate) fn send_event<E: Clone>(tx: &broadcast::Sender<E>, event: E) {
     let _ = tx.send(event);
 }
 
-/// Tracks which files have been read so write/edit tools can enforce a
-/// read-before-write policy. Shared via `Arc<Mutex<...>>` on
-/// [`ExecutionContext`].
+/// Tracks which files have been read — and what they looked like at
+/// the time — so write/edit tools can enforce a read-before-write
+/// policy and refuse to clobber changes made behind the model's back.
+/// Shared via `Arc<Mutex<...>>` on [`ExecutionContext`].
 #[derive(Default)]
 pub struct FileAccessTracker {
-    read_files: HashSet<PathBuf>,
+    /// `None`: read before a restore, state at the time unknown.
+    read_files: HashMap<PathBuf, Option<FileStamp>>,
+}
+
+/// On-disk state of a file when the agent last read or wrote it.
+struct FileStamp {
+    mtime: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+    /// Kept for text files up to [`STALE_DIFF_MAX_BYTES`], so a
+    /// stale-read error can show what changed.
+    content: Option<String>,
+}
+
+impl FileStamp {
+    /// Snapshot the file's current state. `None` if it can't be read.
+    fn capture(path: &Path) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        Some(Self {
+            mtime: std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+            len: bytes.len() as u64,
+            hash: hash_bytes(&bytes),
+            content: (bytes.len() <= STALE_DIFF_MAX_BYTES)
+                .then(|| String::from_utf8(bytes).ok())
+                .flatten(),
+        })
+    }
+}
+
+/// Largest file, before or after the change, a stale-read error
+/// diffs.
+const STALE_DIFF_MAX_BYTES: usize = 64 * 1024;
+
+/// Maximum changed lines shown in a stale-read diff.
+const STALE_DIFF_MAX_LINES: usize = 20;
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Why [`FileAccessTracker::check`] refused a write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileAccessError {
+    /// The file exists but the agent never read it.
+    NotRead,
+    /// The file changed on disk since the agent last read or wrote it.
+    /// `diff` is a short line diff from the agent's view to the
+    /// current content, when both are small text.
+    Changed { diff: Option<String> },
+    /// The agent read the file before the session was restored or
+    /// rewound, so what it saw is unknown.
+    Unverified,
+}
+
+impl std::fmt::Display for FileAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRead => {
+                f.write_str("You must read this file before editing it. Use the read tool first.")
+            }
+            Self::Changed { diff: None } => {
+                f.write_str("File changed since you read it. Read it again before editing.")
+            }
+            Self::Changed { diff: Some(diff) } => write!(
+                f,
+                "File changed since you read it. Read it again before editing.\n\nChanges:\n{diff}"
+            ),
+            Self::Unverified => f.write_str(
+                "This file was read before the conversation was restored. Read it again before \
+                 editing.",
+            ),
+        }
+    }
 }
 
 impl FileAccessTracker {
+    /// Record that the agent read `path`, snapshotting its current
+    /// mtime and content hash.
     pub fn mark_read(&mut self, path: impl Into<PathBuf>) {
-        self.read_files.insert(path.into());
+        let path = path.into();
+        match FileStamp::capture(&path) {
+            Some(stamp) => {
+                self.read_files.insert(path, Some(stamp));
+            }
+            None => {
+                self.read_files.remove(&path);
+            }
+        }
+    }
+
+    /// Record the agent's own write so the next edit isn't flagged as
+    /// an external change.
+    pub fn mark_written(&mut self, path: impl Into<PathBuf>) {
+        self.mark_read(path);
     }
 
     /// Returns `Ok(())` if the file doesn't exist (new file) or has
-    /// been read. Returns an error message otherwise.
+    /// been read and is unchanged since. Returns an error message
+    /// otherwise.
     pub fn require_read(&self, path: &Path) -> Result<(), String> {
-        if path.exists() && !self.read_files.contains(path) {
-            Err("You must read this file before editing it. Use the read tool first.".into())
-        } else {
-            Ok(())
+        self.check(path).map_err(|e| e.to_string())
+    }
+
+    /// Like [`require_read`](Self::require_read), with a typed error
+    /// that distinguishes "never read" from "changed since read".
+    pub fn check(&self, path: &Path) -> Result<(), FileAccessError> {
+        if !path.exists() {
+            return Ok(());
         }
+        let Some(stamp) = self.read_files.get(path) else {
+            return Err(FileAccessError::NotRead);
+        };
+        let Some(stamp) = stamp else {
+            return Err(FileAccessError::Unverified);
+        };
+        let meta = std::fs::metadata(path).ok();
+        let mtime = meta.as_ref().and_then(|m| m.modified().ok());
+        if mtime.is_some() && mtime == stamp.mtime && meta.map(|m| m.len()) == Some(stamp.len) {
+            return Ok(());
+        }
+        // mtime moved (or is unavailable): fall back to content, so a
+        // bare `touch` or a formatter that rewrites identical bytes
+        // doesn't count as a change.
+        let Ok(bytes) = std::fs::read(path) else {
+            return Ok(());
+        };
+        if hash_bytes(&bytes) == stamp.hash {
+            return Ok(());
+        }
+        let diff = match (&stamp.content, std::str::from_utf8(&bytes)) {
+            (Some(before), Ok(after)) if after.len() <= STALE_DIFF_MAX_BYTES => {
+                Some(short_diff(before, after))
+            }
+            _ => None,
+        };
+        Err(FileAccessError::Changed { diff })
     }
 
     pub fn clear(&mut self) {
@@ -54,7 +180,9 @@ impl FileAccessTracker {
     }
 
     /// Rebuild the tracker from a conversation history (for session
-    /// restore). A successful `read` tool call marks its `path` as read.
+    /// restore). A successful `read` tool call marks its `path` as read
+    /// but [unverified](FileAccessError::Unverified): the file may have
+    /// changed since, so the first write asks for a fresh read.
     pub fn rebuild_from_messages(&mut self, messages: &[Message], cwd: &Option<PathBuf>) {
         self.read_files.clear();
         for msg in messages {
@@ -81,13 +209,35 @@ impl FileAccessTracker {
                     continue;
                 }
                 if let Some(path) = resolve_tool_path(arguments, cwd) {
-                    self.read_files.insert(path);
+                    self.read_files.insert(path, None);
                 }
             }
         }
     }
 }
 
+/// Changed lines only (`-`/`+`), capped at [`STALE_DIFF_MAX_LINES`].
+fn short_diff(before: &str, after: &str) -> String {
+    let diff = similar::TextDiff::from_lines(before, after);
+    let mut lines: Vec<String> = diff
+        .iter_all_changes()
+        .filter_map(|change| {
+            let sign = match change.tag() {
+                similar::ChangeTag::Delete => "-",
+                similar::ChangeTag::Insert => "+",
+                similar::ChangeTag::Equal => return None,
+            };
+            Some(format!("{sign}{}", change.value().trim_end_matches('\n')))
+        })
+        .collect();
+    if lines.len() > STALE_DIFF_MAX_LINES {
+        let hidden = lines.len() - STALE_DIFF_MAX_LINES;
+        lines.truncate(STALE_DIFF_MAX_LINES);
+        lines.push(format!("... ({hidden} more changed lines)"));
+    }
+    lines.join("\n")
+}
+
 fn resolve_tool_path(args: &Value, cwd: &Option<PathBuf>) -> Option<PathBuf> {
     let path_str = args.get("path").and_then(|v| v.as_str())?;
     let path = if let Some(rest) = path_str.strip_prefix("~/") {
@@ -254,6 +404,13 @@ pub struct ExecutionContext {
     /// `AgentTool`) read this to enforce host-side recursion limits
     /// without needing per-depth instances.
     pub subagent_depth: u32,
+    /// How the runtime spills oversized results, if it does. When set,
+    /// tools can return their full output instead of truncating it
+    /// themselves — nothing the runtime caps is lost. Tools whose
+    /// output can grow without bound stream it to a file in the spill
+    /// directory (named with [`spill_path`](crate::core::spill::spill_path))
+    /// once it outgrows their in-memory limit.
+    pub spill: Option<ToolOutputSpill>,
 }
 
 impl ExecutionContext {
@@ -275,9 +432,28 @@ impl ExecutionContext {
         self.file_access.lock().mark_read(canonical);
     }
 
+    /// Record the calling tool's own write to `path`.
+    pub fn mark_written(&self, path: &Path) {
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.file_access.lock().mark_written(canonical);
+    }
+
+    /// Enforce read-before-write. When the file changed on disk since
+    /// the agent read it, also emits
+    /// [`AgentEvent::FileModifiedExternally`] so the host can surface it.
     pub fn require_read(&self, path: &Path) -> Result<(), String> {
         let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-        self.file_access.lock().require_read(&canonical)
+        let checked = self.file_access.lock().check(&canonical);
+        checked.map_err(|e| {
+            if let FileAccessError::Changed { diff } = &e {
+                self.progress.emit(AgentEvent::FileModifiedExternally {
+                    path: canonical,
+                    diff: diff.clone(),
+                    tool_call_id: self.progress.tool_call_id().to_string(),
+                });
+            }
+            e.to_string()
+        })
     }
 }
 
@@ -324,6 +500,12 @@ pub trait Tool: Send + Sync {
         ToolRisk::Local
     }
 
+    /// Why [`risk`](Self::risk) rated these arguments as it did, for
+    /// the confirm dialog. Empty when the tool has nothing to add.
+    fn risk_reasons(&self, _arguments: &Value) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Coarse category for UI grouping and snapshot queries. Defaults
     /// to [`ToolCategory::Other`] so existing `Tool` implementations
     /// stay source-compatible.
@@ -343,3 +525,135 @@ pub fn to_api_tool(tool: &dyn Tool) -> tau_ai::Tool {
         parameters: tool.parameters_schema(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unread_existing_file_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let tracker = FileAccessTracker::default();
+        assert!(tracker.check(&path).is_ok(), "new files need no read");
+        std::fs::write(&path, "x").unwrap();
+        assert_eq!(tracker.check(&path), Err(FileAccessError::NotRead));
+    }
+
+    #[test]
+    fn external_change_after_read_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let mut tracker = FileAccessTracker::default();
+        tracker.mark_read(&path);
+        assert!(tracker.check(&path).is_ok());
+
+        std::fs::write(&path, "one\nTWO\n").unwrap();
+        assert_eq!(
+            tracker.check(&path),
+            Err(FileAccessError::Changed {
+                diff: Some("-two\n+TWO".into())
+            })
+        );
+    }
+
+    #[test]
+    fn large_or_binary_files_change_without_a_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = dir.path().join("big.txt");
+        std::fs::write(&big, "x\n".repeat(STALE_DIFF_MAX_BYTES)).unwrap();
+        let binary = dir.path().join("blob.bin");
+        std::fs::write(&binary, [0xff, 0xfe]).unwrap();
+        let mut tracker = FileAccessTracker::default();
+        tracker.mark_read(&big);
+        tracker.mark_read(&binary);
+
+        std::fs::write(&big, "y\n").unwrap();
+        std::fs::write(&binary, [0xff]).unwrap();
+        for path in [&big, &binary] {
+            assert_eq!(
+                tracker.check(path),
+                Err(FileAccessError::Changed { diff: None })
+            );
+        }
+    }
+
+    #[test]
+    fn stale_diffs_are_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "").unwrap();
+        let mut tracker = FileAccessTracker::default();
+        tracker.mark_read(&path);
+
+        let lines: String = (0..50).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(&path, lines).unwrap();
+        let Err(FileAccessError::Changed { diff: Some(diff) }) = tracker.check(&path) else {
+            panic!("expected a diff");
+        };
+        assert_eq!(diff.lines().count(), STALE_DIFF_MAX_LINES + 1);
+        assert!(diff.ends_with("... (30 more changed lines)"));
+    }
+
+    #[test]
+    fn size_change_with_same_mtime_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let mut tracker = FileAccessTracker::default();
+        tracker.mark_read(&path);
+
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        assert!(matches!(
+            tracker.check(&path),
+            Err(FileAccessError::Changed { .. })
+        ));
+    }
+
+    #[test]
+    fn reads_from_a_restored_history_need_a_fresh_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "x").unwrap();
+        let messages = vec![
+            crate::test_utils::make_tool_call_message(
+                "read",
+                "r1",
+                serde_json::json!({ "path": path }),
+            ),
+            Message::tool_result("r1", "read", vec![Content::text("x")], false),
+        ];
+        let mut tracker = FileAccessTracker::default();
+        tracker.rebuild_from_messages(&messages, &None);
+        assert_eq!(tracker.check(&path), Err(FileAccessError::Unverified));
+
+        tracker.mark_read(&path);
+        assert!(tracker.check(&path).is_ok());
+    }
+
+    #[test]
+    fn own_writes_and_identical_rewrites_are_not_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "before").unwrap();
+        let mut tracker = FileAccessTracker::default();
+        tracker.mark_read(&path);
+
+        std::fs::write(&path, "after").unwrap();
+        tracker.mark_written(&path);
+        assert!(tracker.check(&path).is_ok(), "agent's own write");
+
+        // Same bytes, new mtime (e.g. a formatter with nothing to do).
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&path, "after").unwrap();
+        assert!(tracker.check(&path).is_ok(), "content unchanged");
+    }
+}
@@ -25,7 +25,7 @@ use tau_ai::{Content, InjectionSource, Message, Usage};
 
 use crate::core::config::DequeueMode;
 use crate::core::overflow::is_context_overflow;
-use crate::core::state::{Conv, Frame, ToolCall};
+use crate::core::state::{Conv, Frame, HistoryResync, ToolCall};
 use crate::core::stream::StreamOutcome;
 use crate::core::tool::{BoxedTool, Concurrency, ToolResult, to_api_tool};
 use crate::core::transport::AgentRunConfig;
@@ -344,6 +344,49 @@ pub fn apply_clear_error(conv: &mut Conv) {
     conv.conversation.error = None;
 }
 
+/// Note whether the prompt starting now came from the host rather
+/// than the user.
+pub fn apply_prompt_source(conv: &mut Conv, reprompt: bool) {
+    conv.reprompt = reprompt;
+}
+
+/// Record that the first `len` messages are committed to history.
+pub fn apply_history_synced(conv: &mut Conv, len: usize) {
+    conv.history_len = len;
+    // Only the commit carrying the prompt is the reprompt's.
+    conv.reprompt = false;
+}
+
+/// Record that the working copy was compacted but history couldn't
+/// record it. A pending summary outranks a pending prune: recording
+/// it rewrites all of history.
+pub fn apply_history_diverged(conv: &mut Conv, resync: HistoryResync) {
+    if resync == HistoryResync::Summarized || conv.history_resync == Some(HistoryResync::Summarized)
+    {
+        conv.history_resync = Some(HistoryResync::Summarized);
+        conv.history_len = 0;
+    } else {
+        conv.history_resync = Some(HistoryResync::Pruned);
+    }
+}
+
+/// Record that history has caught up with the working copy's
+/// compaction.
+pub fn apply_history_resynced(conv: &mut Conv) {
+    conv.history_resync = None;
+}
+
+/// Replace the working copy with a history the agent just rewound or
+/// forked to. All of it is already committed; usage totals carry over,
+/// since the abandoned turns were still paid for.
+pub fn apply_rewound(conv: &mut Conv, messages: Vec<Message>, previous_summary: Option<String>) {
+    conv.history_len = messages.len();
+    conv.history_resync = None;
+    conv.conversation.messages = messages;
+    conv.conversation.previous_summary = previous_summary;
+    conv.conversation.error = None;
+}
+
 /// Enqueue a steering message for the next tool-batch boundary.
 pub fn apply_enqueue_steering(conv: &mut Conv, msg: Message) {
     conv.steering_queue.push(msg);
@@ -362,13 +405,7 @@ pub fn apply_final_summary(conv: &mut Conv, msg: Message) {
 
 /// Accumulate per-turn token usage into the running total.
 pub fn apply_usage(conv: &mut Conv, usage: &Usage) {
-    conv.conversation.total_usage.input += usage.input;
-    conv.conversation.total_usage.output += usage.output;
-    conv.conversation.total_usage.cache_read += usage.cache_read;
-    conv.conversation.total_usage.cache_write += usage.cache_write;
-    conv.conversation.total_usage.thinking += usage.thinking;
-    conv.conversation.total_usage.cache_creation_1h += usage.cache_creation_1h;
-    conv.conversation.total_usage.cache_creation_5m += usage.cache_creation_5m;
+    conv.conversation.total_usage.accumulate(usage);
 }
 
 /// Commit tool results to the conversation in original request order.
@@ -501,6 +538,9 @@ mod tests {
     fn empty_conv() -> Conv {
         Conv {
             conversation: Conversation::default(),
+            history_len: 0,
+            history_resync: None,
+            reprompt: false,
             steering_queue: vec![],
             follow_up_queue: vec![],
             cwd: None,
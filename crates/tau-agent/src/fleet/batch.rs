@@ -0,0 +1,179 @@
+//! Fan-out/fan-in: one subagent per input, bounded concurrency.
+//!
+//! A batch is a set of independent foreground spawns sharing a spec
+//! and a [`SpawnOpts`] template. Each item is an ordinary
+//! [`lifecycle::spawn`], so it gets the usual `AgentStarted` /
+//! `AgentCompleted` / `Forwarded` events; the batch adds
+//! `BatchStarted`, one `BatchItemFinished` per input, and
+//! `BatchCompleted` on the fleet channel so hosts can show progress.
+//! Failed items are retried up to [`BatchOpts::retries`] times with a
+//! fresh agent; items still failing are reported, not fatal.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::tool::send_event;
+use crate::fleet::lifecycle::{self, LifecycleCtx};
+use crate::fleet::manager::{AgentSpec, SpawnOpts};
+use crate::fleet::result::SubagentResult;
+use crate::types::events::FleetEvent;
+
+/// One unit of work in a batch.
+#[derive(Debug, Clone)]
+pub struct BatchInput {
+    /// Short label for this item; becomes the subagent's description.
+    pub description: String,
+    pub prompt: String,
+}
+
+/// Batch-wide settings.
+#[derive(Clone)]
+pub struct BatchOpts {
+    /// Label for the batch as a whole, carried on the batch events.
+    pub description: String,
+    /// Maximum subagents running at once. Clamped to at least 1.
+    pub concurrency: usize,
+    /// Extra attempts for an item whose spawn fails.
+    pub retries: u32,
+    /// Options applied to every spawn. `description` is replaced per
+    /// item.
+    pub template: SpawnOpts,
+}
+
+impl Default for BatchOpts {
+    fn default() -> Self {
+        Self {
+            description: String::new(),
+            concurrency: 4,
+            retries: 1,
+            template: SpawnOpts::default(),
+        }
+    }
+}
+
+/// Outcome of one input.
+#[derive(Debug, Clone)]
+pub struct BatchItemResult {
+    /// Position of the input in the batch.
+    pub index: usize,
+    pub description: String,
+    /// Spawns made for this item, including the successful one.
+    pub attempts: u32,
+    /// The last attempt's result; errors are stringified so the table
+    /// is `Clone`.
+    pub result: std::result::Result<SubagentResult, String>,
+}
+
+/// Aggregated batch outcome, items in input order.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub batch_id: String,
+    pub items: Vec<BatchItemResult>,
+}
+
+impl BatchResult {
+    pub fn succeeded(&self) -> usize {
+        self.items.iter().filter(|i| i.result.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items.len() - self.succeeded()
+    }
+}
+
+pub async fn spawn_batch(
+    ctx: &LifecycleCtx,
+    spec: impl Into<Arc<AgentSpec>>,
+    inputs: Vec<BatchInput>,
+    opts: BatchOpts,
+    cancel: CancellationToken,
+) -> BatchResult {
+    let spec = spec.into();
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let total = inputs.len();
+    let concurrency = opts.concurrency.max(1);
+
+    send_event(
+        &ctx.fleet_event_tx,
+        FleetEvent::BatchStarted {
+            batch_id: batch_id.clone(),
+            description: opts.description.clone(),
+            total,
+            concurrency,
+        },
+    );
+
+    let mut finished = 0;
+    let mut items: Vec<BatchItemResult> = futures::stream::iter(inputs.into_iter().enumerate())
+        .map(|(index, input)| run_item(ctx, &spec, &opts, index, input, &cancel))
+        .buffer_unordered(concurrency)
+        .inspect(|item| {
+            finished += 1;
+            send_event(
+                &ctx.fleet_event_tx,
+                FleetEvent::BatchItemFinished {
+                    batch_id: batch_id.clone(),
+                    index: item.index,
+                    description: item.description.clone(),
+                    agent_id: item.result.as_ref().ok().map(|r| r.agent_id.clone()),
+                    error: item.result.as_ref().err().cloned(),
+                    attempts: item.attempts,
+                    finished,
+                    total,
+                },
+            );
+        })
+        .collect()
+        .await;
+    items.sort_by_key(|i| i.index);
+
+    let result = BatchResult { batch_id, items };
+    send_event(
+        &ctx.fleet_event_tx,
+        FleetEvent::BatchCompleted {
+            batch_id: result.batch_id.clone(),
+            description: opts.description,
+            succeeded: result.succeeded(),
+            failed: result.failed(),
+        },
+    );
+    result
+}
+
+async fn run_item(
+    ctx: &LifecycleCtx,
+    spec: &Arc<AgentSpec>,
+    opts: &BatchOpts,
+    index: usize,
+    input: BatchInput,
+    cancel: &CancellationToken,
+) -> BatchItemResult {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let spawn_opts = SpawnOpts {
+            description: input.description.clone(),
+            ..opts.template.clone()
+        };
+        let result = lifecycle::spawn(
+            ctx,
+            Arc::clone(spec),
+            input.prompt.clone(),
+            spawn_opts,
+            cancel.child_token(),
+        )
+        .await
+        .map_err(|e| e.to_string());
+        let give_up = result.is_ok() || attempts > opts.retries || cancel.is_cancelled();
+        if give_up {
+            return BatchItemResult {
+                index,
+                description: input.description,
+                attempts,
+                result,
+            };
+        }
+    }
+}
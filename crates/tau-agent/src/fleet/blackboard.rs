@@ -0,0 +1,224 @@
+//! Fleet blackboard: named topics every tracked agent can post to and
+//! read from.
+//!
+//! Subagents otherwise talk only to their parent, so siblings working
+//! on different parts of a task can't tell each other what they found
+//! and end up duplicating work. The blackboard is shared across the
+//! whole fleet. Entries are attributed to the posting agent's id and
+//! numbered with one fleet-wide sequence, so a reader can ask for
+//! "everything after #n".
+//!
+//! Agents may subscribe to a topic.
+//! [`AgentManager::post_to_blackboard`](crate::AgentManager::post_to_blackboard)
+//! steers every *running* subscriber with the new entry; idle
+//! subscribers catch up on their next read. Each topic keeps its most
+//! recent [`MAX_ENTRIES_PER_TOPIC`] entries.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use indexmap::{IndexMap, IndexSet};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Older entries are dropped once a topic holds this many.
+pub const MAX_ENTRIES_PER_TOPIC: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlackboardEntry {
+    /// Fleet-wide sequence number, strictly increasing across topics.
+    pub seq: u64,
+    pub topic: String,
+    /// Id of the agent that posted the entry.
+    pub agent_id: String,
+    pub text: String,
+    pub posted_at: DateTime<Utc>,
+}
+
+/// One topic as seen in a [`FleetSnapshot`](crate::FleetSnapshot).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackboardTopic {
+    pub name: String,
+    /// Agent ids steered with new entries.
+    pub subscribers: Vec<String>,
+    /// Retained entries, oldest first.
+    pub entries: Vec<BlackboardEntry>,
+}
+
+#[derive(Default)]
+struct Topic {
+    entries: VecDeque<BlackboardEntry>,
+    subscribers: IndexSet<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_seq: u64,
+    /// Insertion-ordered so `topics()` lists them in creation order.
+    topics: IndexMap<String, Topic>,
+}
+
+#[derive(Default)]
+pub struct Blackboard {
+    inner: Mutex<Inner>,
+}
+
+/// Topic names are matched case-insensitively, ignoring surrounding
+/// whitespace.
+pub fn normalize_topic(topic: &str) -> String {
+    topic.trim().to_lowercase()
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an entry, creating the topic if needed. Returns the entry
+    /// and the topic's subscribers other than the poster.
+    pub fn post(
+        &self,
+        topic: &str,
+        agent_id: &str,
+        text: impl Into<String>,
+    ) -> (BlackboardEntry, Vec<String>) {
+        let topic = normalize_topic(topic);
+        let mut inner = self.inner.lock();
+        inner.next_seq += 1;
+        let entry = BlackboardEntry {
+            seq: inner.next_seq,
+            topic: topic.clone(),
+            agent_id: agent_id.to_string(),
+            text: text.into(),
+            posted_at: Utc::now(),
+        };
+        let slot = inner.topics.entry(topic).or_default();
+        if slot.entries.len() >= MAX_ENTRIES_PER_TOPIC {
+            slot.entries.pop_front();
+        }
+        slot.entries.push_back(entry.clone());
+        let notify = slot
+            .subscribers
+            .iter()
+            .filter(|id| *id != agent_id)
+            .cloned()
+            .collect();
+        (entry, notify)
+    }
+
+    /// Retained entries of `topic` with `seq > since`, oldest first.
+    pub fn read(&self, topic: &str, since: u64) -> Vec<BlackboardEntry> {
+        self.inner
+            .lock()
+            .topics
+            .get(&normalize_topic(topic))
+            .map(|t| {
+                t.entries
+                    .iter()
+                    .filter(|e| e.seq > since)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Subscribe `agent_id` to `topic`, creating the topic if needed.
+    /// Returns `false` if it was already subscribed.
+    pub fn subscribe(&self, topic: &str, agent_id: &str) -> bool {
+        self.inner
+            .lock()
+            .topics
+            .entry(normalize_topic(topic))
+            .or_default()
+            .subscribers
+            .insert(agent_id.to_string())
+    }
+
+    /// Returns `false` if `agent_id` wasn't subscribed.
+    pub fn unsubscribe(&self, topic: &str, agent_id: &str) -> bool {
+        self.inner
+            .lock()
+            .topics
+            .get_mut(&normalize_topic(topic))
+            .is_some_and(|t| t.subscribers.shift_remove(agent_id))
+    }
+
+    /// Drop every subscription of `agent_id`, once it has left the
+    /// fleet.
+    pub fn forget(&self, agent_id: &str) {
+        for topic in self.inner.lock().topics.values_mut() {
+            topic.subscribers.shift_remove(agent_id);
+        }
+    }
+
+    /// Every topic with its subscribers and retained entries.
+    pub fn topics(&self) -> Vec<BlackboardTopic> {
+        self.inner
+            .lock()
+            .topics
+            .iter()
+            .map(|(name, t)| BlackboardTopic {
+                name: name.clone(),
+                subscribers: t.subscribers.iter().cloned().collect(),
+                entries: t.entries.iter().cloned().collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn post_and_read_since_across_topics() {
+        let board = Blackboard::new();
+        board.post("Findings", "a", "tau-ai has no unsafe");
+        board.post("todo", "b", "audit lifecycle.rs");
+        let (third, _) = board.post(" findings ", "b", "tau-agent has one unsafe");
+        assert_eq!(third.seq, 3, "sequence is fleet-wide");
+
+        let all = board.read("findings", 0);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].agent_id, "a");
+        let newer = board.read("FINDINGS", 1);
+        assert_eq!(newer, vec![third]);
+        assert!(board.read("missing", 0).is_empty());
+    }
+
+    #[test]
+    fn post_notifies_subscribers_except_the_poster() {
+        let board = Blackboard::new();
+        assert!(board.subscribe("findings", "a"));
+        assert!(!board.subscribe("findings", "a"), "already subscribed");
+        board.subscribe("findings", "b");
+
+        let (_, notify) = board.post("findings", "a", "x");
+        assert_eq!(notify, vec!["b".to_string()]);
+        assert!(board.unsubscribe("findings", "b"));
+        let (_, notify) = board.post("findings", "a", "y");
+        assert!(notify.is_empty());
+    }
+
+    #[test]
+    fn forgotten_agents_leave_every_topic() {
+        let board = Blackboard::new();
+        board.subscribe("findings", "a");
+        board.subscribe("todo", "a");
+        board.subscribe("todo", "b");
+        board.forget("a");
+        let subscribers: Vec<_> = board.topics().into_iter().map(|t| t.subscribers).collect();
+        assert_eq!(subscribers, [vec![], vec!["b".to_string()]]);
+    }
+
+    #[test]
+    fn topic_keeps_only_the_most_recent_entries() {
+        let board = Blackboard::new();
+        for i in 0..MAX_ENTRIES_PER_TOPIC + 5 {
+            board.post("log", "a", format!("{i}"));
+        }
+        let entries = board.read("log", 0);
+        assert_eq!(entries.len(), MAX_ENTRIES_PER_TOPIC);
+        assert_eq!(entries[0].text, "5");
+    }
+}
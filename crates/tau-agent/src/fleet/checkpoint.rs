@@ -0,0 +1,477 @@
+//! Incremental on-disk checkpoints of fleet-managed subagents.
+//!
+//! With a checkpoint directory configured
+//! ([`AgentManager::with_checkpoint_dir`](crate::AgentManager::with_checkpoint_dir)),
+//! every spawned subagent is mirrored to disk while it runs:
+//!
+//! ```text
+//! <dir>/<agent_id>/
+//!     agent.json       // AgentCheckpoint: spec name, prompt, cwd/worktree, status
+//!     messages.jsonl   // message log, appended as the agent's turns end
+//! ```
+//!
+//! Mid-turn `MessageEnd`s are batched into at most one write per
+//! [`CHECKPOINT_DEBOUNCE`]; a `TurnEnd` writes at once. The message
+//! log is rewritten whole only when the history changed
+//! under it: the first write of a run, a compaction, a rewind.
+//! `agent.json` is written when the agent starts and flips to
+//! [`CheckpointStatus::Idle`] when a run finishes, so a checkpoint
+//! still marked `Running` after a restart belongs to a run the previous
+//! process never saw finish. Agents that leave the registry (failed
+//! runs, LRU eviction, respec) have their checkpoint removed.
+//! [`AgentManager::restore`](crate::AgentManager::restore) reads the
+//! directory back.
+//!
+//! Like transcripts, checkpoints are best-effort: write failures are
+//! logged, never raised into the run.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tau_ai::{Content, Message};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::handle::AgentHandle;
+use crate::fleet::worktree::WorktreeInfo;
+use crate::types::events::AgentEvent;
+
+/// How long a `MessageEnd` may wait to be checkpointed. Each write
+/// snapshots the agent's whole state, so a turn that streams many
+/// tool results shares one instead of paying for it per message.
+pub const CHECKPOINT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Prompt sent to an interrupted background agent when it is resumed.
+pub const RESUME_PROMPT: &str = "Your previous run was interrupted by a restart before you \
+     finished. Continue the task from where you left off, then give your final answer.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointStatus {
+    /// A run was in flight when this was last written.
+    Running,
+    /// Between runs; resumable with `send`.
+    Idle,
+}
+
+/// Everything needed to rebuild a subagent except its tools, which
+/// are resolved again from `spec_name` on restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentCheckpoint {
+    pub agent_id: String,
+    pub description: String,
+    /// Host spec name the agent was spawned under. Agents without one
+    /// can't be restored.
+    pub spec_name: Option<String>,
+    /// Prompt the agent was spawned with.
+    pub prompt: String,
+    /// Model id override, if the spawn set one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub cwd: Option<String>,
+    /// Worktree the agent ran in, for isolated spawns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<WorktreeInfo>,
+    pub subagent_depth: u32,
+    /// Spawned with `spawn_background`: an interrupted run has no
+    /// caller waiting on it and may be resumed on restore.
+    pub background: bool,
+    pub status: CheckpointStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_summary: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Outcome of [`AgentManager::restore`](crate::AgentManager::restore).
+#[derive(Debug, Clone, Default)]
+pub struct RestoreReport {
+    /// Agents rebuilt into idle storage, least recently used first.
+    pub restored: Vec<String>,
+    /// Interrupted background agents re-prompted with
+    /// [`RESUME_PROMPT`]. Also listed in `restored`.
+    pub resumed: Vec<String>,
+    /// Checkpoints left on disk unrestored, with the reason.
+    pub skipped: Vec<(String, String)>,
+    /// Saved timers re-armed. Ones already due fire right away.
+    pub timers: Vec<String>,
+}
+
+/// Directory of [`AgentCheckpoint`]s, one subdirectory per agent.
+#[derive(Debug)]
+pub struct CheckpointStore {
+    root: PathBuf,
+}
+
+impl CheckpointStore {
+    /// The directory is created lazily on first write.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn dir(&self, agent_id: &str) -> PathBuf {
+        self.root.join(agent_id)
+    }
+
+    /// Overwrite the agent's `agent.json`.
+    pub async fn save(&self, checkpoint: &AgentCheckpoint) {
+        let result = async {
+            let dir = self.dir(&checkpoint.agent_id);
+            tokio::fs::create_dir_all(&dir).await?;
+            let json = serde_json::to_vec_pretty(checkpoint)?;
+            atomic_write(&dir.join("agent.json"), &json).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::debug!(agent_id = %checkpoint.agent_id, "failed to write checkpoint: {e}");
+        }
+    }
+
+    /// Read-modify-write the agent's `agent.json`. No-op when there is
+    /// no checkpoint for `agent_id`.
+    pub async fn update(&self, agent_id: &str, f: impl FnOnce(&mut AgentCheckpoint)) {
+        let Some(mut checkpoint) = self.read(agent_id).await else {
+            return;
+        };
+        f(&mut checkpoint);
+        checkpoint.updated_at = Utc::now();
+        self.save(&checkpoint).await;
+    }
+
+    /// Replace the agent's message log.
+    pub async fn save_messages(&self, agent_id: &str, messages: &[Message]) {
+        let result = async {
+            let dir = self.dir(agent_id);
+            tokio::fs::create_dir_all(&dir).await?;
+            atomic_write(&dir.join("messages.jsonl"), &jsonl(messages)?).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::debug!(agent_id, "failed to write checkpoint messages: {e}");
+        }
+    }
+
+    /// Add `messages` to the end of the agent's message log.
+    pub async fn append_messages(&self, agent_id: &str, messages: &[Message]) {
+        let result = async {
+            let dir = self.dir(agent_id);
+            tokio::fs::create_dir_all(&dir).await?;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join("messages.jsonl"))
+                .await?;
+            file.write_all(&jsonl(messages)?).await?;
+            file.flush().await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::debug!(agent_id, "failed to append checkpoint messages: {e}");
+        }
+    }
+
+    /// Delete the agent's checkpoint.
+    pub async fn remove(&self, agent_id: &str) {
+        match tokio::fs::remove_dir_all(self.dir(agent_id)).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::debug!(agent_id, "failed to remove checkpoint: {e}"),
+        }
+    }
+
+    async fn read(&self, agent_id: &str) -> Option<AgentCheckpoint> {
+        let bytes = tokio::fs::read(self.dir(agent_id).join("agent.json"))
+            .await
+            .ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    async fn read_messages(&self, agent_id: &str) -> std::io::Result<Vec<Message>> {
+        let text = match tokio::fs::read_to_string(self.dir(agent_id).join("messages.jsonl")).await
+        {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        let mut messages = Vec::with_capacity(lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(m) => messages.push(m),
+                // An append the process died in the middle of.
+                Err(_) if i + 1 == lines.len() => {
+                    tracing::warn!(agent_id, "dropping torn last line of checkpoint messages");
+                }
+                Err(e) => return Err(std::io::Error::other(e)),
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Every readable checkpoint with its message log, least recently
+    /// updated first (the registry's idle LRU order). Unreadable
+    /// entries are logged and skipped.
+    pub async fn load(&self) -> std::io::Result<Vec<(AgentCheckpoint, Vec<Message>)>> {
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(e) => e,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut out = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            // The fleet keeps other state (timers) beside the agents.
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            let agent_id = entry.file_name().to_string_lossy().into_owned();
+            let Some(checkpoint) = self.read(&agent_id).await else {
+                tracing::warn!(agent_id, "skipping unreadable subagent checkpoint");
+                continue;
+            };
+            match self.read_messages(&agent_id).await {
+                Ok(messages) => out.push((checkpoint, messages)),
+                Err(e) => tracing::warn!(agent_id, "skipping subagent checkpoint: {e}"),
+            }
+        }
+        out.sort_by_key(|(c, _)| c.updated_at);
+        Ok(out)
+    }
+}
+
+/// How much of an agent's message log is on disk, so
+/// [`record_state`] can append what's new instead of rewriting it.
+#[derive(Debug, Default)]
+pub(crate) struct LogCursor {
+    written: usize,
+    /// The last message written, serialized. A history whose message
+    /// at that index differs was rewound under the log.
+    last: Vec<u8>,
+}
+
+/// Write the agent's current messages and, when the log had to be
+/// rewritten, its compaction summary. A default `log` rewrites.
+pub(crate) async fn record_state(
+    store: &CheckpointStore,
+    agent_id: &str,
+    handle: &AgentHandle,
+    log: &mut LogCursor,
+) {
+    let Some(state) = handle.state().await else {
+        return;
+    };
+    let messages = &state.messages;
+    let extends_log = log.written > 0
+        && log.written <= messages.len()
+        && serde_json::to_vec(&messages[log.written - 1]).ok().as_ref() == Some(&log.last);
+    if extends_log {
+        store
+            .append_messages(agent_id, &messages[log.written..])
+            .await;
+    } else {
+        store.save_messages(agent_id, messages).await;
+        store
+            .update(agent_id, |c| c.previous_summary = state.previous_summary)
+            .await;
+    }
+    log.written = messages.len();
+    log.last = messages
+        .last()
+        .and_then(|m| serde_json::to_vec(m).ok())
+        .unwrap_or_default();
+}
+
+/// Mirror the agent's message log to disk as it grows, debouncing
+/// `MessageEnd`s by [`CHECKPOINT_DEBOUNCE`]. Stops when `shutdown`
+/// fires or the agent's event channel closes (flushing a pending
+/// write first); callers
+/// await the handle before writing the end-of-run checkpoint so the
+/// two never interleave.
+pub(crate) fn spawn_checkpointer(
+    store: std::sync::Arc<CheckpointStore>,
+    agent_id: String,
+    handle: AgentHandle,
+    mut events: broadcast::Receiver<AgentEvent>,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut log = LogCursor::default();
+        // When the oldest unrecorded `MessageEnd` is due on disk.
+        let mut due: Option<tokio::time::Instant> = None;
+        loop {
+            let flush = async {
+                match due {
+                    Some(at) => tokio::time::sleep_until(at).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                _ = flush => {
+                    due = None;
+                    record_state(&store, &agent_id, &handle, &mut log).await;
+                }
+                event = events.recv() => match event {
+                    Ok(AgentEvent::MessageEnd { .. }) => {
+                        due.get_or_insert_with(|| tokio::time::Instant::now() + CHECKPOINT_DEBOUNCE);
+                    }
+                    Ok(AgentEvent::TurnEnd { .. }) => {
+                        due = None;
+                        record_state(&store, &agent_id, &handle, &mut log).await;
+                    }
+                    Ok(AgentEvent::CompactionEnd { .. }) => {
+                        due = None;
+                        log = LogCursor::default();
+                        record_state(&store, &agent_id, &handle, &mut log).await;
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => {
+                        if due.is_some() {
+                            record_state(&store, &agent_id, &handle, &mut log).await;
+                        }
+                        break;
+                    }
+                },
+            }
+        }
+    })
+}
+
+/// Drop a trailing assistant message whose tool calls never got
+/// results — the process died mid-tool — so the restored history is
+/// one a provider will accept.
+pub(crate) fn trim_incomplete_turn(messages: &mut Vec<Message>) {
+    if let Some(Message::Assistant { content, .. }) = messages.last()
+        && content.iter().any(Content::is_tool_call)
+    {
+        messages.pop();
+    }
+}
+
+fn jsonl(messages: &[Message]) -> serde_json::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(messages.len() * 256);
+    for m in messages {
+        serde_json::to_writer(&mut bytes, m)?;
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+async fn atomic_write(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, bytes).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_assistant_message, make_tool_call_message};
+
+    fn checkpoint(agent_id: &str) -> AgentCheckpoint {
+        AgentCheckpoint {
+            agent_id: agent_id.into(),
+            description: "survey".into(),
+            spec_name: Some("explore".into()),
+            prompt: "look around".into(),
+            model: None,
+            cwd: Some("/tmp".into()),
+            worktree: None,
+            subagent_depth: 1,
+            background: true,
+            status: CheckpointStatus::Running,
+            previous_summary: None,
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn save_update_load_remove_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path());
+        store.save(&checkpoint("a")).await;
+        store
+            .save_messages("a", &[Message::user("hi"), make_assistant_message("hello")])
+            .await;
+        store
+            .update("a", |c| c.status = CheckpointStatus::Idle)
+            .await;
+
+        let loaded = store.load().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0.status, CheckpointStatus::Idle);
+        assert_eq!(loaded[0].0.spec_name.as_deref(), Some("explore"));
+        assert_eq!(loaded[0].1.len(), 2);
+
+        store.remove("a").await;
+        assert!(store.load().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn appends_extend_the_log_and_a_torn_tail_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path());
+        store.save(&checkpoint("a")).await;
+        store.save_messages("a", &[Message::user("hi")]).await;
+        store
+            .append_messages("a", &[make_assistant_message("hello")])
+            .await;
+        assert_eq!(store.load().await.unwrap()[0].1.len(), 2);
+
+        let log = dir.path().join("a").join("messages.jsonl");
+        let mut bytes = std::fs::read(&log).unwrap();
+        bytes.extend_from_slice(b"{\"role\":\"us");
+        std::fs::write(&log, bytes).unwrap();
+        let loaded = store.load().await.unwrap();
+        assert_eq!(loaded[0].1.len(), 2, "torn append is dropped");
+    }
+
+    #[tokio::test]
+    async fn message_ends_are_batched_until_the_channel_closes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = std::sync::Arc::new(CheckpointStore::new(dir.path()));
+        let transport = crate::test_utils::MockTransport::new().with_text_response("hello");
+        let (handle, _) = crate::test_utils::spawn_test_agent(transport, vec![]).await;
+        handle.prompt_and_wait("hi").await.unwrap();
+
+        let (tx, rx) = broadcast::channel(16);
+        let task = spawn_checkpointer(
+            store.clone(),
+            "a".into(),
+            handle,
+            rx,
+            CancellationToken::new(),
+        );
+        for _ in 0..3 {
+            let message = make_assistant_message("hello");
+            tx.send(AgentEvent::MessageEnd { message }).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            store.read_messages("a").await.unwrap().is_empty(),
+            "a MessageEnd waits out the debounce"
+        );
+
+        drop(tx);
+        task.await.unwrap();
+        assert_eq!(store.read_messages("a").await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn trims_only_a_dangling_tool_call_turn() {
+        let tool_call = make_tool_call_message("bash", "t1", serde_json::json!({}));
+        let mut messages = vec![Message::user("hi"), tool_call];
+        trim_incomplete_turn(&mut messages);
+        assert_eq!(messages.len(), 1);
+        trim_incomplete_turn(&mut messages);
+        assert_eq!(messages.len(), 1, "user message is kept");
+    }
+}
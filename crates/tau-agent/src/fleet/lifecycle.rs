@@ -3,16 +3,19 @@
 //! These are the user-facing fleet methods. They compose the registry
 //! (invariant-preserving storage), the bus (event forwarding +
 //! interaction routing), the worktree module (filesystem isolation),
-//! and the transcript module (JSONL dump) into the actual lifecycle
-//! behaviors.
+//! the transcript module (live JSONL log), and the checkpoint module
+//! (restart persistence) into the actual lifecycle behaviors.
 //!
 //! Cross-cutting concerns:
 //!
 //! - Every spawn/run is bracketed by `FleetEvent::AgentStarted` and
 //!   `FleetEvent::AgentCompleted` on the manager's fleet channel — even when
 //!   setup itself fails (worktree creation, history inheritance).
-//! - On any exit (success or failure), the transcript is recorded if
-//!   a handle exists.
+//! - Every run appends to the agent's transcript as its events arrive,
+//!   so a failed or crashed run leaves its log up to the last event.
+//! - With a checkpoint store configured, every run is mirrored to disk
+//!   while it executes; idling updates the checkpoint, dropping the
+//!   agent deletes it.
 //! - Cancellation: each spawn receives a `CancellationToken`; the
 //!   lifecycle bridges parent cancellation onto the subagent's
 //!   internal cancel.
@@ -33,12 +36,18 @@ use crate::core::interaction::InteractionRequest;
 use crate::core::tool::send_event;
 use crate::core::transport::Transport;
 use crate::fleet::SubagentMessageExt;
+use crate::fleet::blackboard::Blackboard;
 use crate::fleet::bus;
+use crate::fleet::checkpoint::{
+    self, AgentCheckpoint, CheckpointStatus, CheckpointStore, RestoreReport,
+};
 use crate::fleet::manager::{AgentSpec, Isolation, SpawnOpts};
 use crate::fleet::registry::{AgentEntry, Registry};
 use crate::fleet::result::SubagentResult;
-use crate::fleet::transcript::record_transcript;
-use crate::fleet::worktree::{WorktreeInfo, cleanup_worktree, create_worktree};
+use crate::fleet::transcript::{self, TranscriptRecord};
+use crate::fleet::worktree::{
+    KeptWorktrees, WorktreeInfo, cleanup_worktree, create_worktree, merge_into_worktree,
+};
 use crate::types::error::{Error, Result};
 use crate::types::events::{FleetEvent, SubagentOutcome};
 
@@ -46,6 +55,7 @@ use crate::types::events::{FleetEvent, SubagentOutcome};
 /// composes one of these per-call rather than holding a single
 /// "context" field — keeps each lifecycle function honest about what
 /// it touches.
+#[derive(Clone)]
 pub struct LifecycleCtx {
     pub registry: Arc<Registry>,
     pub transport: Arc<dyn Transport>,
@@ -59,6 +69,39 @@ pub struct LifecycleCtx {
     /// [`AgentBuilder::set_interaction_timeout`]. See
     /// [`AgentManager::with_interaction_timeout`](crate::fleet::manager::AgentManager::with_interaction_timeout).
     pub interaction_timeout: Option<Duration>,
+    /// Worktrees left behind with changes, keyed by agent id. Run
+    /// teardown records into it; the manager's review methods read it.
+    pub worktrees: KeptWorktrees,
+    /// Where runs are checkpointed for restore across restarts. `None`
+    /// disables checkpointing.
+    pub checkpoints: Option<Arc<CheckpointStore>>,
+    /// The fleet blackboard, whose subscriptions of agents that leave
+    /// the registry are dropped with them.
+    pub blackboard: Arc<Blackboard>,
+    /// The manager's shutdown token. Background runs, which have no
+    /// caller to cancel them, take a child of it.
+    pub shutdown: CancellationToken,
+}
+
+impl LifecycleCtx {
+    /// Owned copy for work that outlives the call, e.g. a background
+    /// run's task. Every field is a cheap clone.
+    fn detached(&self) -> LifecycleCtx {
+        LifecycleCtx {
+            registry: Arc::clone(&self.registry),
+            transport: Arc::clone(&self.transport),
+            parent_config: self.parent_config.clone(),
+            fleet_event_tx: self.fleet_event_tx.clone(),
+            parent_interaction_tx: self.parent_interaction_tx.clone(),
+            default_approval: Arc::clone(&self.default_approval),
+            interaction_router_capacity: self.interaction_router_capacity,
+            interaction_timeout: self.interaction_timeout,
+            worktrees: Arc::clone(&self.worktrees),
+            checkpoints: self.checkpoints.clone(),
+            blackboard: Arc::clone(&self.blackboard),
+            shutdown: self.shutdown.clone(),
+        }
+    }
 }
 
 // ─── Foreground spawn ────────────────────────────────────────────────
@@ -74,13 +117,13 @@ pub async fn spawn(
     let spec = spec.into();
 
     ctx.registry.begin_spawn(&agent_id, Arc::clone(&spec));
-    let result = run_one(ctx, &spec, &initial_prompt, &opts, cancel, &agent_id).await;
+    let result = run_one(ctx, &spec, &initial_prompt, &opts, cancel, &agent_id, false).await;
 
     match result {
         Ok((subresult, entry)) => {
             // Snapshot usage + messages-count before moving to idle.
             let final_entry = enrich_entry_for_idle(entry).await;
-            ctx.registry.finish_to_idle(&agent_id, final_entry);
+            settle_idle(ctx, &agent_id, final_entry).await;
             Ok(subresult)
         }
         Err(e) => {
@@ -90,12 +133,42 @@ pub async fn spawn(
             // running entry if commit happened (and the spec either
             // way), and no-ops on the running map if setup failed
             // before commit.
-            ctx.registry.drop_running(&agent_id);
+            discard_failed(ctx, &agent_id).await;
             Err(e)
         }
     }
 }
 
+/// Move a finished run into idle storage and mark its checkpoint idle
+/// with the final history. An agent evicted to make room loses its
+/// checkpoint and subscriptions along with its registry entry.
+async fn settle_idle(ctx: &LifecycleCtx, agent_id: &str, entry: AgentEntry) {
+    let handle = entry.handle.clone();
+    let evicted = ctx.registry.finish_to_idle(agent_id, entry);
+    if let Some(evicted) = &evicted {
+        ctx.blackboard.forget(evicted);
+    }
+    if let Some(store) = &ctx.checkpoints {
+        checkpoint::record_state(store, agent_id, &handle, &mut Default::default()).await;
+        store
+            .update(agent_id, |c| c.status = CheckpointStatus::Idle)
+            .await;
+        if let Some(evicted) = evicted {
+            store.remove(&evicted).await;
+        }
+    }
+}
+
+/// Failure-path cleanup: [`Registry::drop_running`] plus the agent's
+/// checkpoint and subscriptions.
+async fn discard_failed(ctx: &LifecycleCtx, agent_id: &str) {
+    ctx.registry.drop_running(agent_id);
+    ctx.blackboard.forget(agent_id);
+    if let Some(store) = &ctx.checkpoints {
+        store.remove(agent_id).await;
+    }
+}
+
 /// Snapshot the handle's current usage + message count for delta
 /// computation on the next resume.
 async fn enrich_entry_for_idle(mut entry: AgentEntry) -> AgentEntry {
@@ -126,23 +199,12 @@ pub async fn spawn_background(
     let agent_id = uuid::Uuid::new_v4().to_string();
     let description = opts.description.clone();
     let spec = spec.into();
-    let bg_cancel = CancellationToken::new();
+    let bg_cancel = ctx.shutdown.child_token();
 
     parent_handle.expect_follow_up();
     ctx.registry.begin_spawn(&agent_id, Arc::clone(&spec));
 
-    // Build a static copy of everything the spawned future needs;
-    // `LifecycleCtx` itself isn't `Clone`.
-    let inner_ctx = LifecycleCtx {
-        registry: Arc::clone(&ctx.registry),
-        transport: Arc::clone(&ctx.transport),
-        parent_config: ctx.parent_config.clone(),
-        fleet_event_tx: ctx.fleet_event_tx.clone(),
-        parent_interaction_tx: ctx.parent_interaction_tx.clone(),
-        default_approval: Arc::clone(&ctx.default_approval),
-        interaction_router_capacity: ctx.interaction_router_capacity,
-        interaction_timeout: ctx.interaction_timeout,
-    };
+    let inner_ctx = ctx.detached();
     let aid = agent_id.clone();
     let desc = description.clone();
     let bg_cancel_inner = bg_cancel.clone();
@@ -163,6 +225,7 @@ pub async fn spawn_background(
             &opts,
             bg_cancel_inner,
             &aid,
+            true,
         )
         .await;
         cancel_forwarder.abort();
@@ -170,21 +233,13 @@ pub async fn spawn_background(
         match result {
             Ok((subresult, entry)) => {
                 let final_entry = enrich_entry_for_idle(entry).await;
-                inner_ctx.registry.finish_to_idle(&aid, final_entry);
+                settle_idle(&inner_ctx, &aid, final_entry).await;
 
                 let _ = parent_handle
                     .follow_up(Message::subagent_completed(
                         &subresult.agent_id,
                         &desc,
-                        format!(
-                            "{}\n[Agent {} | {} in + {} out tokens | {} tool calls | {}ms]",
-                            subresult.text,
-                            subresult.agent_id,
-                            subresult.input_tokens,
-                            subresult.output_tokens,
-                            subresult.tool_use_count,
-                            subresult.duration_ms,
-                        ),
+                        completion_text(&subresult),
                     ))
                     .await;
             }
@@ -192,7 +247,7 @@ pub async fn spawn_background(
                 // See `spawn`: the agent may already be in the running
                 // set; `drop_running` cleans up whether or not the
                 // commit happened.
-                inner_ctx.registry.drop_running(&aid);
+                discard_failed(&inner_ctx, &aid).await;
                 let _ = parent_handle
                     .follow_up(Message::subagent_failed(&aid, &desc, format!("Error: {e}")))
                     .await;
@@ -203,6 +258,20 @@ pub async fn spawn_background(
     agent_id
 }
 
+/// Follow-up body for a background run that finished: the agent's
+/// final text plus a one-line usage footer.
+fn completion_text(subresult: &SubagentResult) -> String {
+    format!(
+        "{}\n[Agent {} | {} in + {} out tokens | {} tool calls | {}ms]",
+        subresult.text,
+        subresult.agent_id,
+        subresult.input_tokens,
+        subresult.output_tokens,
+        subresult.tool_use_count,
+        subresult.duration_ms,
+    )
+}
+
 // ─── Resume ─────────────────────────────────────────────────────────
 
 pub async fn send(
@@ -210,6 +279,18 @@ pub async fn send(
     agent_id: &str,
     message: &str,
     parent_cancel: CancellationToken,
+) -> Result<SubagentResult> {
+    send_prompt(ctx, agent_id, message, false, parent_cancel).await
+}
+
+/// [`send`], with `reprompt` set when the host wrote `message` (see
+/// [`AgentHandle::reprompt_and_wait`]).
+async fn send_prompt(
+    ctx: &LifecycleCtx,
+    agent_id: &str,
+    message: &str,
+    reprompt: bool,
+    parent_cancel: CancellationToken,
 ) -> Result<SubagentResult> {
     let started_at = Utc::now();
     let start = Instant::now();
@@ -261,8 +342,20 @@ pub async fn send(
         forwarder_shutdown.clone(),
     );
     let cancel_bridge = spawn_cancel_bridge(entry.handle.clone(), parent_cancel.clone());
+    if let Some(store) = &ctx.checkpoints {
+        store
+            .update(agent_id, |c| c.status = CheckpointStatus::Running)
+            .await;
+    }
+    let checkpointer = start_checkpointer(ctx, agent_id, &entry.handle);
+    let (transcript_path, recorder) =
+        start_transcript(agent_id, &description, message, &entry.handle);
 
-    let prompt_result = entry.handle.prompt_and_wait(message).await;
+    let prompt_result = if reprompt {
+        entry.handle.reprompt_and_wait(message).await
+    } else {
+        entry.handle.prompt_and_wait(message).await
+    };
     cancel_bridge.abort();
     // Signal then await the forwarder so it can drain any events
     // (final `TurnEnd` / `ToolExecutionEnd`) still buffered in its
@@ -270,6 +363,8 @@ pub async fn send(
     // this closes.
     forwarder_shutdown.cancel();
     let _ = event_task.await;
+    stop_watcher(checkpointer).await;
+    stop_watcher(recorder).await;
 
     let messages = entry.handle.messages().await.unwrap_or_default();
     let current_state = entry.handle.state().await.unwrap_or_default();
@@ -277,14 +372,11 @@ pub async fn send(
     let tool_use_count = count_tool_uses_since(&messages, messages_at_pause);
     let text = extract_final_text(&messages);
 
-    let transcript_path = record_transcript(agent_id, &messages)
-        .await
-        .map(|p| p.display().to_string());
     entry.usage_at_pause = current_state.total_usage.clone();
     entry.messages_at_pause = messages.len();
 
     // Idle bookkeeping: push back, then drop running.
-    ctx.registry.finish_to_idle(agent_id, entry);
+    settle_idle(ctx, agent_id, entry).await;
 
     let completed_at = Utc::now();
     let duration_ms = start.elapsed().as_millis() as u64;
@@ -365,6 +457,10 @@ pub async fn respec(
     match spawn_interactive(ctx, new_spec, opts).await {
         Ok((handle, _new_id)) => {
             ctx.registry.drop_respec_source(agent_id);
+            ctx.blackboard.forget(agent_id);
+            if let Some(store) = &ctx.checkpoints {
+                store.remove(agent_id).await;
+            }
             Ok(handle)
         }
         Err(e) => {
@@ -381,6 +477,152 @@ pub async fn respec(
     }
 }
 
+// ─── Restore from checkpoints ───────────────────────────────────────
+
+/// Rebuild every checkpointed agent into idle storage so `send`
+/// reaches it again. Specs are resolved by name through `resolve`
+/// (called with the depth of the agent that spawned it, like a tool's
+/// spec resolver); agents whose spec can't be resolved stay on disk
+/// and are reported as skipped.
+///
+/// A checkpoint still marked running is a run the previous process
+/// never finished. With `resume_into` set, interrupted *background*
+/// agents are re-prompted with [`checkpoint::RESUME_PROMPT`] and
+/// report back to that handle as a follow-up, as if freshly spawned
+/// with [`spawn_background`]. Everything else is restored idle.
+pub async fn restore(
+    ctx: &LifecycleCtx,
+    resolve: &(dyn Fn(&str, u32) -> Option<AgentSpec> + Send + Sync),
+    resume_into: Option<&AgentHandle>,
+) -> Result<RestoreReport> {
+    let mut report = RestoreReport::default();
+    let Some(store) = &ctx.checkpoints else {
+        return Ok(report);
+    };
+    let checkpoints = store.load().await.map_err(|e| Error::CheckpointFailed {
+        reason: e.to_string(),
+    })?;
+
+    let mut interrupted = Vec::new();
+    for (cp, mut messages) in checkpoints {
+        let agent_id = cp.agent_id.clone();
+        if ctx.registry.spec_for(&agent_id).is_some() {
+            report.skipped.push((agent_id, "already tracked".into()));
+            continue;
+        }
+        let Some(spec_name) = cp.spec_name.as_deref() else {
+            report
+                .skipped
+                .push((agent_id, "spawned without a spec name".into()));
+            continue;
+        };
+        let Some(spec) = resolve(spec_name, cp.subagent_depth.saturating_sub(1)) else {
+            report
+                .skipped
+                .push((agent_id, format!("unknown spec '{spec_name}'")));
+            continue;
+        };
+
+        // A worktree that still exists holds the agent's changes: run
+        // there again and put it back up for review.
+        let worktree = cp.worktree.clone().filter(|wt| wt.path.exists());
+        let wt_cwd = worktree.as_ref().map(|w| w.path.display().to_string());
+        checkpoint::trim_incomplete_turn(&mut messages);
+        let opts = SpawnOpts {
+            description: cp.description.clone(),
+            model: cp
+                .model
+                .as_deref()
+                .and_then(tau_ai::models::get_model_by_id),
+            cwd: cp.cwd.clone(),
+            spec_name: cp.spec_name.clone(),
+            seed: crate::core::builder::AgentSeed::Messages {
+                messages,
+                previous_summary: cp.previous_summary.clone(),
+            },
+            subagent_depth: cp.subagent_depth,
+            ..Default::default()
+        };
+        let spawned = match configure_builder(ctx, &spec, &opts, &agent_id, wt_cwd.as_deref()).await
+        {
+            Ok(builder) => builder.spawn().await,
+            Err(e) => Err(e),
+        };
+        let handle = match spawned {
+            Ok(h) => h,
+            Err(e) => {
+                report.skipped.push((agent_id, e.to_string()));
+                continue;
+            }
+        };
+        if let Some(wt) = worktree {
+            ctx.worktrees.lock().insert(agent_id.clone(), wt);
+        }
+
+        let was_running = cp.status == CheckpointStatus::Running;
+        let mut entry =
+            enrich_entry_for_idle(AgentEntry::new(handle, cp.description.clone())).await;
+        entry.started_at = Some(cp.started_at);
+        entry.completed_at = (!was_running).then_some(cp.updated_at);
+        if let Some(evicted) = ctx.registry.restore_idle(&agent_id, entry, Arc::new(spec)) {
+            ctx.blackboard.forget(&evicted);
+            store.remove(&evicted).await;
+            report.restored.retain(|id| id != &evicted);
+        }
+        if was_running {
+            store
+                .update(&agent_id, |c| c.status = CheckpointStatus::Idle)
+                .await;
+            if cp.background {
+                interrupted.push((agent_id.clone(), cp.description));
+            }
+        }
+        report.restored.push(agent_id);
+    }
+
+    if let Some(parent) = resume_into {
+        for (agent_id, description) in interrupted {
+            if report.restored.contains(&agent_id) {
+                resume_background(ctx, &agent_id, description, parent.clone());
+                report.resumed.push(agent_id);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// [`send_prompt`] on a background task, reporting to `parent_handle` the way
+/// [`spawn_background`] does.
+fn resume_background(
+    ctx: &LifecycleCtx,
+    agent_id: &str,
+    description: String,
+    parent_handle: AgentHandle,
+) {
+    parent_handle.expect_follow_up();
+    let ctx = ctx.detached();
+    let aid = agent_id.to_string();
+    tokio::spawn(async move {
+        let message = match send_prompt(
+            &ctx,
+            &aid,
+            checkpoint::RESUME_PROMPT,
+            true,
+            ctx.shutdown.child_token(),
+        )
+        .await
+        {
+            Ok(subresult) => Message::subagent_completed(
+                &subresult.agent_id,
+                &description,
+                completion_text(&subresult),
+            ),
+            Err(e) => Message::subagent_failed(&aid, &description, format!("Error: {e}")),
+        };
+        let _ = parent_handle.follow_up(message).await;
+    });
+}
+
 // ─── Interactive spawn (used by respec internally; also public) ──────
 
 pub async fn spawn_interactive(
@@ -573,6 +815,7 @@ async fn run_one(
     opts: &SpawnOpts,
     cancel: CancellationToken,
     agent_id: &str,
+    background: bool,
 ) -> Result<(SubagentResult, AgentEntry)> {
     let started_at = Utc::now();
     let start = Instant::now();
@@ -625,6 +868,35 @@ async fn run_one(
     } else {
         None
     };
+    // Conflict resolution: start the agent on top of the branch being
+    // merged in. Conflict markers are its input, not a setup failure.
+    if let (Some(wt), Some(branch)) = (&worktree, &opts.merge_branch)
+        && let Err(e) = merge_into_worktree(wt, branch).await
+    {
+        let err = Error::WorktreeSetupFailed { reason: e };
+        let (wt_path, wt_branch) = teardown_worktree(ctx, agent_id, &worktree).await;
+        return Err(emit_setup_failure(err, wt_path, wt_branch).await);
+    }
+    if let Some(store) = &ctx.checkpoints {
+        let now = Utc::now();
+        store
+            .save(&AgentCheckpoint {
+                agent_id: agent_id.into(),
+                description: opts.description.clone(),
+                spec_name: opts.spec_name.clone(),
+                prompt: initial_prompt.into(),
+                model: opts.model.as_ref().map(|m| m.id.clone()),
+                cwd: opts.cwd.clone(),
+                worktree: worktree.clone(),
+                subagent_depth: opts.subagent_depth,
+                background,
+                status: CheckpointStatus::Running,
+                previous_summary: None,
+                started_at: now,
+                updated_at: now,
+            })
+            .await;
+    }
 
     let inner = run_agent_inner(
         ctx,
@@ -636,7 +908,7 @@ async fn run_one(
         cancel.clone(),
     )
     .await;
-    let (wt_path, wt_branch) = teardown_worktree(&worktree).await;
+    let (wt_path, wt_branch) = teardown_worktree(ctx, agent_id, &worktree).await;
 
     let completed_at = Utc::now();
     let duration_ms = start.elapsed().as_millis() as u64;
@@ -652,13 +924,7 @@ async fn run_one(
         }
     };
 
-    let messages = handle.messages().await.unwrap_or_default();
-    let transcript_path = record_transcript(agent_id, &messages)
-        .await
-        .map(|p| p.display().to_string());
-
     let outcome = outcome_from(&prompt_result, &cancel);
-    result.transcript_path = transcript_path;
     result.worktree_path = wt_path.clone();
     result.worktree_branch = wt_branch.clone();
     result.duration_ms = duration_ms;
@@ -720,6 +986,9 @@ async fn run_agent_inner(
         agent_id,
         AgentEntry::new(handle.clone(), opts.description.clone()),
     );
+    let checkpointer = start_checkpointer(ctx, agent_id, &handle);
+    let (transcript_path, recorder) =
+        start_transcript(agent_id, &opts.description, initial_prompt, &handle);
 
     let prompt_result = handle.prompt_and_wait(initial_prompt).await;
     cancel_bridge.abort();
@@ -729,6 +998,8 @@ async fn run_agent_inner(
     // this closes.
     forwarder_shutdown.cancel();
     let _ = event_task.await;
+    stop_watcher(checkpointer).await;
+    stop_watcher(recorder).await;
 
     let messages = handle.messages().await.unwrap_or_default();
     let text = extract_final_text(&messages);
@@ -744,7 +1015,7 @@ async fn run_agent_inner(
         duration_ms: 0,
         worktree_path: None,
         worktree_branch: None,
-        transcript_path: None,
+        transcript_path,
     };
     Ok(RunOutcome {
         handle,
@@ -765,11 +1036,66 @@ pub(crate) fn spawn_cancel_bridge(
     })
 }
 
-async fn teardown_worktree(worktree: &Option<WorktreeInfo>) -> (Option<String>, Option<String>) {
+/// A per-run background task and the token that stops it.
+type Watcher = (CancellationToken, tokio::task::JoinHandle<()>);
+
+fn start_checkpointer(ctx: &LifecycleCtx, agent_id: &str, handle: &AgentHandle) -> Option<Watcher> {
+    let store = ctx.checkpoints.as_ref()?;
+    let shutdown = CancellationToken::new();
+    let task = checkpoint::spawn_checkpointer(
+        Arc::clone(store),
+        agent_id.into(),
+        handle.clone(),
+        handle.subscribe(),
+        shutdown.clone(),
+    );
+    Some((shutdown, task))
+}
+
+/// Start appending this run to the agent's transcript. Returns the
+/// transcript path for [`SubagentResult::transcript_path`].
+fn start_transcript(
+    agent_id: &str,
+    description: &str,
+    prompt: &str,
+    handle: &AgentHandle,
+) -> (Option<String>, Option<Watcher>) {
+    let Some(path) = transcript::transcript_path(agent_id) else {
+        return (None, None);
+    };
+    let shutdown = CancellationToken::new();
+    let task = transcript::spawn_transcript_recorder(
+        path.clone(),
+        TranscriptRecord::RunStart {
+            agent_id: agent_id.into(),
+            description: description.into(),
+            prompt: prompt.into(),
+        },
+        handle.subscribe(),
+        shutdown.clone(),
+    );
+    (Some(path.display().to_string()), Some((shutdown, task)))
+}
+
+async fn stop_watcher(watcher: Option<Watcher>) {
+    if let Some((shutdown, task)) = watcher {
+        shutdown.cancel();
+        let _ = task.await;
+    }
+}
+
+async fn teardown_worktree(
+    ctx: &LifecycleCtx,
+    agent_id: &str,
+    worktree: &Option<WorktreeInfo>,
+) -> (Option<String>, Option<String>) {
     match worktree {
         Some(wt) => match cleanup_worktree(wt).await {
             Ok(true) => (None, None),
-            _ => (Some(wt.path.display().to_string()), Some(wt.branch.clone())),
+            _ => {
+                ctx.worktrees.lock().insert(agent_id.into(), wt.clone());
+                (Some(wt.path.display().to_string()), Some(wt.branch.clone()))
+            }
         },
         None => (None, None),
     }
@@ -8,6 +8,7 @@
 //! at the module level, not the type level — `AgentManager` is the
 //! one type a host imports.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -23,12 +24,19 @@ use crate::core::handle::AgentHandle;
 use crate::core::interaction::InteractionRequest;
 use crate::core::tool::BoxedTool;
 use crate::core::transport::Transport;
+use crate::fleet::batch::{self, BatchInput, BatchOpts, BatchResult};
+use crate::fleet::blackboard::{Blackboard, BlackboardEntry};
+use crate::fleet::checkpoint::{CheckpointStore, RestoreReport};
 use crate::fleet::lifecycle::{self, LifecycleCtx};
 pub use crate::fleet::registry::Status as AgentStatus;
 use crate::fleet::registry::{Located, Registry};
 use crate::fleet::result::SubagentResult;
+use crate::fleet::scheduler::{ScheduledTimer, Scheduler};
 use crate::fleet::snapshot::FleetSnapshot;
-use crate::types::error::Result;
+use crate::fleet::worktree::{
+    self, KeptWorktrees, MergeOutcome, MergeStrategy, WorktreeDiff, WorktreeInfo,
+};
+use crate::types::error::{Error, Result};
 use crate::types::events::FleetEvent;
 
 /// Immutable per-agent input. To change any field, spawn a new agent
@@ -72,6 +80,11 @@ pub struct SpawnOpts {
     /// Callers that originate spawns from inside a tool should set this
     /// to `ctx.subagent_depth + 1`; root spawns leave it at `0`.
     pub subagent_depth: u32,
+    /// With [`Isolation::Worktree`], merge this branch into the fresh
+    /// worktree before the agent starts, leaving any conflict markers
+    /// for it to resolve. Set by
+    /// [`AgentManager::resolve_worktree_conflicts`].
+    pub merge_branch: Option<String>,
 }
 
 /// Default capacity of the manager's [`FleetEvent`] broadcast channel.
@@ -98,6 +111,19 @@ pub struct AgentManager {
     /// `None` means subagents inherit the actor-builder default
     /// (unbounded); set via [`Self::with_interaction_timeout`].
     interaction_timeout: Option<Duration>,
+    /// Worktrees subagents left changes in, awaiting review.
+    worktrees: KeptWorktrees,
+    /// Where subagents are checkpointed for [`Self::restore`]. Set via
+    /// [`Self::with_checkpoint_dir`] / [`Self::set_checkpoint_dir`];
+    /// unset means nothing is persisted.
+    checkpoints: ParkingMutex<Option<Arc<CheckpointStore>>>,
+    /// Topics shared by every agent in the fleet.
+    blackboard: Arc<Blackboard>,
+    /// Timers agents scheduled via [`Self::schedule_timer`].
+    scheduler: Scheduler,
+    /// Parent of every background run's token. Cancelled by
+    /// [`Self::shutdown`] and when the manager is dropped.
+    shutdown: CancellationToken,
 }
 
 impl AgentManager {
@@ -116,6 +142,11 @@ impl AgentManager {
             default_approval: ParkingMutex::new(Arc::new(DefaultPolicy)),
             interaction_router_capacity: crate::fleet::bus::DEFAULT_INTERACTION_ROUTER_CAPACITY,
             interaction_timeout: None,
+            worktrees: KeptWorktrees::default(),
+            checkpoints: ParkingMutex::new(None),
+            blackboard: Arc::new(Blackboard::new()),
+            scheduler: Scheduler::new(),
+            shutdown: CancellationToken::new(),
         }
     }
 
@@ -196,6 +227,23 @@ impl AgentManager {
         *self.default_approval.lock() = policy;
     }
 
+    /// Checkpoint every spawned subagent under `dir` so a later process
+    /// can [`Self::restore`] them. See [`crate::fleet::checkpoint`].
+    pub fn with_checkpoint_dir(self, dir: impl Into<PathBuf>) -> Self {
+        self.set_checkpoint_dir(dir);
+        self
+    }
+
+    /// Runtime form of [`Self::with_checkpoint_dir`], for hosts that
+    /// learn their session directory after building the manager. Runs
+    /// already in flight keep the store they started with. Timers are
+    /// saved alongside.
+    pub fn set_checkpoint_dir(&self, dir: impl Into<PathBuf>) {
+        let dir = dir.into();
+        self.scheduler.set_store_dir(&dir);
+        *self.checkpoints.lock() = Some(Arc::new(CheckpointStore::new(dir)));
+    }
+
     fn ctx(&self) -> LifecycleCtx {
         LifecycleCtx {
             registry: Arc::clone(&self.registry),
@@ -206,6 +254,10 @@ impl AgentManager {
             default_approval: self.default_approval.lock().clone(),
             interaction_router_capacity: self.interaction_router_capacity,
             interaction_timeout: self.interaction_timeout,
+            worktrees: Arc::clone(&self.worktrees),
+            checkpoints: self.checkpoints.lock().clone(),
+            blackboard: Arc::clone(&self.blackboard),
+            shutdown: self.shutdown.clone(),
         }
     }
 
@@ -227,6 +279,19 @@ impl AgentManager {
         lifecycle::spawn(&self.ctx(), spec, initial_prompt, opts, cancel).await
     }
 
+    /// Fan out one foreground subagent per input, at most
+    /// `opts.concurrency` at a time, and collect every result. See
+    /// [`crate::fleet::batch`].
+    pub async fn spawn_batch(
+        &self,
+        spec: impl Into<Arc<AgentSpec>>,
+        inputs: Vec<BatchInput>,
+        opts: BatchOpts,
+        cancel: CancellationToken,
+    ) -> BatchResult {
+        batch::spawn_batch(&self.ctx(), spec, inputs, opts, cancel).await
+    }
+
     pub async fn spawn_interactive(
         &self,
         spec: impl Into<Arc<AgentSpec>>,
@@ -240,6 +305,9 @@ impl AgentManager {
     /// held in idle storage.
     pub fn remove_interactive(&self, agent_id: &str) {
         self.registry.drop_running(agent_id);
+        if self.registry.spec_for(agent_id).is_none() {
+            self.blackboard.forget(agent_id);
+        }
     }
 
     /// Spawn a subagent that runs in the background. Returns
@@ -296,6 +364,35 @@ impl AgentManager {
         lifecycle::adopt(&self.registry, handle, description, spec)
     }
 
+    /// Rebuild checkpointed subagents from the checkpoint directory,
+    /// typically once at startup when resuming a session. Idle agents
+    /// come back reachable by [`Self::send`]; interrupted background
+    /// runs are resumed into `resume_into` when given. Saved timers
+    /// are re-armed once the agents are back. See
+    /// [`lifecycle::restore`].
+    pub async fn restore(
+        &self,
+        resolve: &(dyn Fn(&str, u32) -> Option<AgentSpec> + Send + Sync),
+        resume_into: Option<&AgentHandle>,
+    ) -> Result<RestoreReport> {
+        let ctx = self.ctx();
+        let mut report = lifecycle::restore(&ctx, resolve, resume_into).await?;
+        report.timers =
+            self.scheduler
+                .restore(&ctx)
+                .await
+                .map_err(|e| Error::CheckpointFailed {
+                    reason: format!("reading timers: {e}"),
+                })?;
+        Ok(report)
+    }
+
+    /// Cancel every background run, including the ones [`Self::restore`]
+    /// resumed. Foreground runs stop through their callers' tokens.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
     // ─── Lookups ─────────────────────────────────────────────────────
 
     /// Resolve an id-or-description to an agent. See
@@ -321,7 +418,197 @@ impl AgentManager {
     pub fn snapshot(&self) -> FleetSnapshot {
         FleetSnapshot {
             agents: self.registry.snapshot(),
+            blackboard: self.blackboard.topics(),
+            timers: self.scheduler.list(),
         }
     }
 
+    // ─── Blackboard ──────────────────────────────────────────────────
+
+    /// Post `text` to `topic` as `agent_id`, emit
+    /// [`FleetEvent::BlackboardPosted`], and steer every running
+    /// subscriber other than the poster with the entry. Idle
+    /// subscribers see it on their next read.
+    pub async fn post_to_blackboard(
+        &self,
+        agent_id: &str,
+        topic: &str,
+        text: impl Into<String>,
+    ) -> BlackboardEntry {
+        let (entry, subscribers) = self.blackboard.post(topic, agent_id, text);
+        let description = self
+            .registry
+            .find(agent_id)
+            .map(|l| l.description)
+            .unwrap_or_else(|| agent_id.to_string());
+        let _ = self.fleet_event_tx.send(FleetEvent::BlackboardPosted {
+            seq: entry.seq,
+            topic: entry.topic.clone(),
+            agent_id: entry.agent_id.clone(),
+            description: description.clone(),
+            text: entry.text.clone(),
+        });
+        let steer = format!(
+            "[Blackboard #{} from {} ({}), entry {}]: {}",
+            entry.topic, description, entry.agent_id, entry.seq, entry.text
+        );
+        for subscriber in subscribers {
+            if let Some(handle) = self.registry.handle_for(&subscriber) {
+                let _ = handle.steer(tau_ai::Message::user(&steer)).await;
+            }
+        }
+        entry
+    }
+
+    /// Entries of `topic` newer than `since` (`0` for all retained).
+    pub fn read_blackboard(&self, topic: &str, since: u64) -> Vec<BlackboardEntry> {
+        self.blackboard.read(topic, since)
+    }
+
+    /// Have new posts to `topic` steered into `agent_id` while it runs.
+    /// Returns `false` if it was already subscribed.
+    pub fn subscribe_blackboard(&self, agent_id: &str, topic: &str) -> bool {
+        self.blackboard.subscribe(topic, agent_id)
+    }
+
+    /// Returns `false` if `agent_id` wasn't subscribed to `topic`.
+    pub fn unsubscribe_blackboard(&self, agent_id: &str, topic: &str) -> bool {
+        self.blackboard.unsubscribe(topic, agent_id)
+    }
+
+    // ─── Timers ──────────────────────────────────────────────────────
+
+    /// Send `prompt` to `agent_id` after `delay`, then every `every` if
+    /// given. Emits [`FleetEvent::TimerFired`] each time it comes due.
+    /// See [`crate::fleet::scheduler`] for how the prompt is delivered.
+    pub fn schedule_timer(
+        &self,
+        agent_id: &str,
+        prompt: impl Into<String>,
+        delay: Duration,
+        every: Option<Duration>,
+    ) -> Result<ScheduledTimer> {
+        self.scheduler
+            .schedule(self.ctx(), agent_id, prompt.into(), delay, every)
+    }
+
+    /// Stop a pending timer. Returns it if it existed.
+    pub fn cancel_timer(&self, timer_id: &str) -> Option<ScheduledTimer> {
+        self.scheduler.cancel(timer_id)
+    }
+
+    /// Pending timers, soonest first.
+    pub fn timers(&self) -> Vec<ScheduledTimer> {
+        self.scheduler.list()
+    }
+
+    // ─── Worktree review ─────────────────────────────────────────────
+
+    /// Worktrees that finished subagents left changes in, sorted by
+    /// agent id. Entries leave this list when merged or discarded.
+    pub fn worktrees(&self) -> Vec<(String, WorktreeInfo)> {
+        let mut kept: Vec<_> = self
+            .worktrees
+            .lock()
+            .iter()
+            .map(|(id, info)| (id.clone(), info.clone()))
+            .collect();
+        kept.sort_by(|a, b| a.0.cmp(&b.0));
+        kept
+    }
+
+    fn worktree(&self, agent_id: &str) -> Result<WorktreeInfo> {
+        self.worktrees
+            .lock()
+            .get(agent_id)
+            .cloned()
+            .ok_or_else(|| Error::NoWorktree {
+                id: agent_id.into(),
+            })
+    }
+
+    /// The agent's changes relative to the parent checkout's `HEAD`.
+    /// Commits the worktree's pending changes onto its branch first.
+    pub async fn worktree_diff(&self, agent_id: &str) -> Result<WorktreeDiff> {
+        let info = self.worktree(agent_id)?;
+        worktree::diff_worktree(&info)
+            .await
+            .map_err(|reason| worktree_error(agent_id, reason))
+    }
+
+    /// Bring the agent's changes into the parent checkout. On success
+    /// (or when there was nothing to merge) the worktree and its branch
+    /// are removed. On conflicts the parent is left untouched and the
+    /// worktree stays tracked — resolve by hand, discard it, or hand it
+    /// to [`Self::resolve_worktree_conflicts`].
+    pub async fn merge_worktree(
+        &self,
+        agent_id: &str,
+        strategy: MergeStrategy,
+    ) -> Result<MergeOutcome> {
+        let info = self.worktree(agent_id)?;
+        let outcome = worktree::merge_worktree(&info, strategy)
+            .await
+            .map_err(|reason| worktree_error(agent_id, reason))?;
+        if !matches!(outcome, MergeOutcome::Conflicted { .. }) {
+            self.discard_worktree(agent_id).await?;
+        }
+        Ok(outcome)
+    }
+
+    /// Remove the agent's worktree and branch, dropping its changes.
+    pub async fn discard_worktree(&self, agent_id: &str) -> Result<()> {
+        let info = self.worktree(agent_id)?;
+        worktree::discard_worktree(&info)
+            .await
+            .map_err(|reason| worktree_error(agent_id, reason))?;
+        self.worktrees.lock().remove(agent_id);
+        Ok(())
+    }
+
+    /// Spawn a subagent in a fresh worktree with `agent_id`'s branch
+    /// merged in, conflict markers and all, and have it resolve them.
+    /// Commits the worktree's pending changes onto its branch first, so
+    /// the resolver sees all of them. The resolver's worktree becomes
+    /// the one to review and merge; the original is discarded once the
+    /// resolver leaves changes behind.
+    pub async fn resolve_worktree_conflicts(
+        &self,
+        agent_id: &str,
+        spec: impl Into<Arc<AgentSpec>>,
+        mut opts: SpawnOpts,
+        cancel: CancellationToken,
+    ) -> Result<SubagentResult> {
+        let info = self.worktree(agent_id)?;
+        worktree::snapshot_worktree(&info)
+            .await
+            .map_err(|reason| worktree_error(agent_id, reason))?;
+        let prompt = format!(
+            "This worktree has branch `{}` merged into it, and the merge stopped on conflicts. \
+             Find the conflicted files with `git diff --name-only --diff-filter=U`, resolve \
+             every conflict keeping the intent of both sides, and check that the project still \
+             builds. Do not commit; your changes are collected when you finish.",
+            info.branch
+        );
+        opts.isolation = Some(Isolation::Worktree);
+        opts.merge_branch = Some(info.branch.clone());
+        let result = self.spawn(spec, prompt, opts, cancel).await?;
+        if result.worktree_branch.is_some() {
+            self.discard_worktree(agent_id).await?;
+        }
+        Ok(result)
+    }
+}
+
+impl Drop for AgentManager {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+fn worktree_error(agent_id: &str, reason: String) -> Error {
+    Error::WorktreeFailed {
+        id: agent_id.into(),
+        reason,
+    }
 }
@@ -11,13 +11,23 @@
 //!   routing.
 //!
 //! [`AgentManager`](manager::AgentManager) holds the three.
+//! [`batch`] layers fan-out/fan-in on top of `lifecycle::spawn`;
+//! [`checkpoint`] persists the fleet for restore across restarts;
+//! [`blackboard`] holds the topics agents share findings through;
+//! [`scheduler`] fires the timers agents set for themselves;
+//! [`telemetry`] exports the fleet's activity as OpenTelemetry spans.
 
+pub mod batch;
+pub mod blackboard;
 pub mod bus;
+pub mod checkpoint;
 pub mod lifecycle;
 pub mod manager;
 pub mod registry;
 pub mod result;
+pub mod scheduler;
 pub mod snapshot;
+pub mod telemetry;
 pub mod transcript;
 pub mod worktree;
 
@@ -122,6 +122,19 @@ struct Inner {
     max_agents: usize,
 }
 
+impl Inner {
+    /// Pop the oldest idle entry (and its spec) if idle storage is at
+    /// capacity.
+    fn evict_for_idle(&mut self) -> Option<String> {
+        if self.idle.len() < self.max_agents {
+            return None;
+        }
+        let (evicted_id, _) = self.idle.pop_front()?;
+        self.specs.remove(&evicted_id);
+        Some(evicted_id)
+    }
+}
+
 pub struct Registry {
     inner: Mutex<Inner>,
 }
@@ -182,7 +195,9 @@ impl Registry {
     /// caller typically passes a freshly-built `AgentEntry` that
     /// doesn't reflect the bus's running updates. `completed_at` is
     /// stamped here.
-    pub fn finish_to_idle(&self, agent_id: &str, mut entry: AgentEntry) {
+    ///
+    /// Returns the id of the idle agent evicted to make room, if any.
+    pub fn finish_to_idle(&self, agent_id: &str, mut entry: AgentEntry) -> Option<String> {
         let mut inner = self.inner.lock();
         if let Some(running) = inner.running.shift_remove(agent_id) {
             // Preserve fields the bus mutated during the run.
@@ -194,12 +209,26 @@ impl Registry {
             entry.started_at = Some(Utc::now());
         }
         entry.completed_at = Some(Utc::now());
-        if inner.idle.len() >= inner.max_agents {
-            if let Some((evicted_id, _)) = inner.idle.pop_front() {
-                inner.specs.remove(&evicted_id);
-            }
-        }
+        let evicted = inner.evict_for_idle();
+        inner.idle.push_back((agent_id.to_string(), entry));
+        evicted
+    }
+
+    /// Insert an agent rebuilt from a checkpoint straight into idle
+    /// storage, spec and entry together. Timestamps on `entry` are kept
+    /// as restored. Same eviction rule and return value as
+    /// [`Self::finish_to_idle`].
+    pub fn restore_idle(
+        &self,
+        agent_id: &str,
+        entry: AgentEntry,
+        spec: Arc<AgentSpec>,
+    ) -> Option<String> {
+        let mut inner = self.inner.lock();
+        let evicted = inner.evict_for_idle();
+        inner.specs.insert(agent_id.to_string(), spec);
         inner.idle.push_back((agent_id.to_string(), entry));
+        evicted
     }
 
     /// Drop a running agent without idling it. This is *the* failure-path
@@ -488,13 +517,7 @@ fn snapshot_of(id: &str, entry: &AgentEntry, status: Status) -> AgentSnapshot {
 }
 
 fn add_usage(dst: &mut Usage, src: &Usage) {
-    dst.input = dst.input.saturating_add(src.input);
-    dst.output = dst.output.saturating_add(src.output);
-    dst.cache_read = dst.cache_read.saturating_add(src.cache_read);
-    dst.cache_write = dst.cache_write.saturating_add(src.cache_write);
-    dst.thinking = dst.thinking.saturating_add(src.thinking);
-    dst.cache_creation_1h = dst.cache_creation_1h.saturating_add(src.cache_creation_1h);
-    dst.cache_creation_5m = dst.cache_creation_5m.saturating_add(src.cache_creation_5m);
+    dst.accumulate(src);
     if dst.service_tier.is_none() {
         dst.service_tier = src.service_tier.clone();
     }
@@ -564,6 +587,27 @@ mod tests {
         assert!(r.spec_for("c").is_some());
     }
 
+    #[test]
+    fn restore_idle_inserts_spec_and_reports_eviction() {
+        let r = Registry::new(1);
+        let spec = Arc::new(AgentSpec {
+            system_prompt: String::new(),
+            tools: vec![],
+            max_turns: 1,
+        });
+        let evicted = r.restore_idle(
+            "a",
+            AgentEntry::new(dummy_handle(), "a".into()),
+            Arc::clone(&spec),
+        );
+        assert_eq!(evicted, None);
+        assert_eq!(r.find("a").map(|l| l.status), Some(Status::Idle));
+        let evicted = r.restore_idle("b", AgentEntry::new(dummy_handle(), "b".into()), spec);
+        assert_eq!(evicted.as_deref(), Some("a"));
+        assert!(r.spec_for("a").is_none(), "evicted spec dropped");
+        assert!(r.spec_for("b").is_some());
+    }
+
     #[test]
     fn detach_for_respec_blocks_when_running() {
         let r = Registry::new(4);
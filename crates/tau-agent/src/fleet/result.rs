@@ -28,6 +28,7 @@ pub struct SubagentResult {
     /// was *not* cleanly removed (i.e., left changes behind).
     pub worktree_path: Option<String>,
     pub worktree_branch: Option<String>,
-    /// Path to the JSONL transcript on disk, when recording succeeded.
+    /// Path to the agent's live JSONL transcript (see
+    /// [`crate::fleet::transcript`]), when a data directory exists.
     pub transcript_path: Option<String>,
 }
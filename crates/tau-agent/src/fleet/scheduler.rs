@@ -0,0 +1,329 @@
+//! Timers agents schedule for themselves.
+//!
+//! A [`ScheduledTimer`] sends a prompt to an agent after a delay, once
+//! or on a fixed interval. Timers belong to the
+//! [`AgentManager`](crate::AgentManager), not the actor, so they
+//! outlive the run that created them. When one fires:
+//!
+//! - an agent running a prompt gets it as a follow-up, picked up when
+//!   its current turn loop would otherwise finish;
+//! - an idle fleet agent is resumed with it, as `send_message` would;
+//! - anything else — a host-driven root between prompts, or an agent
+//!   that has left the fleet — is left to the host:
+//!   [`FleetEvent::TimerFired`] carries `delivered: false`.
+//!
+//! With a checkpoint directory set, the timer list is rewritten to
+//! `<dir>/timers.json` on every change and read back by
+//! [`AgentManager::restore`](crate::AgentManager::restore). Timers that
+//! came due while nothing was running fire right away.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tau_ai::Message;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::tool::send_event;
+use crate::fleet::lifecycle::{self, LifecycleCtx};
+use crate::fleet::registry::Status;
+use crate::types::error::{Error, Result};
+use crate::types::events::FleetEvent;
+use crate::types::health::AgentHealth;
+
+/// Shortest interval a recurring timer may use.
+pub const MIN_TIMER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest delay or repeat interval a timer may use.
+pub const MAX_TIMER_DELAY: Duration = Duration::from_secs(30 * 86_400);
+
+/// Most timers the fleet holds at once.
+pub const MAX_TIMERS: usize = 32;
+
+const TIMERS_FILE: &str = "timers.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTimer {
+    /// Short fleet-unique id (`t1`, `t2`, …).
+    pub id: String,
+    /// Agent the prompt is delivered to.
+    pub agent_id: String,
+    pub prompt: String,
+    pub next_fire: DateTime<Utc>,
+    /// Repeat interval; `None` for a one-shot timer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_secs: Option<u64>,
+    /// Times this timer has fired so far.
+    #[serde(default)]
+    pub fired: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScheduledTimer {
+    /// The message the agent receives when the timer fires.
+    pub fn message(&self) -> String {
+        format!("[Timer {} fired] {}", self.id, self.prompt)
+    }
+}
+
+struct Inner {
+    timers: Mutex<IndexMap<String, (ScheduledTimer, CancellationToken)>>,
+    next_id: AtomicU64,
+    store: Mutex<Option<PathBuf>>,
+}
+
+impl Inner {
+    /// Rewrite the timer file. Best-effort, like checkpoints.
+    fn persist(&self) {
+        let Some(path) = self.store.lock().clone() else {
+            return;
+        };
+        let timers: Vec<ScheduledTimer> = self
+            .timers
+            .lock()
+            .values()
+            .map(|(t, _)| t.clone())
+            .collect();
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, serde_json::to_vec_pretty(&timers)?)
+        };
+        if let Err(e) = write() {
+            tracing::warn!(path = %path.display(), "could not save timers: {e}");
+        }
+    }
+}
+
+/// The fleet's timers. Cancels every pending timer when dropped.
+pub(crate) struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                timers: Mutex::new(IndexMap::new()),
+                next_id: AtomicU64::new(1),
+                store: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Persist timers into `dir` from now on.
+    pub fn set_store_dir(&self, dir: impl Into<PathBuf>) {
+        *self.inner.store.lock() = Some(dir.into().join(TIMERS_FILE));
+    }
+
+    pub fn schedule(
+        &self,
+        ctx: LifecycleCtx,
+        agent_id: &str,
+        prompt: String,
+        delay: Duration,
+        every: Option<Duration>,
+    ) -> Result<ScheduledTimer> {
+        if every.is_some_and(|e| e < MIN_TIMER_INTERVAL) {
+            return Err(Error::TimerRejected {
+                reason: format!(
+                    "recurring timers must be at least {}s apart",
+                    MIN_TIMER_INTERVAL.as_secs()
+                ),
+            });
+        }
+        if delay > MAX_TIMER_DELAY || every.is_some_and(|e| e > MAX_TIMER_DELAY) {
+            return Err(Error::TimerRejected {
+                reason: format!(
+                    "timers can be at most {} days out",
+                    MAX_TIMER_DELAY.as_secs() / 86_400
+                ),
+            });
+        }
+        if self.inner.timers.lock().len() >= MAX_TIMERS {
+            return Err(Error::TimerRejected {
+                reason: format!("the fleet already has {MAX_TIMERS} timers"),
+            });
+        }
+        let now = Utc::now();
+        let next_fire = TimeDelta::from_std(delay)
+            .ok()
+            .and_then(|d| now.checked_add_signed(d))
+            .ok_or_else(|| Error::TimerRejected {
+                reason: "delay is out of range".to_string(),
+            })?;
+        let timer = ScheduledTimer {
+            id: format!("t{}", self.inner.next_id.fetch_add(1, Ordering::Relaxed)),
+            agent_id: agent_id.to_string(),
+            prompt,
+            next_fire,
+            every_secs: every.map(|e| e.as_secs()),
+            fired: 0,
+            created_at: now,
+        };
+        self.arm(ctx, timer.clone());
+        self.inner.persist();
+        Ok(timer)
+    }
+
+    /// Stop a timer. Returns it if it existed.
+    pub fn cancel(&self, id: &str) -> Option<ScheduledTimer> {
+        let (timer, token) = self.inner.timers.lock().shift_remove(id)?;
+        token.cancel();
+        self.inner.persist();
+        Some(timer)
+    }
+
+    /// Pending timers, soonest first.
+    pub fn list(&self) -> Vec<ScheduledTimer> {
+        let mut timers: Vec<ScheduledTimer> = self
+            .inner
+            .timers
+            .lock()
+            .values()
+            .map(|(t, _)| t.clone())
+            .collect();
+        timers.sort_by_key(|t| t.next_fire);
+        timers
+    }
+
+    /// Re-arm the timers saved in the store directory. Returns the ids
+    /// armed; ids already pending are left alone.
+    pub async fn restore(&self, ctx: &LifecycleCtx) -> std::io::Result<Vec<String>> {
+        let Some(path) = self.inner.store.lock().clone() else {
+            return Ok(Vec::new());
+        };
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(b) => b,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let saved: Vec<ScheduledTimer> = serde_json::from_slice(&bytes)?;
+        let mut armed = Vec::new();
+        for timer in saved {
+            if self.inner.timers.lock().contains_key(&timer.id) {
+                continue;
+            }
+            if let Some(n) = timer
+                .id
+                .strip_prefix('t')
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                self.inner.next_id.fetch_max(n + 1, Ordering::Relaxed);
+            }
+            armed.push(timer.id.clone());
+            self.arm(ctx.clone(), timer);
+        }
+        Ok(armed)
+    }
+
+    fn arm(&self, ctx: LifecycleCtx, timer: ScheduledTimer) {
+        let token = CancellationToken::new();
+        self.inner
+            .timers
+            .lock()
+            .insert(timer.id.clone(), (timer.clone(), token.clone()));
+        let inner = Arc::clone(&self.inner);
+        tokio::spawn(run_timer(inner, ctx, timer, token));
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        for (_, token) in self.inner.timers.lock().values() {
+            token.cancel();
+        }
+    }
+}
+
+async fn run_timer(
+    inner: Arc<Inner>,
+    ctx: LifecycleCtx,
+    mut timer: ScheduledTimer,
+    token: CancellationToken,
+) {
+    loop {
+        let wait = (timer.next_fire - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+        timer.fired += 1;
+        fire(&ctx, &timer).await;
+
+        let mut timers = inner.timers.lock();
+        // A hand-edited timer file may hold an interval out of range;
+        // such a timer fires once.
+        let Some(every) = timer
+            .every_secs
+            .and_then(|s| i64::try_from(s).ok())
+            .and_then(TimeDelta::try_seconds)
+            .filter(|every| *every > TimeDelta::zero())
+        else {
+            timers.shift_remove(&timer.id);
+            drop(timers);
+            inner.persist();
+            return;
+        };
+        // Skip occurrences missed while the process was down.
+        let now = Utc::now();
+        while timer.next_fire <= now {
+            match timer.next_fire.checked_add_signed(every) {
+                Some(next) => timer.next_fire = next,
+                None => {
+                    timers.shift_remove(&timer.id);
+                    drop(timers);
+                    inner.persist();
+                    return;
+                }
+            }
+        }
+        match timers.get_mut(&timer.id) {
+            Some((slot, _)) => *slot = timer.clone(),
+            None => return,
+        }
+        drop(timers);
+        inner.persist();
+    }
+}
+
+async fn fire(ctx: &LifecycleCtx, timer: &ScheduledTimer) {
+    let message = timer.message();
+    let handle = ctx.registry.handle_any(&timer.agent_id);
+    let delivered = if let Some(h) = handle.filter(|h| matches!(h.health(), AgentHealth::Running)) {
+        h.follow_up(Message::user(&message)).await.is_ok()
+    } else if ctx
+        .registry
+        .find(&timer.agent_id)
+        .is_some_and(|l| l.agent_id == timer.agent_id && l.status == Status::Idle)
+    {
+        let ctx = ctx.clone();
+        let (agent_id, message) = (timer.agent_id.clone(), message.clone());
+        // No caller to cancel it: the run ends when the manager shuts
+        // down.
+        let cancel = ctx.shutdown.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = lifecycle::send(&ctx, &agent_id, &message, cancel).await {
+                tracing::warn!(agent_id, "timer could not resume agent: {e}");
+            }
+        });
+        true
+    } else {
+        false
+    };
+    send_event(
+        &ctx.fleet_event_tx,
+        FleetEvent::TimerFired {
+            timer_id: timer.id.clone(),
+            agent_id: timer.agent_id.clone(),
+            message,
+            delivered,
+        },
+    );
+}
@@ -12,7 +12,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tau_ai::Usage;
 
+use crate::fleet::blackboard::BlackboardTopic;
 use crate::fleet::manager::AgentStatus;
+use crate::fleet::scheduler::ScheduledTimer;
 
 /// One agent's worth of snapshot data. Cheap to clone.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,4 +55,10 @@ pub struct AgentSnapshot {
 #[non_exhaustive]
 pub struct FleetSnapshot {
     pub agents: Vec<AgentSnapshot>,
+    /// Blackboard topics in creation order.
+    #[serde(default)]
+    pub blackboard: Vec<BlackboardTopic>,
+    /// Pending timers, soonest first.
+    #[serde(default)]
+    pub timers: Vec<ScheduledTimer>,
 }
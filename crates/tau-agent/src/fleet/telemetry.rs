@@ -0,0 +1,1013 @@
+//! OpenTelemetry-compatible tracing of agent runs.
+//!
+//! [`spawn_trace_exporter`] folds the root agent's [`AgentEvent`]s and
+//! the manager's [`FleetEvent`]s into nested spans and exports them as
+//! OTLP/JSON — appended to a file (one `ExportTraceServiceRequest` per
+//! line, the collector file-exporter format) or POSTed to an OTLP/HTTP
+//! endpoint such as a local Jaeger or Tempo on `:4318`.
+//!
+//! One trace per exporter, shaped like:
+//!
+//! ```text
+//! session
+//! └─ prompt                          AgentStart → AgentEnd
+//!    └─ turn 1                       TurnStart → next turn / AgentEnd
+//!       ├─ chat <model>              TurnStart → TurnEnd (usage, cost)
+//!       └─ tool agent                ToolExecutionStart → End
+//!          └─ subagent <desc>        AgentStarted → AgentCompleted
+//!             └─ prompt → turn → …   forwarded child events
+//! ```
+//!
+//! Approvals, compactions, errors and goal checks become span events.
+//! Attribute names follow the OpenTelemetry GenAI conventions
+//! (`gen_ai.*`) where one exists and use a `tau.` prefix otherwise.
+//!
+//! Like transcripts, export is best-effort: failures are logged, never
+//! raised into the run.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+use tau_ai::{Message, Usage};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::events::{
+    AgentEvent, FleetEvent, GoalOutcome, SubagentOutcome, ToolApprovalOutcome,
+};
+
+/// Finished spans are exported once this many are buffered, or every
+/// [`FLUSH_INTERVAL`], whichever comes first.
+const BATCH_SIZE: usize = 64;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Tool names whose span parents the subagents they spawn.
+const SPAWNING_TOOLS: &[&str] = &["agent", "agent_batch"];
+
+/// Where finished spans go.
+#[derive(Debug, Clone)]
+pub enum TraceSink {
+    /// Append OTLP/JSON requests to a file, one per line.
+    File(PathBuf),
+    /// POST OTLP/JSON to an OTLP/HTTP endpoint. A bare base URL
+    /// (`http://localhost:4318`) gets `/v1/traces` appended.
+    Http(String),
+}
+
+impl TraceSink {
+    fn http_url(endpoint: &str) -> String {
+        let endpoint = endpoint.trim_end_matches('/');
+        if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{endpoint}/v1/traces")
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceConfig {
+    pub sink: TraceSink,
+    /// `service.name` resource attribute.
+    pub service_name: String,
+    /// Extra resource attributes (e.g. `tau.session.id`).
+    pub resource: Vec<(String, String)>,
+}
+
+impl TraceConfig {
+    pub fn new(sink: TraceSink) -> Self {
+        Self {
+            sink,
+            service_name: "tau".into(),
+            resource: Vec::new(),
+        }
+    }
+
+    pub fn with_resource(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.resource.push((key.into(), value.into()));
+        self
+    }
+}
+
+// ─── Span model ─────────────────────────────────────────────────────
+
+type SpanId = [u8; 8];
+
+#[derive(Debug, Clone, PartialEq)]
+enum AttrValue {
+    Str(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+impl From<&str> for AttrValue {
+    fn from(v: &str) -> Self {
+        Self::Str(v.into())
+    }
+}
+impl From<String> for AttrValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+impl From<u64> for AttrValue {
+    fn from(v: u64) -> Self {
+        Self::Int(v as i64)
+    }
+}
+impl From<u32> for AttrValue {
+    fn from(v: u32) -> Self {
+        Self::Int(v.into())
+    }
+}
+impl From<f64> for AttrValue {
+    fn from(v: f64) -> Self {
+        Self::Double(v)
+    }
+}
+impl From<bool> for AttrValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+type Attrs = Vec<(&'static str, AttrValue)>;
+
+#[derive(Debug, Clone)]
+struct SpanEvent {
+    time: u64,
+    name: String,
+    attrs: Attrs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpanKind {
+    Internal,
+    Client,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    id: SpanId,
+    parent: Option<SpanId>,
+    name: String,
+    kind: SpanKind,
+    start: u64,
+    end: u64,
+    attrs: Attrs,
+    events: Vec<SpanEvent>,
+    error: Option<String>,
+}
+
+impl Span {
+    fn attr(&self, key: &str) -> Option<&AttrValue> {
+        self.attrs.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn new_span_id() -> SpanId {
+    let bytes = uuid::Uuid::new_v4().into_bytes();
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&bytes[..8]);
+    id
+}
+
+fn usage_attrs(usage: &Usage, attrs: &mut Attrs) {
+    attrs.push(("gen_ai.usage.input_tokens", usage.input.into()));
+    attrs.push(("gen_ai.usage.output_tokens", usage.output.into()));
+    if usage.cache_read > 0 {
+        attrs.push(("tau.usage.cache_read_tokens", usage.cache_read.into()));
+    }
+    if usage.cache_write > 0 {
+        attrs.push(("tau.usage.cache_write_tokens", usage.cache_write.into()));
+    }
+}
+
+// ─── Tracer ─────────────────────────────────────────────────────────
+
+/// Spans open for one agent (the root, or a subagent by id).
+#[derive(Default)]
+struct Track {
+    /// Subagent lifecycle span; `None` for the root agent.
+    agent: Option<SpanId>,
+    prompt: Option<SpanId>,
+    turn: Option<SpanId>,
+    chat: Option<SpanId>,
+    compaction: Option<SpanId>,
+    tools: HashMap<String, SpanId>,
+}
+
+/// Pure event → span state machine. Finished spans accumulate until
+/// [`Tracer::take_finished`].
+struct Tracer {
+    session: SpanId,
+    open: HashMap<SpanId, Span>,
+    /// Open span ids in start order, for "most recent open" lookups.
+    order: Vec<SpanId>,
+    finished: Vec<Span>,
+    root: Track,
+    agents: HashMap<String, Track>,
+    batches: HashMap<String, SpanId>,
+}
+
+impl Tracer {
+    fn new() -> Self {
+        let mut tracer = Self {
+            session: [0; 8],
+            open: HashMap::new(),
+            order: Vec::new(),
+            finished: Vec::new(),
+            root: Track::default(),
+            agents: HashMap::new(),
+            batches: HashMap::new(),
+        };
+        tracer.session = tracer.start(None, "session".into(), SpanKind::Internal, Vec::new());
+        tracer
+    }
+
+    fn start(
+        &mut self,
+        parent: Option<SpanId>,
+        name: String,
+        kind: SpanKind,
+        attrs: Attrs,
+    ) -> SpanId {
+        let id = new_span_id();
+        self.open.insert(
+            id,
+            Span {
+                id,
+                parent,
+                name,
+                kind,
+                start: now_nanos(),
+                end: 0,
+                attrs,
+                events: Vec::new(),
+                error: None,
+            },
+        );
+        self.order.push(id);
+        id
+    }
+
+    fn span_mut(&mut self, id: Option<SpanId>) -> Option<&mut Span> {
+        id.and_then(|id| self.open.get_mut(&id))
+    }
+
+    fn end(&mut self, id: Option<SpanId>) {
+        let Some(id) = id else { return };
+        // Close children first so every span ends within its parent.
+        let children: Vec<SpanId> = self
+            .order
+            .iter()
+            .filter(|c| self.open.get(*c).is_some_and(|s| s.parent == Some(id)))
+            .copied()
+            .collect();
+        for child in children {
+            self.end(Some(child));
+        }
+        if let Some(mut span) = self.open.remove(&id) {
+            span.end = now_nanos().max(span.start);
+            self.order.retain(|s| *s != id);
+            self.finished.push(span);
+        }
+    }
+
+    fn event(&mut self, id: Option<SpanId>, name: &str, attrs: Attrs) {
+        if let Some(span) = self.span_mut(id) {
+            span.events.push(SpanEvent {
+                time: now_nanos(),
+                name: name.into(),
+                attrs,
+            });
+        }
+    }
+
+    fn track(&mut self, agent_id: Option<&str>) -> &mut Track {
+        match agent_id {
+            None => &mut self.root,
+            Some(id) => self.agents.entry(id.to_string()).or_default(),
+        }
+    }
+
+    /// Where a new subagent span hangs: the open batch span, else the
+    /// most recently opened spawning-tool span, else the root prompt,
+    /// else the session.
+    fn subagent_parent(&self) -> SpanId {
+        if let Some(batch) = self
+            .order
+            .iter()
+            .rev()
+            .find(|id| self.batches.values().any(|b| b == *id))
+        {
+            return *batch;
+        }
+        self.order
+            .iter()
+            .rev()
+            .find(|id| {
+                self.open.get(*id).is_some_and(|s| {
+                    matches!(s.attr("gen_ai.tool.name"), Some(AttrValue::Str(n)) if SPAWNING_TOOLS.contains(&n.as_str()))
+                })
+            })
+            .copied()
+            .or(self.root.prompt)
+            .unwrap_or(self.session)
+    }
+
+    fn on_agent_event(&mut self, agent_id: Option<&str>, event: &AgentEvent) {
+        let track = self.track(agent_id);
+        let (agent_span, prompt, turn, chat) = (track.agent, track.prompt, track.turn, track.chat);
+        match event {
+            AgentEvent::AgentStart => {
+                self.end(prompt);
+                let parent = agent_span.unwrap_or(self.session);
+                let mut attrs = Attrs::new();
+                if let Some(id) = agent_id {
+                    attrs.push(("tau.agent.id", id.into()));
+                }
+                let span = self.start(Some(parent), "prompt".into(), SpanKind::Internal, attrs);
+                self.track(agent_id).prompt = Some(span);
+            }
+            AgentEvent::TurnStart { turn_number } => {
+                self.end(turn);
+                let parent = prompt.or(agent_span).unwrap_or(self.session);
+                let turn = self.start(
+                    Some(parent),
+                    format!("turn {turn_number}"),
+                    SpanKind::Internal,
+                    vec![("tau.turn.number", (*turn_number).into())],
+                );
+                let chat = self.start(Some(turn), "chat".into(), SpanKind::Client, Vec::new());
+                let track = self.track(agent_id);
+                track.turn = Some(turn);
+                track.chat = Some(chat);
+            }
+            AgentEvent::TurnEnd { message, usage, .. } => {
+                if let Some(span) = self.span_mut(chat) {
+                    let mut attrs = Attrs::new();
+                    if let Message::Assistant { metadata, .. } = message {
+                        if let Some(model_id) = &metadata.model {
+                            span.name = format!("chat {model_id}");
+                            attrs.push(("gen_ai.request.model", model_id.clone().into()));
+                            if let Some(model) = tau_ai::models::get_model_by_id(model_id) {
+                                let cost = usage.calculate_cost(&model).total;
+                                attrs.push(("tau.cost.usd", cost.into()));
+                            }
+                        }
+                        if let Some(provider) = &metadata.provider {
+                            attrs.push((
+                                "gen_ai.system",
+                                format!("{provider:?}").to_lowercase().into(),
+                            ));
+                        }
+                        if let Some(reason) = &metadata.stop_reason {
+                            attrs.push((
+                                "gen_ai.response.finish_reasons",
+                                format!("{reason:?}").to_lowercase().into(),
+                            ));
+                        }
+                        if let Some(err) = &metadata.error_message {
+                            span.error = Some(err.clone());
+                        }
+                    }
+                    usage_attrs(usage, &mut attrs);
+                    span.attrs.extend(attrs);
+                }
+                self.end(chat);
+                self.track(agent_id).chat = None;
+            }
+            AgentEvent::ToolExecutionStart {
+                tool_call_id,
+                tool_name,
+                ..
+            } => {
+                let parent = turn.or(prompt).or(agent_span).unwrap_or(self.session);
+                let span = self.start(
+                    Some(parent),
+                    format!("tool {tool_name}"),
+                    SpanKind::Internal,
+                    vec![
+                        ("gen_ai.tool.name", tool_name.as_str().into()),
+                        ("gen_ai.tool.call.id", tool_call_id.as_str().into()),
+                    ],
+                );
+                self.track(agent_id)
+                    .tools
+                    .insert(tool_call_id.clone(), span);
+            }
+            AgentEvent::ToolExecutionEnd {
+                tool_call_id,
+                is_error,
+                result,
+                ..
+            } => {
+                let span = self.track(agent_id).tools.remove(tool_call_id);
+                if let Some(s) = self.span_mut(span) {
+                    s.attrs.push(("tau.tool.is_error", (*is_error).into()));
+                    if *is_error {
+                        s.error = Some(truncate(result, 200));
+                    }
+                }
+                self.end(span);
+            }
+            AgentEvent::ToolApprovalResolved {
+                tool_call_id,
+                tool_name,
+                outcome,
+            } => {
+                let target = self
+                    .track(agent_id)
+                    .tools
+                    .get(tool_call_id)
+                    .copied()
+                    .or(turn)
+                    .or(prompt);
+                let outcome = match outcome {
+                    ToolApprovalOutcome::AutoApproved => "auto_approved".to_string(),
+                    ToolApprovalOutcome::Approved => "approved".to_string(),
+                    ToolApprovalOutcome::Rejected { reason } => format!("rejected: {reason}"),
+                };
+                self.event(
+                    target,
+                    "approval",
+                    vec![
+                        ("gen_ai.tool.name", tool_name.as_str().into()),
+                        ("tau.approval.outcome", outcome.into()),
+                    ],
+                );
+            }
+            AgentEvent::CompactionStart { reason } => {
+                let parent = prompt.or(agent_span).unwrap_or(self.session);
+                let span = self.start(
+                    Some(parent),
+                    "compaction".into(),
+                    SpanKind::Internal,
+                    vec![(
+                        "tau.compaction.reason",
+                        format!("{reason:?}").to_lowercase().into(),
+                    )],
+                );
+                self.track(agent_id).compaction = Some(span);
+            }
+            AgentEvent::CompactionEnd {
+                tokens_before,
+                tokens_after,
+            } => {
+                let span = self.track(agent_id).compaction.take();
+                if let Some(s) = self.span_mut(span) {
+                    s.attrs
+                        .push(("tau.compaction.tokens_before", (*tokens_before).into()));
+                    s.attrs
+                        .push(("tau.compaction.tokens_after", (*tokens_after).into()));
+                }
+                self.end(span);
+            }
+            AgentEvent::Error { message } => {
+                let target = chat.or(turn).or(prompt);
+                self.event(target, "error", vec![("message", message.as_str().into())]);
+                if let Some(s) = self.span_mut(target) {
+                    s.error = Some(message.clone());
+                }
+            }
+            AgentEvent::GoalCheck {
+                iteration, passed, ..
+            } => {
+                self.event(
+                    prompt,
+                    "goal_check",
+                    vec![
+                        ("tau.goal.iteration", (*iteration).into()),
+                        ("tau.goal.passed", (*passed).into()),
+                    ],
+                );
+            }
+            AgentEvent::GoalEnd { outcome } => {
+                let outcome = match outcome {
+                    GoalOutcome::Passed { .. } => "passed",
+                    GoalOutcome::MaxIterations { .. } => "max_iterations",
+                    GoalOutcome::BudgetExhausted { .. } => "budget_exhausted",
+                    GoalOutcome::Aborted { .. } => "aborted",
+                };
+                self.event(
+                    prompt,
+                    "goal_end",
+                    vec![("tau.goal.outcome", outcome.into())],
+                );
+            }
+            AgentEvent::AgentEnd {
+                total_turns,
+                total_usage,
+                interrupted,
+            } => {
+                if let Some(s) = self.span_mut(prompt) {
+                    s.attrs.push(("tau.turns", (*total_turns).into()));
+                    s.attrs.push(("tau.interrupted", (*interrupted).into()));
+                    usage_attrs(total_usage, &mut s.attrs);
+                }
+                self.end(prompt);
+                let track = self.track(agent_id);
+                track.prompt = None;
+                track.turn = None;
+                track.chat = None;
+                track.tools.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn on_fleet_event(&mut self, event: &FleetEvent) {
+        match event {
+            FleetEvent::AgentStarted {
+                agent_id,
+                spec_name,
+                description,
+                ..
+            } => self.start_subagent(agent_id, description, spec_name.as_deref(), false),
+            FleetEvent::AgentResumed {
+                agent_id,
+                description,
+                ..
+            } => self.start_subagent(agent_id, description, None, true),
+            FleetEvent::AgentCompleted {
+                agent_id,
+                outcome,
+                usage,
+                tool_use_count,
+                ..
+            } => {
+                let Some(track) = self.agents.remove(agent_id) else {
+                    return;
+                };
+                if let Some(s) = self.span_mut(track.agent) {
+                    usage_attrs(usage, &mut s.attrs);
+                    s.attrs
+                        .push(("tau.tool_use_count", (*tool_use_count).into()));
+                    let (label, error) = match outcome {
+                        SubagentOutcome::Completed => ("completed", None),
+                        SubagentOutcome::Aborted { reason } => ("aborted", Some(reason)),
+                        SubagentOutcome::Failed { reason } => ("failed", Some(reason)),
+                    };
+                    s.attrs.push(("tau.agent.outcome", label.into()));
+                    if let Some(reason) = error {
+                        s.error = Some(reason.clone());
+                    }
+                }
+                self.end(track.agent);
+            }
+            FleetEvent::AgentReport {
+                agent_id, summary, ..
+            } => {
+                let span = self.agents.get(agent_id).and_then(|t| t.agent);
+                self.event(
+                    span,
+                    "report",
+                    vec![("tau.report.summary", summary.as_str().into())],
+                );
+            }
+            FleetEvent::Forwarded {
+                agent_id, event, ..
+            } => self.on_agent_event(Some(agent_id), event),
+            FleetEvent::BatchStarted {
+                batch_id,
+                description,
+                total,
+                concurrency,
+            } => {
+                let parent = self.subagent_parent();
+                let span = self.start(
+                    Some(parent),
+                    format!("batch {description}"),
+                    SpanKind::Internal,
+                    vec![
+                        ("tau.batch.total", (*total as u64).into()),
+                        ("tau.batch.concurrency", (*concurrency as u64).into()),
+                    ],
+                );
+                self.batches.insert(batch_id.clone(), span);
+            }
+            FleetEvent::BatchCompleted {
+                batch_id,
+                succeeded,
+                failed,
+                ..
+            } => {
+                let span = self.batches.remove(batch_id);
+                if let Some(s) = self.span_mut(span) {
+                    s.attrs
+                        .push(("tau.batch.succeeded", (*succeeded as u64).into()));
+                    s.attrs.push(("tau.batch.failed", (*failed as u64).into()));
+                }
+                self.end(span);
+            }
+            _ => {}
+        }
+    }
+
+    fn start_subagent(
+        &mut self,
+        agent_id: &str,
+        description: &str,
+        spec: Option<&str>,
+        resumed: bool,
+    ) {
+        let parent = self.subagent_parent();
+        let mut attrs: Attrs = vec![
+            ("tau.agent.id", agent_id.into()),
+            ("tau.agent.description", description.into()),
+            ("tau.agent.resumed", resumed.into()),
+        ];
+        if let Some(spec) = spec {
+            attrs.push(("tau.agent.spec", spec.into()));
+        }
+        let span = self.start(
+            Some(parent),
+            format!("subagent {description}"),
+            SpanKind::Internal,
+            attrs,
+        );
+        self.agents.insert(
+            agent_id.to_string(),
+            Track {
+                agent: Some(span),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn take_finished(&mut self) -> Vec<Span> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Close every open span, the session last.
+    fn finish(&mut self) -> Vec<Span> {
+        self.end(Some(self.session));
+        let leftovers: Vec<SpanId> = self.order.clone();
+        for id in leftovers {
+            self.end(Some(id));
+        }
+        self.take_finished()
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    let mut chars = s.chars();
+    let head: String = chars.by_ref().take(max).collect();
+    if chars.next().is_some() {
+        format!("{head}…")
+    } else {
+        head
+    }
+}
+
+// ─── OTLP/JSON encoding ─────────────────────────────────────────────
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn attr_json(key: &str, value: &AttrValue) -> Value {
+    let value = match value {
+        AttrValue::Str(s) => json!({ "stringValue": s }),
+        // OTLP/JSON carries 64-bit integers as strings.
+        AttrValue::Int(i) => json!({ "intValue": i.to_string() }),
+        AttrValue::Double(d) => json!({ "doubleValue": d }),
+        AttrValue::Bool(b) => json!({ "boolValue": b }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn span_json(trace_id: &str, span: &Span) -> Value {
+    let status = match &span.error {
+        Some(message) => json!({ "code": 2, "message": message }),
+        None => json!({ "code": 0 }),
+    };
+    json!({
+        "traceId": trace_id,
+        "spanId": hex(&span.id),
+        "parentSpanId": span.parent.map(|p| hex(&p)).unwrap_or_default(),
+        "name": span.name,
+        "kind": match span.kind {
+            SpanKind::Internal => 1,
+            SpanKind::Client => 3,
+        },
+        "startTimeUnixNano": span.start.to_string(),
+        "endTimeUnixNano": span.end.to_string(),
+        "attributes": span.attrs.iter().map(|(k, v)| attr_json(k, v)).collect::<Vec<_>>(),
+        "events": span.events.iter().map(|e| json!({
+            "timeUnixNano": e.time.to_string(),
+            "name": e.name,
+            "attributes": e.attrs.iter().map(|(k, v)| attr_json(k, v)).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "status": status,
+    })
+}
+
+/// One `ExportTraceServiceRequest` carrying `spans`.
+fn export_request(config: &TraceConfig, trace_id: &str, spans: &[Span]) -> Value {
+    let mut resource = vec![attr_json(
+        "service.name",
+        &AttrValue::Str(config.service_name.clone()),
+    )];
+    for (k, v) in &config.resource {
+        resource.push(attr_json(k, &AttrValue::Str(v.clone())));
+    }
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": resource },
+            "scopeSpans": [{
+                "scope": { "name": "tau-agent", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans.iter().map(|s| span_json(trace_id, s)).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+struct Exporter {
+    config: TraceConfig,
+    trace_id: String,
+    client: Option<reqwest::Client>,
+}
+
+impl Exporter {
+    async fn export(&self, spans: &[Span]) {
+        if spans.is_empty() {
+            return;
+        }
+        let request = export_request(&self.config, &self.trace_id, spans);
+        let result = match &self.config.sink {
+            TraceSink::File(path) => append_line(path, &request).await,
+            TraceSink::Http(endpoint) => {
+                let client = self.client.as_ref().expect("http sink has a client");
+                client
+                    .post(TraceSink::http_url(endpoint))
+                    .json(&request)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map(|_| ())
+                    .map_err(std::io::Error::other)
+            }
+        };
+        if let Err(e) = result {
+            tracing::debug!("failed to export {} span(s): {e}", spans.len());
+        }
+    }
+}
+
+async fn append_line(path: &std::path::Path, request: &Value) -> std::io::Result<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(&line).await?;
+    file.flush().await
+}
+
+/// Trace the root agent (`agent_events`, typically `handle.subscribe()`)
+/// and its fleet (`fleet_events`, `manager.subscribe()`) until
+/// `shutdown` fires or both channels close. On shutdown, buffered events
+/// are drained, every open span is closed, and the rest is flushed;
+/// await the handle to be sure the export finished.
+pub fn spawn_trace_exporter(
+    config: TraceConfig,
+    mut agent_events: broadcast::Receiver<AgentEvent>,
+    mut fleet_events: broadcast::Receiver<FleetEvent>,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let exporter = Exporter {
+            client: matches!(config.sink, TraceSink::Http(_)).then(reqwest::Client::new),
+            trace_id: hex(uuid::Uuid::new_v4().as_bytes()),
+            config,
+        };
+        let mut tracer = Tracer::new();
+        let mut tick = tokio::time::interval(FLUSH_INTERVAL);
+        let (mut agent_open, mut fleet_open) = (true, true);
+        while agent_open || fleet_open {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                ev = agent_events.recv(), if agent_open => match ev {
+                    Ok(ev) => tracer.on_agent_event(None, &ev),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => agent_open = false,
+                },
+                ev = fleet_events.recv(), if fleet_open => match ev {
+                    Ok(ev) => tracer.on_fleet_event(&ev),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => fleet_open = false,
+                },
+                _ = tick.tick() => {
+                    exporter.export(&tracer.take_finished()).await;
+                    continue;
+                }
+            }
+            if tracer.finished.len() >= BATCH_SIZE {
+                exporter.export(&tracer.take_finished()).await;
+            }
+        }
+        while let Ok(ev) = agent_events.try_recv() {
+            tracer.on_agent_event(None, &ev);
+        }
+        while let Ok(ev) = fleet_events.try_recv() {
+            tracer.on_fleet_event(&ev);
+        }
+        exporter.export(&tracer.finish()).await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_assistant_message;
+
+    fn names(spans: &[Span]) -> Vec<&str> {
+        spans.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn by_name<'a>(spans: &'a [Span], name: &str) -> &'a Span {
+        spans.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn nests_turns_tools_and_subagents() {
+        let mut t = Tracer::new();
+        t.on_agent_event(None, &AgentEvent::AgentStart);
+        t.on_agent_event(None, &AgentEvent::TurnStart { turn_number: 1 });
+        t.on_agent_event(
+            None,
+            &AgentEvent::TurnEnd {
+                turn_number: 1,
+                message: make_assistant_message("spawning"),
+                usage: Usage {
+                    input: 100,
+                    output: 20,
+                    ..Default::default()
+                },
+            },
+        );
+        t.on_agent_event(
+            None,
+            &AgentEvent::ToolExecutionStart {
+                tool_call_id: "t1".into(),
+                tool_name: "agent".into(),
+                arguments: json!({}),
+                activity: String::new(),
+            },
+        );
+        t.on_fleet_event(&FleetEvent::AgentStarted {
+            agent_id: "c1".into(),
+            spec_name: Some("explore".into()),
+            description: "survey".into(),
+            prompt: "look".into(),
+            started_at: chrono::Utc::now(),
+        });
+        for ev in [
+            AgentEvent::AgentStart,
+            AgentEvent::TurnStart { turn_number: 1 },
+        ] {
+            t.on_fleet_event(&FleetEvent::Forwarded {
+                agent_id: "c1".into(),
+                description: "survey".into(),
+                event: ev,
+            });
+        }
+        t.on_fleet_event(&FleetEvent::AgentCompleted {
+            agent_id: "c1".into(),
+            description: "survey".into(),
+            outcome: SubagentOutcome::Failed {
+                reason: "boom".into(),
+            },
+            started_at: chrono::Utc::now(),
+            completed_at: chrono::Utc::now(),
+            duration_ms: 1,
+            usage: Usage::default(),
+            tool_use_count: 0,
+            worktree_path: None,
+            worktree_branch: None,
+        });
+        t.on_agent_event(
+            None,
+            &AgentEvent::ToolExecutionEnd {
+                tool_call_id: "t1".into(),
+                tool_name: "agent".into(),
+                result: "done".into(),
+                is_error: false,
+            },
+        );
+        let spans = t.finish();
+
+        assert_eq!(t.open.len(), 0);
+        let session = by_name(&spans, "session");
+        let prompts: Vec<&Span> = spans.iter().filter(|s| s.name == "prompt").collect();
+        assert_eq!(
+            prompts.len(),
+            2,
+            "root and subagent prompts: {:?}",
+            names(&spans)
+        );
+        let root_prompt = prompts
+            .iter()
+            .find(|p| p.parent == Some(session.id))
+            .unwrap();
+        assert!(
+            spans
+                .iter()
+                .any(|s| s.name == "turn 1" && s.parent == Some(root_prompt.id))
+        );
+        let chat = spans.iter().find(|s| s.kind == SpanKind::Client).unwrap();
+        assert_eq!(
+            chat.attr("gen_ai.usage.input_tokens"),
+            Some(&AttrValue::Int(100))
+        );
+        let tool = by_name(&spans, "tool agent");
+        let sub = by_name(&spans, "subagent survey");
+        assert_eq!(
+            sub.parent,
+            Some(tool.id),
+            "subagent hangs off the spawning tool"
+        );
+        assert_eq!(sub.error.as_deref(), Some("boom"));
+        assert!(spans.iter().all(|s| s.end >= s.start));
+    }
+
+    #[test]
+    fn encodes_otlp_json() {
+        let mut t = Tracer::new();
+        t.on_agent_event(None, &AgentEvent::AgentStart);
+        let spans = t.finish();
+        let config =
+            TraceConfig::new(TraceSink::File("x".into())).with_resource("tau.session.id", "s1");
+        let request = export_request(&config, &"ab".repeat(16), &spans);
+        let scope = &request["resourceSpans"][0]["scopeSpans"][0];
+        assert_eq!(scope["spans"].as_array().unwrap().len(), 2);
+        let span = &scope["spans"][0];
+        assert_eq!(span["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(span["spanId"].as_str().unwrap().len(), 16);
+        assert!(span["startTimeUnixNano"].is_string());
+        let resource = &request["resourceSpans"][0]["resource"]["attributes"];
+        assert_eq!(resource[1]["value"]["stringValue"], "s1");
+        assert_eq!(
+            TraceSink::http_url("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+    }
+
+    #[tokio::test]
+    async fn exporter_flushes_to_file_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let (agent_tx, agent_rx) = broadcast::channel(16);
+        let (_fleet_tx, fleet_rx) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+        let task = spawn_trace_exporter(
+            TraceConfig::new(TraceSink::File(path.clone())),
+            agent_rx,
+            fleet_rx,
+            shutdown.clone(),
+        );
+        agent_tx.send(AgentEvent::AgentStart).unwrap();
+        agent_tx
+            .send(AgentEvent::AgentEnd {
+                total_turns: 0,
+                total_usage: Usage::default(),
+                interrupted: false,
+            })
+            .unwrap();
+        shutdown.cancel();
+        task.await.unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let spans: usize = text
+            .lines()
+            .map(|l| {
+                let v: Value = serde_json::from_str(l).unwrap();
+                v["resourceSpans"][0]["scopeSpans"][0]["spans"]
+                    .as_array()
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        assert_eq!(spans, 2, "prompt + session");
+    }
+}
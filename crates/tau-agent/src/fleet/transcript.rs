@@ -1,42 +1,420 @@
-//! JSONL transcript recording for subagents.
+//! Live JSONL transcripts of subagent runs.
 //!
-//! Writes one message per line to
-//! `~/.local/share/tau/agent-transcripts/{agent_id}.jsonl`. Failures
-//! are logged and `None` is returned — never raised, since the
-//! transcript is a diagnostic, not a correctness concern.
+//! Each run appends to
+//! `~/.local/share/tau/agent-transcripts/{agent_id}.jsonl` as the
+//! agent's events arrive, one [`TranscriptEntry`] per line: the prompt,
+//! assistant messages, tool starts and ends (with durations and
+//! results), approvals, compactions, per-turn usage and errors. A
+//! crashed or still-running agent leaves everything up to its last
+//! event on disk. Resumes (`send`) append to the same file.
+//!
+//! Transcripts written before this format held bare [`Message`]s;
+//! [`read_transcript`] still reads those.
+//!
+//! Failures are logged and swallowed — the transcript is a diagnostic,
+//! not a correctness concern.
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use tau_ai::Message;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tau_ai::{Message, Usage};
 use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 
-pub async fn record_transcript(agent_id: &str, messages: &[Message]) -> Option<PathBuf> {
-    let dir = dirs::data_dir().map(|d| d.join("tau/agent-transcripts"))?;
+use crate::types::events::{AgentEvent, CompactionReason, ToolApprovalOutcome};
 
-    if tokio::fs::create_dir_all(&dir).await.is_err() {
-        return None;
-    }
+/// One transcript line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// When the record was written. `None` for legacy lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub record: TranscriptRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptRecord {
+    /// A spawn or resume began with `prompt`.
+    RunStart {
+        agent_id: String,
+        description: String,
+        prompt: String,
+    },
+    /// A complete message. Live transcripts record assistant messages;
+    /// tool results arrive as [`Self::ToolEnd`].
+    Message {
+        message: Message,
+    },
+    ToolStart {
+        tool_call_id: String,
+        tool_name: String,
+        arguments: serde_json::Value,
+    },
+    ToolEnd {
+        tool_call_id: String,
+        tool_name: String,
+        result: String,
+        is_error: bool,
+        /// Wall-clock time since the matching `ToolStart`, when seen.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
+    },
+    Approval {
+        tool_call_id: String,
+        tool_name: String,
+        outcome: ToolApprovalOutcome,
+    },
+    Compaction {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<CompactionReason>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tokens_before: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tokens_after: Option<u64>,
+        /// Summary text, when the source recorded it (session logs do;
+        /// live agent events don't carry it).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        summary: Option<String>,
+    },
+    /// Usage of one turn.
+    Usage {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        turn_number: Option<u32>,
+        usage: Usage,
+    },
+    FileChanged {
+        path: PathBuf,
+        tool_call_id: String,
+    },
+    Error {
+        message: String,
+    },
+    /// The run ended; `usage` is the agent's running total.
+    RunEnd {
+        total_turns: u32,
+        usage: Usage,
+        interrupted: bool,
+    },
+}
 
-    let path = dir.join(format!("{agent_id}.jsonl"));
-    let mut file = match tokio::fs::File::create(&path).await {
-        Ok(f) => f,
-        Err(e) => {
-            tracing::debug!("Failed to create transcript file: {e}");
-            return None;
+impl TranscriptRecord {
+    fn now(self) -> TranscriptEntry {
+        TranscriptEntry {
+            at: Some(Utc::now()),
+            record: self,
         }
-    };
+    }
+}
+
+/// Directory agent transcripts are written to.
+pub fn transcript_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("tau/agent-transcripts"))
+}
+
+/// Where `agent_id`'s transcript lives.
+pub fn transcript_path(agent_id: &str) -> Option<PathBuf> {
+    transcript_dir().map(|d| d.join(format!("{agent_id}.jsonl")))
+}
+
+/// Parse a transcript file. Unparseable lines are skipped; legacy bare
+/// messages become [`TranscriptRecord::Message`] entries without a
+/// timestamp.
+pub async fn read_transcript(path: &Path) -> std::io::Result<Vec<TranscriptEntry>> {
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(parse_transcript(&text))
+}
 
-    for msg in messages {
-        if let Ok(json) = serde_json::to_string(msg) {
-            if file
-                .write_all(format!("{json}\n").as_bytes())
+/// See [`read_transcript`].
+pub fn parse_transcript(text: &str) -> Vec<TranscriptEntry> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| {
+            if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(l) {
+                return Some(entry);
+            }
+            match serde_json::from_str::<Message>(l) {
+                Ok(message) => Some(TranscriptEntry {
+                    at: None,
+                    record: TranscriptRecord::Message { message },
+                }),
+                Err(e) => {
+                    tracing::debug!("skipping transcript line: {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Appends entries to one transcript file.
+struct Appender {
+    path: PathBuf,
+    file: Option<tokio::fs::File>,
+}
+
+impl Appender {
+    async fn open(path: PathBuf) -> Self {
+        let file = async {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
                 .await
-                .is_err()
-            {
+        }
+        .await;
+        let file = match file {
+            Ok(f) => Some(f),
+            Err(e) => {
+                tracing::debug!(path = %path.display(), "failed to open transcript: {e}");
+                None
+            }
+        };
+        Self { path, file }
+    }
+
+    async fn append(&mut self, entry: &TranscriptEntry) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let Ok(mut line) = serde_json::to_vec(entry) else {
+            return;
+        };
+        line.push(b'\n');
+        // Flush per line: tokio buffers file writes, and the tail of a
+        // crashed run is what the transcript is for.
+        let written = match file.write_all(&line).await {
+            Ok(()) => file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            tracing::debug!(path = %self.path.display(), "failed to append transcript: {e}");
+            // Stop writing rather than leave a half-written tail of
+            // interleaved partial lines.
+            self.file = None;
+        }
+    }
+}
+
+/// Translates agent events into transcript records.
+#[derive(Default)]
+struct Recorder {
+    tool_started: HashMap<String, Instant>,
+    compaction_reason: Option<CompactionReason>,
+}
+
+impl Recorder {
+    fn record(&mut self, event: AgentEvent) -> Option<TranscriptRecord> {
+        Some(match event {
+            AgentEvent::MessageEnd { message } => TranscriptRecord::Message { message },
+            AgentEvent::ToolExecutionStart {
+                tool_call_id,
+                tool_name,
+                arguments,
+                ..
+            } => {
+                self.tool_started
+                    .insert(tool_call_id.clone(), Instant::now());
+                TranscriptRecord::ToolStart {
+                    tool_call_id,
+                    tool_name,
+                    arguments,
+                }
+            }
+            AgentEvent::ToolExecutionEnd {
+                tool_call_id,
+                tool_name,
+                result,
+                is_error,
+            } => {
+                let duration_ms = self
+                    .tool_started
+                    .remove(&tool_call_id)
+                    .map(|t| t.elapsed().as_millis() as u64);
+                TranscriptRecord::ToolEnd {
+                    tool_call_id,
+                    tool_name,
+                    result,
+                    is_error,
+                    duration_ms,
+                }
+            }
+            AgentEvent::ToolApprovalResolved {
+                tool_call_id,
+                tool_name,
+                outcome,
+            } => TranscriptRecord::Approval {
+                tool_call_id,
+                tool_name,
+                outcome,
+            },
+            AgentEvent::CompactionStart { reason } => {
+                self.compaction_reason = Some(reason);
                 return None;
             }
+            AgentEvent::CompactionEnd {
+                tokens_before,
+                tokens_after,
+            } => TranscriptRecord::Compaction {
+                reason: self.compaction_reason.take(),
+                tokens_before: Some(tokens_before),
+                tokens_after: Some(tokens_after),
+                summary: None,
+            },
+            AgentEvent::TurnEnd {
+                turn_number, usage, ..
+            } => TranscriptRecord::Usage {
+                turn_number: Some(turn_number),
+                usage,
+            },
+            AgentEvent::FileChanged {
+                path, tool_call_id, ..
+            } => TranscriptRecord::FileChanged { path, tool_call_id },
+            AgentEvent::Error { message } => TranscriptRecord::Error { message },
+            AgentEvent::AgentEnd {
+                total_turns,
+                total_usage,
+                interrupted,
+            } => TranscriptRecord::RunEnd {
+                total_turns,
+                usage: total_usage,
+                interrupted,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Append `start` to the transcript at `path`, then every recordable
+/// event from `events` until `shutdown` fires or the channel closes.
+/// On shutdown, events already buffered in the receiver are drained
+/// first so the run's tail (`TurnEnd`, `AgentEnd`) isn't lost.
+pub(crate) fn spawn_transcript_recorder(
+    path: PathBuf,
+    start: TranscriptRecord,
+    mut events: broadcast::Receiver<AgentEvent>,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut out = Appender::open(path).await;
+        let mut recorder = Recorder::default();
+        out.append(&start.now()).await;
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => {
+                    while let Ok(event) = events.try_recv() {
+                        if let Some(record) = recorder.record(event) {
+                            out.append(&record.now()).await;
+                        }
+                    }
+                    break;
+                }
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        if let Some(record) = recorder.record(event) {
+                            out.append(&record.now()).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        let message = format!("transcript dropped {n} event(s)");
+                        out.append(&TranscriptRecord::Error { message }.now()).await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
         }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_assistant_message;
+
+    #[tokio::test]
+    async fn recorder_appends_events_live_and_drains_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.jsonl");
+        let (tx, rx) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+        let task = spawn_transcript_recorder(
+            path.clone(),
+            TranscriptRecord::RunStart {
+                agent_id: "a".into(),
+                description: "survey".into(),
+                prompt: "look around".into(),
+            },
+            rx,
+            shutdown.clone(),
+        );
+
+        tx.send(AgentEvent::ToolExecutionStart {
+            tool_call_id: "t1".into(),
+            tool_name: "read".into(),
+            arguments: serde_json::json!({ "path": "a.rs" }),
+            activity: "Reading a.rs".into(),
+        })
+        .unwrap();
+        tx.send(AgentEvent::ToolExecutionEnd {
+            tool_call_id: "t1".into(),
+            tool_name: "read".into(),
+            result: "fn main() {}".into(),
+            is_error: false,
+        })
+        .unwrap();
+        tx.send(AgentEvent::MessageEnd {
+            message: make_assistant_message("done"),
+        })
+        .unwrap();
+        tx.send(AgentEvent::AgentEnd {
+            total_turns: 1,
+            total_usage: Usage::default(),
+            interrupted: false,
+        })
+        .unwrap();
+        shutdown.cancel();
+        task.await.unwrap();
+
+        let entries = read_transcript(&path).await.unwrap();
+        let types: Vec<_> = entries
+            .iter()
+            .map(|e| match &e.record {
+                TranscriptRecord::RunStart { .. } => "run_start",
+                TranscriptRecord::ToolStart { .. } => "tool_start",
+                TranscriptRecord::ToolEnd { duration_ms, .. } => {
+                    assert!(duration_ms.is_some());
+                    "tool_end"
+                }
+                TranscriptRecord::Message { .. } => "message",
+                TranscriptRecord::RunEnd { .. } => "run_end",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(
+            types,
+            ["run_start", "tool_start", "tool_end", "message", "run_end"]
+        );
+        assert!(entries.iter().all(|e| e.at.is_some()));
     }
 
-    Some(path)
+    #[test]
+    fn reads_legacy_bare_message_lines() {
+        let legacy = serde_json::to_string(&Message::user("hi")).unwrap();
+        let entries = parse_transcript(&format!("{legacy}\nnot json\n"));
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].at.is_none());
+        assert!(matches!(
+            entries[0].record,
+            TranscriptRecord::Message { .. }
+        ));
+    }
 }
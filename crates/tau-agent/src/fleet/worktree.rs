@@ -3,20 +3,93 @@
 //! When a spec opts into worktree isolation and the spawn opts request
 //! it, the manager creates a fresh worktree on a per-agent branch and
 //! runs the subagent there. On cleanup, if the worktree has no diff
-//! and no untracked files, it's removed; otherwise the path and
-//! branch are returned so the host can surface them.
+//! and no untracked files, it's removed; otherwise it's kept and
+//! tracked by the manager for review.
+//!
+//! Review works against the parent checkout the worktree was created
+//! from: [`diff_worktree`] shows the agent's changes relative to the
+//! merge base with the parent's `HEAD`, [`merge_worktree`] brings them
+//! back (merge or cherry-pick), and [`discard_worktree`] throws them
+//! away. Before diffing or merging, the agent's uncommitted work is
+//! committed onto its branch ([`snapshot_worktree`]) so git can treat
+//! it like any other branch.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 
-use std::path::PathBuf;
+/// Kept worktrees by agent id, shared between the manager and the
+/// lifecycle operations that create them.
+pub(crate) type KeptWorktrees = Arc<Mutex<HashMap<String, WorktreeInfo>>>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeInfo {
     pub path: PathBuf,
     pub branch: String,
     pub head_commit: String,
+    /// Top level of the checkout the worktree was created from — where
+    /// merges land.
+    pub parent_root: PathBuf,
+}
+
+/// How [`merge_worktree`] brings an agent's branch into the parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// `git merge --no-ff`: one merge commit on the parent.
+    Merge,
+    /// `git cherry-pick` of the branch's commits: linear history.
+    /// Fails on branches that contain merge commits (conflict-resolver
+    /// output); use [`MergeStrategy::Merge`] for those.
+    CherryPick,
+}
+
+/// One path touched by a worktree's changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedFile {
+    /// `git diff --name-status` letter(s): `A`, `M`, `D`, `R100`, …
+    pub status: String,
+    pub path: String,
+}
+
+/// A kept worktree's changes relative to the parent checkout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeDiff {
+    /// Merge base of the parent's `HEAD` and the agent's branch.
+    pub base: String,
+    /// Tip of the agent's branch (after snapshotting).
+    pub head: String,
+    pub files: Vec<ChangedFile>,
+    /// Unified diff `base..head`.
+    pub patch: String,
+}
+
+/// Result of [`merge_worktree`]. Conflicts are an outcome, not an
+/// error: the parent checkout is rolled back to where it was and the
+/// conflicting paths are reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MergeOutcome {
+    /// The changes landed; `head` is the parent's new `HEAD`.
+    Merged { head: String },
+    /// The branch had no changes the parent doesn't already have.
+    NothingToMerge,
+    /// The merge stopped on conflicts and was aborted.
+    Conflicted { conflicts: Vec<String> },
 }
 
 pub async fn create_worktree(agent_id: &str) -> Result<WorktreeInfo, String> {
+    create_worktree_in(Path::new("."), agent_id).await
+}
+
+pub(crate) async fn create_worktree_in(dir: &Path, agent_id: &str) -> Result<WorktreeInfo, String> {
     let git_root_output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
         .args(["rev-parse", "--show-toplevel"])
         .output()
         .await
@@ -34,17 +107,17 @@ pub async fn create_worktree(agent_id: &str) -> Result<WorktreeInfo, String> {
         .await
         .map_err(|e| format!("Failed to create worktree directory: {e}"))?;
 
-    let output = tokio::process::Command::new("git")
-        .args([
+    let output = git(
+        &git_root,
+        &[
             "worktree",
             "add",
             &path.display().to_string(),
             "-b",
             &branch,
-        ])
-        .output()
-        .await
-        .map_err(|e| format!("git worktree add failed: {e}"))?;
+        ],
+    )
+    .await?;
 
     if !output.status.success() {
         return Err(format!(
@@ -53,20 +126,13 @@ pub async fn create_worktree(agent_id: &str) -> Result<WorktreeInfo, String> {
         ));
     }
 
-    let head_output = tokio::process::Command::new("git")
-        .args(["rev-parse", "HEAD"])
-        .output()
-        .await
-        .map_err(|e| format!("git rev-parse HEAD failed: {e}"))?;
-
-    let head_commit = String::from_utf8_lossy(&head_output.stdout)
-        .trim()
-        .to_string();
+    let head_commit = rev_parse(&git_root, "HEAD").await?;
 
     Ok(WorktreeInfo {
         path,
         branch,
         head_commit,
+        parent_root: git_root,
     })
 }
 
@@ -110,3 +176,316 @@ pub async fn cleanup_worktree(info: &WorktreeInfo) -> Result<bool, String> {
         Ok(false)
     }
 }
+
+/// Merge `branch` into a freshly created worktree, leaving any
+/// conflict markers in place for the agent that will run there.
+/// Returns the conflicting paths (empty when the merge was clean).
+pub async fn merge_into_worktree(info: &WorktreeInfo, branch: &str) -> Result<Vec<String>, String> {
+    let mut args = identity_args(&info.path).await;
+    args.extend(["merge", "--no-ff", "--no-edit", branch].map(String::from));
+    let out = git(&info.path, &args).await?;
+    if out.status.success() {
+        return Ok(Vec::new());
+    }
+    let conflicts = conflicted_paths(&info.path).await?;
+    if conflicts.is_empty() {
+        return Err(git_failure("merge", &out));
+    }
+    Ok(conflicts)
+}
+
+/// Commit everything in the worktree (tracked changes, new files,
+/// and an in-progress merge's resolution) onto its branch. Returns
+/// the branch tip. A no-op when there's nothing to commit.
+pub async fn snapshot_worktree(info: &WorktreeInfo) -> Result<String, String> {
+    let add = git(&info.path, &["add", "-A"]).await?;
+    if !add.status.success() {
+        return Err(git_failure("add", &add));
+    }
+    let staged_clean = git(&info.path, &["diff", "--cached", "--quiet"])
+        .await?
+        .status
+        .success();
+    let merging = git(&info.path, &["rev-parse", "-q", "--verify", "MERGE_HEAD"])
+        .await?
+        .status
+        .success();
+    if !staged_clean || merging {
+        let mut args = identity_args(&info.path).await;
+        args.extend(
+            [
+                "commit",
+                "--no-verify",
+                "-m",
+                &format!("Agent changes from {}", info.branch),
+            ]
+            .map(String::from),
+        );
+        let commit = git(&info.path, &args).await?;
+        if !commit.status.success() {
+            return Err(git_failure("commit", &commit));
+        }
+    }
+    rev_parse(&info.path, "HEAD").await
+}
+
+/// Snapshot the worktree, then diff its branch against the merge base
+/// with the parent's `HEAD`.
+pub async fn diff_worktree(info: &WorktreeInfo) -> Result<WorktreeDiff, String> {
+    let head = snapshot_worktree(info).await?;
+    let base = merge_base(&info.parent_root, &head).await?;
+
+    let names = git(&info.parent_root, &["diff", "--name-status", &base, &head]).await?;
+    if !names.status.success() {
+        return Err(git_failure("diff", &names));
+    }
+    let files = String::from_utf8_lossy(&names.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (status, path) = line.split_once('\t')?;
+            Some(ChangedFile {
+                status: status.to_string(),
+                path: path.replace('\t', " -> "),
+            })
+        })
+        .collect();
+
+    let patch = git(&info.parent_root, &["diff", &base, &head]).await?;
+    Ok(WorktreeDiff {
+        base,
+        head,
+        files,
+        patch: String::from_utf8_lossy(&patch.stdout).into_owned(),
+    })
+}
+
+/// Snapshot the worktree and bring its branch into the parent
+/// checkout. On conflicts the operation is aborted, leaving the parent
+/// as it was. The worktree itself is left alone either way. Refuses a
+/// parent with uncommitted changes, which the abort would throw away.
+pub async fn merge_worktree(
+    info: &WorktreeInfo,
+    strategy: MergeStrategy,
+) -> Result<MergeOutcome, String> {
+    let parent = &info.parent_root;
+    let status = git(parent, &["status", "--porcelain", "--untracked-files=no"]).await?;
+    if !status.status.success() {
+        return Err(git_failure("status", &status));
+    }
+    if !status.stdout.is_empty() {
+        return Err(format!(
+            "{} has uncommitted changes; commit or stash them before merging",
+            parent.display()
+        ));
+    }
+    let head = snapshot_worktree(info).await?;
+    let base = merge_base(parent, &head).await?;
+    if base == head {
+        return Ok(MergeOutcome::NothingToMerge);
+    }
+
+    let mut args = identity_args(parent).await;
+    let op = match strategy {
+        MergeStrategy::Merge => {
+            args.extend(["merge", "--no-ff", "--no-edit", &info.branch].map(String::from));
+            "merge"
+        }
+        MergeStrategy::CherryPick => {
+            args.extend(["cherry-pick".to_string(), format!("{base}..{head}")]);
+            "cherry-pick"
+        }
+    };
+    let out = git(parent, &args).await?;
+    if out.status.success() {
+        return Ok(MergeOutcome::Merged {
+            head: rev_parse(parent, "HEAD").await?,
+        });
+    }
+
+    let conflicts = conflicted_paths(parent).await?;
+    let _ = git(parent, &[op, "--abort"]).await;
+    if conflicts.is_empty() {
+        return Err(git_failure(op, &out));
+    }
+    Ok(MergeOutcome::Conflicted { conflicts })
+}
+
+/// Remove the worktree and delete its branch, discarding any changes.
+pub async fn discard_worktree(info: &WorktreeInfo) -> Result<(), String> {
+    let path_str = info.path.display().to_string();
+    let remove = git(
+        &info.parent_root,
+        &["worktree", "remove", "--force", &path_str],
+    )
+    .await?;
+    if !remove.status.success() {
+        return Err(git_failure("worktree remove", &remove));
+    }
+    let branch = git(&info.parent_root, &["branch", "-D", &info.branch]).await?;
+    if !branch.status.success() {
+        return Err(git_failure("branch -D", &branch));
+    }
+    Ok(())
+}
+
+// ─── Helpers ────────────────────────────────────────────────────────
+
+async fn git<S: AsRef<std::ffi::OsStr>>(dir: &Path, args: &[S]) -> Result<Output, String> {
+    tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run git: {e}"))
+}
+
+fn git_failure(op: &str, out: &Output) -> String {
+    format!(
+        "git {op} failed: {}",
+        String::from_utf8_lossy(&out.stderr).trim()
+    )
+}
+
+async fn rev_parse(dir: &Path, rev: &str) -> Result<String, String> {
+    let out = git(dir, &["rev-parse", rev]).await?;
+    if !out.status.success() {
+        return Err(git_failure("rev-parse", &out));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+async fn merge_base(parent: &Path, head: &str) -> Result<String, String> {
+    let out = git(parent, &["merge-base", "HEAD", head]).await?;
+    if !out.status.success() {
+        return Err(git_failure("merge-base", &out));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+async fn conflicted_paths(dir: &Path) -> Result<Vec<String>, String> {
+    let out = git(dir, &["diff", "--name-only", "--diff-filter=U"]).await?;
+    Ok(String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Commits made on the agent's behalf must not fail on machines with
+/// no git identity configured; fall back to a fixed one there.
+async fn identity_args(dir: &Path) -> Vec<String> {
+    let configured = git(dir, &["config", "user.email"])
+        .await
+        .is_ok_and(|o| o.status.success());
+    if configured {
+        Vec::new()
+    } else {
+        ["-c", "user.name=tau", "-c", "user.email=tau@localhost"]
+            .map(String::from)
+            .to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn sh(dir: &Path, script: &str) {
+        let status = tokio::process::Command::new("sh")
+            .args(["-c", script])
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .await
+            .unwrap();
+        assert!(status.success(), "script failed: {script}");
+    }
+
+    async fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        sh(
+            dir.path(),
+            "git init -q -b main && printf 'one\\ntwo\\n' > a.txt && \
+             echo .tau-worktrees > .gitignore && git add -A && git commit -qm init",
+        )
+        .await;
+        dir
+    }
+
+    #[tokio::test]
+    async fn diff_and_merge_bring_changes_back() {
+        let repo = repo().await;
+        let wt = create_worktree_in(repo.path(), "a1").await.unwrap();
+        sh(&wt.path, "echo three >> a.txt && echo new > b.txt").await;
+
+        let diff = diff_worktree(&wt).await.unwrap();
+        let paths: Vec<_> = diff
+            .files
+            .iter()
+            .map(|f| (f.status.as_str(), f.path.as_str()))
+            .collect();
+        assert_eq!(paths, [("M", "a.txt"), ("A", "b.txt")]);
+        assert!(diff.patch.contains("+three"));
+
+        let outcome = merge_worktree(&wt, MergeStrategy::Merge).await.unwrap();
+        assert!(matches!(outcome, MergeOutcome::Merged { .. }));
+        let merged = std::fs::read_to_string(repo.path().join("a.txt")).unwrap();
+        assert_eq!(merged, "one\ntwo\nthree\n");
+        assert!(repo.path().join("b.txt").exists());
+
+        assert_eq!(
+            merge_worktree(&wt, MergeStrategy::Merge).await.unwrap(),
+            MergeOutcome::NothingToMerge
+        );
+        discard_worktree(&wt).await.unwrap();
+        assert!(!wt.path.exists());
+    }
+
+    #[tokio::test]
+    async fn conflicting_merge_is_reported_and_rolled_back() {
+        let repo = repo().await;
+        let wt = create_worktree_in(repo.path(), "a2").await.unwrap();
+        sh(&wt.path, "printf 'agent\\ntwo\\n' > a.txt").await;
+        sh(
+            repo.path(),
+            "printf 'parent\\ntwo\\n' > a.txt && git commit -qam parent",
+        )
+        .await;
+
+        for strategy in [MergeStrategy::Merge, MergeStrategy::CherryPick] {
+            let outcome = merge_worktree(&wt, strategy).await.unwrap();
+            assert_eq!(
+                outcome,
+                MergeOutcome::Conflicted {
+                    conflicts: vec!["a.txt".into()]
+                }
+            );
+            let parent = std::fs::read_to_string(repo.path().join("a.txt")).unwrap();
+            assert_eq!(parent, "parent\ntwo\n", "parent rolled back");
+        }
+
+        // A resolver worktree gets the conflict markers to work on.
+        let resolver = create_worktree_in(repo.path(), "a3").await.unwrap();
+        let conflicts = merge_into_worktree(&resolver, &wt.branch).await.unwrap();
+        assert_eq!(conflicts, ["a.txt"]);
+        let marked = std::fs::read_to_string(resolver.path.join("a.txt")).unwrap();
+        assert!(marked.contains("<<<<<<<"));
+    }
+
+    #[tokio::test]
+    async fn merge_refuses_a_dirty_parent() {
+        let repo = repo().await;
+        let wt = create_worktree_in(repo.path(), "a4").await.unwrap();
+        sh(&wt.path, "echo new > b.txt").await;
+        sh(repo.path(), "echo local >> a.txt").await;
+
+        let err = merge_worktree(&wt, MergeStrategy::Merge).await.unwrap_err();
+        assert!(err.contains("uncommitted changes"), "{err}");
+        let parent = std::fs::read_to_string(repo.path().join("a.txt")).unwrap();
+        assert_eq!(parent, "one\ntwo\nlocal\n", "local edit kept");
+        assert!(!repo.path().join("b.txt").exists());
+    }
+}
@@ -36,26 +36,47 @@ pub use crate::core::compaction::{
     CompactionConfig, CompactionReason, CompactionThreshold, summary_message,
 };
 pub use crate::core::config::{AgentConfig, AgentConfigBuilder, DequeueMode};
+pub use crate::core::goal::GoalConfig;
 pub use crate::core::handle::AgentHandle;
 pub use crate::core::interaction::{
     InteractionKind, InteractionRequest, InteractionResponse, QuestionOption,
 };
+pub use crate::core::replay::Replay;
+pub use crate::core::spill::{ToolOutputSpill, spill_path};
 pub use crate::core::tool::{
-    BoxedTool, Concurrency, ExecutionContext, FileAccessTracker, ProgressSender, Tool,
-    ToolCategory, ToolResult,
+    BoxedTool, Concurrency, ExecutionContext, FileAccessError, FileAccessTracker, ProgressSender,
+    Tool, ToolCategory, ToolResult,
 };
 pub use crate::core::transport::{AgentEventStream, AgentRunConfig, ProviderTransport, Transport};
 
 pub use crate::types::conversation::Conversation;
 pub use crate::types::error::{Error, Result};
 pub use crate::types::events::{
-    AgentEvent, ConsoleLevel, ConsoleLine, FleetEvent, SubagentOutcome, ToolApprovalOutcome,
+    AgentEvent, ConsoleLevel, ConsoleLine, FleetEvent, GoalOutcome, SubagentOutcome,
+    ToolApprovalOutcome,
 };
 pub use crate::types::health::AgentHealth;
 pub use crate::types::info::{ContextStats, ToolInfo};
+pub use crate::types::rewind::{RewindTarget, Rewound};
 
 pub use crate::fleet::SubagentMessageExt;
+pub use crate::fleet::batch::{BatchInput, BatchItemResult, BatchOpts, BatchResult};
+pub use crate::fleet::blackboard::{Blackboard, BlackboardEntry, BlackboardTopic};
+pub use crate::fleet::checkpoint::{
+    AgentCheckpoint, CheckpointStatus, CheckpointStore, RestoreReport,
+};
 pub use crate::fleet::manager::{AgentManager, AgentSpec, AgentStatus, Isolation, SpawnOpts};
 pub use crate::fleet::registry::Located;
 pub use crate::fleet::result::SubagentResult;
+pub use crate::fleet::scheduler::{
+    MAX_TIMER_DELAY, MAX_TIMERS, MIN_TIMER_INTERVAL, ScheduledTimer,
+};
 pub use crate::fleet::snapshot::{AgentSnapshot, FleetSnapshot};
+pub use crate::fleet::telemetry::{TraceConfig, TraceSink, spawn_trace_exporter};
+pub use crate::fleet::transcript::{
+    TranscriptEntry, TranscriptRecord, parse_transcript, read_transcript, transcript_dir,
+    transcript_path,
+};
+pub use crate::fleet::worktree::{
+    ChangedFile, MergeOutcome, MergeStrategy, WorktreeDiff, WorktreeInfo,
+};
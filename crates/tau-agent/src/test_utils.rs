@@ -84,11 +84,13 @@ pub fn make_test_config() -> AgentConfig {
     AgentConfig {
         system_prompt: None,
         model: make_test_model(),
+        utility_model: None,
         reasoning: ReasoningLevel::Off,
         thinking_adaptive: false,
         max_tokens: None,
         max_turns: None,
         compaction: CompactionConfig::default(),
+        tool_output_spill: None,
         steering_mode: DequeueMode::All,
         follow_up_mode: DequeueMode::All,
         cache_scope: None,
@@ -108,6 +110,7 @@ pub fn make_execution_context() -> ExecutionContext {
         file_access: Arc::new(ParkingMutex::new(FileAccessTracker::default())),
         agent_id: None,
         subagent_depth: 0,
+        spill: None,
     }
 }
 
@@ -379,6 +382,9 @@ fn event_type_name(event: &AgentEvent) -> &'static str {
         AgentEvent::ToolExecutionEnd { .. } => "ToolExecutionEnd",
         AgentEvent::ToolApprovalResolved { .. } => "ToolApprovalResolved",
         AgentEvent::FileChanged { .. } => "FileChanged",
+        AgentEvent::FileModifiedExternally { .. } => "FileModifiedExternally",
+        AgentEvent::GoalCheck { .. } => "GoalCheck",
+        AgentEvent::GoalEnd { .. } => "GoalEnd",
         AgentEvent::AgentReport { .. } => "AgentReport",
         AgentEvent::TurnEnd { .. } => "TurnEnd",
         AgentEvent::AgentEnd { .. } => "AgentEnd",
@@ -67,6 +67,47 @@ pub enum Error {
     #[error("worktree setup failed: {reason}")]
     WorktreeSetupFailed { reason: String },
 
+    /// A worktree review operation referenced an agent with no kept
+    /// worktree — it never ran isolated, left no changes, or its
+    /// worktree was already merged or discarded.
+    #[error("agent '{id}' has no worktree awaiting review")]
+    NoWorktree { id: String },
+
+    /// A git operation on an agent's kept worktree (diff, merge,
+    /// discard) failed. Merge conflicts are not errors; they come back
+    /// as [`MergeOutcome::Conflicted`](crate::MergeOutcome::Conflicted).
+    #[error("worktree operation for agent '{id}' failed: {reason}")]
+    WorktreeFailed { id: String, reason: String },
+
+    /// The subagent checkpoint directory couldn't be read during
+    /// [`AgentManager::restore`](crate::AgentManager::restore).
+    /// Individual unreadable checkpoints are skipped, not raised.
+    #[error("reading subagent checkpoints failed: {reason}")]
+    CheckpointFailed { reason: String },
+
+    /// A recorded log couldn't be prepared for
+    /// [`Replay`](crate::Replay) — the requested turn is out of range or
+    /// the log has no prompt to start from.
+    #[error("replay failed: {reason}")]
+    ReplayFailed { reason: String },
+
+    /// A timer couldn't be scheduled via
+    /// [`AgentManager::schedule_timer`](crate::AgentManager::schedule_timer)
+    /// — its interval is too short or the fleet holds too many.
+    #[error("timer rejected: {reason}")]
+    TimerRejected { reason: String },
+
+    /// The agent's [`History`](tau_history::History) backend failed
+    /// to load at spawn, or to list or go back to an earlier turn.
+    #[error("conversation history failed: {reason}")]
+    History { reason: String },
+
+    /// [`AgentHandle::rewind`](crate::AgentHandle::rewind) or
+    /// [`fork_at`](crate::AgentHandle::fork_at) named a turn the
+    /// conversation doesn't have. Turns count user prompts from 1.
+    #[error("no turn {turn}: the conversation has {turns} turn(s)")]
+    NoSuchTurn { turn: usize, turns: usize },
+
     /// Unstructured error. Reserved for situations that don't yet
     /// have a dedicated variant — channel-closed-after-actor-death,
     /// internal invariant violations, etc. New error conditions
@@ -151,6 +151,33 @@ pub enum AgentEvent {
         tool_call_id: String,
     },
 
+    /// A file the agent had read changed on disk behind its back (user
+    /// edit, formatter, another process). Emitted when an `edit`/`write`
+    /// is refused because of it. `diff` shows the external change when
+    /// the file was small enough for its read to be kept.
+    FileModifiedExternally {
+        path: PathBuf,
+        diff: Option<String>,
+        tool_call_id: String,
+    },
+
+    /// Goal mode ran its check command after the agent finished a
+    /// round. See [`AgentHandle::prompt_until`](crate::AgentHandle::prompt_until).
+    GoalCheck {
+        /// 1-based round number.
+        iteration: u32,
+        max_iterations: u32,
+        passed: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        /// Tail of the check's combined stdout + stderr.
+        output: String,
+    },
+    /// Goal mode stopped.
+    GoalEnd {
+        outcome: GoalOutcome,
+    },
+
     /// Tool-emitted self-label. The fleet bus translates this into
     /// [`FleetEvent::AgentReport`] when forwarding, stamping the
     /// emitting agent's id; consumers subscribed directly to a single
@@ -165,7 +192,7 @@ pub enum AgentEvent {
 /// Events emitted on [`AgentManager`](crate::AgentManager)'s
 /// broadcast channel.
 ///
-/// Three kinds:
+/// Six kinds:
 ///
 /// - **Lifecycle** (`AgentStarted` / `AgentResumed` / `AgentCompleted`)
 ///   — emitted by the manager itself when an agent crosses a
@@ -175,6 +202,15 @@ pub enum AgentEvent {
 ///   event. The originating agent's id is stamped on the variant.
 /// - **Forwarded** (`Forwarded`) — every other [`AgentEvent`] a tracked
 ///   agent emits, stamped with `agent_id` and `description`.
+/// - **Batch progress** (`BatchStarted` / `BatchItemFinished` /
+///   `BatchCompleted`) — emitted around
+///   [`AgentManager::spawn_batch`](crate::AgentManager::spawn_batch).
+/// - **Blackboard** (`BlackboardPosted`) — an agent posted to a fleet
+///   blackboard topic via
+///   [`AgentManager::post_to_blackboard`](crate::AgentManager::post_to_blackboard).
+/// - **Timers** (`TimerFired`) — a timer set via
+///   [`AgentManager::schedule_timer`](crate::AgentManager::schedule_timer)
+///   came due.
 ///
 /// Nesting is structurally impossible: `Forwarded::event` is an
 /// [`AgentEvent`], not a `FleetEvent`. A grandchild's events arrive on
@@ -228,6 +264,55 @@ pub enum FleetEvent {
         description: String,
         event: AgentEvent,
     },
+    /// A batch spawn began. Its items follow as ordinary agent
+    /// lifecycle events.
+    BatchStarted {
+        batch_id: String,
+        description: String,
+        total: usize,
+        concurrency: usize,
+    },
+    /// One batch input finished — successfully, or failing after its
+    /// last retry.
+    BatchItemFinished {
+        batch_id: String,
+        index: usize,
+        description: String,
+        /// The successful attempt's agent id.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        agent_id: Option<String>,
+        /// The last attempt's error.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        attempts: u32,
+        /// Items finished so far, this one included.
+        finished: usize,
+        total: usize,
+    },
+    /// Every item of a batch has finished.
+    BatchCompleted {
+        batch_id: String,
+        description: String,
+        succeeded: usize,
+        failed: usize,
+    },
+    /// An agent posted to a blackboard topic.
+    BlackboardPosted {
+        seq: u64,
+        topic: String,
+        agent_id: String,
+        description: String,
+        text: String,
+    },
+    /// A scheduled timer came due. `delivered` is false when the
+    /// target was neither running nor an idle fleet agent; the host
+    /// decides what to do with `message` then.
+    TimerFired {
+        timer_id: String,
+        agent_id: String,
+        message: String,
+        delivered: bool,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -238,6 +323,27 @@ pub enum SubagentOutcome {
     Failed { reason: String },
 }
 
+/// Why [`AgentHandle::prompt_until`](crate::AgentHandle::prompt_until)
+/// stopped. `iterations` counts completed prompt → check rounds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GoalOutcome {
+    /// The check command exited zero.
+    Passed { iterations: u32 },
+    /// The check still failed after the last allowed round.
+    MaxIterations { iterations: u32 },
+    /// The goal's token budget ran out before the check passed.
+    BudgetExhausted { iterations: u32, tokens_used: u64 },
+    /// The agent was aborted or interrupted mid-goal.
+    Aborted { iterations: u32 },
+}
+
+impl GoalOutcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, GoalOutcome::Passed { .. })
+    }
+}
+
 impl AgentEvent {
     /// Whether this event terminates the agent's own timeline.
     pub fn is_terminal(&self) -> bool {
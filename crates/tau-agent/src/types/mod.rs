@@ -9,3 +9,4 @@ pub mod error;
 pub mod events;
 pub mod health;
 pub mod info;
+pub mod rewind;
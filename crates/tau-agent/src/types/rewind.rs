@@ -0,0 +1,43 @@
+//! What [`AgentHandle::rewind`](crate::AgentHandle::rewind) and
+//! [`AgentHandle::fork_at`](crate::AgentHandle::fork_at) go back to,
+//! and what they report.
+
+use tau_history::ObjectHash;
+
+/// A point in the conversation's history to go back to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewindTarget {
+    /// Just before the `n`th user prompt, counting from 1 in
+    /// [`AgentHandle::user_turns`](crate::AgentHandle::user_turns)
+    /// order — the state to retry that prompt from.
+    Turn(usize),
+    /// An exact commit: a turn's `commit`, a head recorded earlier, or
+    /// the `abandoned` head of a previous rewind, to undo it.
+    Commit(ObjectHash),
+}
+
+impl From<usize> for RewindTarget {
+    fn from(turn: usize) -> Self {
+        Self::Turn(turn)
+    }
+}
+
+impl From<ObjectHash> for RewindTarget {
+    fn from(commit: ObjectHash) -> Self {
+        Self::Commit(commit)
+    }
+}
+
+/// Outcome of a rewind or fork.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Rewound {
+    /// Where the live conversation now is. `None` if it went back to
+    /// before the first commit.
+    pub head: Option<ObjectHash>,
+    /// Where it was. Stays reachable in the history's repository, so
+    /// it can be diffed against `head` or rewound to later.
+    pub abandoned: Option<ObjectHash>,
+    /// Messages in the live conversation after the rewind.
+    pub messages: usize,
+}
@@ -41,6 +41,7 @@ fn small_compaction_config() -> CompactionConfig {
         enabled: true,
         reserve: tau_agent::CompactionThreshold::Tokens(100),
         keep_recent: tau_agent::CompactionThreshold::Tokens(50),
+        ..CompactionConfig::default()
     }
 }
 
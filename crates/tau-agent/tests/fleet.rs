@@ -234,3 +234,488 @@ async fn eviction_drops_oldest_when_at_capacity() {
         assert!(mgr.spec_for(id).is_some(), "agent {id} retained");
     }
 }
+
+/// Answers with text after a short delay, failing the first
+/// `fail_first` calls, and records the peak number of concurrent runs.
+struct BatchTransport {
+    calls: std::sync::atomic::AtomicU32,
+    fail_first: u32,
+    in_flight: std::sync::atomic::AtomicUsize,
+    peak: std::sync::atomic::AtomicUsize,
+}
+
+impl BatchTransport {
+    fn create(fail_first: u32) -> Arc<Self> {
+        Arc::new(Self {
+            calls: Default::default(),
+            fail_first,
+            in_flight: Default::default(),
+            peak: Default::default(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for BatchTransport {
+    async fn run(
+        &self,
+        _messages: Vec<tau_ai::Message>,
+        config: &AgentRunConfig,
+        _cancel: tokio_util::sync::CancellationToken,
+    ) -> tau_ai::Result<AgentEventStream> {
+        use std::sync::atomic::Ordering;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.fail_first {
+            return Err(tau_ai::Error::InvalidApiKey);
+        }
+        let msg = test_utils::make_assistant_message("done");
+        let turn_number = config.turn_number;
+        Ok(Box::pin(futures::stream::iter(vec![
+            AgentEvent::TurnStart { turn_number },
+            AgentEvent::MessageEnd {
+                message: msg.clone(),
+            },
+            AgentEvent::TurnEnd {
+                turn_number,
+                message: msg,
+                usage: Default::default(),
+            },
+        ])))
+    }
+}
+
+fn batch_inputs(n: usize) -> Vec<BatchInput> {
+    (0..n)
+        .map(|i| BatchInput {
+            description: format!("item {i}"),
+            prompt: format!("look at crate {i}"),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn spawn_batch_bounds_concurrency_and_reports_progress() {
+    let transport = BatchTransport::create(0);
+    let mgr = Arc::new(AgentManager::new(
+        test_utils::test_config(),
+        transport.clone(),
+        16,
+    ));
+    let mut events = mgr.subscribe();
+
+    let opts = BatchOpts {
+        description: "survey".into(),
+        concurrency: 2,
+        ..Default::default()
+    };
+    let result = mgr
+        .spawn_batch(
+            empty_spec(),
+            batch_inputs(5),
+            opts,
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await;
+
+    assert_eq!(result.succeeded(), 5);
+    let order: Vec<usize> = result.items.iter().map(|i| i.index).collect();
+    assert_eq!(order, [0, 1, 2, 3, 4], "results come back in input order");
+    assert!(result.items.iter().all(|i| i.attempts == 1));
+    assert_eq!(transport.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    let mut finished = Vec::new();
+    let mut completed = None;
+    while let Ok(ev) = events.try_recv() {
+        match ev {
+            FleetEvent::BatchItemFinished { finished: n, .. } => finished.push(n),
+            FleetEvent::BatchCompleted {
+                succeeded, failed, ..
+            } => completed = Some((succeeded, failed)),
+            _ => {}
+        }
+    }
+    assert_eq!(finished, [1, 2, 3, 4, 5]);
+    assert_eq!(completed, Some((5, 0)));
+}
+
+#[tokio::test]
+async fn spawn_batch_retries_then_reports_failures() {
+    // First three runs fail: item 0 fails twice (no retries left), item
+    // 1 fails once and then succeeds on its retry.
+    let transport = BatchTransport::create(3);
+    let mgr = Arc::new(AgentManager::new(test_utils::test_config(), transport, 16));
+
+    let opts = BatchOpts {
+        concurrency: 1,
+        retries: 1,
+        ..Default::default()
+    };
+    let result = mgr
+        .spawn_batch(
+            empty_spec(),
+            batch_inputs(2),
+            opts,
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await;
+
+    assert_eq!(result.items[0].attempts, 2);
+    assert!(result.items[0].result.is_err());
+    assert_eq!(result.items[1].attempts, 2);
+    assert_eq!(result.items[1].result.as_ref().unwrap().text, "done");
+    assert_eq!((result.succeeded(), result.failed()), (1, 1));
+}
+
+fn resolve_explore(name: &str, _depth: u32) -> Option<AgentSpec> {
+    (name == "explore").then(empty_spec)
+}
+
+#[tokio::test]
+async fn checkpointed_agent_restores_idle_and_resumes_with_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = Arc::new(
+        AgentManager::new(
+            test_utils::test_config(),
+            test_utils::TextTransport::create("noted"),
+            4,
+        )
+        .with_checkpoint_dir(dir.path()),
+    );
+    let spawned = first
+        .spawn(
+            empty_spec(),
+            "remember the number 7".into(),
+            SpawnOpts {
+                spec_name: Some("explore".into()),
+                ..spawn_opts("memory")
+            },
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .expect("spawn");
+    // An agent without a spec name is checkpointed but can't come back.
+    first
+        .spawn(
+            empty_spec(),
+            "anonymous".into(),
+            spawn_opts("nameless"),
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .expect("spawn");
+
+    let stored = CheckpointStore::new(dir.path()).load().await.unwrap();
+    let (checkpoint, messages) = stored
+        .iter()
+        .find(|(c, _)| c.agent_id == spawned.agent_id)
+        .expect("checkpoint written");
+    assert_eq!(checkpoint.status, CheckpointStatus::Idle);
+    assert_eq!(messages.len(), 2, "prompt and reply on disk");
+
+    // "Restart": a fresh manager over the same directory.
+    let second = Arc::new(
+        AgentManager::new(
+            test_utils::test_config(),
+            test_utils::TextTransport::create("still 7"),
+            4,
+        )
+        .with_checkpoint_dir(dir.path()),
+    );
+    let report = second
+        .restore(&resolve_explore, None)
+        .await
+        .expect("restore");
+    assert_eq!(report.restored, vec![spawned.agent_id.clone()]);
+    assert_eq!(report.skipped.len(), 1, "nameless agent skipped");
+    assert!(report.resumed.is_empty());
+    let located = second
+        .find_agent("memory")
+        .expect("restored agent locatable");
+    assert_eq!(located.status, AgentStatus::Idle);
+
+    let resumed = second
+        .send(
+            &spawned.agent_id,
+            "what was the number?",
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .expect("send after restore");
+    assert_eq!(resumed.text, "still 7");
+    let stored = CheckpointStore::new(dir.path()).load().await.unwrap();
+    let (_, messages) = stored
+        .iter()
+        .find(|(c, _)| c.agent_id == spawned.agent_id)
+        .unwrap();
+    assert_eq!(messages.len(), 4, "history carried across the restart");
+}
+
+/// Checkpoint a background agent `bg-1` whose run never finished.
+async fn save_interrupted_background(store: &CheckpointStore) {
+    let now = chrono::Utc::now();
+    let checkpoint = AgentCheckpoint {
+        agent_id: "bg-1".into(),
+        description: "long survey".into(),
+        spec_name: Some("explore".into()),
+        prompt: "survey the repo".into(),
+        model: None,
+        cwd: None,
+        worktree: None,
+        subagent_depth: 1,
+        background: true,
+        status: CheckpointStatus::Running,
+        previous_summary: None,
+        started_at: now,
+        updated_at: now,
+    };
+    store.save(&checkpoint).await;
+    store
+        .save_messages("bg-1", &[tau_ai::Message::user("survey the repo")])
+        .await;
+}
+
+async fn wait_for_completion(fleet: &mut tokio::sync::broadcast::Receiver<FleetEvent>) -> bool {
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(FleetEvent::AgentCompleted { agent_id, .. }) = fleet.recv().await
+                && agent_id == "bg-1"
+            {
+                break;
+            }
+        }
+    })
+    .await
+    .is_ok()
+}
+
+#[tokio::test]
+async fn restore_resumes_interrupted_background_agent() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = CheckpointStore::new(dir.path());
+    save_interrupted_background(&store).await;
+
+    let transport = test_utils::TextTransport::create("survey done");
+    let mgr = Arc::new(
+        AgentManager::new(test_utils::test_config(), transport.clone(), 4)
+            .with_checkpoint_dir(dir.path()),
+    );
+    let mut fleet = mgr.subscribe();
+    let parent = AgentBuilder::new(test_utils::test_config(), transport)
+        .spawn()
+        .await
+        .unwrap();
+
+    let report = mgr
+        .restore(&resolve_explore, Some(&parent))
+        .await
+        .expect("restore");
+    assert_eq!(report.resumed, vec!["bg-1".to_string()]);
+
+    assert!(
+        wait_for_completion(&mut fleet).await,
+        "resumed run completes"
+    );
+    let stored = store.load().await.unwrap();
+    assert_eq!(stored[0].0.status, CheckpointStatus::Idle);
+    assert_eq!(
+        stored[0].1.len(),
+        3,
+        "original prompt, resume prompt, reply"
+    );
+}
+
+#[tokio::test]
+async fn shutdown_cancels_resumed_background_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    save_interrupted_background(&CheckpointStore::new(dir.path())).await;
+
+    let transport = test_utils::SlowTransport::create(60_000);
+    let mgr = Arc::new(
+        AgentManager::new(test_utils::test_config(), transport.clone(), 4)
+            .with_checkpoint_dir(dir.path()),
+    );
+    let mut fleet = mgr.subscribe();
+    let parent = AgentBuilder::new(test_utils::test_config(), transport)
+        .spawn()
+        .await
+        .unwrap();
+    let report = mgr
+        .restore(&resolve_explore, Some(&parent))
+        .await
+        .expect("restore");
+    assert_eq!(report.resumed, vec!["bg-1".to_string()]);
+    // Shut down once the resumed turn is waiting on the model.
+    loop {
+        if let Ok(FleetEvent::Forwarded {
+            event: AgentEvent::TurnStart { .. },
+            ..
+        }) = fleet.recv().await
+        {
+            break;
+        }
+    }
+
+    mgr.shutdown();
+    assert!(
+        wait_for_completion(&mut fleet).await,
+        "resumed run stops with the manager"
+    );
+}
+
+#[tokio::test]
+async fn blackboard_posts_are_attributed_and_snapshotted() {
+    let mgr = make_manager(test_utils::TextTransport::create("ok"));
+    let handle = AgentBuilder::new(
+        test_utils::test_config(),
+        test_utils::TextTransport::create("ok"),
+    )
+    .spawn()
+    .await
+    .unwrap();
+    let root_id = mgr.adopt(&handle, "root", empty_spec());
+    let mut events = mgr.subscribe();
+
+    assert!(mgr.subscribe_blackboard("sibling", "findings"));
+    let entry = mgr
+        .post_to_blackboard(&root_id, "Findings", "tau-ai has no unsafe")
+        .await;
+    assert_eq!(entry.topic, "findings");
+    assert_eq!(mgr.read_blackboard("findings", 0), vec![entry.clone()]);
+    assert!(mgr.read_blackboard("findings", entry.seq).is_empty());
+
+    match events.try_recv().expect("posted event") {
+        FleetEvent::BlackboardPosted {
+            agent_id,
+            description,
+            text,
+            ..
+        } => {
+            assert_eq!(agent_id, root_id);
+            assert_eq!(description, "root");
+            assert_eq!(text, "tau-ai has no unsafe");
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+
+    let snapshot = mgr.snapshot();
+    assert_eq!(snapshot.blackboard.len(), 1);
+    assert_eq!(snapshot.blackboard[0].subscribers, ["sibling"]);
+    assert_eq!(snapshot.blackboard[0].entries, [entry]);
+}
+
+#[tokio::test]
+async fn agents_leaving_the_fleet_lose_their_subscriptions() {
+    let mgr = make_manager(test_utils::TextTransport::create("ok"));
+    let (_handle, id) = mgr
+        .spawn_interactive(empty_spec(), SpawnOpts::default())
+        .await
+        .unwrap();
+    assert!(mgr.subscribe_blackboard(&id, "findings"));
+
+    mgr.remove_interactive(&id);
+    assert!(mgr.snapshot().blackboard[0].subscribers.is_empty());
+}
+
+#[tokio::test]
+async fn timers_resume_idle_agents_and_hand_the_rest_to_the_host() {
+    let mgr = make_manager(test_utils::TextTransport::create("checked"));
+    let idle = mgr
+        .spawn(
+            empty_spec(),
+            "watch CI".into(),
+            spawn_opts("ci watcher"),
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .expect("spawn");
+    let mut events = mgr.subscribe();
+
+    let timer = mgr
+        .schedule_timer(&idle.agent_id, "check CI", std::time::Duration::ZERO, None)
+        .expect("schedule");
+    let mut resumed = false;
+    let mut fired = None;
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while !(resumed && fired.is_some()) {
+            match events.recv().await.unwrap() {
+                FleetEvent::TimerFired {
+                    timer_id,
+                    message,
+                    delivered,
+                    ..
+                } => fired = Some((timer_id, message, delivered)),
+                FleetEvent::AgentResumed { agent_id, .. } if agent_id == idle.agent_id => {
+                    resumed = true;
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("timer fires and resumes the agent");
+    assert_eq!(
+        fired,
+        Some((timer.id.clone(), "[Timer t1 fired] check CI".into(), true))
+    );
+    assert!(mgr.timers().is_empty(), "one-shot timers are removed");
+
+    mgr.schedule_timer("root", "ping", std::time::Duration::ZERO, None)
+        .unwrap();
+    let delivered = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(FleetEvent::TimerFired { delivered, .. }) = events.recv().await {
+                break delivered;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert!(!delivered, "untracked agents are left to the host");
+}
+
+#[tokio::test]
+async fn timers_persist_and_restore_with_the_fleet() {
+    let dir = tempfile::tempdir().unwrap();
+    let hour = std::time::Duration::from_secs(3600);
+    let first = make_manager(test_utils::TextTransport::create("ok"));
+    first.set_checkpoint_dir(dir.path());
+    assert!(matches!(
+        first.schedule_timer(
+            "root",
+            "poll",
+            hour,
+            Some(std::time::Duration::from_secs(5))
+        ),
+        Err(Error::TimerRejected { .. })
+    ));
+    for too_far in [std::time::Duration::MAX, MAX_TIMER_DELAY + hour] {
+        assert!(matches!(
+            first.schedule_timer("root", "later", too_far, None),
+            Err(Error::TimerRejected { .. })
+        ));
+    }
+    let kept = first
+        .schedule_timer("root", "re-run flaky test", hour, Some(hour))
+        .unwrap();
+    let dropped = first
+        .schedule_timer("root", "never mind", hour, None)
+        .unwrap();
+    assert_eq!(first.cancel_timer(&dropped.id), Some(dropped));
+    drop(first);
+
+    let second = make_manager(test_utils::TextTransport::create("ok"));
+    second.set_checkpoint_dir(dir.path());
+    let report = second
+        .restore(&resolve_explore, None)
+        .await
+        .expect("timers file is not mistaken for a checkpoint");
+    assert_eq!(report.timers, vec![kept.id.clone()]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(second.snapshot().timers, vec![kept]);
+    let next = second.schedule_timer("root", "later", hour, None).unwrap();
+    assert_eq!(next.id, "t2", "ids continue past the restored ones");
+}
@@ -0,0 +1,184 @@
+//! Tests for goal mode (`AgentHandle::prompt_until`).
+
+use tau_agent::test_utils::*;
+use tau_agent::*;
+use tau_ai::Message;
+
+/// A check that fails until it has been run `passes_on` times. The run
+/// count lives in a file under `dir`.
+fn flaky_check(dir: &std::path::Path, passes_on: u32) -> GoalConfig {
+    let counter = dir.join("runs");
+    GoalConfig::new(format!(
+        "n=$(cat '{0}' 2>/dev/null || echo 0); n=$((n+1)); echo $n > '{0}'; \
+         echo \"run $n\"; [ $n -ge {passes_on} ]",
+        counter.display()
+    ))
+}
+
+#[tokio::test]
+async fn passing_check_stops_after_first_round() {
+    let handle = AgentBuilder::new(test_config(), TextTransport::create("done"))
+        .spawn()
+        .await
+        .unwrap();
+    let collector = EventCollector::from_handle(&handle);
+
+    let outcome = handle
+        .prompt_until("fix it", &GoalConfig::new("true"))
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, GoalOutcome::Passed { iterations: 1 });
+    collector
+        .wait_for_event(|e| matches!(e, AgentEvent::GoalEnd { .. }))
+        .await;
+    let names = collector.event_names();
+    assert_eq!(names.iter().filter(|n| **n == "GoalCheck").count(), 1);
+    assert_eq!(names.iter().filter(|n| **n == "GoalEnd").count(), 1);
+}
+
+#[tokio::test]
+async fn failing_output_is_fed_back_until_check_passes() {
+    let dir = tempfile::tempdir().unwrap();
+    let handle = AgentBuilder::new(test_config(), TextTransport::create("done"))
+        .spawn()
+        .await
+        .unwrap();
+
+    let outcome = handle
+        .prompt_until("fix it", &flaky_check(dir.path(), 3))
+        .await
+        .unwrap();
+    assert_eq!(outcome, GoalOutcome::Passed { iterations: 3 });
+
+    let users: Vec<String> = handle
+        .messages()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|m| matches!(m, Message::User { .. }))
+        .map(|m| m.text())
+        .collect();
+    assert_eq!(users.len(), 3);
+    assert_eq!(users[0], "fix it");
+    assert!(users[1].contains("exited with code 1"));
+    assert!(users[1].contains("run 1"));
+    assert!(users[2].contains("run 2"));
+}
+
+#[tokio::test]
+async fn stops_at_max_iterations() {
+    let handle = AgentBuilder::new(test_config(), TextTransport::create("done"))
+        .spawn()
+        .await
+        .unwrap();
+    let mut goal = GoalConfig::new("false");
+    goal.max_iterations = 2;
+
+    let outcome = handle.prompt_until("fix it", &goal).await.unwrap();
+    assert_eq!(outcome, GoalOutcome::MaxIterations { iterations: 2 });
+}
+
+#[tokio::test]
+async fn stops_when_token_budget_is_spent() {
+    // TextTransport reports 150 tokens per turn.
+    let handle = AgentBuilder::new(test_config(), TextTransport::create("done"))
+        .spawn()
+        .await
+        .unwrap();
+    let mut goal = GoalConfig::new("false");
+    goal.token_budget = Some(250);
+
+    let outcome = handle.prompt_until("fix it", &goal).await.unwrap();
+    assert_eq!(
+        outcome,
+        GoalOutcome::BudgetExhausted {
+            iterations: 2,
+            tokens_used: 300
+        }
+    );
+}
+
+#[tokio::test]
+async fn abort_mid_goal_ends_it() {
+    let handle = AgentBuilder::new(test_config(), SlowTransport::create(300))
+        .spawn()
+        .await
+        .unwrap();
+    let collector = EventCollector::from_handle(&handle);
+    let task = {
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            handle
+                .prompt_until("fix it", &GoalConfig::new("false"))
+                .await
+        })
+    };
+
+    // Abort once the second round is in flight.
+    collector
+        .wait_for_event(|e| matches!(e, AgentEvent::GoalCheck { .. }))
+        .await;
+    while collector
+        .event_names()
+        .iter()
+        .filter(|n| **n == "TurnStart")
+        .count()
+        < 2
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    handle.abort();
+
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), task)
+        .await
+        .expect("goal kept running after abort")
+        .unwrap()
+        .unwrap();
+    assert_eq!(outcome, GoalOutcome::Aborted { iterations: 1 });
+    collector
+        .wait_for_event(|e| matches!(e, AgentEvent::GoalEnd { .. }))
+        .await;
+}
+
+#[tokio::test]
+async fn abort_during_check_ends_the_goal() {
+    let handle = AgentBuilder::new(test_config(), TextTransport::create("done"))
+        .spawn()
+        .await
+        .unwrap();
+    let collector = EventCollector::from_handle(&handle);
+    let task = {
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            handle
+                .prompt_until("fix it", &GoalConfig::new("sleep 30"))
+                .await
+        })
+    };
+
+    collector.wait_for_end().await;
+    handle.abort();
+
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), task)
+        .await
+        .expect("check kept running after abort")
+        .unwrap()
+        .unwrap();
+    assert_eq!(outcome, GoalOutcome::Aborted { iterations: 0 });
+}
+
+#[tokio::test]
+async fn check_runs_in_the_agent_cwd_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("marker"), "").unwrap();
+    let mut builder = AgentBuilder::new(test_config(), TextTransport::create("done"));
+    builder.set_cwd(dir.path());
+    let handle = builder.spawn().await.unwrap();
+
+    let outcome = handle
+        .prompt_until("fix it", &GoalConfig::new("test -f marker"))
+        .await
+        .unwrap();
+    assert_eq!(outcome, GoalOutcome::Passed { iterations: 1 });
+}
@@ -0,0 +1,459 @@
+//! The actor's writes to a host-supplied `History`: one commit per
+//! turn, resume from an existing branch, compaction via
+//! `compact_prefix`, which commits count as user turns, rewinding
+//! or forking to an earlier turn, and moving onto a host's branch.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tau_agent::test_utils::*;
+use tau_agent::{
+    AgentBuilder, AgentHandle, AgentSeed, CompactionConfig, CompactionThreshold, Error, Transport,
+};
+use tau_history::{CommitKind, CommitMeta, History, HistoryError, Repository, TreePatch};
+
+/// Number of commits from `tip` back to the root.
+fn depth(repo: &Repository, branch: &tau_history::Branch) -> usize {
+    let mut n = 0;
+    let mut at = branch.tip();
+    while let Some(hash) = at {
+        n += 1;
+        at = repo.get_commit(&hash).unwrap().parent;
+    }
+    n
+}
+
+fn json(messages: &[tau_ai::Message]) -> serde_json::Value {
+    serde_json::to_value(messages).unwrap()
+}
+
+#[tokio::test]
+async fn each_turn_is_one_commit() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let mut builder = AgentBuilder::new(test_config(), ToolCallTransport::create(1, "echo"));
+    builder.add_tool(Arc::new(EchoTool));
+    builder.set_history(branch.clone());
+    let handle = builder.spawn().await.unwrap();
+
+    handle.prompt_and_wait("call echo").await.unwrap();
+
+    // user + tool-call assistant + tool result, then the final answer.
+    assert_eq!(depth(&repo, &branch), 2);
+    let committed = branch.messages().await.unwrap();
+    let roles: Vec<_> = committed.iter().map(|m| m.role()).collect();
+    assert_eq!(roles, ["user", "assistant", "tool_result", "assistant"]);
+    assert_eq!(json(&committed), json(&handle.messages().await.unwrap()));
+
+    let meta = &repo.get_commit(&branch.tip().unwrap()).unwrap().meta;
+    assert_eq!(meta.kind, Some(CommitKind::Turn));
+    assert!(meta.timestamp.is_some());
+    assert!(meta.usage.is_some(), "the batch has an assistant message");
+}
+
+#[tokio::test]
+async fn resumes_from_an_existing_branch() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    branch
+        .commit(
+            TreePatch::new()
+                .add_message(tau_ai::Message::user("earlier question"))
+                .add_message(make_assistant_message("earlier answer")),
+        )
+        .await
+        .unwrap();
+    let forked = branch.fork();
+
+    let mut builder = AgentBuilder::new(test_config(), TextTransport::create("new answer"));
+    builder.seed(AgentSeed::Messages {
+        messages: vec![tau_ai::Message::user("ignored seed")],
+        previous_summary: None,
+    });
+    builder.set_history(forked.clone());
+    let handle = builder.spawn().await.unwrap();
+    assert_eq!(handle.messages().await.unwrap().len(), 2);
+
+    handle.prompt_and_wait("follow-up").await.unwrap();
+    assert_eq!(forked.messages().await.unwrap().len(), 4);
+    // The original branch is untouched by the fork's new turn.
+    assert_eq!(branch.messages().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn summarizing_compaction_goes_through_compact_prefix() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let cfg = test_config()
+        .into_builder()
+        .compaction(CompactionConfig {
+            enabled: true,
+            reserve: CompactionThreshold::Tokens(100),
+            keep_recent: CompactionThreshold::Tokens(50),
+            ..CompactionConfig::default()
+        })
+        .build();
+    let transport = CapturingTransport::create("SUMMARY-OK");
+    let mut builder = AgentBuilder::new(cfg, transport as Arc<dyn Transport>);
+    let seed: Vec<_> = (0..20)
+        .flat_map(|i| {
+            [
+                tau_ai::Message::user(format!("user message number {i:02} blah blah")),
+                make_assistant_message(&format!("assistant reply number {i:02} blah blah")),
+            ]
+        })
+        .collect();
+    builder.seed(AgentSeed::Messages {
+        messages: seed,
+        previous_summary: None,
+    });
+    builder.set_history(branch.clone());
+    let handle = builder.spawn().await.unwrap();
+    assert_eq!(branch.messages().await.unwrap().len(), 40);
+
+    let reply = handle.compact(None).await.unwrap().await.unwrap();
+    assert!(reply.result.is_ok(), "{:?}", reply.result.err());
+
+    assert_eq!(
+        branch.previous_summary().await.unwrap().as_deref(),
+        Some("SUMMARY-OK")
+    );
+    let committed = branch.messages().await.unwrap();
+    assert!(committed.len() < 40);
+    assert_eq!(json(&committed), json(&handle.messages().await.unwrap()));
+
+    let meta = &repo.get_commit(&branch.tip().unwrap()).unwrap().meta;
+    assert_eq!(meta.kind, Some(CommitKind::Compaction));
+    assert!(meta.timestamp.is_some());
+    assert!(meta.usage.is_some_and(|u| u.output > 0), "summarizer usage");
+}
+
+#[tokio::test]
+async fn pruning_compaction_is_committed() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let cfg = test_config()
+        .into_builder()
+        .compaction(CompactionConfig {
+            enabled: true,
+            // Every turn's reported usage trips the threshold.
+            reserve: CompactionThreshold::Tokens(199_950),
+            keep_recent: CompactionThreshold::Tokens(50),
+            prune_target: CompactionThreshold::Tokens(1_000_000),
+            ..CompactionConfig::default()
+        })
+        .build();
+    let mut seed = vec![
+        tau_ai::Message::user("dump the log"),
+        make_tool_call_message("bash", "t1", serde_json::json!({})),
+        tau_ai::Message::tool_result(
+            "t1",
+            "bash",
+            vec![tau_ai::Content::text("log line\n".repeat(1_000))],
+            false,
+        ),
+        make_assistant_message("the log is long"),
+    ];
+    seed.extend((0..10).flat_map(|i| {
+        [
+            tau_ai::Message::user(format!("question {i} blah blah blah")),
+            make_assistant_message(&format!("answer {i} blah blah blah")),
+        ]
+    }));
+    let mut builder = AgentBuilder::new(cfg, TextTransport::create("ok"));
+    builder.seed(AgentSeed::Messages {
+        messages: seed,
+        previous_summary: None,
+    });
+    builder.set_history(branch.clone());
+    let handle = builder.spawn().await.unwrap();
+
+    handle.prompt_and_wait("one more").await.unwrap();
+
+    let committed = branch.messages().await.unwrap();
+    assert!(committed[2].text().len() < 1_000, "tool output pruned");
+    let pruned = (0..)
+        .scan(branch.tip(), |at, _| {
+            let commit = repo.get_commit(&(*at)?).unwrap();
+            *at = commit.parent;
+            Some(commit.meta.clone())
+        })
+        .find(|meta| meta.kind == Some(CommitKind::Compaction))
+        .expect("a compaction commit");
+    assert!(pruned.timestamp.is_some());
+    assert_eq!(json(&committed), json(&handle.messages().await.unwrap()));
+    assert_eq!(branch.previous_summary().await.unwrap(), None);
+}
+
+/// A branch whose compaction commits fail while `broken` is set.
+struct BrokenCompaction {
+    branch: Arc<tau_history::Branch>,
+    broken: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl History for BrokenCompaction {
+    async fn messages(&self) -> Result<Vec<tau_ai::Message>, HistoryError> {
+        self.branch.messages().await
+    }
+    async fn system_prompt(&self) -> Result<Option<Vec<tau_ai::Content>>, HistoryError> {
+        self.branch.system_prompt().await
+    }
+    async fn tools(&self) -> Result<Vec<tau_history::ToolDef>, HistoryError> {
+        self.branch.tools().await
+    }
+    async fn previous_summary(&self) -> Result<Option<String>, HistoryError> {
+        self.branch.previous_summary().await
+    }
+    async fn append(&self, messages: Vec<tau_ai::Message>) -> Result<(), HistoryError> {
+        self.branch.append(messages).await
+    }
+    async fn compact_prefix(
+        &self,
+        end: usize,
+        summary_message: tau_ai::Message,
+        summary_text: String,
+    ) -> Result<(), HistoryError> {
+        self.compact_prefix_with(end, summary_message, summary_text, CommitMeta::new())
+            .await
+    }
+    async fn compact_prefix_with(
+        &self,
+        end: usize,
+        summary_message: tau_ai::Message,
+        summary_text: String,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        if self.broken.load(Ordering::Relaxed) {
+            return Err(HistoryError::msg("disk full"));
+        }
+        self.branch
+            .compact_prefix_with(end, summary_message, summary_text, meta)
+            .await
+    }
+}
+
+#[tokio::test]
+async fn compaction_history_missed_is_recorded_later() {
+    let branch = Repository::new().new_branch();
+    let history = Arc::new(BrokenCompaction {
+        branch: branch.clone(),
+        broken: AtomicBool::new(true),
+    });
+    let cfg = test_config()
+        .into_builder()
+        .compaction(CompactionConfig {
+            enabled: true,
+            reserve: CompactionThreshold::Tokens(100),
+            keep_recent: CompactionThreshold::Tokens(50),
+            ..CompactionConfig::default()
+        })
+        .build();
+    let transport = CapturingTransport::create("SUMMARY-OK");
+    let mut builder = AgentBuilder::new(cfg, transport as Arc<dyn Transport>);
+    let seed: Vec<_> = (0..20)
+        .flat_map(|i| {
+            [
+                tau_ai::Message::user(format!("user message number {i:02} blah blah")),
+                make_assistant_message(&format!("assistant reply number {i:02} blah blah")),
+            ]
+        })
+        .collect();
+    builder.seed(AgentSeed::Messages {
+        messages: seed,
+        previous_summary: None,
+    });
+    builder.set_history(history.clone());
+    let handle = builder.spawn().await.unwrap();
+
+    let reply = handle.compact(None).await.unwrap().await.unwrap();
+    assert!(reply.result.is_ok(), "{:?}", reply.result.err());
+    let compacted = handle.messages().await.unwrap();
+    assert!(compacted.len() < 40);
+    assert_eq!(branch.messages().await.unwrap().len(), 40, "not recorded");
+
+    // Still broken: the turn waits with the summary.
+    handle.prompt_and_wait("one").await.unwrap();
+    assert_eq!(branch.messages().await.unwrap().len(), 40);
+
+    history.broken.store(false, Ordering::Relaxed);
+    handle.prompt_and_wait("two").await.unwrap();
+    assert_eq!(
+        json(&branch.messages().await.unwrap()),
+        json(&handle.messages().await.unwrap())
+    );
+    assert_eq!(
+        branch.previous_summary().await.unwrap().as_deref(),
+        Some("SUMMARY-OK")
+    );
+}
+
+#[tokio::test]
+async fn compacted_seed_commits_its_summary() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let mut builder = AgentBuilder::new(test_config(), TextTransport::create("ok"));
+    builder.seed(AgentSeed::Messages {
+        messages: vec![
+            tau_agent::summary_message("we fixed the parser"),
+            tau_ai::Message::user("and now the lexer"),
+        ],
+        previous_summary: Some("we fixed the parser".into()),
+    });
+    builder.set_history(branch.clone());
+    let handle = builder.spawn().await.unwrap();
+
+    assert_eq!(
+        branch.previous_summary().await.unwrap().as_deref(),
+        Some("we fixed the parser")
+    );
+    assert_eq!(
+        json(&branch.messages().await.unwrap()),
+        json(&handle.messages().await.unwrap())
+    );
+}
+
+#[tokio::test]
+async fn seeds_and_goal_reprompts_are_not_user_turns() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let mut builder = AgentBuilder::new(test_config(), TextTransport::create("ok"));
+    builder.seed(AgentSeed::Messages {
+        messages: vec![
+            tau_ai::Message::user("from the old log"),
+            make_assistant_message("sure"),
+        ],
+        previous_summary: None,
+    });
+    builder.set_history(branch.clone());
+    let handle = builder.spawn().await.unwrap();
+
+    // Fails once, then passes: one feedback prompt.
+    let ran = dir.path().join("ran");
+    let check = format!("[ -f '{0}' ] || {{ touch '{0}'; false; }}", ran.display());
+    handle
+        .prompt_until("fix it", &tau_agent::GoalConfig::new(check))
+        .await
+        .unwrap();
+
+    let turns = handle.user_turns().await.unwrap();
+    let prompts: Vec<_> = turns.iter().map(|t| t.prompt.as_str()).collect();
+    assert_eq!(prompts, ["fix it"]);
+    let kind = |hash| repo.get_commit(&hash).unwrap().meta.kind;
+    assert_eq!(kind(branch.tip().unwrap()), Some(CommitKind::Reprompt));
+    assert_eq!(kind(turns[0].before.unwrap()), Some(CommitKind::Seed));
+}
+
+fn texts(messages: &[tau_ai::Message]) -> Vec<String> {
+    messages.iter().map(|m| m.text()).collect()
+}
+
+async fn three_turns(branch: Arc<tau_history::Branch>) -> AgentHandle {
+    let mut builder = AgentBuilder::new(test_config(), TextTransport::create("ok"));
+    builder.set_history(branch);
+    let handle = builder.spawn().await.unwrap();
+    for prompt in ["one", "two", "three"] {
+        handle.prompt_and_wait(prompt).await.unwrap();
+    }
+    handle
+}
+
+#[tokio::test]
+async fn rewind_to_a_turn_and_back_again() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let handle = three_turns(branch.clone()).await;
+
+    let turns = handle.user_turns().await.unwrap();
+    let prompts: Vec<_> = turns.iter().map(|t| t.prompt.as_str()).collect();
+    assert_eq!(prompts, ["one", "two", "three"]);
+
+    let rewound = handle.rewind(2).await.unwrap();
+    assert_eq!(rewound.head, turns[1].before);
+    assert_eq!(rewound.messages, 2);
+    assert_eq!(branch.tip(), turns[1].before, "the history itself moved");
+    let abandoned = rewound.abandoned.unwrap();
+    assert_eq!(
+        repo.resolve_ref(&format!("refs/abandoned/{abandoned}"))
+            .unwrap(),
+        Some(abandoned)
+    );
+
+    handle.prompt_and_wait("two, differently").await.unwrap();
+    assert_eq!(
+        texts(&handle.messages().await.unwrap()),
+        ["one", "ok", "two, differently", "ok"]
+    );
+    assert_eq!(
+        json(&branch.messages().await.unwrap()),
+        json(&handle.messages().await.unwrap())
+    );
+
+    // The abandoned line is a commit like any other.
+    handle.rewind(abandoned).await.unwrap();
+    assert_eq!(handle.messages().await.unwrap().len(), 6);
+    assert!(matches!(
+        handle.rewind(9).await,
+        Err(Error::NoSuchTurn { turn: 9, turns: 3 })
+    ));
+}
+
+#[tokio::test]
+async fn fork_at_leaves_the_original_history_alone() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let handle = three_turns(branch.clone()).await;
+    let tip = branch.tip();
+
+    let forked = handle.fork_at(1).await.unwrap();
+    assert_eq!((forked.head, forked.abandoned), (None, tip));
+    assert!(handle.messages().await.unwrap().is_empty());
+
+    handle.prompt_and_wait("fresh start").await.unwrap();
+    assert_eq!(
+        texts(&handle.messages().await.unwrap()),
+        ["fresh start", "ok"]
+    );
+    assert_eq!(branch.tip(), tip);
+    assert_eq!(branch.messages().await.unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn switch_history_continues_on_a_named_branch() {
+    let repo = Repository::new();
+    let branch = repo.branch_named("refs/sessions/a").unwrap();
+    let handle = three_turns(branch.clone()).await;
+    let tip = branch.tip();
+    let turns = handle.user_turns().await.unwrap();
+
+    let point = turns[1].before.unwrap();
+    repo.update_ref("refs/sessions/b", None, point, "rewind")
+        .unwrap();
+    let fork = repo.branch_named("refs/sessions/b").unwrap();
+    let switched = handle.switch_history(fork).await.unwrap();
+    assert_eq!((switched.head, switched.abandoned), (Some(point), tip));
+    assert_eq!(switched.messages, 2);
+
+    handle.prompt_and_wait("two, differently").await.unwrap();
+    assert_eq!(repo.resolve_ref("refs/sessions/a").unwrap(), tip);
+    let b = repo.branch_named("refs/sessions/b").unwrap();
+    assert_eq!(
+        texts(&b.messages().await.unwrap()),
+        ["one", "ok", "two, differently", "ok"]
+    );
+}
+
+#[tokio::test]
+async fn cannot_go_back_mid_prompt() {
+    let handle = AgentBuilder::new(test_config(), SlowTransport::create(500))
+        .spawn()
+        .await
+        .unwrap();
+    let _reply = handle.prompt("slow").await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert!(matches!(handle.rewind(1).await, Err(Error::Busy)));
+    assert!(matches!(handle.user_turns().await, Err(Error::Busy)));
+    handle.abort();
+}
@@ -0,0 +1,84 @@
+//! Replay: recorded turns are served without model calls or tool side
+//! effects, then the run continues live.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tau_agent::test_utils::*;
+use tau_agent::*;
+use tau_ai::{Content, Message};
+
+struct CountingTool(Arc<AtomicU32>);
+
+#[async_trait]
+impl Tool for CountingTool {
+    fn name(&self) -> &str {
+        "bash"
+    }
+    fn description(&self) -> &str {
+        "counts executions"
+    }
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "object" })
+    }
+    async fn execute(&self, _arguments: serde_json::Value, _ctx: ExecutionContext) -> ToolResult {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ToolResult::text("live result")
+    }
+}
+
+fn recorded_log() -> Vec<Message> {
+    vec![
+        Message::user("list files"),
+        make_tool_call_message("bash", "c1", serde_json::json!({ "command": "ls" })),
+        Message::ToolResult {
+            tool_call_id: "c1".into(),
+            tool_name: "bash".into(),
+            content: vec![Content::text("a.rs b.txt")],
+            is_error: false,
+            timestamp: 0,
+        },
+        Message::user("only rust files"),
+        make_assistant_message("a.rs"),
+        Message::user("thanks"),
+        make_assistant_message("welcome"),
+    ]
+}
+
+#[tokio::test]
+async fn replays_recorded_turns_then_continues_live() {
+    let executions = Arc::new(AtomicU32::new(0));
+    let live = CapturingTransport::create("live answer");
+    let replay = Replay::new(recorded_log(), 3, Some("what about tests?".into())).unwrap();
+
+    let mut builder = AgentBuilder::new(test_config(), replay.transport(live.clone()));
+    let tools: Vec<BoxedTool> = vec![Arc::new(CountingTool(executions.clone()))];
+    builder.set_tools(replay.wrap_tools(&tools));
+    let handle = builder.spawn().await.unwrap();
+    replay.attach(&handle);
+
+    handle.prompt_and_wait(replay.prompt()).await.unwrap();
+
+    assert_eq!(
+        executions.load(Ordering::SeqCst),
+        0,
+        "recorded call re-executed"
+    );
+    assert!(replay.is_live());
+    let calls = live.calls();
+    assert_eq!(calls.len(), 1, "only turn 3 should reach the model");
+    let texts: Vec<String> = calls[0].messages.iter().map(|m| m.text()).collect();
+    assert_eq!(
+        texts,
+        [
+            "list files",
+            "",
+            "a.rs b.txt",
+            "only rust files",
+            "a.rs",
+            "what about tests?"
+        ]
+    );
+    let history = handle.messages().await.unwrap();
+    assert_eq!(history.last().unwrap().text(), "live answer");
+}
@@ -195,6 +195,22 @@ impl Usage {
             total: input + output + cache_read + cache_write + thinking,
         }
     }
+
+    /// Add `other`'s token counts to these. `service_tier` is left
+    /// alone.
+    pub fn accumulate(&mut self, other: &Usage) {
+        self.input = self.input.saturating_add(other.input);
+        self.output = self.output.saturating_add(other.output);
+        self.cache_read = self.cache_read.saturating_add(other.cache_read);
+        self.cache_write = self.cache_write.saturating_add(other.cache_write);
+        self.thinking = self.thinking.saturating_add(other.thinking);
+        self.cache_creation_1h = self
+            .cache_creation_1h
+            .saturating_add(other.cache_creation_1h);
+        self.cache_creation_5m = self
+            .cache_creation_5m
+            .saturating_add(other.cache_creation_5m);
+    }
 }
 
 /// Cost breakdown in dollars
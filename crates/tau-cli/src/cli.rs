@@ -10,16 +10,24 @@
 //! tau auth status
 //! tau sessions ls
 //! tau sessions resume <id>
+//! tau sessions rm <id>
+//! tau transcript <agent-or-session> [--format markdown|html]
+//! tau replay <session> --from-turn <n> [--prompt <text>]
+//! tau history export <ref>... -o <file>
+//! tau history import <file>
+//! tau history gc
 //! tau config init
 //! ```
 //!
 //! Common runtime flags (`--model`, `--provider`, `--reasoning`,
 //! `--working-dir`, `--no-tui`, `--verbose`) sit on the top level and
-//! apply to the implicit-default and `run` commands.
+//! apply to the implicit-default, `run` and `replay` commands.
 
 use clap::{Parser, Subcommand};
 use tau_ai::{CostInfo, InputType, Model, Provider, ReasoningLevel};
 
+use crate::transcript::TranscriptFormat;
+
 /// tau - AI-powered coding agent
 #[derive(Parser, Debug)]
 #[command(name = "tau")]
@@ -68,11 +76,21 @@ pub(crate) enum Command {
     Run {
         /// The prompt to send to the agent.
         prompt: String,
+        /// Goal mode: after the agent finishes, run this shell command
+        /// and feed failures back until it exits zero.
+        #[arg(long, value_name = "CMD")]
+        until: Option<String>,
+        /// Maximum prompt → check rounds in goal mode.
+        #[arg(long, default_value_t = 5, requires = "until")]
+        max_iterations: u32,
+        /// Stop goal mode once this many tokens have been used.
+        #[arg(long, value_name = "TOKENS", requires = "until")]
+        token_budget: Option<u64>,
     },
     /// OAuth login / logout / status.
     #[command(subcommand)]
     Auth(AuthCmd),
-    /// List or resume saved sessions.
+    /// List, resume or delete saved sessions.
     #[command(subcommand)]
     Sessions(SessionsCmd),
     /// Manage the configuration file.
@@ -84,6 +102,69 @@ pub(crate) enum Command {
     /// Inspect configured MCP servers.
     #[command(subcommand)]
     Mcp(McpCmd),
+    /// Re-run a saved session from a given turn. Earlier turns replay
+    /// from the log (recorded tool results, no side effects); the rest
+    /// runs live, optionally with `--model` or a different prompt.
+    Replay {
+        /// The session id (or short prefix).
+        session: String,
+        /// First turn (model call, counting from 1) to run live.
+        #[arg(long, value_name = "N")]
+        from_turn: usize,
+        /// Replace the user message that led to that turn.
+        #[arg(long)]
+        prompt: Option<String>,
+    },
+    /// Render a subagent or session transcript.
+    Transcript {
+        /// Transcript file, subagent id, or session id (or prefix).
+        target: String,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = TranscriptFormat::Markdown)]
+        format: TranscriptFormat,
+        /// Write to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Move conversation history between repositories as bundles.
+    #[command(subcommand)]
+    History(HistoryCmd),
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum HistoryCmd {
+    /// Write commits and their full ancestry to a bundle file.
+    Export {
+        /// Tags, `refs/…` names, session ids or full commit hashes to
+        /// include. Tags keep their names in the bundle.
+        #[arg(required = true)]
+        refs: Vec<String>,
+        /// Bundle file to write.
+        #[arg(short, long, value_name = "FILE")]
+        output: std::path::PathBuf,
+        /// History repository directory (default: the tau data
+        /// directory's `history/`).
+        #[arg(long, value_name = "DIR")]
+        repo: Option<std::path::PathBuf>,
+    },
+    /// Verify a bundle file and add its history to the repository.
+    /// Existing tags are never overwritten.
+    Import {
+        /// Bundle file to read.
+        file: std::path::PathBuf,
+        /// History repository directory (default: the tau data
+        /// directory's `history/`).
+        #[arg(long, value_name = "DIR")]
+        repo: Option<std::path::PathBuf>,
+    },
+    /// Expire old rewound turns, delete objects no tag or ref reaches
+    /// any more, and pack the rest. Safe while sessions are running.
+    Gc {
+        /// History repository directory (default: the tau data
+        /// directory's `history/`).
+        #[arg(long, value_name = "DIR")]
+        repo: Option<std::path::PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -116,6 +197,16 @@ pub(crate) enum SessionsCmd {
     Resume {
         /// The session id (or short prefix).
         id: String,
+        /// Also continue background subagents that were still running
+        /// when the session ended.
+        #[arg(long)]
+        resume_subagents: bool,
+    },
+    /// Delete a saved session: its log, subagent checkpoints and saved
+    /// tool output.
+    Rm {
+        /// The session id (or short prefix).
+        id: String,
     },
 }
 
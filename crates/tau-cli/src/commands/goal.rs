@@ -0,0 +1,47 @@
+//! /goal command - keep prompting until a check command passes
+
+use async_trait::async_trait;
+use tau_agent::GoalConfig;
+
+use super::Command;
+use crate::driver::{Frontend, Session};
+
+pub struct GoalCommand;
+
+#[async_trait]
+impl Command for GoalCommand {
+    fn name(&self) -> &str {
+        "goal"
+    }
+    fn description(&self) -> &str {
+        "Re-prompt until a check passes (/goal <command> | /goal off)"
+    }
+    async fn execute(&self, args: &str, session: &mut Session, frontend: &mut dyn Frontend) {
+        match args.trim() {
+            "" => {
+                let msg = match session.goal() {
+                    Some(goal) => format!(
+                        "Goal mode on: `{}` (up to {} rounds per prompt)",
+                        goal.check_command, goal.max_iterations
+                    ),
+                    None => "Goal mode off. Usage: /goal <check command>".to_string(),
+                };
+                frontend.show_system(&msg).await;
+            }
+            "off" => {
+                session.set_goal(None);
+                frontend.show_system("Goal mode off").await;
+            }
+            cmd => {
+                let goal = GoalConfig::new(cmd);
+                frontend
+                    .show_system(&format!(
+                        "Goal mode on: each prompt repeats until `{}` passes (up to {} rounds)",
+                        goal.check_command, goal.max_iterations
+                    ))
+                    .await;
+                session.set_goal(Some(goal));
+            }
+        }
+    }
+}
@@ -7,11 +7,15 @@
 //! command by name/alias and calls `execute`.
 
 mod branch;
+mod goal;
 pub(crate) mod mcp;
 mod model;
 mod plan;
+mod rewind;
 mod session;
 mod thinking;
+mod timers;
+mod worktree;
 
 use async_trait::async_trait;
 
@@ -39,7 +43,11 @@ pub fn all_commands() -> Vec<Box<dyn Command>> {
         Box::new(thinking::ThinkingCommand),
         Box::new(session::SessionCommand),
         Box::new(branch::BranchCommand),
+        Box::new(rewind::RewindCommand),
         Box::new(plan::PlanCommand),
+        Box::new(goal::GoalCommand),
+        Box::new(worktree::WorktreeCommand),
+        Box::new(timers::TimersCommand),
         Box::new(CompactCommand),
     ]
 }
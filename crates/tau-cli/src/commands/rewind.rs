@@ -0,0 +1,78 @@
+//! /rewind command - go back to before an earlier prompt
+
+use async_trait::async_trait;
+use tau_agent::RewindTarget;
+use tau_history::ObjectHash;
+
+use super::Command;
+use crate::driver::{Frontend, Session};
+
+pub struct RewindCommand;
+
+#[async_trait]
+impl Command for RewindCommand {
+    fn name(&self) -> &str {
+        "rewind"
+    }
+    fn description(&self) -> &str {
+        "Go back to before an earlier prompt (/rewind [n | commit] | /rewind fork <n>)"
+    }
+    async fn execute(&self, args: &str, session: &mut Session, frontend: &mut dyn Frontend) {
+        if args.is_empty() {
+            let turns = match session.user_turns().await {
+                Ok(turns) => turns,
+                Err(e) => {
+                    frontend.show_error(&format!("/rewind failed: {e}")).await;
+                    return;
+                }
+            };
+            if turns.is_empty() {
+                frontend
+                    .show_system("Nothing to rewind to. Start a conversation first.")
+                    .await;
+                return;
+            }
+            // Prefer the frontend's native picker (TUI popup); fall
+            // back to a text list if the frontend can't render one.
+            if frontend.open_rewind_selector(&turns).await {
+                return;
+            }
+            let mut out = String::from("Prompts in conversation:\n");
+            for (i, turn) in turns.iter().enumerate() {
+                let preview: String = turn
+                    .prompt
+                    .chars()
+                    .take(60)
+                    .collect::<String>()
+                    .replace('\n', " ");
+                out.push_str(&format!("  {}: {}\n", i + 1, preview));
+            }
+            out.push_str(
+                "\nUse /rewind <n> to go back to before prompt n, or /rewind fork <n> \
+                 to continue from there on a new branch.",
+            );
+            frontend.show_system(&out).await;
+            return;
+        }
+
+        let (fork, target) = match args.strip_prefix("fork") {
+            Some(rest) if rest.is_empty() || rest.starts_with(' ') => (true, rest.trim()),
+            _ => (false, args),
+        };
+        let to = if let Ok(n) = target.parse::<usize>() {
+            RewindTarget::Turn(n)
+        } else if let Some(hash) = ObjectHash::from_hex(target) {
+            RewindTarget::Commit(hash)
+        } else {
+            frontend
+                .show_system(&format!(
+                    "Invalid turn '{}'. Use a prompt number, a commit hash, or no argument \
+                     to list prompts.",
+                    target
+                ))
+                .await;
+            return;
+        };
+        session.rewind(to, fork, frontend).await;
+    }
+}
@@ -0,0 +1,74 @@
+//! /timers command - list and cancel timers agents have scheduled
+
+use async_trait::async_trait;
+
+use super::Command;
+use crate::driver::{Frontend, Session};
+
+pub struct TimersCommand;
+
+#[async_trait]
+impl Command for TimersCommand {
+    fn name(&self) -> &str {
+        "timers"
+    }
+    fn description(&self) -> &str {
+        "List scheduled timers (/timers [cancel <id>])"
+    }
+    async fn execute(&self, args: &str, session: &mut Session, frontend: &mut dyn Frontend) {
+        let mut parts = args.split_whitespace();
+        match parts.next().unwrap_or("list") {
+            "list" => list(session, frontend).await,
+            "cancel" => {
+                let Some(id) = parts.next() else {
+                    frontend
+                        .show_system("Usage: /timers cancel <timer id>")
+                        .await;
+                    return;
+                };
+                match session.manager().cancel_timer(id) {
+                    Some(t) => {
+                        frontend
+                            .show_system(&format!("Cancelled {}: {}", t.id, t.prompt))
+                            .await
+                    }
+                    None => frontend.show_error(&format!("No timer '{id}'")).await,
+                }
+            }
+            other => {
+                frontend
+                    .show_system(&format!(
+                        "Unknown subcommand: '{other}'\nValid: list, cancel"
+                    ))
+                    .await
+            }
+        }
+    }
+}
+
+async fn list(session: &Session, frontend: &mut dyn Frontend) {
+    let timers = session.manager().timers();
+    if timers.is_empty() {
+        frontend.show_system("No timers scheduled.").await;
+        return;
+    }
+    let mut out = String::from("Scheduled timers:\n");
+    for t in &timers {
+        let who = session
+            .manager()
+            .find_agent(&t.agent_id)
+            .map_or_else(|| t.agent_id.clone(), |l| l.description);
+        let repeat = t
+            .every_secs
+            .map(|s| format!(", every {s}s"))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "  {:<4} {}{repeat}  {who}: {}\n",
+            t.id,
+            t.next_fire.with_timezone(&chrono::Local).format("%H:%M:%S"),
+            t.prompt
+        ));
+    }
+    out.push_str("\nCancel with /timers cancel <id>.");
+    frontend.show_system(&out).await;
+}
@@ -0,0 +1,208 @@
+//! /worktree command - review and merge back isolated subagents' changes
+
+use async_trait::async_trait;
+use tau_agent::{MergeOutcome, MergeStrategy, SpawnOpts};
+
+use super::Command;
+use crate::driver::{Frontend, Session};
+
+/// Longest patch shown by `/worktree diff` before truncating.
+const MAX_PATCH_CHARS: usize = 20_000;
+
+/// Spec used for `/worktree resolve`.
+const RESOLVER_SPEC: &str = "general-purpose";
+
+pub struct WorktreeCommand;
+
+#[async_trait]
+impl Command for WorktreeCommand {
+    fn name(&self) -> &str {
+        "worktree"
+    }
+    fn aliases(&self) -> &[&str] {
+        &["wt"]
+    }
+    fn description(&self) -> &str {
+        "Review subagent worktrees (/worktree [diff|merge|pick|discard|resolve <id>])"
+    }
+    async fn execute(&self, args: &str, session: &mut Session, frontend: &mut dyn Frontend) {
+        let mut parts = args.split_whitespace();
+        let sub = parts.next().unwrap_or("list");
+        if sub == "list" {
+            list(session, frontend).await;
+            return;
+        }
+        let Some(prefix) = parts.next() else {
+            frontend
+                .show_system(&format!("Usage: /worktree {sub} <agent id>"))
+                .await;
+            return;
+        };
+        let id = match resolve_id(session, prefix) {
+            Ok(id) => id,
+            Err(msg) => {
+                frontend.show_system(&msg).await;
+                return;
+            }
+        };
+        match sub {
+            "diff" => diff(session, frontend, &id).await,
+            "merge" => merge(session, frontend, &id, MergeStrategy::Merge).await,
+            "pick" => merge(session, frontend, &id, MergeStrategy::CherryPick).await,
+            "discard" => match session.manager().discard_worktree(&id).await {
+                Ok(()) => frontend.show_system(&format!("Discarded worktree of {id}")).await,
+                Err(e) => frontend.show_error(&e.to_string()).await,
+            },
+            "resolve" => resolve(session, frontend, &id).await,
+            other => {
+                frontend
+                    .show_system(&format!(
+                        "Unknown subcommand: '{other}'\nValid: list, diff, merge, pick, discard, resolve"
+                    ))
+                    .await
+            }
+        }
+    }
+}
+
+async fn list(session: &Session, frontend: &mut dyn Frontend) {
+    let kept = session.manager().worktrees();
+    if kept.is_empty() {
+        frontend
+            .show_system("No subagent worktrees awaiting review.")
+            .await;
+        return;
+    }
+    let mut out = String::from("Subagent worktrees awaiting review:\n");
+    for (id, info) in &kept {
+        out.push_str(&format!(
+            "  {}  {}  {}\n",
+            short(id),
+            info.branch,
+            info.path.display()
+        ));
+    }
+    out.push_str("\nUse /worktree diff <id>, then merge, pick, discard, or resolve.");
+    frontend.show_system(&out).await;
+}
+
+async fn diff(session: &Session, frontend: &mut dyn Frontend, id: &str) {
+    let diff = match session.manager().worktree_diff(id).await {
+        Ok(d) => d,
+        Err(e) => {
+            frontend.show_error(&e.to_string()).await;
+            return;
+        }
+    };
+    if diff.files.is_empty() {
+        frontend
+            .show_system(&format!("{} has no changes to merge.", short(id)))
+            .await;
+        return;
+    }
+    let mut out = format!("Changes in {} ({} files):\n", short(id), diff.files.len());
+    for file in &diff.files {
+        out.push_str(&format!("  {:<5} {}\n", file.status, file.path));
+    }
+    out.push('\n');
+    out.push_str(&crate::utils::truncate_chars(&diff.patch, MAX_PATCH_CHARS));
+    frontend.show_system(&out).await;
+}
+
+async fn merge(session: &Session, frontend: &mut dyn Frontend, id: &str, strategy: MergeStrategy) {
+    match session.manager().merge_worktree(id, strategy).await {
+        Ok(MergeOutcome::Merged { head }) => {
+            frontend
+                .show_system(&format!(
+                    "Merged {} into the working tree (HEAD {}).",
+                    short(id),
+                    short(&head)
+                ))
+                .await
+        }
+        Ok(MergeOutcome::NothingToMerge) => {
+            frontend
+                .show_system(&format!(
+                    "{} had nothing to merge; worktree removed.",
+                    short(id)
+                ))
+                .await
+        }
+        Ok(MergeOutcome::Conflicted { conflicts }) => {
+            frontend
+                .show_system(&format!(
+                    "Merging {} conflicts in:\n  {}\nNothing was changed. Use /worktree resolve {} to hand the conflicts to a subagent, or /worktree discard {}.",
+                    short(id),
+                    conflicts.join("\n  "),
+                    short(id),
+                    short(id)
+                ))
+                .await
+        }
+        Err(e) => frontend.show_error(&e.to_string()).await,
+    }
+}
+
+async fn resolve(session: &mut Session, frontend: &mut dyn Frontend, id: &str) {
+    let Some(spec) = session.resolve_spec(RESOLVER_SPEC) else {
+        frontend
+            .show_error(&format!(
+                "Conflict resolver unavailable: '{RESOLVER_SPEC}' spec not registered."
+            ))
+            .await;
+        return;
+    };
+    frontend
+        .show_system(&format!(
+            "Resolving conflicts of {} in a new worktree...",
+            short(id)
+        ))
+        .await;
+    let opts = SpawnOpts {
+        description: format!("Resolve conflicts: {}", short(id)),
+        spec_name: Some(RESOLVER_SPEC.into()),
+        ..Default::default()
+    };
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let manager = std::sync::Arc::clone(session.manager());
+    let work = manager.resolve_worktree_conflicts(id, spec, opts, cancel.clone());
+    match session.run_cancellable(frontend, &cancel, work).await {
+        Ok(result) if result.worktree_branch.is_some() => {
+            frontend
+                .show_system(&format!(
+                    "Resolver {} finished. Review it with /worktree diff {}.",
+                    short(&result.agent_id),
+                    short(&result.agent_id)
+                ))
+                .await
+        }
+        Ok(_) => {
+            frontend
+                .show_system("Resolver finished without leaving changes.")
+                .await
+        }
+        Err(e) => frontend.show_error(&format!("Resolver failed: {e}")).await,
+    }
+}
+
+/// Resolve a unique id prefix against the kept worktrees.
+fn resolve_id(session: &Session, prefix: &str) -> Result<String, String> {
+    let matches: Vec<String> = session
+        .manager()
+        .worktrees()
+        .into_iter()
+        .map(|(id, _)| id)
+        .filter(|id| id.starts_with(prefix))
+        .collect();
+    match matches.as_slice() {
+        [id] => Ok(id.clone()),
+        [] => Err(format!("No subagent worktree matches '{prefix}'.")),
+        _ => Err(format!(
+            "'{prefix}' matches several worktrees; use more of the id."
+        )),
+    }
+}
+
+fn short(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
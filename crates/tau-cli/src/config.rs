@@ -1,10 +1,17 @@
 //! Configuration file support
 
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use tau_agent::{AgentConfig, CompactionConfig, DequeueMode};
+use tau_agent::{
+    AgentConfig, ApprovalPolicy, ArgMatch, ArgPattern, CompactionConfig, DequeueMode, RulePolicy,
+    ToolRule,
+};
 use tau_ai::{Model, ReasoningLevel};
 
 /// Configuration for tau
@@ -15,6 +22,9 @@ pub struct Config {
     pub model: Option<String>,
     /// Default provider
     pub provider: Option<String>,
+    /// Cheaper model for compaction summaries (same provider as
+    /// `model`)
+    pub utility_model: Option<String>,
     /// Default reasoning level
     pub reasoning_level: Option<String>,
     /// Use adaptive thinking (model decides when to think)
@@ -33,6 +43,13 @@ pub struct Config {
     /// Cache settings
     #[serde(default)]
     pub cache: Option<CacheSettings>,
+    /// Trace export settings
+    #[serde(default)]
+    pub telemetry: Option<TelemetrySettings>,
+    /// Tool approval rules, merged with the project's
+    /// `.tau/approval.toml`
+    #[serde(default)]
+    pub approval: Option<ApprovalSettings>,
     /// Enable Anthropic-internal prompt additions (stricter verification,
     /// comment philosophy, faithful reporting, richer communication style)
     pub acolyte_mode: Option<bool>,
@@ -84,6 +101,202 @@ pub struct CacheSettings {
     pub prompt_boundary: Option<String>,
 }
 
+/// Settings for exporting OTLP/JSON traces of each session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    /// Append OTLP/JSON trace requests to this file (one per line)
+    pub otlp_file: Option<String>,
+    /// POST traces to this OTLP/HTTP endpoint (e.g. "http://localhost:4318")
+    pub otlp_endpoint: Option<String>,
+}
+
+/// Tool approval rules. In the user config these live under
+/// `[approval]`; a project's `.tau/approval.toml` holds the same lists
+/// at top level, but only its `ask` and `deny` rules apply. A call
+/// matching a `deny` rule is rejected, then `ask` gates it, then
+/// `allow` auto-approves it; anything else falls back to risk-based
+/// gating.
+///
+/// A `bash` allow rule checks the `command` argument and must match
+/// every simple command in it; a line that chains, pipes, substitutes
+/// or redirects output to a file is never auto-approved by a pattern.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalSettings {
+    pub allow: Vec<ApprovalRule>,
+    pub ask: Vec<ApprovalRule>,
+    pub deny: Vec<ApprovalRule>,
+}
+
+/// One `[[approval.allow]]` / `[[approval.ask]]` / `[[approval.deny]]`
+/// entry. Without a matcher it covers every call of `tool`; otherwise
+/// set exactly one of `contains`, `equals`, `regex`, `glob`, `within`
+/// or `outside`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApprovalRule {
+    /// Tool name (e.g. "bash", "write")
+    pub tool: String,
+    /// Argument to check, as a JSON path (e.g. "command", "path");
+    /// omit to check every string argument
+    pub arg: Option<String>,
+    pub contains: Option<String>,
+    pub equals: Option<String>,
+    pub regex: Option<String>,
+    pub glob: Option<String>,
+    /// Path argument inside this directory (relative to the agent's cwd)
+    pub within: Option<String>,
+    /// Path argument outside this directory
+    pub outside: Option<String>,
+    /// Shown when a deny rule rejects a call
+    pub reason: Option<String>,
+}
+
+impl ApprovalRule {
+    fn to_rule(&self) -> anyhow::Result<ToolRule> {
+        if self.tool.trim().is_empty() {
+            anyhow::bail!("approval rule is missing `tool`");
+        }
+        let mut patterns = Vec::new();
+        if let Some(needle) = &self.contains {
+            patterns.push(ArgPattern::Contains(needle.clone()));
+        }
+        if let Some(value) = &self.equals {
+            patterns.push(ArgPattern::Equals(value.clone()));
+        }
+        if let Some(re) = &self.regex {
+            patterns
+                .push(ArgPattern::regex(re).with_context(|| {
+                    format!("approval rule for '{}': invalid regex", self.tool)
+                })?);
+        }
+        if let Some(glob) = &self.glob {
+            patterns.push(
+                ArgPattern::glob(glob)
+                    .with_context(|| format!("approval rule for '{}': invalid glob", self.tool))?,
+            );
+        }
+        if let Some(dir) = &self.within {
+            patterns.push(ArgPattern::PathWithin(dir.into()));
+        }
+        if let Some(dir) = &self.outside {
+            patterns.push(ArgPattern::Not(Box::new(ArgPattern::PathWithin(
+                dir.into(),
+            ))));
+        }
+        if patterns.len() > 1 {
+            anyhow::bail!(
+                "approval rule for '{}': set only one of contains, equals, regex, glob, within, outside",
+                self.tool
+            );
+        }
+        if patterns.is_empty() && self.arg.is_some() {
+            anyhow::bail!(
+                "approval rule for '{}': `arg` needs a matcher (contains, equals, regex, glob, within or outside)",
+                self.tool
+            );
+        }
+        Ok(ToolRule {
+            tool: self.tool.clone(),
+            matches: patterns
+                .into_iter()
+                .map(|pattern| ArgMatch {
+                    path: self.arg.clone(),
+                    pattern,
+                })
+                .collect(),
+            reason: self.reason.clone(),
+        })
+    }
+}
+
+impl ApprovalSettings {
+    /// Compile into [`ApprovalRules`], failing on the first bad rule.
+    pub fn compile(&self) -> anyhow::Result<ApprovalRules> {
+        let compile = |rules: &[ApprovalRule]| -> anyhow::Result<Vec<ToolRule>> {
+            rules.iter().map(ApprovalRule::to_rule).collect()
+        };
+        Ok(ApprovalRules {
+            allow: compile(&self.allow)?
+                .into_iter()
+                .map(per_bash_command)
+                .collect(),
+            ask: compile(&self.ask)?,
+            deny: compile(&self.deny)?,
+        })
+    }
+}
+
+/// Make a `bash` allow rule hold for each simple command of the
+/// `command` argument rather than the line as a whole, so
+/// `^cargo test` doesn't approve `cargo test && rm -rf ~`.
+fn per_bash_command(mut rule: ToolRule) -> ToolRule {
+    if rule.tool != "bash" {
+        return rule;
+    }
+    for m in &mut rule.matches {
+        m.path.get_or_insert_with(|| "command".into());
+        let each = std::mem::replace(&mut m.pattern, ArgPattern::Contains(String::new()));
+        m.pattern = ArgPattern::EachCommand {
+            each: Box::new(each),
+            split: bash_commands,
+        };
+    }
+    rule
+}
+
+/// The simple commands of a bash line, as their words joined by
+/// spaces, or `None` if the line chains, pipes or substitutes commands,
+/// runs one named by an expansion, or writes output to a file.
+fn bash_commands(line: &str) -> Option<Vec<String>> {
+    let compound = ["&&", "||", ";", "|", "$(", "`", "<(", ">(", "\n"];
+    if compound.iter().any(|op| line.contains(op)) {
+        return None;
+    }
+    let commands = tau_tools::shell::parse(line).ok()?;
+    commands
+        .iter()
+        .map(|c| {
+            let writes = c.writes.iter().any(|w| w != "/dev/null");
+            (!writes && !c.dynamic_name).then(|| c.words.join(" "))
+        })
+        .collect()
+}
+
+/// Compiled approval rules from the user and project config.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalRules {
+    pub allow: Vec<ToolRule>,
+    pub ask: Vec<ToolRule>,
+    pub deny: Vec<ToolRule>,
+}
+
+impl ApprovalRules {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.ask.is_empty() && self.deny.is_empty()
+    }
+
+    /// A [`RulePolicy`] applying these rules before `fallback`.
+    pub fn policy(&self, fallback: Arc<dyn ApprovalPolicy>) -> RulePolicy {
+        RulePolicy {
+            allow: self.allow.clone(),
+            ask: self.ask.clone(),
+            deny: self.deny.clone(),
+            fallback,
+        }
+    }
+
+    fn extend(&mut self, other: ApprovalRules) {
+        self.allow.extend(other.allow);
+        self.ask.extend(other.ask);
+        self.deny.extend(other.deny);
+    }
+}
+
+/// Project approval rules, relative to the working directory.
+pub const PROJECT_APPROVAL_FILE: &str = ".tau/approval.toml";
+
 /// Settings for context compaction
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -94,6 +307,8 @@ pub struct CompactionSettings {
     pub reserve_tokens: Option<u64>,
     /// Keep at least this many tokens of recent messages when compacting
     pub keep_recent_tokens: Option<u64>,
+    /// Elide large old tool outputs before falling back to an LLM summary (default: true)
+    pub prune_tool_outputs: Option<bool>,
 }
 
 /// API key configuration
@@ -207,6 +422,9 @@ impl Config {
                 anyhow::bail!("MCP server '{}': timeout_secs must be >= 1", name);
             }
         }
+        if let Some(ref approval) = self.approval {
+            approval.compile()?;
+        }
         if let Some(ref cache) = self.cache {
             if let Some(ref scope) = cache.scope {
                 match scope.as_str() {
@@ -259,6 +477,7 @@ impl Config {
         let default_config = Config {
             model: Some("claude-sonnet-4-5-20250929".to_string()),
             provider: Some("anthropic".to_string()),
+            utility_model: None,
             reasoning_level: Some("off".to_string()),
             thinking_adaptive: None,
             tui: Some(true),
@@ -266,6 +485,8 @@ impl Config {
             api_keys: ApiKeys::default(),
             compaction: None,
             cache: None,
+            telemetry: None,
+            approval: None,
             acolyte_mode: None,
             mcp_servers: Default::default(),
         };
@@ -274,6 +495,53 @@ impl Config {
         Ok(path)
     }
 
+    /// Where to export traces, if anywhere. A configured `otlp_file`
+    /// wins over an endpoint; `OTEL_EXPORTER_OTLP_ENDPOINT` is the
+    /// fallback when neither is set.
+    pub fn trace_sink(&self) -> Option<tau_agent::TraceSink> {
+        let telemetry = self.telemetry.clone().unwrap_or_default();
+        if let Some(file) = telemetry.otlp_file {
+            return Some(tau_agent::TraceSink::File(PathBuf::from(file)));
+        }
+        telemetry
+            .otlp_endpoint
+            .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+            .filter(|url| !url.trim().is_empty())
+            .map(tau_agent::TraceSink::Http)
+    }
+
+    /// Approval rules from this config plus the `ask` and `deny` rules
+    /// of `<project>/.tau/approval.toml`, if present. The project's
+    /// `allow` rules are dropped: a cloned repository mustn't be able
+    /// to auto-approve tools for whoever runs tau in it.
+    pub fn approval_rules(&self, project: &Path) -> anyhow::Result<ApprovalRules> {
+        let mut rules = match &self.approval {
+            Some(approval) => approval.compile()?,
+            None => ApprovalRules::default(),
+        };
+        let path = project.join(PROJECT_APPROVAL_FILE);
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let mut settings: ApprovalSettings = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            if !settings.allow.is_empty() {
+                tracing::warn!(
+                    "ignoring {} allow rule(s) in {}: only ask and deny rules apply from a project",
+                    settings.allow.len(),
+                    path.display()
+                );
+                settings.allow.clear();
+            }
+            rules.extend(
+                settings
+                    .compile()
+                    .with_context(|| format!("Invalid rule in {}", path.display()))?,
+            );
+        }
+        Ok(rules)
+    }
+
     /// Convert configured MCP servers into transport-agnostic specs
     /// for `tau_tools::mcp::McpManager`, dropping `enabled = false`
     /// entries. `${VAR}` values stay unexpanded — the manager expands
@@ -347,8 +615,14 @@ impl Config {
     }
 
     /// Build the runtime [`AgentConfig`] from this config plus CLI-supplied
-    /// `model` and `reasoning` (which override any config values).
-    pub fn to_agent_config(&self, model: Model, reasoning: ReasoningLevel) -> AgentConfig {
+    /// `model` and `reasoning` (which override any config values) and the
+    /// resolved `utility_model`, if one is configured.
+    pub fn to_agent_config(
+        &self,
+        model: Model,
+        utility_model: Option<Model>,
+        reasoning: ReasoningLevel,
+    ) -> AgentConfig {
         // Start from the runtime default (fraction-scaled across model
         // sizes) and only override what the user explicitly pinned in
         // their TOML. Setting `reserve_tokens` or `keep_recent_tokens`
@@ -368,6 +642,9 @@ impl Config {
                 if let Some(n) = c.keep_recent_tokens {
                     cfg.keep_recent = tau_agent::CompactionThreshold::Tokens(n);
                 }
+                if let Some(prune) = c.prune_tool_outputs {
+                    cfg.prune_tool_outputs = prune;
+                }
                 cfg
             }
             None => CompactionConfig::default(),
@@ -379,6 +656,9 @@ impl Config {
             .compaction(compaction)
             .steering_mode(DequeueMode::All)
             .follow_up_mode(DequeueMode::All);
+        if let Some(utility) = utility_model {
+            builder = builder.utility_model(utility);
+        }
         if let Some(cache) = &self.cache {
             if let Some(scope) = &cache.scope {
                 builder = builder.cache_scope(scope.clone());
@@ -422,6 +702,10 @@ model = "claude-sonnet-4-5-20250929"
 # Default provider (anthropic, openai, google)
 provider = "anthropic"
 
+# Cheaper model for compaction summaries (optional; same provider as
+# `model`)
+# utility_model = "claude-haiku-4-5-20251001"
+
 # Default reasoning level (off, minimal, low, medium, high)
 reasoning_level = "off"
 
@@ -467,12 +751,39 @@ tui = true
 # enabled = true
 # reserve_tokens = 16384
 # keep_recent_tokens = 20000
+# prune_tool_outputs = true  # elide old tool output before summarizing
 
 # Prompt caching settings (optional)
 # [cache]
 # scope = "org"           # "global" (1P only) or "org"
 # ttl = "1h"              # "1h" or "5m" (default: 5m)
 # prompt_boundary = "<!-- DYNAMIC_BOUNDARY -->"
+
+# Trace export (optional). Prompts, turns, model calls, tool runs,
+# approvals and subagents become nested OTLP spans you can load into
+# Jaeger or Tempo. OTEL_EXPORTER_OTLP_ENDPOINT also enables the endpoint.
+# [telemetry]
+# otlp_file = "/tmp/tau-traces.jsonl"
+# otlp_endpoint = "http://localhost:4318"
+
+# Tool approval rules (optional). deny beats ask beats allow; calls no
+# rule matches keep the default risk-based prompting. Each rule names a
+# `tool`, optionally an `arg` (JSON path), and one matcher: contains,
+# equals, regex, glob, within or outside (paths relative to the cwd).
+# A bash allow rule must match every command in the line, and lines
+# that chain, pipe, substitute or redirect to a file never match one.
+# A project's .tau/approval.toml adds [[ask]]/[[deny]] rules; its
+# [[allow]] rules are ignored.
+# [[approval.allow]]
+# tool = "bash"
+# arg = "command"
+# regex = "^cargo (check|test|clippy)( -p [a-z0-9_-]+)?$"
+#
+# [[approval.deny]]
+# tool = "write"
+# arg = "path"
+# outside = "."
+# reason = "stay inside the project"
 "#
 }
 
@@ -553,4 +864,111 @@ headers = { Authorization = "Bearer x" }
         assert!(parse("[mcp_servers.a]\ncommand = \"x\"\nheaders = { Authorization = \"y\" }\n").is_err());
         assert!(parse("[mcp_servers.a]\nurl = \"http://y\"\nenv = { K = \"v\" }\n").is_err());
     }
+
+    #[test]
+    fn telemetry_file_sink_wins_over_endpoint() {
+        let cfg = parse(
+            "[telemetry]\notlp_file = \"/tmp/t.jsonl\"\notlp_endpoint = \"http://localhost:4318\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            cfg.trace_sink(),
+            Some(tau_agent::TraceSink::File(p)) if p == std::path::Path::new("/tmp/t.jsonl")
+        ));
+    }
+
+    #[test]
+    fn approval_rules_compile_and_merge_project_file() {
+        let cfg = parse(
+            r#"
+[[approval.allow]]
+tool = "bash"
+arg = "command"
+regex = "^cargo test"
+
+[[approval.deny]]
+tool = "write"
+arg = "path"
+outside = "src"
+reason = "keep writes in src"
+"#,
+        )
+        .unwrap();
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir(project.path().join(".tau")).unwrap();
+        std::fs::write(
+            project.path().join(PROJECT_APPROVAL_FILE),
+            "[[ask]]\ntool = \"web_fetch\"\n",
+        )
+        .unwrap();
+
+        let rules = cfg.approval_rules(project.path()).unwrap();
+        assert_eq!(
+            (rules.allow.len(), rules.ask.len(), rules.deny.len()),
+            (1, 1, 1)
+        );
+        let policy = rules.policy(Arc::new(tau_agent::DefaultPolicy));
+        let write = serde_json::json!({ "path": "README.md" });
+        assert!(matches!(
+            policy.classify_in("write", &write, tau_agent::ToolRisk::Local, project.path()),
+            tau_agent::ApprovalDecision::Reject(r) if r.ends_with("(keep writes in src)")
+        ));
+
+        // Two matchers, a matcher-less `arg` and a bad regex are all rejected.
+        assert!(
+            parse("[[approval.allow]]\ntool = \"bash\"\ncontains = \"a\"\nglob = \"b\"\n").is_err()
+        );
+        assert!(parse("[[approval.allow]]\ntool = \"bash\"\narg = \"command\"\n").is_err());
+        assert!(parse("[[approval.deny]]\ntool = \"bash\"\nregex = \"(\"\n").is_err());
+    }
+
+    #[test]
+    fn project_allow_rules_are_ignored() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir(project.path().join(".tau")).unwrap();
+        std::fs::write(
+            project.path().join(PROJECT_APPROVAL_FILE),
+            "[[allow]]\ntool = \"bash\"\n\n[[deny]]\ntool = \"web_fetch\"\n",
+        )
+        .unwrap();
+        let rules = Config::default().approval_rules(project.path()).unwrap();
+        assert_eq!(
+            (rules.allow.len(), rules.ask.len(), rules.deny.len()),
+            (0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn bash_allow_rules_match_every_command() {
+        let cfg =
+            parse("[[approval.allow]]\ntool = \"bash\"\nregex = \"^cargo (check|test)( |$)\"\n")
+                .unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let policy = cfg
+            .approval_rules(project.path())
+            .unwrap()
+            .policy(Arc::new(tau_agent::DefaultPolicy));
+        let decide = |command: &str| {
+            let call = serde_json::json!({ "command": command, "description": "cargo test" });
+            policy.classify_in("bash", &call, tau_agent::ToolRisk::Elevated, project.path())
+        };
+        assert!(matches!(
+            decide("cargo test -p tau-cli"),
+            tau_agent::ApprovalDecision::Auto
+        ));
+        for command in [
+            "cargo test && rm -rf ~",
+            "cargo test; rm -rf ~",
+            "cargo test | sh",
+            "cargo test $(rm -rf ~)",
+            "cargo test `rm -rf ~`",
+            "cargo test > ~/.bashrc",
+            "rm -rf ~",
+        ] {
+            assert!(
+                matches!(decide(command), tau_agent::ApprovalDecision::Gate),
+                "{command}"
+            );
+        }
+    }
 }
@@ -81,6 +81,11 @@ pub trait Frontend: Send {
     /// confirmation system message).
     async fn reset_view(&mut self) {}
 
+    /// Render `messages` as the conversation so far, after a
+    /// [`reset_view`](Self::reset_view) that replaced it (e.g.
+    /// `/rewind`). Default: no-op (stdout printed them as they came).
+    async fn show_messages(&mut self, _messages: &[tau_ai::Message]) {}
+
     /// Notify the frontend that the agent's runtime config changed
     /// (typically after `/model` or `/thinking`). TUI frontends should
     /// refresh their status line. Default: no-op.
@@ -95,6 +100,15 @@ pub trait Frontend: Send {
         false
     }
 
+    /// Open a rewind picker over the conversation's user prompts.
+    /// Return `true` if the frontend handled the selection (and will
+    /// emit `/rewind <n>` or `/rewind fork <n>` via its own input
+    /// channel when the user picks), `false` if the caller should fall
+    /// back to a text list. Default: `false`.
+    async fn open_rewind_selector(&mut self, _turns: &[tau_history::UserTurn]) -> bool {
+        false
+    }
+
     /// Drain any pending side-channel action. Polled by the
     /// [`Session`](super::Session) after each command / prompt cycle.
     /// Default: no action.
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use tau_agent::{
-    AgentEvent, AgentHandle, AgentManager, AutoAcceptAll, FleetEvent,
+    AgentEvent, AgentHandle, AgentManager, AutoAcceptAll, FleetEvent, GoalConfig,
     InteractionRequest, SpawnOpts,
 };
 use tau_ai::{Message, Model, Usage};
@@ -12,6 +12,7 @@ use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 
 use crate::commands;
+use crate::config::ApprovalRules;
 use crate::session::SessionManager;
 
 use super::frontend::{Frontend, FrontendAction, SessionStart, UserInput};
@@ -40,6 +41,11 @@ pub struct SessionConfig {
     /// Optional persistence sink for the main conversation. Plan-mode
     /// traffic does not write here.
     pub persistence: Option<SessionManager>,
+    /// Goal mode for main-agent prompts (`tau run --until`, `/goal`).
+    pub goal: Option<GoalConfig>,
+    /// Configured approval rules. Already applied to `handle` and
+    /// `manager`; kept to rebuild the policy when it changes.
+    pub approval_rules: ApprovalRules,
 }
 
 pub struct Session {
@@ -55,6 +61,7 @@ pub struct Session {
     fleet_events: broadcast::Receiver<FleetEvent>,
     available_models: Vec<Model>,
     persistence: Option<SessionManager>,
+    goal: Option<GoalConfig>,
     prev_usage: Usage,
     state: State,
     /// Set by a command (typically `/quit`) to exit the driver loop on
@@ -70,13 +77,37 @@ pub struct Session {
     /// Applied as a [`SessionAllowlistPolicy`] on the root agent and
     /// the manager (so subagents inherit it).
     always_allowed_tools: std::collections::HashSet<String>,
+    approval_rules: ApprovalRules,
 }
 
-/// [`DefaultPolicy`] plus a session allowlist: tools the user approved
-/// with "always allow" skip the gate; everything else keeps the
-/// default risk-based gating.
+/// The configured rules over [`DefaultPolicy`], plus a session
+/// allowlist: tools the user approved with "always allow" skip the
+/// gate. Deny rules still apply to them, and calls an explicit `ask`
+/// rule matches stay gated.
 struct SessionAllowlistPolicy {
     allowed: std::collections::HashSet<String>,
+    ask: Vec<tau_agent::ToolRule>,
+    fallback: Arc<dyn tau_agent::ApprovalPolicy>,
+}
+
+impl SessionAllowlistPolicy {
+    fn allow_gated(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Value,
+        cwd: &std::path::Path,
+        decision: tau_agent::ApprovalDecision,
+    ) -> tau_agent::ApprovalDecision {
+        match decision {
+            tau_agent::ApprovalDecision::Gate
+                if self.allowed.contains(tool)
+                    && !self.ask.iter().any(|r| r.matches_in(tool, arguments, cwd)) =>
+            {
+                tau_agent::ApprovalDecision::Auto
+            }
+            other => other,
+        }
+    }
 }
 
 impl tau_agent::ApprovalPolicy for SessionAllowlistPolicy {
@@ -86,10 +117,28 @@ impl tau_agent::ApprovalPolicy for SessionAllowlistPolicy {
         arguments: &serde_json::Value,
         risk: tau_agent::ToolRisk,
     ) -> tau_agent::ApprovalDecision {
-        if self.allowed.contains(tool) {
-            return tau_agent::ApprovalDecision::Auto;
-        }
-        tau_agent::ApprovalPolicy::classify(&tau_agent::DefaultPolicy, tool, arguments, risk)
+        let cwd = std::env::current_dir().unwrap_or_default();
+        self.allow_gated(
+            tool,
+            arguments,
+            &cwd,
+            self.fallback.classify(tool, arguments, risk),
+        )
+    }
+
+    fn classify_in(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Value,
+        risk: tau_agent::ToolRisk,
+        cwd: &std::path::Path,
+    ) -> tau_agent::ApprovalDecision {
+        self.allow_gated(
+            tool,
+            arguments,
+            cwd,
+            self.fallback.classify_in(tool, arguments, risk, cwd),
+        )
     }
 }
 
@@ -107,12 +156,14 @@ impl Session {
             fleet_events,
             available_models: cfg.available_models,
             persistence: cfg.persistence,
+            goal: cfg.goal,
             prev_usage: Usage::default(),
             state: State::Idle,
             exit_requested: false,
             warned_persistence: false,
             had_agent_error: false,
             always_allowed_tools: std::collections::HashSet::new(),
+            approval_rules: cfg.approval_rules,
         }
     }
 
@@ -154,6 +205,26 @@ impl Session {
             .unwrap_or_default()
     }
 
+    /// Active goal-mode config, if any. Used by `/goal`.
+    pub(crate) fn goal(&self) -> Option<&GoalConfig> {
+        self.goal.as_ref()
+    }
+
+    pub(crate) fn set_goal(&mut self, goal: Option<GoalConfig>) {
+        self.goal = goal;
+    }
+
+    /// The fleet manager. Used by `/worktree` to review subagent
+    /// worktrees.
+    pub(crate) fn manager(&self) -> &Arc<AgentManager> {
+        &self.manager
+    }
+
+    /// Look up a registered subagent spec by name.
+    pub(crate) fn resolve_spec(&self, name: &str) -> Option<tau_agent::AgentSpec> {
+        (self.spec_resolver)(name, 0)
+    }
+
     /// Request the driver loop to exit after the current command.
     pub(crate) fn request_exit(&mut self) {
         self.exit_requested = true;
@@ -200,9 +271,12 @@ impl Session {
     pub async fn drive(&mut self, frontend: &mut dyn Frontend) -> anyhow::Result<()> {
         // If the frontend can't render approval prompts, swap the
         // policy on both the root agent and the manager so subagents
-        // inherit it.
+        // inherit it. Deny and allow rules still apply; there is no
+        // one to ask.
         if !frontend.can_render_approval() {
-            let auto = Arc::new(AutoAcceptAll);
+            let mut policy = self.approval_rules.policy(Arc::new(AutoAcceptAll));
+            policy.ask.clear();
+            let auto: Arc<dyn tau_agent::ApprovalPolicy> = Arc::new(policy);
             self.handle.set_approval_policy(auto.clone()).await?;
             self.manager.set_default_approval_policy(auto);
         }
@@ -241,7 +315,14 @@ impl Session {
                     // events while we're sitting idle between prompts;
                     // render them so the agent tree stays current.
                     match fleet_ev {
-                        Ok(event) => frontend.render_fleet_event(event).await,
+                        Ok(event) => {
+                            let due = self.due_timer(&event);
+                            frontend.render_fleet_event(event).await;
+                            if let Some(message) = due {
+                                self.submit_prompt(&message, frontend).await?;
+                                self.drain_frontend_action(frontend).await?;
+                            }
+                        }
                         Err(RecvError::Closed) => {}
                         Err(RecvError::Lagged(n)) => {
                             tracing::warn!(dropped = n, "fleet event stream lagged (idle)");
@@ -279,6 +360,63 @@ impl Session {
         Ok(())
     }
 
+    /// The prompt of a timer the fleet could not deliver, when it
+    /// belongs to the main agent and is ours to run. Undelivered timers
+    /// of any other agent (a planner or an agent that has left the
+    /// fleet) are dropped.
+    fn due_timer(&self, event: &FleetEvent) -> Option<String> {
+        let FleetEvent::TimerFired {
+            timer_id,
+            agent_id,
+            message,
+            delivered: false,
+        } = event
+        else {
+            return None;
+        };
+        if self.handle.agent_id() == Some(agent_id.as_str()) {
+            return Some(message.clone());
+        }
+        tracing::info!(timer_id, agent_id, "dropping timer for an agent that is gone");
+        None
+    }
+
+    /// Drive `work` — a command's long-running fleet call given
+    /// `cancel` — while still serving the frontend: fleet events are
+    /// rendered, subagent approvals answered, and an abort (or quit)
+    /// cancels `cancel`. Returns `work`'s output either way.
+    pub(crate) async fn run_cancellable<T>(
+        &mut self,
+        frontend: &mut dyn Frontend,
+        cancel: &tokio_util::sync::CancellationToken,
+        work: impl std::future::Future<Output = T>,
+    ) -> T {
+        let mut work = std::pin::pin!(work);
+        loop {
+            tokio::select! {
+                out = &mut work => return out,
+                fleet_ev = self.fleet_events.recv() => match fleet_ev {
+                    Ok(event) => frontend.render_fleet_event(event).await,
+                    Err(RecvError::Closed) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(dropped = n, "fleet event stream lagged");
+                    }
+                },
+                Some(req) = self.interaction_rx.recv() => {
+                    frontend.handle_interaction(req).await;
+                }
+                tick_input = frontend.tick() => match tick_input {
+                    Some(UserInput::Abort) => cancel.cancel(),
+                    Some(UserInput::Quit) => {
+                        cancel.cancel();
+                        self.exit_requested = true;
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
     /// The handle currently receiving prompts: the plan agent while
     /// it's actively drafting, otherwise the main agent.
     fn effective_handle(&self) -> &AgentHandle {
@@ -325,11 +463,27 @@ impl Session {
         let summary_before = handle.state().await.and_then(|s| s.previous_summary);
 
         // Fire-and-await the prompt. The actor will start producing
-        // events; we drain them in this same task via select!.
+        // events; we drain them in this same task via select!. Goal
+        // mode applies to the main agent only, never the planner.
         let prompt_owned = prompt.to_string();
         let handle_for_task = handle.clone();
-        let mut prompt_task =
-            tokio::spawn(async move { handle_for_task.prompt_and_wait(&prompt_owned).await });
+        let goal = self
+            .goal
+            .clone()
+            .filter(|_| matches!(self.state, State::Idle));
+        let goal_mode = goal.is_some();
+        let mut prompt_task = tokio::spawn(async move {
+            match goal {
+                Some(goal) => handle_for_task
+                    .prompt_until(&prompt_owned, &goal)
+                    .await
+                    .map(Some),
+                None => handle_for_task
+                    .prompt_and_wait(&prompt_owned)
+                    .await
+                    .map(|()| None),
+            }
+        });
 
         // Cumulative-text tracker for MessageEnd: not needed — frontend
         // handles its own delta state via render_event.
@@ -338,7 +492,7 @@ impl Session {
         // event *before* `prompt_task` is polled to completion, so the
         // result is also collected after the loop — otherwise a prompt
         // error is silently dropped (and `tau run` exits 0 on failure).
-        let mut prompt_res: Option<Result<(), tau_agent::Error>> = None;
+        let mut prompt_res: Option<Result<Option<tau_agent::GoalOutcome>, tau_agent::Error>> = None;
         loop {
             // No `biased;`: fair polling so frontend.tick() (and the
             // input it carries) doesn't starve when agent events stream
@@ -352,7 +506,11 @@ impl Session {
                             total_turns: 0,
                             interrupted,
                         }).await;
-                        break;
+                        // Goal mode runs several rounds; only the
+                        // task's completion ends it.
+                        if !goal_mode {
+                            break;
+                        }
                     }
                     Ok(event) => frontend.render_event(event).await,
                     Err(RecvError::Closed) => break,
@@ -361,7 +519,12 @@ impl Session {
                     }
                 },
                 fleet_ev = self.fleet_events.recv() => match fleet_ev {
-                    Ok(event) => frontend.render_fleet_event(event).await,
+                    Ok(event) => {
+                        if let Some(message) = self.due_timer(&event) {
+                            let _ = handle.try_follow_up(tau_ai::Message::user(&message));
+                        }
+                        frontend.render_fleet_event(event).await;
+                    }
                     Err(RecvError::Closed) => {}
                     Err(RecvError::Lagged(n)) => {
                         tracing::warn!(dropped = n, "fleet event stream lagged");
@@ -388,7 +551,7 @@ impl Session {
                 res = &mut prompt_task => {
                     // Prompt completed but we may still have events
                     // queued. Drain them non-blockingly.
-                    prompt_res = Some(res.unwrap_or(Ok(())));
+                    prompt_res = Some(res.unwrap_or(Ok(None)));
                     while let Ok(event) = events.try_recv() {
                         let is_end = matches!(event, AgentEvent::AgentEnd { .. });
                         if let AgentEvent::AgentEnd { total_usage: u, .. } = &event {
@@ -413,15 +576,21 @@ impl Session {
             Some(r) => r,
             None => tokio::time::timeout(std::time::Duration::from_secs(2), &mut prompt_task)
                 .await
-                .map(|join| join.unwrap_or(Ok(())))
+                .map(|join| join.unwrap_or(Ok(None)))
                 .unwrap_or_else(|_elapsed| {
                     prompt_task.abort();
-                    Ok(())
+                    Ok(None)
                 }),
         };
-        if let Err(e) = res {
-            self.had_agent_error = true;
-            frontend.show_error(&format!("{}", e)).await;
+        match res {
+            Err(e) => {
+                self.had_agent_error = true;
+                frontend.show_error(&format!("{}", e)).await;
+            }
+            // The outcome itself was rendered from `GoalEnd`; a goal
+            // that never passed still fails `tau run`.
+            Ok(Some(outcome)) if !outcome.passed() => self.had_agent_error = true,
+            Ok(_) => {}
         }
 
         frontend.render_turn_end(&total_usage, &model).await;
@@ -524,6 +693,10 @@ impl Session {
                     self.always_allowed_tools.insert(tool_name.clone());
                     let policy = Arc::new(SessionAllowlistPolicy {
                         allowed: self.always_allowed_tools.clone(),
+                        ask: self.approval_rules.ask.clone(),
+                        fallback: Arc::new(
+                            self.approval_rules.policy(Arc::new(tau_agent::DefaultPolicy)),
+                        ),
                     });
                     if self.handle.set_approval_policy(policy.clone()).await.is_ok() {
                         self.manager.set_default_approval_policy(policy);
@@ -584,6 +757,17 @@ impl Session {
                 }
                 self.handle = new_handle;
                 self.prev_usage = Usage::default();
+                // The old log resumes from its history ref, which ends
+                // before the clear; log what follows as a new session.
+                if self.persistence.is_some() {
+                    let model_id = self
+                        .handle
+                        .config()
+                        .await
+                        .map(|c| c.model().id.clone())
+                        .unwrap_or_default();
+                    self.persistence = SessionManager::new(&model_id).ok();
+                }
                 frontend.reset_view().await;
                 frontend.show_system("Cleared conversation.").await;
             }
@@ -594,6 +778,11 @@ impl Session {
     pub(crate) async fn compact(&mut self, frontend: &mut dyn Frontend) {
         frontend.show_system("Compacting context...").await;
         let handle = self.effective_handle().clone();
+        // Only the main agent commits to the session's history ref.
+        let head_before = match (&self.state, &self.persistence) {
+            (State::Idle, Some(persist)) => crate::history::session_head(&persist.history_ref()),
+            _ => None,
+        };
         match handle.compact(None).await {
             Ok(rx) => match rx.await {
                 Ok(r) if r.result.is_ok() => {
@@ -615,12 +804,24 @@ impl Session {
                     if let Some(e) = persist_err {
                         self.report_persist_error(frontend, &e).await;
                     }
-                    frontend
-                        .show_system(&format!(
-                            "Context compacted. {} messages remaining.",
-                            msgs.len()
-                        ))
-                        .await;
+                    let mut note = format!("Context compacted. {} messages remaining.", msgs.len());
+                    if let (Some(before), Some(persist), Some(config)) =
+                        (head_before, &self.persistence, handle.config().await)
+                        && let Some(p) = crate::history::recache_prediction(
+                            &persist.history_ref(),
+                            before,
+                            config.model(),
+                        )
+                    {
+                        note.push_str(&format!(
+                            "\nThe next request re-caches ~{} tokens (${:.4}); ~{} cached tokens \
+                             were dropped.",
+                            crate::utils::format_tokens(p.cache_write_tokens),
+                            p.recache_cost(),
+                            crate::utils::format_tokens(p.invalidated_tokens)
+                        ));
+                    }
+                    frontend.show_system(&note).await;
                 }
                 _ => frontend.show_error("Compaction failed.").await,
             },
@@ -821,15 +1022,17 @@ impl Session {
                 return;
             }
         };
-        let new_session = match crate::session::branch::branch_from(messages, index, &model_id) {
-            Ok(s) => s,
-            Err(e) => {
-                frontend
-                    .show_error(&format!("Failed to create branch: {}", e))
-                    .await;
-                return;
-            }
-        };
+        let id = uuid::Uuid::new_v4().to_string();
+        let new_session =
+            match crate::session::branch::branch_from(id, messages, index, &model_id) {
+                Ok(s) => s,
+                Err(e) => {
+                    frontend
+                        .show_error(&format!("Failed to create branch: {}", e))
+                        .await;
+                    return;
+                }
+            };
         let msg_count = index.map(|i| i + 1).unwrap_or(0);
         frontend
             .show_system(&format!(
@@ -883,5 +1086,258 @@ impl Session {
             Err(e) => frontend.show_error(&format!("/branch failed: {e}")).await,
         }
     }
+
+    /// The main conversation's user prompts, oldest first. Used by
+    /// `/rewind`.
+    pub(crate) async fn user_turns(&self) -> tau_agent::Result<Vec<tau_history::UserTurn>> {
+        self.handle.user_turns().await
+    }
+
+    /// Move the main conversation back to `to`, in place or (`fork`)
+    /// onto a new branch. A saved session continues as a new one (see
+    /// [`rewind_saved`]); the session it leaves keeps its log and
+    /// history ref, so resuming it still finds the abandoned line.
+    pub(crate) async fn rewind(
+        &mut self,
+        to: tau_agent::RewindTarget,
+        fork: bool,
+        frontend: &mut dyn Frontend,
+    ) {
+        if self.is_plan_mode() {
+            frontend
+                .show_system("/rewind is unavailable in plan mode. Use /plan exit first.")
+                .await;
+            return;
+        }
+        // Name the prompt being undone while it's still in the history.
+        let prompt = match to {
+            tau_agent::RewindTarget::Turn(n) => self
+                .user_turns()
+                .await
+                .ok()
+                .and_then(|turns| turns.get(n.wrapping_sub(1)).map(|t| t.prompt.clone())),
+            tau_agent::RewindTarget::Commit(_) => None,
+        };
+        let result = match &self.persistence {
+            Some(_) => rewind_saved(&self.handle, to)
+                .await
+                .map(|(r, saved)| (r, Some(saved))),
+            None => {
+                let rewound = if fork {
+                    self.handle.fork_at(to).await
+                } else {
+                    self.handle.rewind(to).await
+                };
+                rewound.map(|r| (r, None)).map_err(Into::into)
+            }
+        };
+        let (rewound, saved) = match result {
+            Ok(r) => r,
+            Err(e) => {
+                frontend.show_error(&format!("/rewind failed: {e}")).await;
+                return;
+            }
+        };
+        match saved {
+            Some(Ok(new_session)) => self.persistence = Some(new_session),
+            Some(Err(e)) => {
+                // Appending to the old log would replay the abandoned
+                // turns on resume; stop logging instead.
+                self.persistence = None;
+                frontend
+                    .show_error(&format!(
+                        "Failed to start a new session log: {e}. This session will no \
+                         longer be saved."
+                    ))
+                    .await;
+            }
+            None => {}
+        }
+
+        let messages = self.handle.messages().await.unwrap_or_default();
+
+        let point = match to {
+            tau_agent::RewindTarget::Turn(n) => format!("before prompt {n}"),
+            tau_agent::RewindTarget::Commit(hash) => format!("commit {hash}"),
+        };
+        frontend.reset_view().await;
+        frontend.show_messages(&messages).await;
+        let mut note = format!(
+            "{} to {point} ({} message(s)).",
+            if fork { "Forked" } else { "Rewound" },
+            rewound.messages
+        );
+        if let Some(prompt) = prompt {
+            let preview = crate::utils::truncate_chars(&prompt.replace('\n', " "), 60);
+            note.push_str(&format!("\nUndone prompt: {preview}"));
+        }
+        if let Some(abandoned) = rewound.abandoned {
+            note.push_str(&format!(
+                "\nThe previous conversation is kept; /rewind {abandoned} returns to it."
+            ));
+        }
+        frontend.show_system(&note).await;
+    }
+}
+
+/// Carry a saved session's `/rewind` onto a new session: its history
+/// ref starts at the rewind point, the agent moves onto it, and its
+/// log is seeded with the kept messages. The old session's ref and log
+/// stay where they were. The new log's outcome comes back separately,
+/// since the agent has moved on either way.
+async fn rewind_saved(
+    handle: &AgentHandle,
+    to: tau_agent::RewindTarget,
+) -> anyhow::Result<(tau_agent::Rewound, std::io::Result<SessionManager>)> {
+    let point = match to {
+        tau_agent::RewindTarget::Commit(hash) => Some(hash),
+        tau_agent::RewindTarget::Turn(n) => {
+            let turns = handle.user_turns().await?;
+            match n.checked_sub(1).and_then(|i| turns.get(i)) {
+                Some(turn) => turn.before,
+                None => {
+                    return Err(tau_agent::Error::NoSuchTurn {
+                        turn: n,
+                        turns: turns.len(),
+                    }
+                    .into());
+                }
+            }
+        }
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let history_ref = SessionManager::history_ref_for(&id);
+    let branch = crate::history::fork_session_branch(&history_ref, point)?;
+    let rewound = match handle.switch_history(branch).await {
+        Ok(r) => r,
+        Err(e) => {
+            crate::history::drop_session_branch(&history_ref);
+            return Err(e.into());
+        }
+    };
+
+    let messages = handle.messages().await.unwrap_or_default();
+    let model_id = handle
+        .config()
+        .await
+        .map(|c| c.model().id.clone())
+        .unwrap_or_default();
+    let session = crate::session::branch::branch_from(
+        id,
+        &messages,
+        messages.len().checked_sub(1),
+        &model_id,
+    );
+    Ok((rewound, session))
 }
 
+#[cfg(test)]
+mod tests {
+    use tau_agent::AgentBuilder;
+    use tau_agent::test_utils::{TextTransport, test_config};
+
+    use super::*;
+
+    /// An agent on `session`'s history, the way `tau` starts one.
+    async fn agent(session: &SessionManager, seed: Vec<Message>) -> AgentHandle {
+        let mut builder = AgentBuilder::new(test_config(), TextTransport::create("ok"));
+        builder.seed(tau_agent::AgentSeed::Messages {
+            messages: seed,
+            previous_summary: None,
+        });
+        builder.set_history(crate::history::session_branch(&session.history_ref()).unwrap());
+        builder.spawn().await.unwrap()
+    }
+
+    async fn prompt(handle: &AgentHandle, session: &mut SessionManager, text: &str) {
+        let before = handle.messages().await.unwrap().len();
+        handle.prompt_and_wait(text).await.unwrap();
+        for message in &handle.messages().await.unwrap()[before..] {
+            session.append_message(message).unwrap();
+        }
+    }
+
+    /// What `tau --resume <id>` comes back to: its messages and the
+    /// prompts `/rewind` lists.
+    async fn resume(id: &str) -> (Vec<String>, Vec<String>) {
+        let (session, messages, _) = SessionManager::load(id).unwrap();
+        let handle = agent(&session, messages).await;
+        let messages = handle.messages().await.unwrap();
+        let turns = handle.user_turns().await.unwrap();
+        (
+            messages.iter().map(|m| m.text()).collect(),
+            turns.into_iter().map(|t| t.prompt).collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn rewound_session_resumes_apart_from_the_one_it_left() {
+        crate::session::store::tests::scratch_data_dir();
+        let mut old = SessionManager::new("test-model").unwrap();
+        let handle = agent(&old, Vec::new()).await;
+        for text in ["one", "two", "three"] {
+            prompt(&handle, &mut old, text).await;
+        }
+
+        let (rewound, new) = rewind_saved(&handle, tau_agent::RewindTarget::Turn(2))
+            .await
+            .unwrap();
+        let mut new = new.unwrap();
+        assert_eq!(rewound.messages, 2);
+        prompt(&handle, &mut new, "two, differently").await;
+
+        let (messages, turns) = resume(old.id()).await;
+        assert_eq!(messages, ["one", "ok", "two", "ok", "three", "ok"]);
+        assert_eq!(turns, ["one", "two", "three"]);
+
+        let (messages, turns) = resume(new.id()).await;
+        assert_eq!(messages, ["one", "ok", "two, differently", "ok"]);
+        assert_eq!(turns, ["one", "two, differently"]);
+    }
+
+    /// Aborts whatever is running on its first tick.
+    struct AbortingFrontend;
+
+    #[async_trait::async_trait]
+    impl Frontend for AbortingFrontend {
+        async fn next_input(&mut self) -> Option<UserInput> {
+            None
+        }
+        async fn render_event(&mut self, _event: AgentEvent) {}
+        async fn show_system(&mut self, _text: &str) {}
+        async fn show_error(&mut self, _text: &str) {}
+        async fn handle_interaction(&mut self, _req: InteractionRequest) {}
+        async fn tick(&mut self) -> Option<UserInput> {
+            Some(UserInput::Abort)
+        }
+    }
+
+    #[tokio::test]
+    async fn abort_cancels_a_running_command() {
+        let handle = AgentBuilder::new(test_config(), TextTransport::create("ok"))
+            .spawn()
+            .await
+            .unwrap();
+        let transport = TextTransport::create("ok");
+        let manager = Arc::new(AgentManager::new(test_config(), transport, 4));
+        let (_interaction_tx, interaction_rx) = mpsc::channel(1);
+        let mut session = Session::new(SessionConfig {
+            handle,
+            manager,
+            spec_resolver: Arc::new(|_, _| None),
+            interaction_rx,
+            available_models: Vec::new(),
+            persistence: None,
+            goal: None,
+            approval_rules: ApprovalRules::default(),
+        });
+
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let work = cancel.clone().cancelled_owned();
+        session
+            .run_cancellable(&mut AbortingFrontend, &cancel, work)
+            .await;
+        assert!(cancel.is_cancelled());
+    }
+}
@@ -199,6 +199,25 @@ impl Frontend for StdoutFrontend {
             } => {
                 println!("[Compacted: ~{} -> ~{} tokens]", tokens_before, tokens_after);
             }
+            AgentEvent::FileModifiedExternally { path, .. } => {
+                println!("[{} changed outside the agent]", path.display());
+            }
+            AgentEvent::GoalCheck {
+                iteration,
+                max_iterations,
+                passed,
+                exit_code,
+                ..
+            } => match (passed, exit_code) {
+                (true, _) => println!("[Goal check {iteration}/{max_iterations}: passed]"),
+                (false, Some(code)) => {
+                    println!("[Goal check {iteration}/{max_iterations}: failed (exit {code})]")
+                }
+                (false, None) => println!("[Goal check {iteration}/{max_iterations}: failed]"),
+            },
+            AgentEvent::GoalEnd { outcome } => {
+                println!("[{}]", crate::utils::goal_outcome_str(&outcome));
+            }
             AgentEvent::Error { message } => {
                 eprintln!("\nError: {}", message);
             }
@@ -293,6 +312,10 @@ impl Frontend for StdoutFrontend {
                         .and_then(|v| v.as_str())
                         .unwrap_or("");
                     println!("\nApproval required for {tool_name}: {activity}");
+                    let reasons = payload.get("risk_reasons").and_then(|v| v.as_array());
+                    for reason in reasons.into_iter().flatten().filter_map(|r| r.as_str()) {
+                        println!("  - {reason}");
+                    }
                     print!("Approve? [y/N]: ");
                     io::stdout().flush().ok();
                     let line = read_line_blocking().await;
@@ -0,0 +1,196 @@
+//! `tau history export` / `tau history import` — hand a conversation
+//! graph to someone else as a bundle file — and `tau history gc`,
+//! which collects what no session needs any more. Nothing collects
+//! on its own; the history only grows until `gc` is run.
+//!
+//! Both work on a persisted tau-history repository, by default
+//! [`history_dir`], where each saved session keeps its conversation
+//! under `refs/sessions/<id>` (see [`session_branch`]). A ref is a tag
+//! name, a `refs/…` name, a session id or a full commit hash; tags
+//! travel with their names and come back as tags on import, the rest
+//! as bare commits.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tau_ai::Model;
+use tau_history::{Branch, CachePrediction, ObjectHash, Repository};
+
+use crate::cli::HistoryCmd;
+
+/// How long the turns a `/rewind` abandoned stay reachable under
+/// `refs/abandoned/` before a new session expires them.
+const ABANDONED_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How recently an object must have been written for `tau history gc`
+/// to keep it though nothing reaches it: long enough for any running
+/// session to have referenced what it wrote.
+const GC_GRACE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// Default history repository directory.
+fn history_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("tau")
+        .join("history")
+}
+
+/// The persisted branch backing a saved session's main agent, named
+/// by [`SessionManager::history_ref`]. A resumed session picks up
+/// where its branch left off.
+///
+/// [`SessionManager::history_ref`]: crate::session::SessionManager::history_ref
+pub(crate) fn session_branch(history_ref: &str) -> anyhow::Result<Arc<Branch>> {
+    let repo = open(None)?;
+    if let Err(e) = repo.expire_abandoned(ABANDONED_TTL) {
+        tracing::warn!(error = %e, "expiring rewound turns failed");
+    }
+    Ok(repo.branch_named(history_ref)?)
+}
+
+/// Start a new session's branch under `history_ref` at `point`, where
+/// a `/rewind` goes back to (`None`: before the first commit). The
+/// session it continues from keeps its own ref where it was.
+pub(crate) fn fork_session_branch(
+    history_ref: &str,
+    point: Option<ObjectHash>,
+) -> anyhow::Result<Arc<Branch>> {
+    let repo = open(None)?;
+    if let Some(point) = point {
+        if repo.try_get_commit(&point)?.is_none() {
+            anyhow::bail!("no commit {point} in the history");
+        }
+        repo.update_ref(history_ref, None, point, "rewind")?;
+    }
+    Ok(repo.branch_named(history_ref)?)
+}
+
+/// Best-effort removal of a session's history ref: a deleted session's,
+/// or one [`fork_session_branch`] created for a session that never
+/// started.
+pub(crate) fn drop_session_branch(history_ref: &str) {
+    let Ok(repo) = open(None) else { return };
+    if let Ok(Some(tip)) = repo.resolve_ref(history_ref)
+        && let Err(e) = repo.delete_ref(history_ref, tip, "rewind failed")
+    {
+        tracing::warn!(error = %e, "removing an unused session ref failed");
+    }
+}
+
+/// Where a saved session's history ref points now, if anywhere.
+pub(crate) fn session_head(history_ref: &str) -> Option<ObjectHash> {
+    open(None).ok()?.resolve_ref(history_ref).ok()?
+}
+
+/// What moving `history_ref` from `before` to where it points now did
+/// to `model`'s prompt cache. `None` if the ref didn't move or the
+/// prediction failed.
+pub(crate) fn recache_prediction(
+    history_ref: &str,
+    before: ObjectHash,
+    model: &Model,
+) -> Option<CachePrediction> {
+    let repo = open(None).ok()?;
+    let after = repo.resolve_ref(history_ref).ok()??;
+    if after == before {
+        return None;
+    }
+    match repo.predict_cache(&before, &after, model) {
+        Ok(prediction) => Some(prediction),
+        Err(e) => {
+            tracing::warn!(error = %e, "predicting the prompt cache failed");
+            None
+        }
+    }
+}
+
+pub(crate) fn run(cmd: HistoryCmd) -> anyhow::Result<()> {
+    match cmd {
+        HistoryCmd::Export { refs, output, repo } => {
+            let repo = open(repo)?;
+            export(&repo, &refs, &output)
+        }
+        HistoryCmd::Import { file, repo } => {
+            let repo = open(repo)?;
+            import(&repo, &file)
+        }
+        HistoryCmd::Gc { repo } => {
+            let repo = open(repo)?;
+            let expired = repo.expire_abandoned(ABANDONED_TTL)?;
+            let deleted = repo.gc_store([], GC_GRACE)?;
+            let packed = repo.pack()?;
+            println!("Expired {expired} rewound refs, deleted {deleted} objects, packed {packed}");
+            Ok(())
+        }
+    }
+}
+
+fn open(dir: Option<PathBuf>) -> anyhow::Result<Arc<Repository>> {
+    let dir = dir.unwrap_or_else(history_dir);
+    Repository::open(&dir)
+        .map_err(|e| anyhow::anyhow!("can't open history at {}: {e}", dir.display()))
+}
+
+fn export(repo: &Repository, refs: &[String], output: &Path) -> anyhow::Result<()> {
+    let mut tags = BTreeMap::new();
+    let mut commits = Vec::new();
+    for r in refs {
+        if let Some(hash) = repo.resolve_tag(r) {
+            tags.insert(r.clone(), hash);
+        } else if let Some(hash) = ObjectHash::from_hex(r) {
+            commits.push(hash);
+        } else if let Some(hash) = resolve_ref(repo, r)? {
+            commits.push(hash);
+        } else {
+            anyhow::bail!("'{r}' is not a tag, ref, session or commit hash");
+        }
+    }
+    let mut out = BufWriter::new(File::create(output)?);
+    let manifest = repo.export_bundle(&tags, &commits, &mut out)?;
+    eprintln!(
+        "Wrote {} ({} refs, {} objects)",
+        output.display(),
+        manifest.refs.len() + manifest.commits.len(),
+        manifest.objects
+    );
+    Ok(())
+}
+
+/// A `refs/…` name, or a session id (or unique prefix) standing for
+/// its session ref.
+fn resolve_ref(repo: &Repository, name: &str) -> anyhow::Result<Option<ObjectHash>> {
+    if name.starts_with("refs/") {
+        return Ok(repo.resolve_ref(name)?);
+    }
+    let Ok(id) = crate::session::SessionManager::resolve_id(name) else {
+        return Ok(None);
+    };
+    let (session, _, _) = crate::session::SessionManager::load(&id)?;
+    Ok(repo.resolve_ref(&session.history_ref())?)
+}
+
+fn import(repo: &Repository, file: &Path) -> anyhow::Result<()> {
+    let mut input = BufReader::new(File::open(file)?);
+    let report = repo.import_bundle(&mut input)?;
+    println!(
+        "Imported {} new objects ({} in bundle)",
+        report.objects_added, report.manifest.objects
+    );
+    for name in &report.tags_added {
+        println!("  tag {name} → {}", report.manifest.refs[name]);
+    }
+    for name in &report.tags_kept {
+        println!(
+            "  tag {name} already exists; kept it (bundle has {})",
+            report.manifest.refs[name]
+        );
+    }
+    for hash in &report.manifest.commits {
+        println!("  commit {hash}");
+    }
+    Ok(())
+}
@@ -7,10 +7,12 @@ mod config;
 mod context;
 mod driver;
 mod frontends;
+mod history;
 mod oauth;
 mod prompts;
 mod session;
 mod subagents;
+mod transcript;
 mod ui;
 mod utils;
 
@@ -36,8 +38,11 @@ async fn main() -> anyhow::Result<()> {
 
     // Subcommands that don't need an agent.
     let mut resume_id: Option<String> = None;
+    let mut resume_subagents = false;
     let mut run_prompt: Option<String> = None;
     let mut mcp_cmd: Option<McpCmd> = None;
+    let mut goal: Option<tau_agent::GoalConfig> = None;
+    let mut replay: Option<(String, String, tau_agent::Replay)> = None;
     match args.command {
         Some(Command::Config(ConfigCmd::Init)) => {
             return match config::Config::init() {
@@ -64,13 +69,52 @@ async fn main() -> anyhow::Result<()> {
         Some(Command::Sessions(SessionsCmd::Ls)) => {
             return session::list_sessions_cli();
         }
-        Some(Command::Sessions(SessionsCmd::Resume { id })) => {
+        Some(Command::Sessions(SessionsCmd::Rm { id })) => {
+            return session::remove_session_cli(&id);
+        }
+        Some(Command::Sessions(SessionsCmd::Resume {
+            id,
+            resume_subagents: resume_bg,
+        })) => {
             // Resolve prefix → full id now, before the auth gate, so a
             // bad session id fails with a session error.
             resume_id = Some(session::SessionManager::resolve_id(&id)?);
+            resume_subagents = resume_bg;
         }
-        Some(Command::Run { prompt }) => {
+        Some(Command::Run {
+            prompt,
+            until,
+            max_iterations,
+            token_budget,
+        }) => {
             run_prompt = Some(prompt);
+            goal = until.map(|cmd| {
+                let mut goal = tau_agent::GoalConfig::new(cmd);
+                goal.max_iterations = max_iterations;
+                goal.token_budget = token_budget;
+                goal
+            });
+        }
+        Some(Command::Replay {
+            session,
+            from_turn,
+            prompt,
+        }) => {
+            let id = session::SessionManager::resolve_id(&session)?;
+            let (recorded_model, messages) = session::SessionManager::read_history(&id)?;
+            let r = tau_agent::Replay::new(messages, from_turn, prompt)?;
+            run_prompt = Some(r.prompt().to_string());
+            replay = Some((id, recorded_model, r));
+        }
+        Some(Command::Transcript {
+            target,
+            format,
+            output,
+        }) => {
+            return transcript::export(&target, format, output).await;
+        }
+        Some(Command::History(cmd)) => {
+            return history::run(cmd);
         }
         Some(Command::Models(ModelsCmd::List)) => {
             cli::print_models_list();
@@ -99,12 +143,19 @@ async fn main() -> anyhow::Result<()> {
         .or(cfg.provider.clone())
         .unwrap_or_else(|| "anthropic".to_string());
 
+    // A replay continues on the session's model unless told otherwise.
     let model_id = args
         .model
+        .or_else(|| replay.as_ref().map(|(_, model, _)| model.clone()))
+        .filter(|id| !id.is_empty())
         .or(cfg.model.clone())
         .unwrap_or_else(|| "claude-sonnet-4-5-20250929".to_string());
 
     let model = get_model(&provider, &model_id).await?;
+    let utility_model = match cfg.utility_model.as_deref() {
+        Some(id) => Some(get_model(&provider, id).await?),
+        None => None,
+    };
 
     let reasoning = if args.reasoning {
         ReasoningLevel::Medium
@@ -153,8 +204,55 @@ async fn main() -> anyhow::Result<()> {
         tau_agent::ProviderTransport::new()
     });
 
-    let agent_config = cfg.to_agent_config(model.clone(), reasoning);
-    let mut builder = tau_agent::AgentBuilder::new(agent_config, transport.clone());
+    let is_one_shot = run_prompt.is_some();
+    // The session this run logs to, settled before the agent is built
+    // so oversized tool results can be kept with it.
+    let mut resumed = None;
+    let persistence = if replay.is_some() {
+        // The replayed branch is saved as a new session so it can be
+        // resumed like any other.
+        session::SessionManager::new(&model.id).ok()
+    } else if let Some(ref session_id) = resume_id {
+        match session::SessionManager::load(session_id) {
+            Ok((session_mgr, messages, previous_summary)) => {
+                resumed = Some((messages, previous_summary));
+                Some(session_mgr)
+            }
+            Err(e) => {
+                eprintln!("Error loading session: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if is_one_shot {
+        None
+    } else {
+        session::SessionManager::new(&model.id).ok()
+    };
+
+    // Oversized tool results land here; the model pages through them
+    // with `read`. A saved session keeps them until it's deleted; an
+    // unsaved run removes them when it exits.
+    let spill_dir = match &persistence {
+        Some(p) => session::SpillDir::for_session(p.id()),
+        None => session::SpillDir::for_this_run(),
+    };
+    let agent_config = cfg
+        .to_agent_config(model.clone(), utility_model, reasoning)
+        .into_builder()
+        .tool_output_spill(tau_agent::ToolOutputSpill::new(spill_dir.path()))
+        .build();
+    let root_transport: Arc<dyn tau_agent::Transport> = match &replay {
+        Some((_, _, r)) => r.transport(transport.clone()),
+        None => transport.clone(),
+    };
+    let mut builder = tau_agent::AgentBuilder::new(agent_config, root_transport);
+
+    let approval_rules = cfg.approval_rules(&std::env::current_dir()?)?;
+    let approval: Option<Arc<dyn tau_agent::ApprovalPolicy>> = (!approval_rules.is_empty())
+        .then(|| Arc::new(approval_rules.policy(Arc::new(tau_agent::DefaultPolicy))) as _);
+    if let Some(policy) = &approval {
+        builder.set_approval_policy(policy.clone());
+    }
 
     // Set up interaction channel for tools that need user input
     let (interaction_tx, interaction_rx) =
@@ -193,10 +291,21 @@ async fn main() -> anyhow::Result<()> {
         builder.add_tool(tool);
     }
 
-    let manager = Arc::new(
-        tau_agent::AgentManager::new(builder.config().clone(), transport.clone(), 20)
-            .with_parent_interaction_sender(interaction_tx),
-    );
+    let mut manager = tau_agent::AgentManager::new(builder.config().clone(), transport.clone(), 20)
+        .with_parent_interaction_sender(interaction_tx);
+    if let Some(policy) = approval {
+        manager = manager.with_default_approval_policy(policy);
+    }
+    let manager = Arc::new(manager);
+    // Before `build_resolver` too: every subagent spec can share
+    // findings through the blackboard and set timers for itself.
+    builder.add_tool(Arc::new(tau_tools::BlackboardPostTool::new(
+        manager.clone(),
+    )));
+    builder.add_tool(Arc::new(tau_tools::BlackboardReadTool::new(
+        manager.clone(),
+    )));
+    builder.add_tool(Arc::new(tau_tools::ScheduleTool::new(manager.clone())));
 
     let cwd = std::env::current_dir()
         .map(|p| p.display().to_string())
@@ -205,9 +314,13 @@ async fn main() -> anyhow::Result<()> {
     let resolver_for_host = resolver.clone();
 
     let agent_tool = tau_tools::AgentTool::new(manager.clone())
-        .with_spec_resolver(resolver)
+        .with_spec_resolver(resolver.clone())
         .with_worktree_specs(subagents::worktree_specs());
     builder.add_tool(Arc::new(agent_tool));
+    let agent_batch_tool = tau_tools::AgentBatchTool::new(manager.clone())
+        .with_spec_resolver(resolver)
+        .with_worktree_specs(subagents::worktree_specs());
+    builder.add_tool(Arc::new(agent_batch_tool));
     builder.add_tool(Arc::new(tau_tools::SendMessageTool::new(manager.clone())));
 
     // Enable web search for Anthropic models
@@ -233,31 +346,21 @@ async fn main() -> anyhow::Result<()> {
     };
     builder.set_system_prompt(parent_system_prompt.clone());
 
-    let mut resumed_session: Option<session::SessionManager> = None;
-    if let Some(ref session_id) = resume_id {
-        match session::SessionManager::load(session_id) {
-            Ok((session_mgr, messages, previous_summary)) => {
-                println!(
-                    "Resuming session {} ({} messages{})",
-                    session_id,
-                    messages.len(),
-                    if previous_summary.is_some() {
-                        ", with compacted context"
-                    } else {
-                        ""
-                    }
-                );
-                builder.seed(tau_agent::AgentSeed::Messages {
-                    messages,
-                    previous_summary,
-                });
-                resumed_session = Some(session_mgr);
+    if let (Some(session_id), Some((messages, previous_summary))) = (&resume_id, resumed) {
+        println!(
+            "Resuming session {} ({} messages{})",
+            session_id,
+            messages.len(),
+            if previous_summary.is_some() {
+                ", with compacted context"
+            } else {
+                ""
             }
-            Err(e) => {
-                eprintln!("Error loading session: {}", e);
-                std::process::exit(1);
-            }
-        }
+        );
+        builder.seed(tau_agent::AgentSeed::Messages {
+            messages,
+            previous_summary,
+        });
     }
 
     // Snapshot pieces needed to build the "root" spec before consuming the builder.
@@ -268,20 +371,86 @@ async fn main() -> anyhow::Result<()> {
         max_turns: 200,
     };
 
+    // Replayed turns return recorded tool results instead of running
+    // the tools; the root spec above keeps the real ones.
+    if let Some((_, _, ref r)) = replay {
+        let wrapped = r.wrap_tools(builder.tools());
+        builder.set_tools(wrapped);
+        builder.seed(r.seed());
+    }
+
+    if let Some((ref id, _, ref r)) = replay {
+        println!(
+            "Replaying {} of {} turns from session {}; turn {} onward runs live{}",
+            r.replayed_turns(),
+            r.recorded_turns(),
+            &id[..8.min(id.len())],
+            r.replayed_turns() + 1,
+            persistence
+                .as_ref()
+                .map(|b| format!(" (saved as {})", b.id()))
+                .unwrap_or_default()
+        );
+    }
+    // A saved session's conversation graph lives in the shared history
+    // repository, where `tau history export` can reach it.
+    if let Some(ref persistence) = persistence {
+        match history::session_branch(&persistence.history_ref()) {
+            Ok(branch) => {
+                builder.set_history(branch);
+            }
+            Err(e) => eprintln!("warning: conversation history won't be kept: {e}"),
+        }
+    }
+
     // Spawn the agent actor — from here on we use the handle
     let handle = builder.spawn().await?;
     // Register the root with the manager so handle.respec works.
     let _root_id = manager.adopt(&handle, "root", root_spec);
+    if let Some((_, _, ref r)) = replay {
+        r.attach(&handle);
+    }
 
     // Dispatch to the appropriate frontend. Session owns the loop;
     // the frontend handles I/O.
     let available_models = get_available_models();
-    let is_one_shot = run_prompt.is_some();
-    let persistence = if is_one_shot {
-        None
-    } else {
-        resumed_session.or_else(|| session::SessionManager::new(&model.id).ok())
-    };
+    // Subagents are checkpointed next to the session log so a resumed
+    // session can `send_message` the ones it left idle.
+    if let Some(ref persistence) = persistence {
+        manager.set_checkpoint_dir(session::SessionManager::fleet_dir(persistence.id()));
+        if resume_id.is_some() {
+            let resume_into = resume_subagents.then_some(&handle);
+            match manager.restore(&*resolver_for_host, resume_into).await {
+                Ok(report) if !report.restored.is_empty() => println!(
+                    "Restored {} subagent(s){}",
+                    report.restored.len(),
+                    if report.resumed.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", resuming {}", report.resumed.len())
+                    }
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("warning: could not restore subagents: {e}"),
+            }
+        }
+    }
+    // Tracing subscribes before the first prompt so the root's spans
+    // are complete; it's flushed after the frontend returns.
+    let tracing = cfg.trace_sink().map(|sink| {
+        let mut config = tau_agent::TraceConfig::new(sink);
+        if let Some(ref persistence) = persistence {
+            config = config.with_resource("tau.session.id", persistence.id());
+        }
+        let token = tokio_util::sync::CancellationToken::new();
+        let task = tau_agent::spawn_trace_exporter(
+            config,
+            handle.subscribe(),
+            manager.subscribe(),
+            token.clone(),
+        );
+        (token, task)
+    });
     let mut sess = driver::Session::new(driver::SessionConfig {
         handle: handle.clone(),
         manager: manager.clone(),
@@ -289,6 +458,8 @@ async fn main() -> anyhow::Result<()> {
         interaction_rx,
         available_models: available_models.clone(),
         persistence,
+        goal,
+        approval_rules,
     });
     let result = if use_tui && !is_one_shot {
         let agent_config = handle
@@ -308,6 +479,12 @@ async fn main() -> anyhow::Result<()> {
 
     mcp_manager.shutdown_all().await;
     lsp_manager.shutdown_all().await;
+    if let Some((token, task)) = tracing {
+        token.cancel();
+        let _ = task.await;
+    }
+    // `process::exit` below skips destructors.
+    drop(spill_dir);
     result?;
     // Scripting contract: `tau run` exits non-zero when the prompt
     // failed, so pipelines can detect failure.
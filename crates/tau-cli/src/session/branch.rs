@@ -8,16 +8,16 @@ use tau_ai::Message;
 
 use super::store::{SessionEntry, SessionManager};
 
-/// Create a branched session from `messages` up to and including
+/// Create a branched session `id` from `messages` up to and including
 /// `branch_index`. `None` produces an empty session. The returned
 /// `SessionManager` is open for further appends; its file lives in the
-/// shared sessions directory under a fresh UUID.
+/// shared sessions directory.
 pub fn branch_from(
+    id: String,
     messages: &[Message],
     branch_index: Option<usize>,
     model: &str,
 ) -> std::io::Result<SessionManager> {
-    let id = uuid::Uuid::new_v4().to_string();
     let sessions_dir = SessionManager::sessions_dir();
     fs::create_dir_all(&sessions_dir)?;
 
@@ -40,3 +40,12 @@ pub(crate) fn list_sessions_cli() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// `tau sessions rm`: delete a saved session and drop its history ref.
+pub(crate) fn remove_session_cli(prefix: &str) -> anyhow::Result<()> {
+    let id = SessionManager::resolve_id(prefix)?;
+    SessionManager::delete(&id)?;
+    crate::history::drop_session_branch(&SessionManager::history_ref_for(&id));
+    println!("Deleted session {id}");
+    Ok(())
+}
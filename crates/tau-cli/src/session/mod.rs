@@ -6,11 +6,11 @@
 //!   handle plus `SessionInfo` for listing.
 //! - [`branch`]: free function that creates a new session pre-seeded
 //!   with a prefix of an existing conversation.
-//! - [`cli`]: rendering for `tau sessions ls`.
+//! - [`cli`]: rendering for `tau sessions ls` and `tau sessions rm`.
 
 pub mod branch;
 mod cli;
 pub mod store;
 
-pub(crate) use cli::list_sessions_cli;
-pub use store::SessionManager;
+pub(crate) use cli::{list_sessions_cli, remove_session_cli};
+pub use store::{SessionManager, SpillDir};
@@ -3,7 +3,7 @@
 use std::{
     fs::{self, File},
     io::{BufRead, BufReader, BufWriter, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use serde::{Deserialize, Serialize};
@@ -80,6 +80,32 @@ impl SessionManager {
             .join("sessions")
     }
 
+    /// Directory the session's subagents are checkpointed into, next
+    /// to its log. Ignored by listing, which only reads `.jsonl` files.
+    pub fn fleet_dir(id: &str) -> PathBuf {
+        Self::sessions_dir().join(format!("{id}.fleet"))
+    }
+
+    /// Directory the session's oversized tool results are saved into.
+    /// Spills stay with the session whose run wrote them, even after a
+    /// `/rewind` or `/branch` moves logging to a new one.
+    pub fn spill_dir(id: &str) -> PathBuf {
+        Self::sessions_dir().join(format!("{id}.spill"))
+    }
+
+    /// Delete the session `id`: its log, subagent checkpoints and
+    /// spilled tool output. Its history ref is left to the caller.
+    pub fn delete(id: &str) -> std::io::Result<()> {
+        fs::remove_file(Self::sessions_dir().join(format!("{id}.jsonl")))?;
+        for dir in [Self::fleet_dir(id), Self::spill_dir(id)] {
+            match fs::remove_dir_all(&dir) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Create a new session
     pub fn new(model: &str) -> std::io::Result<Self> {
         let id = uuid::Uuid::new_v4().to_string();
@@ -169,11 +195,53 @@ impl SessionManager {
         ))
     }
 
+    /// Read a session's full message history, oldest first, ignoring
+    /// compactions, along with the model it was recorded with. The
+    /// kept tail each compaction re-appends is dropped so every
+    /// message appears once.
+    pub fn read_history(id: &str) -> std::io::Result<(String, Vec<Message>)> {
+        let path = Self::sessions_dir().join(format!("{}.jsonl", id));
+        let reader = BufReader::new(File::open(&path)?);
+
+        let mut model = String::new();
+        let mut messages = Vec::new();
+        let mut logged = std::collections::HashSet::new();
+        let mut after_compaction = false;
+        for line in reader.lines() {
+            let line = line?;
+            match serde_json::from_str::<SessionEntry>(&line) {
+                Ok(SessionEntry::Metadata { model: m, .. }) => model = m,
+                Ok(SessionEntry::Message { message, .. }) => {
+                    let key = serde_json::to_string(&message)?;
+                    if after_compaction && logged.contains(&key) {
+                        continue;
+                    }
+                    logged.insert(key);
+                    messages.push(message);
+                }
+                Ok(SessionEntry::Compaction { .. }) => after_compaction = true,
+                Ok(SessionEntry::Usage { .. }) => {}
+                Err(_) => {}
+            }
+        }
+        Ok((model, messages))
+    }
+
     /// Get session ID
     pub fn id(&self) -> &str {
         &self.id
     }
 
+    /// History ref this session's conversation is committed under.
+    pub fn history_ref(&self) -> String {
+        Self::history_ref_for(&self.id)
+    }
+
+    /// History ref of the session `id`, which needn't exist yet.
+    pub fn history_ref_for(id: &str) -> String {
+        format!("refs/sessions/{id}")
+    }
+
     /// Append a message to the session
     pub fn append_message(&mut self, message: &Message) -> std::io::Result<()> {
         if let Some(ref mut writer) = self.writer {
@@ -274,6 +342,7 @@ impl SessionManager {
             created_at,
             model,
             working_dir,
+            ..
         }) = serde_json::from_str(&first_line)
         else {
             return None;
@@ -389,10 +458,94 @@ impl SessionInfo {
     }
 }
 
+/// Directory for oversized tool results
+/// ([`tau_agent::ToolOutputSpill`]), next to the session logs and
+/// ignored by listing like [`SessionManager::fleet_dir`]. A saved
+/// session's is kept until [`SessionManager::delete`]; a run without
+/// one gets its own, removed when dropped.
+pub struct SpillDir {
+    path: PathBuf,
+    remove_on_drop: bool,
+}
+
+impl SpillDir {
+    /// The saved session `id`'s directory.
+    pub fn for_session(id: &str) -> Self {
+        Self {
+            path: SessionManager::spill_dir(id),
+            remove_on_drop: false,
+        }
+    }
+
+    /// A directory for this run alone, removed when dropped.
+    pub fn for_this_run() -> Self {
+        Self::at(SessionManager::spill_dir(&uuid::Uuid::new_v4().to_string()))
+    }
+
+    fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            remove_on_drop: true,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// Point the sessions directory and the history repository at a
+    /// scratch directory, once per test binary.
+    pub(crate) fn scratch_data_dir() {
+        static DIR: std::sync::Once = std::sync::Once::new();
+        DIR.call_once(|| {
+            let dir = tempfile::tempdir().unwrap().keep();
+            // SAFETY: set once, before any test reads it.
+            unsafe { std::env::set_var("XDG_DATA_HOME", dir) };
+        });
+    }
+
+    #[test]
+    fn session_spills_are_kept_until_the_session_is_deleted() {
+        scratch_data_dir();
+        let session = SessionManager::new("m").unwrap();
+        let id = session.id().to_string();
+        drop(session);
+        let spill = SpillDir::for_session(&id);
+        fs::create_dir_all(spill.path()).unwrap();
+        fs::write(spill.path().join("call.txt"), "output").unwrap();
+        let path = spill.path().to_path_buf();
+        drop(spill);
+        assert!(path.exists());
+
+        SessionManager::delete(&id).unwrap();
+        assert!(!path.exists());
+        assert!(SessionManager::load(&id).is_err());
+    }
+
+    #[test]
+    fn spill_dir_is_removed_when_dropped() {
+        let root = tempfile::tempdir().unwrap();
+        let spill = SpillDir::at(root.path().join("run.spill"));
+        fs::create_dir_all(spill.path()).unwrap();
+        fs::write(spill.path().join("call.txt"), "output").unwrap();
+        let path = spill.path().to_path_buf();
+        drop(spill);
+        assert!(!path.exists());
+    }
+
     fn user(text: &str) -> Message {
         Message::user(text)
     }
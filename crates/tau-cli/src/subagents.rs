@@ -69,7 +69,7 @@ const SPECS: &[SubagentDef] = &[
         prompt: include_str!("prompts/agent_explore.md"),
         prompt_suffix: None,
         tools: ToolSet::Whitelist(&["read", "glob", "grep", "list", "lsp"]),
-        extras: &["subagent_report"],
+        extras: &["subagent_report", "blackboard_post", "blackboard_read"],
         allows_worktree: false,
         can_spawn: None,
     },
@@ -78,7 +78,12 @@ const SPECS: &[SubagentDef] = &[
         prompt: include_str!("prompts/agent_plan.md"),
         prompt_suffix: None,
         tools: ToolSet::Whitelist(&["read", "glob", "grep", "list", "lsp"]),
-        extras: &["subagent_report", "submit_plan"],
+        extras: &[
+            "subagent_report",
+            "submit_plan",
+            "blackboard_post",
+            "blackboard_read",
+        ],
         allows_worktree: false,
         can_spawn: Some(&["explore", "plan"]),
     },
@@ -0,0 +1,494 @@
+//! `tau transcript` — render an agent or session transcript to
+//! Markdown or self-contained HTML.
+//!
+//! The target is a transcript file path, a subagent id (looked up in
+//! [`tau_agent::transcript_dir`]), or a saved session id/prefix. Session
+//! logs are converted into [`TranscriptEntry`]s first so both kinds go
+//! through one renderer.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeZone, Utc};
+use tau_agent::{ToolApprovalOutcome, TranscriptEntry, TranscriptRecord};
+use tau_ai::{Content, Message, Usage};
+
+use crate::session::SessionManager;
+use crate::session::store::SessionEntry;
+
+/// Tool results longer than this are cut in the rendered output.
+const MAX_RESULT_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum TranscriptFormat {
+    Markdown,
+    Html,
+}
+
+pub(crate) async fn export(
+    target: &str,
+    format: TranscriptFormat,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let (title, entries) = load(target).await?;
+    let blocks = to_blocks(&title, &entries);
+    let rendered = match format {
+        TranscriptFormat::Markdown => render_markdown(&blocks),
+        TranscriptFormat::Html => render_html(&title, &blocks),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+async fn load(target: &str) -> anyhow::Result<(String, Vec<TranscriptEntry>)> {
+    let as_path = Path::new(target);
+    if as_path.is_file() {
+        let text = std::fs::read_to_string(as_path)?;
+        return Ok(parse_any(&display_stem(as_path), &text));
+    }
+    if let Some(path) = tau_agent::transcript_path(target)
+        && path.is_file()
+    {
+        let entries = tau_agent::read_transcript(&path).await?;
+        return Ok((format!("Agent {target}"), entries));
+    }
+    let id = SessionManager::resolve_id(target).map_err(|e| {
+        anyhow::anyhow!("'{target}' is not a transcript file, subagent id, or session: {e}")
+    })?;
+    let text = std::fs::read_to_string(SessionManager::sessions_dir().join(format!("{id}.jsonl")))?;
+    Ok(parse_any(&id, &text))
+}
+
+fn display_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Session logs start with a metadata line; anything else is an agent
+/// transcript.
+fn parse_any(name: &str, text: &str) -> (String, Vec<TranscriptEntry>) {
+    let first = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    match serde_json::from_str::<SessionEntry>(first) {
+        Ok(SessionEntry::Metadata { .. }) => session_to_transcript(name, text),
+        _ => (format!("Agent {name}"), tau_agent::parse_transcript(text)),
+    }
+}
+
+fn from_millis(ms: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(ms).single()
+}
+
+/// Convert a session log. A compaction re-appends its kept tail after
+/// the marker; those repeats are dropped so each message shows once.
+fn session_to_transcript(name: &str, text: &str) -> (String, Vec<TranscriptEntry>) {
+    let mut title = format!("Session {name}");
+    let mut entries = Vec::new();
+    let mut logged: HashSet<String> = HashSet::new();
+    let mut after_compaction = false;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(entry) = serde_json::from_str::<SessionEntry>(line) else {
+            continue;
+        };
+        let (at, record) = match entry {
+            SessionEntry::Metadata {
+                id,
+                model,
+                working_dir,
+                ..
+            } => {
+                title = format!("Session {id} ({model}, {working_dir})");
+                continue;
+            }
+            SessionEntry::Message { message, timestamp } => {
+                let key = serde_json::to_string(&message).unwrap_or_default();
+                if after_compaction && logged.contains(&key) {
+                    continue;
+                }
+                logged.insert(key);
+                (timestamp, TranscriptRecord::Message { message })
+            }
+            SessionEntry::Usage {
+                input,
+                output,
+                cache_read,
+                cache_write,
+                timestamp,
+            } => (
+                timestamp,
+                TranscriptRecord::Usage {
+                    turn_number: None,
+                    usage: Usage {
+                        input,
+                        output,
+                        cache_read,
+                        cache_write,
+                        ..Default::default()
+                    },
+                },
+            ),
+            SessionEntry::Compaction {
+                summary, timestamp, ..
+            } => {
+                after_compaction = true;
+                (
+                    timestamp,
+                    TranscriptRecord::Compaction {
+                        reason: None,
+                        tokens_before: None,
+                        tokens_after: None,
+                        summary: Some(summary),
+                    },
+                )
+            }
+        };
+        entries.push(TranscriptEntry {
+            at: from_millis(at),
+            record,
+        });
+    }
+    (title, entries)
+}
+
+// ─── Rendering ──────────────────────────────────────────────────────
+
+/// Format-neutral pieces both renderers emit.
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(u8, String),
+    Text(String),
+    /// Small annotation line (tool timings, usage, approvals).
+    Meta(String),
+    Quote(String),
+    Code(String),
+    Error(String),
+}
+
+fn to_blocks(title: &str, entries: &[TranscriptEntry]) -> Vec<Block> {
+    let mut out = vec![Block::Heading(1, title.to_string())];
+    for entry in entries {
+        record_blocks(entry, &mut out);
+    }
+    out
+}
+
+fn record_blocks(entry: &TranscriptEntry, out: &mut Vec<Block>) {
+    let time = entry
+        .at
+        .map(|t| format!(" · {}", t.format("%Y-%m-%d %H:%M:%S")))
+        .unwrap_or_default();
+    match &entry.record {
+        TranscriptRecord::RunStart {
+            agent_id,
+            description,
+            prompt,
+        } => {
+            out.push(Block::Heading(2, format!("Run: {description}")));
+            out.push(Block::Meta(format!("{agent_id}{time}")));
+            out.push(Block::Heading(3, "Prompt".into()));
+            out.push(Block::Text(prompt.clone()));
+        }
+        TranscriptRecord::Message { message } => message_blocks(message, &time, out),
+        // Arguments are shown with the assistant message's tool call.
+        TranscriptRecord::ToolStart { .. } => {}
+        TranscriptRecord::ToolEnd {
+            tool_name,
+            result,
+            is_error,
+            duration_ms,
+            ..
+        } => {
+            let status = if *is_error { "failed" } else { "finished" };
+            let took = duration_ms
+                .map(|ms| format!(" in {}", format_duration(ms)))
+                .unwrap_or_default();
+            out.push(Block::Meta(format!("{tool_name} {status}{took}")));
+            out.push(Block::Code(clip(result)));
+        }
+        TranscriptRecord::Approval {
+            tool_name, outcome, ..
+        } => {
+            let outcome = match outcome {
+                ToolApprovalOutcome::AutoApproved => "auto-approved".to_string(),
+                ToolApprovalOutcome::Approved => "approved".to_string(),
+                ToolApprovalOutcome::Rejected { reason } => format!("rejected: {reason}"),
+            };
+            out.push(Block::Meta(format!("{tool_name} {outcome}")));
+        }
+        TranscriptRecord::Compaction {
+            reason,
+            tokens_before,
+            tokens_after,
+            summary,
+        } => {
+            let mut line = "Context compacted".to_string();
+            if let Some(reason) = reason {
+                line.push_str(&format!(" ({reason:?})").to_lowercase());
+            }
+            if let (Some(before), Some(after)) = (tokens_before, tokens_after) {
+                line.push_str(&format!(": {before} → {after} tokens"));
+            }
+            out.push(Block::Meta(format!("{line}{time}")));
+            if let Some(summary) = summary {
+                out.push(Block::Quote(summary.clone()));
+            }
+        }
+        TranscriptRecord::Usage { turn_number, usage } => {
+            let turn = turn_number
+                .map(|n| format!("Turn {n} usage"))
+                .unwrap_or_else(|| "Usage".to_string());
+            out.push(Block::Meta(format!("{turn}: {}", format_usage(usage))));
+        }
+        TranscriptRecord::FileChanged { path, .. } => {
+            out.push(Block::Meta(format!("Changed {}", path.display())));
+        }
+        TranscriptRecord::Error { message } => out.push(Block::Error(message.clone())),
+        TranscriptRecord::RunEnd {
+            total_turns,
+            usage,
+            interrupted,
+        } => {
+            let how = if *interrupted { "interrupted" } else { "ended" };
+            out.push(Block::Meta(format!(
+                "Run {how} after {total_turns} turn(s); total {}{time}",
+                format_usage(usage)
+            )));
+        }
+    }
+}
+
+fn message_blocks(message: &Message, time: &str, out: &mut Vec<Block>) {
+    match message {
+        Message::User { content, .. } => {
+            out.push(Block::Heading(3, format!("User{time}")));
+            content_blocks(content, out);
+        }
+        Message::Assistant { content, .. } => {
+            out.push(Block::Heading(3, format!("Assistant{time}")));
+            content_blocks(content, out);
+        }
+        Message::ToolResult {
+            tool_name,
+            is_error,
+            ..
+        } => {
+            let status = if *is_error { "failed" } else { "result" };
+            out.push(Block::Meta(format!("{tool_name} {status}")));
+            out.push(Block::Code(clip(&message.text())));
+        }
+        Message::SystemInjection { content, .. } => {
+            out.push(Block::Heading(3, format!("System{time}")));
+            content_blocks(content, out);
+        }
+    }
+}
+
+fn content_blocks(content: &[Content], out: &mut Vec<Block>) {
+    for c in content {
+        match c {
+            Content::Text { text } if !text.trim().is_empty() => {
+                out.push(Block::Text(text.clone()))
+            }
+            Content::Thinking { thinking, .. } => out.push(Block::Quote(thinking.clone())),
+            Content::ToolCall {
+                name, arguments, ..
+            } => {
+                out.push(Block::Meta(format!("Tool call: {name}")));
+                out.push(Block::Code(
+                    serde_json::to_string_pretty(arguments).unwrap_or_default(),
+                ));
+            }
+            Content::ServerToolUse { name, input, .. } => {
+                out.push(Block::Meta(format!("Server tool: {name}")));
+                out.push(Block::Code(
+                    serde_json::to_string_pretty(input).unwrap_or_default(),
+                ));
+            }
+            Content::Image { mime_type, .. } => {
+                out.push(Block::Meta(format!("[image: {mime_type}]")))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn clip(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= MAX_RESULT_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(MAX_RESULT_CHARS).collect();
+    format!("{kept}\n… ({} more characters)", count - MAX_RESULT_CHARS)
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+fn format_usage(usage: &Usage) -> String {
+    let mut s = format!("{} in / {} out tokens", usage.input, usage.output);
+    if usage.cache_read > 0 {
+        s.push_str(&format!(", {} cache read", usage.cache_read));
+    }
+    s
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&"#".repeat(*level as usize));
+                out.push(' ');
+                out.push_str(text);
+            }
+            Block::Text(text) => out.push_str(text.trim_end()),
+            Block::Meta(text) => out.push_str(&format!("_{}_", text.replace('_', "\\_"))),
+            Block::Quote(text) => {
+                let quoted: Vec<String> = text.lines().map(|l| format!("> {l}")).collect();
+                out.push_str(&quoted.join("\n"));
+            }
+            Block::Code(text) => {
+                let fence = code_fence(text);
+                out.push_str(&format!("{fence}\n{}\n{fence}", text.trim_end()));
+            }
+            Block::Error(text) => out.push_str(&format!("**Error:** {text}")),
+        }
+        out.push_str("\n\n");
+    }
+    out
+}
+
+/// A backtick fence longer than any run inside `text`.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+const HTML_STYLE: &str = "body{font:15px/1.5 system-ui,sans-serif;max-width:60rem;margin:2rem auto;\
+padding:0 1rem;color:#1f2328}h1,h2{border-bottom:1px solid #d0d7de;padding-bottom:.3rem}\
+h3{margin-bottom:.3rem}p{white-space:pre-wrap}.meta{color:#59636e;font-size:13px;margin:.2rem 0}\
+pre{background:#f6f8fa;padding:.75rem;overflow:auto;border-radius:6px;font-size:13px}\
+blockquote{color:#59636e;border-left:3px solid #d0d7de;margin:0;padding:0 1rem;white-space:pre-wrap}\
+.error{color:#d1242f;font-weight:600}";
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n",
+        escape_html(title)
+    );
+    for block in blocks {
+        let html = match block {
+            Block::Heading(level, text) => format!("<h{level}>{}</h{level}>", escape_html(text)),
+            Block::Text(text) => format!("<p>{}</p>", escape_html(text.trim_end())),
+            Block::Meta(text) => format!("<p class=\"meta\">{}</p>", escape_html(text)),
+            Block::Quote(text) => format!("<blockquote>{}</blockquote>", escape_html(text)),
+            Block::Code(text) => format!("<pre><code>{}</code></pre>", escape_html(text)),
+            Block::Error(text) => format!("<p class=\"error\">Error: {}</p>", escape_html(text)),
+        };
+        out.push_str(&html);
+        out.push('\n');
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(record: TranscriptRecord) -> TranscriptEntry {
+        TranscriptEntry { at: None, record }
+    }
+
+    #[test]
+    fn agent_transcript_renders_tool_timings_and_escapes_html() {
+        let entries = vec![
+            entry(TranscriptRecord::RunStart {
+                agent_id: "a1".into(),
+                description: "survey".into(),
+                prompt: "look at <src>".into(),
+            }),
+            entry(TranscriptRecord::ToolEnd {
+                tool_call_id: "t1".into(),
+                tool_name: "bash".into(),
+                result: "```nested```".into(),
+                is_error: true,
+                duration_ms: Some(1500),
+            }),
+        ];
+        let blocks = to_blocks("Agent a1", &entries);
+        assert!(blocks.contains(&Block::Meta("bash failed in 1.5s".into())));
+
+        let md = render_markdown(&blocks);
+        assert!(md.starts_with("# Agent a1\n\n## Run: survey"));
+        assert!(
+            md.contains("````\n```nested```\n````"),
+            "fence outgrows content"
+        );
+
+        let html = render_html("Agent a1", &blocks);
+        assert!(html.contains("<p>look at &lt;src&gt;</p>"));
+        assert!(html.starts_with("<!DOCTYPE html>"));
+    }
+
+    #[test]
+    fn session_log_drops_compaction_repeats() {
+        let user = serde_json::to_string(&SessionEntry::Message {
+            message: Message::user("hi"),
+            timestamp: 1,
+        })
+        .unwrap();
+        let meta = serde_json::to_string(&SessionEntry::Metadata {
+            id: "s1".into(),
+            created_at: 0,
+            model: "m".into(),
+            working_dir: "/w".into(),
+        })
+        .unwrap();
+        let compaction = serde_json::to_string(&SessionEntry::Compaction {
+            summary: "said hi".into(),
+            first_kept_message_index: 1,
+            timestamp: 2,
+        })
+        .unwrap();
+        let log = [meta, user.clone(), compaction, user].join("\n");
+
+        let (title, entries) = parse_any("s1", &log);
+        assert_eq!(title, "Session s1 (m, /w)");
+        assert_eq!(entries.len(), 2, "message + compaction, repeat dropped");
+        assert!(matches!(
+            entries[1].record,
+            TranscriptRecord::Compaction {
+                summary: Some(_),
+                ..
+            }
+        ));
+    }
+}
@@ -169,6 +169,44 @@ impl TuiState {
                 )));
                 self.scroll_to_bottom();
             }
+            AgentEvent::FileModifiedExternally { path, diff, .. } => {
+                let mut note = format!(
+                    "{} changed outside the agent; it will re-read before editing",
+                    path.display()
+                );
+                if let Some(diff) = diff {
+                    note.push_str(&format!("\n{diff}"));
+                }
+                self.messages.push(ChatMessage::system(note));
+                self.scroll_to_bottom();
+            }
+            AgentEvent::GoalCheck {
+                iteration,
+                max_iterations,
+                passed,
+                exit_code,
+                ..
+            } => {
+                let verdict = match (passed, exit_code) {
+                    (true, _) => "passed".to_string(),
+                    (false, Some(code)) => format!("failed (exit {code})"),
+                    (false, None) => "failed".to_string(),
+                };
+                self.messages.push(ChatMessage::system(format!(
+                    "Goal check {iteration}/{max_iterations}: {verdict}"
+                )));
+                self.scroll_to_bottom();
+            }
+            AgentEvent::GoalEnd { outcome } => {
+                self.messages.push(ChatMessage {
+                    role: "system".to_string(),
+                    content: crate::utils::goal_outcome_str(&outcome),
+                    is_error: !outcome.passed(),
+                    is_streaming: false,
+                    id: None,
+                });
+                self.scroll_to_bottom();
+            }
             AgentEvent::TurnStart { .. }
             | AgentEvent::MessageStart { .. }
             | AgentEvent::ToolApprovalResolved { .. }
@@ -229,6 +267,47 @@ impl TuiState {
                 }
             }
             FleetEvent::AgentReport { .. } => {}
+            FleetEvent::BatchStarted {
+                description,
+                total,
+                concurrency,
+                ..
+            } => {
+                self.messages.push(ChatMessage::system(format!(
+                    "Batch \"{description}\": {total} agents, {concurrency} at a time"
+                )));
+                self.scroll_to_bottom();
+            }
+            FleetEvent::BatchItemFinished {
+                finished, total, ..
+            } => {
+                self.status = format!("Batch: {finished}/{total} done");
+            }
+            FleetEvent::BatchCompleted {
+                description,
+                succeeded,
+                failed,
+                ..
+            } => {
+                self.messages.push(ChatMessage {
+                    role: "system".to_string(),
+                    content: format!(
+                        "Batch \"{description}\" finished: {succeeded} succeeded, {failed} failed"
+                    ),
+                    is_error: failed > 0,
+                    is_streaming: false,
+                    id: None,
+                });
+                self.scroll_to_bottom();
+            }
+            FleetEvent::BlackboardPosted {
+                topic, description, ..
+            } => {
+                self.status = format!("{description} posted to #{topic}");
+            }
+            FleetEvent::TimerFired { timer_id, .. } => {
+                self.status = format!("Timer {timer_id} fired");
+            }
             FleetEvent::Forwarded {
                 agent_id,
                 event: inner,
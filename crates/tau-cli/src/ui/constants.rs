@@ -13,6 +13,9 @@ pub const TOOL_RESULT_PREVIEW_CHARS: usize = 200;
 /// Maximum characters shown for branch selector message previews.
 pub const BRANCH_PREVIEW_CHARS: usize = 50;
 
+/// Maximum characters shown for rewind selector prompt previews.
+pub const REWIND_PREVIEW_CHARS: usize = 60;
+
 /// Interval between background git branch refresh polls (seconds).
 pub const GIT_BRANCH_REFRESH_SECS: u64 = 5;
 
@@ -22,6 +22,33 @@ impl TuiState {
 
     /// Handle keyboard action.
     pub async fn handle_action(&mut self, action: Action, width: u16) -> bool {
+        if self.rewind_selector.visible {
+            match action {
+                Action::Up => {
+                    self.rewind_selector.up(self.rewind_prompts.len());
+                    return true;
+                }
+                Action::Down => {
+                    self.rewind_selector.down(self.rewind_prompts.len());
+                    return true;
+                }
+                Action::Submit | Action::Tab => {
+                    let turn = self.rewind_selector.selected + 1;
+                    let fork = matches!(action, Action::Tab);
+                    self.rewind_selector.hide();
+                    self.send_ui(UiMessage::Rewind { turn, fork }).await;
+                    return true;
+                }
+                Action::Escape => {
+                    self.rewind_selector.hide();
+                    return true;
+                }
+                _ => {
+                    return true;
+                }
+            }
+        }
+
         if self.branch_selector.visible {
             match action {
                 Action::Up => {
@@ -165,6 +165,11 @@ impl TuiFrontend {
                 Some(i) => format!("/branch {}", i),
                 None => "/branch".into(),
             })),
+            UiMessage::Rewind { turn, fork } => Some(UserInput::Command(if fork {
+                format!("/rewind fork {}", turn)
+            } else {
+                format!("/rewind {}", turn)
+            })),
         }
     }
 
@@ -547,6 +552,10 @@ impl Frontend for TuiFrontend {
                                 .unwrap_or_else(|| v.to_string())
                         })
                         .unwrap_or_default();
+                    let reasons = payload
+                        .get("risk_reasons")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_default();
                     let args = payload
                         .get("arguments")
                         .map(|v| serde_json::to_string_pretty(v).unwrap_or_else(|_| v.to_string()))
@@ -556,6 +565,7 @@ impl Frontend for TuiFrontend {
                         tool_name,
                         activity,
                         risk,
+                        reasons,
                         args,
                         response_tx: req.response_tx,
                         scroll: 0,
@@ -617,6 +627,13 @@ impl Frontend for TuiFrontend {
         true
     }
 
+    async fn open_rewind_selector(&mut self, turns: &[tau_history::UserTurn]) -> bool {
+        let prompts = turns.iter().map(|t| t.prompt.clone()).collect();
+        self.state.open_rewind_selector(prompts);
+        self.redraw();
+        true
+    }
+
     async fn tick(&mut self) -> Option<UserInput> {
         // During an in-flight turn, drive one frame and check for
         // crossterm input. Returns `Some` when the Session needs to act
@@ -679,4 +696,9 @@ impl Frontend for TuiFrontend {
         self.state.status = "Ready".to_string();
         self.redraw();
     }
+
+    async fn show_messages(&mut self, messages: &[tau_ai::Message]) {
+        self.state.show_messages(messages);
+        self.redraw();
+    }
 }
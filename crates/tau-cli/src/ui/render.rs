@@ -49,6 +49,8 @@ impl TuiState {
             self.render_model_selector(frame, size);
         } else if self.branch_selector.visible {
             self.render_branch_selector(frame, size);
+        } else if self.rewind_selector.visible {
+            self.render_rewind_selector(frame, size);
         }
     }
 
@@ -103,8 +105,14 @@ impl TuiState {
                 Span::styled(format!("  [{}]", pa.risk), self.theme.error_style()),
             ]),
             Line::from(Span::styled(pa.activity.clone(), self.theme.base_style())),
-            Line::from(""),
         ];
+        for reason in &pa.reasons {
+            lines.push(Line::from(Span::styled(
+                format!("  • {reason}"),
+                self.theme.dim_style(),
+            )));
+        }
+        lines.push(Line::from(""));
         for arg_line in pa.args.lines().skip(pa.scroll as usize) {
             lines.push(Line::from(Span::styled(
                 arg_line.to_string(),
@@ -199,6 +207,31 @@ impl TuiState {
         selector.render_centered(area, frame.buffer_mut());
     }
 
+    fn render_rewind_selector(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<OwnedSelectorItem> = self
+            .rewind_prompts
+            .iter()
+            .enumerate()
+            .map(|(i, prompt)| {
+                let preview = crate::utils::truncate_chars(prompt, constants::REWIND_PREVIEW_CHARS);
+                OwnedSelectorItem {
+                    label: format!("{}: {}", i + 1, preview.replace('\n', " ")),
+                    description: None,
+                    is_current: false,
+                }
+            })
+            .collect();
+
+        let selector = OwnedSelector::new(
+            "Rewind to before prompt (Enter restore, Tab fork)",
+            items,
+            &self.theme,
+        )
+        .with_selected(self.rewind_selector.selected);
+
+        selector.render_centered(area, frame.buffer_mut());
+    }
+
     /// Full-screen overlay rendering a `PendingPlan`. Three modes:
     /// * `Reviewing` — plan body + footer `[A]pprove [E]xecute now [R]eject`.
     /// * `EnteringReason` — body dimmed; user types reason in the main
@@ -2,9 +2,10 @@ use std::collections::HashMap;
 
 use ratatui::layout::Rect;
 use tau_agent::AgentConfig;
-use tau_ai::Model;
+use tau_ai::{Message, Model};
 
 use super::{
+    constants,
     theme::Theme,
     types::PendingApproval,
     widgets::{InputBox, SelectorState, message_list::ChatMessage},
@@ -100,6 +101,10 @@ pub(super) struct TuiState {
     pub model_selector: SelectorState,
     /// Branch selector state
     pub branch_selector: SelectorState,
+    /// Rewind selector state
+    pub rewind_selector: SelectorState,
+    /// User prompts listed by the rewind selector, oldest first
+    pub rewind_prompts: Vec<String>,
     /// Pending interaction request (question waiting for user to pick an option)
     pub pending_interaction: Option<PendingInteraction>,
     /// Pending plan submission awaiting Approve / Execute now / Reject.
@@ -148,6 +153,8 @@ impl TuiState {
             ui_tx,
             model_selector,
             branch_selector: SelectorState::default(),
+            rewind_selector: SelectorState::default(),
+            rewind_prompts: Vec::new(),
             pending_interaction: None,
             pending_plan: None,
             pending_approvals: std::collections::VecDeque::new(),
@@ -165,6 +172,19 @@ impl TuiState {
         }
     }
 
+    /// Open the rewind selector popup over `prompts`. Invoked by the
+    /// TUI frontend's `Frontend::open_rewind_selector` impl when the
+    /// user runs bare `/rewind`. Enter emits `UiMessage::Rewind` to
+    /// restore the conversation to before the selected prompt; Tab
+    /// emits it with `fork` set.
+    pub fn open_rewind_selector(&mut self, prompts: Vec<String>) {
+        if !prompts.is_empty() {
+            self.rewind_selector.selected = prompts.len() - 1;
+            self.rewind_prompts = prompts;
+            self.rewind_selector.show();
+        }
+    }
+
     /// Send a UI message, logging a warning if the channel is closed.
     pub async fn send_ui(&self, msg: UiMessage) {
         if self.ui_tx.send(msg).await.is_err() {
@@ -182,6 +202,37 @@ impl TuiState {
         self.scroll_to_bottom();
     }
 
+    /// Show a conversation's messages the way they looked live:
+    /// prompts, replies and tool results. Injected notices aren't
+    /// shown.
+    pub fn show_messages(&mut self, messages: &[Message]) {
+        for message in messages {
+            let chat = match message {
+                Message::User { .. } => ChatMessage::user(message.text()),
+                Message::Assistant { .. } => {
+                    let text = message.text();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    ChatMessage::assistant(text)
+                }
+                Message::ToolResult {
+                    tool_name,
+                    is_error,
+                    ..
+                } => {
+                    let text = message.text();
+                    let preview =
+                        crate::utils::truncate_chars(&text, constants::TOOL_RESULT_PREVIEW_CHARS);
+                    ChatMessage::tool(tool_name, preview, *is_error)
+                }
+                Message::SystemInjection { .. } => continue,
+            };
+            self.messages.push(chat);
+        }
+        self.scroll_to_bottom();
+    }
+
     /// Sync model/reasoning from agent config (call before rendering).
     /// Sync mutable agent config (model, reasoning level, thinking
     /// mode) into the display. Called by the TUI frontend's
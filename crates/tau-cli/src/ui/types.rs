@@ -46,6 +46,8 @@ pub(super) struct PendingApproval {
     pub activity: String,
     /// Risk classification as reported by the gate payload.
     pub risk: String,
+    /// Why the tool rated the call that risky (may be empty).
+    pub reasons: Vec<String>,
     /// Pretty-printed tool arguments.
     pub args: String,
     pub response_tx: tokio::sync::oneshot::Sender<tau_agent::InteractionResponse>,
@@ -70,6 +72,9 @@ pub enum UiMessage {
     ChangeModel(usize),
     /// Create branch from message index (None = empty branch)
     Branch(Option<usize>),
+    /// Go back to before user prompt `turn` (1-based), in place or on
+    /// a fork
+    Rewind { turn: usize, fork: bool },
 }
 
 /// Cached git branch name with background refresh.
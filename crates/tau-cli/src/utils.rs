@@ -31,3 +31,24 @@ pub fn compaction_reason_str(reason: tau_agent::CompactionReason) -> &'static st
         tau_agent::CompactionReason::Manual => "manual",
     }
 }
+
+/// One-line summary of how a goal-mode run ended.
+pub fn goal_outcome_str(outcome: &tau_agent::GoalOutcome) -> String {
+    match outcome {
+        tau_agent::GoalOutcome::Passed { iterations } => {
+            format!("Goal met after {iterations} round(s)")
+        }
+        tau_agent::GoalOutcome::MaxIterations { iterations } => {
+            format!("Goal not met: check still failing after {iterations} round(s)")
+        }
+        tau_agent::GoalOutcome::BudgetExhausted {
+            iterations,
+            tokens_used,
+        } => format!(
+            "Goal not met: token budget spent ({tokens_used} tokens, {iterations} round(s))"
+        ),
+        tau_agent::GoalOutcome::Aborted { iterations } => {
+            format!("Goal stopped: aborted after {iterations} completed round(s)")
+        }
+    }
+}
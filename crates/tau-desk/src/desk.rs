@@ -608,6 +608,7 @@ impl DeskAgent {
             file_access: Arc::new(parking_lot::Mutex::new(FileAccessTracker::default())),
             agent_id: None,
             subagent_depth: 0,
+            spill: None,
         };
 
         // Dispatch. `is_error` on ToolResult signals a logical failure;
@@ -2,9 +2,10 @@
 //! for constructing commits.
 //!
 //! `Branch` exposes the git-flavored API (`commit`, `merge`, `fork`,
-//! `tip`) and implements [`History`] for the agent runtime's
+//! `reset`, `tip`) and implements [`History`] for the agent runtime's
 //! conversation-flavored view (`messages`, `system_prompt`, `tools`,
-//! `previous_summary`, `append`, `compact_prefix`).
+//! `previous_summary`, `append`, `compact_prefix`, and `rewind` /
+//! `fork_at` to go back to an earlier turn).
 //!
 //! # Tree layout
 //!
@@ -94,9 +95,12 @@ use async_trait::async_trait;
 use parking_lot::Mutex;
 use tau_ai::{Content, Message};
 
+use crate::canonical;
 use crate::history::{History, HistoryError};
+use crate::log::UserTurn;
 use crate::objects::{
-    Blob, Commit, ObjectHash, ObjectKind, StoreError, ToolDef, Tree, TreeEntry,
+    Blob, Commit, CommitKind, CommitMeta, ObjectHash, ObjectKind, StoreError, ToolDef, Tree,
+    TreeEntry,
 };
 use crate::repository::Repository;
 
@@ -281,18 +285,15 @@ struct BranchState {
 pub struct Branch {
     repo: Arc<Repository>,
     state: Mutex<BranchState>,
+    /// The ref this branch moves on every commit, if opened with
+    /// [`Repository::branch_named`].
+    ref_name: Option<String>,
 }
 
 impl Branch {
     /// `git checkout --orphan` — a brand-new branch with no commits.
     pub(crate) fn empty(repo: Arc<Repository>) -> Arc<Self> {
-        Arc::new(Self {
-            repo,
-            state: Mutex::new(BranchState {
-                tip: None,
-                next_seq: 1,
-            }),
-        })
+        Self::tracked(repo, None, 1, None)
     }
 
     /// `git checkout <commit>` — open a branch positioned at the
@@ -300,13 +301,31 @@ impl Branch {
     /// the last entry across all type subtrees.
     pub(crate) fn at(repo: Arc<Repository>, tip: ObjectHash) -> Arc<Self> {
         let next_seq = derive_next_seq(&repo, &tip).unwrap_or(1);
-        Arc::new(Self {
+        Self::tracked(repo, Some(tip), next_seq, None)
+    }
+
+    /// A branch bound to the ref `name`, positioned at `tip` (its
+    /// current value).
+    pub(crate) fn named(repo: Arc<Repository>, name: String, tip: Option<ObjectHash>) -> Arc<Self> {
+        let next_seq = tip.map_or(1, |tip| derive_next_seq(&repo, &tip).unwrap_or(1));
+        Self::tracked(repo, tip, next_seq, Some(name))
+    }
+
+    /// Build a branch and register it with the repository, whose
+    /// [`gc`](Repository::gc) keeps live tips reachable.
+    fn tracked(
+        repo: Arc<Repository>,
+        tip: Option<ObjectHash>,
+        next_seq: u64,
+        ref_name: Option<String>,
+    ) -> Arc<Self> {
+        let branch = Arc::new(Self {
             repo,
-            state: Mutex::new(BranchState {
-                tip: Some(tip),
-                next_seq,
-            }),
-        })
+            state: Mutex::new(BranchState { tip, next_seq }),
+            ref_name,
+        });
+        branch.repo.track(&branch);
+        branch
     }
 
     /// `git rev-parse HEAD` — the current tip commit, or `None` if
@@ -315,10 +334,26 @@ impl Branch {
         self.state.lock().tip
     }
 
+    /// The ref this branch keeps up to date, if it was opened with
+    /// [`Repository::branch_named`].
+    pub fn ref_name(&self) -> Option<&str> {
+        self.ref_name.as_deref()
+    }
+
     /// `git commit` — apply `patch` and record a new commit on top
     /// of the current tip.
     pub async fn commit(&self, patch: TreePatch) -> Result<(), HistoryError> {
-        self.commit_inner(patch, Vec::new())
+        self.commit_inner(patch, Vec::new(), CommitMeta::default())
+    }
+
+    /// `git commit -m` — like [`commit`](Self::commit), recording
+    /// `meta` on the new commit.
+    pub async fn commit_with(
+        &self,
+        patch: TreePatch,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        self.commit_inner(patch, Vec::new(), meta)
     }
 
     /// `git merge --no-ff` — like `commit`, but also record one or
@@ -330,26 +365,73 @@ impl Branch {
         patch: TreePatch,
         extra_parents: Vec<ObjectHash>,
     ) -> Result<(), HistoryError> {
-        self.commit_inner(patch, extra_parents)
+        self.commit_inner(patch, extra_parents, CommitMeta::default())
+    }
+
+    /// Like [`merge`](Self::merge), recording `meta` on the merge
+    /// commit.
+    pub async fn merge_with(
+        &self,
+        patch: TreePatch,
+        extra_parents: Vec<ObjectHash>,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        self.commit_inner(patch, extra_parents, meta)
     }
 
     /// `git checkout -b <new-branch>` — open a new branch sharing
     /// the same tip. Cheap: the new branch is a tip-pointer clone.
     pub fn fork(self: &Arc<Self>) -> Arc<Self> {
-        let s = self.state.lock();
-        Arc::new(Self {
-            repo: Arc::clone(&self.repo),
-            state: Mutex::new(BranchState {
-                tip: s.tip,
-                next_seq: s.next_seq,
-            }),
-        })
+        let (tip, next_seq) = {
+            let s = self.state.lock();
+            (s.tip, s.next_seq)
+        };
+        Self::tracked(Arc::clone(&self.repo), tip, next_seq, None)
+    }
+
+    /// `git reset --hard <commit>` — move the tip to `to` (`None`: back
+    /// to empty) and return the tip it replaced. A branch opened with
+    /// [`Repository::branch_named`] moves its ref too, by
+    /// compare-and-swap. The replaced tip is kept under
+    /// `refs/abandoned/<hash>`, so it survives the branch and
+    /// [`gc`](Repository::gc) and can be compared against or reset to
+    /// later, until [`Repository::expire_abandoned`] deletes it.
+    pub fn reset(&self, to: Option<ObjectHash>) -> Result<Option<ObjectHash>, HistoryError> {
+        let next_seq = match &to {
+            Some(hash) => derive_next_seq(&self.repo, hash)?,
+            None => 1,
+        };
+
+        let _pin = self.repo.gc_gate.read();
+        let mut state = self.state.lock();
+        let old = state.tip;
+        if old == to {
+            return Ok(old);
+        }
+        if let Some(old) = old {
+            keep_abandoned(&self.repo, old)?;
+        }
+        if let Some(name) = &self.ref_name {
+            match (to, old) {
+                (Some(hash), _) => {
+                    self.repo
+                        .update_ref(name, old, hash, format!("reset: moving to {hash}"))?
+                }
+                (None, Some(old)) => self.repo.delete_ref(name, old, "reset")?,
+                (None, None) => {}
+            }
+        }
+
+        state.tip = to;
+        state.next_seq = next_seq;
+        Ok(old)
     }
 
     fn commit_inner(
         &self,
         patch: TreePatch,
         extra_parents: Vec<ObjectHash>,
+        meta: CommitMeta,
     ) -> Result<(), HistoryError> {
         if patch.is_empty() && extra_parents.is_empty() {
             return Err(HistoryError::msg(
@@ -357,6 +439,9 @@ impl Branch {
             ));
         }
 
+        // Taken before the state lock; `gc` holds it exclusively while
+        // reading tips, so this order can't deadlock.
+        let _pin = self.repo.gc_gate.read();
         let mut state = self.state.lock();
         let parent_tip = state.tip;
         let commit_seq = state.next_seq;
@@ -371,12 +456,29 @@ impl Branch {
 
         let new_root_hash = apply_patch(&self.repo, parent_root.as_deref(), &patch, commit_seq)?;
 
+        let action = if extra_parents.is_empty() {
+            "commit"
+        } else {
+            "merge"
+        };
+        let reflog_message = match &meta.message {
+            Some(message) => format!("{action}: {message}"),
+            None => action.to_string(),
+        };
         let commit = Commit {
             parent: parent_tip,
             extra_parents,
             tree: new_root_hash,
+            meta,
         };
         let commit_hash = self.repo.put_commit(commit);
+        // Don't advance the tip past objects the backing store never
+        // received.
+        self.repo.flush()?;
+        if let Some(name) = &self.ref_name {
+            self.repo
+                .update_ref(name, parent_tip, commit_hash, reflog_message)?;
+        }
 
         state.tip = Some(commit_hash);
         state.next_seq = commit_seq + 1;
@@ -401,7 +503,7 @@ fn apply_patch(
 
     // system_prompt
     if let Some(sp) = &patch.system_prompt {
-        let bytes = serde_json::to_vec(sp).map_err(HistoryError::from)?;
+        let bytes = canonical::to_vec(sp).map_err(HistoryError::from)?;
         let hash = repo.put_blob(Blob::new(bytes));
         root.insert("system_prompt", TreeEntry::Blob(hash));
     }
@@ -411,7 +513,7 @@ fn apply_patch(
     if let Some(tools) = &patch.tools {
         let mut tools_tree = Tree::new();
         for t in tools {
-            let bytes = serde_json::to_vec(t).map_err(HistoryError::from)?;
+            let bytes = canonical::to_vec(t).map_err(HistoryError::from)?;
             let blob_hash = repo.put_blob(Blob::new(bytes));
             tools_tree.insert(t.name.clone(), TreeEntry::Blob(blob_hash));
         }
@@ -571,7 +673,7 @@ fn write_typed_messages(
         };
 
         for (batch_pos, msg) in entries {
-            let bytes = serde_json::to_vec(msg).map_err(HistoryError::from)?;
+            let bytes = canonical::to_vec(msg).map_err(HistoryError::from)?;
             let blob_hash = repo.put_blob(Blob::new(bytes));
             let entry = entry_name(commit_seq, *batch_pos);
             bucket.insert(entry, TreeEntry::Blob(blob_hash));
@@ -648,10 +750,20 @@ fn derive_next_seq(repo: &Repository, tip: &ObjectHash) -> Result<u64, HistoryEr
     Ok(max_seq + 1)
 }
 
+/// Point `refs/abandoned/<tip>` at `tip`, unless it already does.
+fn keep_abandoned(repo: &Repository, tip: ObjectHash) -> Result<(), StoreError> {
+    match repo.update_ref(&format!("refs/abandoned/{tip}"), None, tip, "abandoned") {
+        Err(StoreError::RefConflict {
+            actual: Some(kept), ..
+        }) if kept == tip => Ok(()),
+        other => other,
+    }
+}
+
 // ─── Read helpers ────────────────────────────────────────────────────
 
 fn get_commit(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Commit>, HistoryError> {
-    repo.get_commit(hash).ok_or_else(|| {
+    repo.try_get_commit(hash)?.ok_or_else(|| {
         HistoryError::from(StoreError::NotFound {
             hash: *hash,
             kind: ObjectKind::Commit,
@@ -660,7 +772,7 @@ fn get_commit(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Commit>, Histo
 }
 
 fn get_tree(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Tree>, HistoryError> {
-    repo.get_tree(hash).ok_or_else(|| {
+    repo.try_get_tree(hash)?.ok_or_else(|| {
         HistoryError::from(StoreError::NotFound {
             hash: *hash,
             kind: ObjectKind::Tree,
@@ -669,7 +781,7 @@ fn get_tree(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Tree>, HistoryEr
 }
 
 fn get_blob(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Blob>, HistoryError> {
-    repo.get_blob(hash).ok_or_else(|| {
+    repo.try_get_blob(hash)?.ok_or_else(|| {
         HistoryError::from(StoreError::NotFound {
             hash: *hash,
             kind: ObjectKind::Blob,
@@ -690,59 +802,70 @@ fn read_root_tree(branch: &Branch) -> Result<Option<Arc<Tree>>, HistoryError> {
     }
 }
 
-// ─── History impl ────────────────────────────────────────────────────
+/// The blob hashes of a root tree's messages, in conversation order.
+pub(crate) fn message_blobs(
+    repo: &Repository,
+    root: &Tree,
+) -> Result<Vec<ObjectHash>, HistoryError> {
+    let messages_tree = match root.get("messages") {
+        Some(TreeEntry::Tree(h)) => get_tree(repo, h)?,
+        _ => return Ok(Vec::new()),
+    };
 
-#[async_trait]
-impl History for Branch {
-    async fn messages(&self) -> Result<Vec<Message>, HistoryError> {
-        let Some(root) = read_root_tree(self)? else {
-            return Ok(Vec::new());
-        };
-        let messages_tree = match root.get("messages") {
-            Some(TreeEntry::Tree(h)) => get_tree(&self.repo, h)?,
-            _ => return Ok(Vec::new()),
+    let mut collected: Vec<((u64, u64), ObjectHash)> = Vec::new();
+    for type_name in ALL_TYPES.iter() {
+        let type_tree = match messages_tree.get(*type_name) {
+            Some(TreeEntry::Tree(h)) => get_tree(repo, h)?,
+            _ => continue,
         };
-
-        let mut collected: Vec<((u64, u64), Message)> = Vec::new();
-        for type_name in ALL_TYPES.iter() {
-            let type_tree = match messages_tree.get(*type_name) {
-                Some(TreeEntry::Tree(h)) => get_tree(&self.repo, h)?,
-                _ => continue,
+        for (_bucket_name, bucket_entry) in &type_tree.entries {
+            let bucket_hash = match bucket_entry {
+                TreeEntry::Tree(h) => h,
+                _ => {
+                    return Err(HistoryError::from(StoreError::TypeMismatch {
+                        path: format!("messages/{type_name}/<bucket>"),
+                        expected: ObjectKind::Tree,
+                        actual: ObjectKind::Blob,
+                    }))
+                }
             };
-            for (_bucket_name, bucket_entry) in &type_tree.entries {
-                let bucket_hash = match bucket_entry {
-                    TreeEntry::Tree(h) => h,
+            let bucket = get_tree(repo, bucket_hash)?;
+            for (entry_name_str, blob_entry) in &bucket.entries {
+                let blob_hash = match blob_entry {
+                    TreeEntry::Blob(h) => h,
                     _ => {
                         return Err(HistoryError::from(StoreError::TypeMismatch {
-                            path: format!("messages/{type_name}/<bucket>"),
-                            expected: ObjectKind::Tree,
-                            actual: ObjectKind::Blob,
+                            path: format!("messages/{type_name}/<bucket>/{entry_name_str}"),
+                            expected: ObjectKind::Blob,
+                            actual: ObjectKind::Tree,
                         }))
                     }
                 };
-                let bucket = get_tree(&self.repo, bucket_hash)?;
-                for (entry_name_str, blob_entry) in &bucket.entries {
-                    let blob_hash = match blob_entry {
-                        TreeEntry::Blob(h) => h,
-                        _ => {
-                            return Err(HistoryError::from(StoreError::TypeMismatch {
-                                path: format!("messages/{type_name}/<bucket>/{entry_name_str}"),
-                                expected: ObjectKind::Blob,
-                                actual: ObjectKind::Tree,
-                            }))
-                        }
-                    };
-                    let blob = get_blob(&self.repo, blob_hash)?;
-                    let msg: Message =
-                        serde_json::from_slice(&blob.bytes).map_err(HistoryError::from)?;
-                    let key = parse_entry_key(entry_name_str)?;
-                    collected.push((key, msg));
-                }
+                let key = parse_entry_key(entry_name_str)?;
+                collected.push((key, *blob_hash));
             }
         }
+    }
 
-        collected.sort_unstable_by_key(|(k, _)| *k);
-        Ok(collected.into_iter().map(|(_, m)| m).collect())
+    collected.sort_unstable_by_key(|(k, _)| *k);
+    Ok(collected.into_iter().map(|(_, h)| h).collect())
+}
+
+// ─── History impl ────────────────────────────────────────────────────
+
+#[async_trait]
+impl History for Branch {
+    async fn messages(&self) -> Result<Vec<Message>, HistoryError> {
+        let Some(root) = read_root_tree(self)? else {
+            return Ok(Vec::new());
+        };
+        let mut messages = Vec::new();
+        for blob_hash in message_blobs(&self.repo, &root)? {
+            let blob = get_blob(&self.repo, &blob_hash)?;
+            let msg: Message = serde_json::from_slice(&blob.bytes).map_err(HistoryError::from)?;
+            messages.push(msg);
+        }
+        Ok(messages)
     }
 
     async fn system_prompt(&self) -> Result<Option<Vec<Content>>, HistoryError> {
@@ -824,11 +947,31 @@ impl History for Branch {
         self.commit(TreePatch::new().add_messages(messages)).await
     }
 
+    async fn append_with(
+        &self,
+        messages: Vec<Message>,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        self.commit_with(TreePatch::new().add_messages(messages), meta)
+            .await
+    }
+
     async fn compact_prefix(
         &self,
         end: usize,
         summary_message: Message,
         summary_text: String,
+    ) -> Result<(), HistoryError> {
+        self.compact_prefix_with(end, summary_message, summary_text, CommitMeta::new())
+            .await
+    }
+
+    async fn compact_prefix_with(
+        &self,
+        end: usize,
+        summary_message: Message,
+        summary_text: String,
+        meta: CommitMeta,
     ) -> Result<(), HistoryError> {
         let existing = self.messages().await?;
         if end > existing.len() {
@@ -840,11 +983,52 @@ impl History for Branch {
         let mut new_messages = Vec::with_capacity(existing.len() - end + 1);
         new_messages.push(summary_message);
         new_messages.extend(existing.into_iter().skip(end));
-        self.commit(
+        self.commit_with(
             TreePatch::new()
                 .replace_messages(new_messages)
                 .with_previous_summary(Some(summary_text)),
+            meta.with_kind(CommitKind::Compaction),
+        )
+        .await
+    }
+
+    async fn prune(&self, messages: Vec<Message>, meta: CommitMeta) -> Result<(), HistoryError> {
+        self.commit_with(
+            TreePatch::new().replace_messages(messages),
+            meta.with_kind(CommitKind::Compaction),
         )
         .await
     }
+
+    fn head(&self) -> Option<ObjectHash> {
+        self.tip()
+    }
+
+    async fn user_turns(&self) -> Result<Vec<UserTurn>, HistoryError> {
+        match self.tip() {
+            Some(tip) => self.repo.user_turns(tip),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn rewind(&self, to: Option<ObjectHash>) -> Result<Option<ObjectHash>, HistoryError> {
+        self.reset(to)
+    }
+
+    async fn fork_at(&self, at: Option<ObjectHash>) -> Result<Arc<dyn History>, HistoryError> {
+        if let Some(hash) = &at {
+            get_commit(&self.repo, hash)?;
+        }
+        // A named branch's ref already keeps its tip.
+        if self.ref_name.is_none()
+            && let Some(tip) = self.tip()
+        {
+            keep_abandoned(&self.repo, tip)?;
+        }
+        let repo = Arc::clone(&self.repo);
+        Ok(match at {
+            Some(hash) => Self::at(repo, hash),
+            None => Self::empty(repo),
+        })
+    }
 }
@@ -0,0 +1,375 @@
+//! Portable bundles: a set of commits and everything they reach, in
+//! one file, for handing history to another [`Repository`].
+//!
+//! ```text
+//! TAUBNDL1                                   magic
+//! <u32 LE length><manifest JSON>             heads, object count, checksum
+//! <hash[32]><u32 LE length><object>…         zstd("<kind>\0" + payload)
+//! ```
+//!
+//! The manifest lists the bundle's heads — tags travel with their
+//! names, bare commits without — the [`HASH_VERSION`] the objects were
+//! hashed under, and a SHA-256 of the object section. Objects are
+//! encoded exactly like the entries of an [`FsStore`](crate::FsStore)
+//! pack and sorted by hash, so the same heads always produce the same
+//! file.
+//!
+//! [`Repository::import_bundle`] checks all of that, re-hashes every
+//! object, and makes sure the heads' full history is present before it
+//! stores anything.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::canonical::{self, HASH_VERSION};
+use crate::objects::{Blob, Commit, ObjectHash, ObjectKind, StoreError, Tree, TreeEntry};
+use crate::repository::Repository;
+use crate::store;
+
+const BUNDLE_MAGIC: &[u8; 8] = b"TAUBNDL1";
+
+/// Largest object, decompressed, a bundle may carry. Import refuses
+/// anything bigger rather than inflate it.
+pub const MAX_BUNDLE_OBJECT_SIZE: u64 = 64 << 20;
+
+/// What a bundle contains. Returned by
+/// [`Repository::export_bundle`] and, as part of [`BundleImport`], by
+/// [`Repository::import_bundle`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleManifest {
+    pub hash_version: u32,
+    /// Named heads. Imported as tags.
+    pub refs: BTreeMap<String, ObjectHash>,
+    /// Unnamed heads.
+    pub commits: Vec<ObjectHash>,
+    /// Number of objects in the bundle.
+    pub objects: usize,
+}
+
+/// What [`Repository::import_bundle`] did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleImport {
+    pub manifest: BundleManifest,
+    /// Objects the repository didn't already have.
+    pub objects_added: usize,
+    /// Refs created as new tags.
+    pub tags_added: Vec<String>,
+    /// Refs whose name was already a tag for a different commit. The
+    /// existing tag was left alone; the ref's commit was still
+    /// imported.
+    pub tags_kept: Vec<String>,
+}
+
+/// On-disk manifest. Hashes are hex so the header reads sensibly in a
+/// pager.
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    hash_version: u32,
+    refs: BTreeMap<String, String>,
+    commits: Vec<String>,
+    objects: usize,
+    checksum: String,
+}
+
+/// A decoded bundle object.
+enum Object {
+    Blob(Blob),
+    Tree(Tree),
+    Commit(Box<Commit>),
+}
+
+impl Repository {
+    /// `git bundle create` — write the commits `refs` and `commits`
+    /// point at, with every commit, tree and blob reachable from them,
+    /// to `out`. Fails with [`StoreError::NotFound`] if any of that
+    /// history isn't in this repository.
+    pub fn export_bundle(
+        &self,
+        refs: &BTreeMap<String, ObjectHash>,
+        commits: &[ObjectHash],
+        out: &mut dyn Write,
+    ) -> Result<BundleManifest, StoreError> {
+        let heads = refs.values().chain(commits).copied();
+        let objects = self.reachable_payloads(heads)?;
+
+        let mut section = Vec::new();
+        for (hash, kind, payload) in &objects {
+            let encoded = store::encode(*kind, payload)?;
+            section.extend_from_slice(hash.as_bytes());
+            section.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+            section.extend_from_slice(&encoded);
+        }
+        let manifest = BundleManifest {
+            hash_version: HASH_VERSION,
+            refs: refs.clone(),
+            commits: commits.to_vec(),
+            objects: objects.len(),
+        };
+        let header = canonical::to_vec(&ManifestFile {
+            hash_version: manifest.hash_version,
+            refs: refs
+                .iter()
+                .map(|(n, h)| (n.clone(), h.to_string()))
+                .collect(),
+            commits: commits.iter().map(ToString::to_string).collect(),
+            objects: manifest.objects,
+            checksum: hex(&Sha256::digest(&section)),
+        })
+        .map_err(|e| StoreError::Corrupt {
+            what: format!("bundle manifest: {e}"),
+        })?;
+
+        out.write_all(BUNDLE_MAGIC)?;
+        out.write_all(&(header.len() as u32).to_le_bytes())?;
+        out.write_all(&header)?;
+        out.write_all(&section)?;
+        out.flush()?;
+        Ok(manifest)
+    }
+
+    /// `git bundle unbundle` — verify a bundle read from `input` and
+    /// add its objects to this repository, then tag its named heads.
+    /// An existing tag is never moved: a ref whose name is taken by a
+    /// different commit is reported in [`BundleImport::tags_kept`].
+    ///
+    /// Nothing is stored unless the whole bundle checks out: magic,
+    /// hash version, checksum, every object's hash, and a complete
+    /// history behind every head.
+    pub fn import_bundle(&self, input: &mut dyn Read) -> Result<BundleImport, StoreError> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        let (manifest, objects) = parse(&bytes)?;
+
+        let heads = manifest.refs.values().chain(&manifest.commits).copied();
+        check_complete(&objects, heads)?;
+
+        // Held until the tags are set, so a concurrent gc can't sweep
+        // imported objects before anything points at them.
+        let _pin = self.gc_gate.read();
+        let mut objects_added = 0;
+        for (hash, object) in objects {
+            let added = match object {
+                Object::Blob(blob) => {
+                    let new = self.try_get_blob(&hash)?.is_none();
+                    self.put_blob(blob);
+                    new
+                }
+                Object::Tree(tree) => {
+                    let new = self.try_get_tree(&hash)?.is_none();
+                    self.put_tree(tree);
+                    new
+                }
+                Object::Commit(commit) => {
+                    let new = self.try_get_commit(&hash)?.is_none();
+                    self.put_commit(*commit);
+                    new
+                }
+            };
+            objects_added += usize::from(added);
+        }
+        self.flush()?;
+
+        let mut tags_added = Vec::new();
+        let mut tags_kept = Vec::new();
+        for (name, hash) in &manifest.refs {
+            match self.resolve_tag(name) {
+                None => {
+                    self.set_tag(name.clone(), *hash);
+                    tags_added.push(name.clone());
+                }
+                Some(existing) if existing != *hash => tags_kept.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        self.flush()?;
+
+        Ok(BundleImport {
+            manifest,
+            objects_added,
+            tags_added,
+            tags_kept,
+        })
+    }
+
+    /// Kind and payload of every object reachable from `heads`, sorted
+    /// by hash.
+    fn reachable_payloads(
+        &self,
+        heads: impl IntoIterator<Item = ObjectHash>,
+    ) -> Result<Vec<(ObjectHash, ObjectKind, Vec<u8>)>, StoreError> {
+        let mut out = HashMap::new();
+        let mut pending: Vec<(ObjectHash, ObjectKind)> =
+            heads.into_iter().map(|h| (h, ObjectKind::Commit)).collect();
+        while let Some((hash, kind)) = pending.pop() {
+            if out.contains_key(&hash) {
+                continue;
+            }
+            let not_found = StoreError::NotFound { hash, kind };
+            let payload = match kind {
+                ObjectKind::Commit => {
+                    let commit = self.try_get_commit(&hash)?.ok_or(not_found)?;
+                    pending.extend(commit.parent.map(|p| (p, ObjectKind::Commit)));
+                    pending.extend(
+                        commit
+                            .extra_parents
+                            .iter()
+                            .map(|p| (*p, ObjectKind::Commit)),
+                    );
+                    pending.push((commit.tree, ObjectKind::Tree));
+                    commit.encode()
+                }
+                ObjectKind::Tree => {
+                    let tree = self.try_get_tree(&hash)?.ok_or(not_found)?;
+                    pending.extend(tree.entries.values().map(entry_kind));
+                    tree.encode()
+                }
+                ObjectKind::Blob => self.try_get_blob(&hash)?.ok_or(not_found)?.bytes.clone(),
+            };
+            out.insert(hash, (kind, payload));
+        }
+        let mut out: Vec<_> = out
+            .into_iter()
+            .map(|(hash, (kind, payload))| (hash, kind, payload))
+            .collect();
+        out.sort_by_key(|(hash, _, _)| *hash.as_bytes());
+        Ok(out)
+    }
+}
+
+fn entry_kind(entry: &TreeEntry) -> (ObjectHash, ObjectKind) {
+    match entry {
+        TreeEntry::Blob(h) => (*h, ObjectKind::Blob),
+        TreeEntry::Tree(h) => (*h, ObjectKind::Tree),
+    }
+}
+
+fn corrupt(what: impl Into<String>) -> StoreError {
+    StoreError::Corrupt {
+        what: format!("bundle: {}", what.into()),
+    }
+}
+
+/// Split a bundle into its manifest and verified objects.
+fn parse(bytes: &[u8]) -> Result<(BundleManifest, HashMap<ObjectHash, Object>), StoreError> {
+    let rest = bytes
+        .strip_prefix(BUNDLE_MAGIC)
+        .ok_or_else(|| corrupt("bad magic"))?;
+    let (header, section) = take_chunk(rest).ok_or_else(|| corrupt("truncated manifest"))?;
+    let file: ManifestFile =
+        serde_json::from_slice(header).map_err(|e| corrupt(format!("manifest: {e}")))?;
+    if file.hash_version != HASH_VERSION {
+        return Err(StoreError::Outdated {
+            found: file.hash_version,
+            expected: HASH_VERSION,
+        });
+    }
+    if hex(&Sha256::digest(section)) != file.checksum {
+        return Err(corrupt("checksum mismatch"));
+    }
+    let parse_hash =
+        |hex: &str| ObjectHash::from_hex(hex).ok_or_else(|| corrupt(format!("bad hash {hex:?}")));
+    let manifest = BundleManifest {
+        hash_version: file.hash_version,
+        refs: file
+            .refs
+            .iter()
+            .map(|(name, hex)| Ok((name.clone(), parse_hash(hex)?)))
+            .collect::<Result<_, StoreError>>()?,
+        commits: file
+            .commits
+            .iter()
+            .map(|hex| parse_hash(hex))
+            .collect::<Result<_, _>>()?,
+        objects: file.objects,
+    };
+
+    let mut objects = HashMap::new();
+    let mut rest = section;
+    while !rest.is_empty() {
+        let (hash, tail) = rest
+            .split_first_chunk::<32>()
+            .ok_or_else(|| corrupt("truncated object"))?;
+        let hash = ObjectHash::from_bytes(*hash);
+        let (encoded, tail) = take_chunk(tail).ok_or_else(|| corrupt("truncated object"))?;
+        rest = tail;
+        let (kind, payload) = store::decode_limited(&hash, encoded, MAX_BUNDLE_OBJECT_SIZE)?;
+        let object =
+            decode_object(kind, payload).map_err(|e| corrupt(format!("{kind} {hash}: {e}")))?;
+        let actual = match &object {
+            Object::Blob(b) => b.hash(),
+            Object::Tree(t) => t.hash(),
+            Object::Commit(c) => c.hash(),
+        };
+        if actual != hash {
+            return Err(corrupt(format!(
+                "{kind} listed as {hash} hashes to {actual}"
+            )));
+        }
+        objects.insert(hash, object);
+    }
+    if objects.len() != manifest.objects {
+        return Err(corrupt(format!(
+            "manifest lists {} objects, found {}",
+            manifest.objects,
+            objects.len()
+        )));
+    }
+    Ok((manifest, objects))
+}
+
+/// Split a `u32`-length-prefixed chunk off the front of `bytes`.
+fn take_chunk(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = bytes.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
+
+fn decode_object(kind: ObjectKind, payload: Vec<u8>) -> serde_json::Result<Object> {
+    Ok(match kind {
+        ObjectKind::Blob => Object::Blob(Blob::new(payload)),
+        ObjectKind::Tree => Object::Tree(Tree::decode(&payload)?),
+        ObjectKind::Commit => Object::Commit(Box::new(serde_json::from_slice(&payload)?)),
+    })
+}
+
+/// Every object reachable from `heads` must be in `objects`, with the
+/// kind it's referenced as.
+fn check_complete(
+    objects: &HashMap<ObjectHash, Object>,
+    heads: impl IntoIterator<Item = ObjectHash>,
+) -> Result<(), StoreError> {
+    let mut seen = HashSet::new();
+    let mut pending: Vec<(ObjectHash, ObjectKind)> =
+        heads.into_iter().map(|h| (h, ObjectKind::Commit)).collect();
+    while let Some((hash, kind)) = pending.pop() {
+        if !seen.insert(hash) {
+            continue;
+        }
+        match (kind, objects.get(&hash)) {
+            (_, None) => return Err(corrupt(format!("missing {kind} {hash}"))),
+            (ObjectKind::Commit, Some(Object::Commit(commit))) => {
+                pending.extend(commit.parent.map(|p| (p, ObjectKind::Commit)));
+                pending.extend(
+                    commit
+                        .extra_parents
+                        .iter()
+                        .map(|p| (*p, ObjectKind::Commit)),
+                );
+                pending.push((commit.tree, ObjectKind::Tree));
+            }
+            (ObjectKind::Tree, Some(Object::Tree(tree))) => {
+                pending.extend(tree.entries.values().map(entry_kind));
+            }
+            (ObjectKind::Blob, Some(Object::Blob(_))) => {}
+            _ => return Err(corrupt(format!("{hash} is not a {kind}"))),
+        }
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,197 @@
+//! Prompt-cache prediction: how much of the prompt cached for one
+//! commit the request for another would reuse, and what the
+//! difference costs.
+//!
+//! The request prefix is tools, then system prompt, then messages —
+//! the same order [`CacheBreak`] uses — and the provider caches it up
+//! to the last breakpoint, which the runtime places at the end of the
+//! conversation. So the next request reads the shared prefix from the
+//! cache and writes everything after it.
+//!
+//! Token counts are estimates: roughly four characters per token of
+//! text, a flat 1,200 tokens per image, and four bytes per token of a
+//! tool definition's JSON. Good enough to tell a cheap request from an
+//! expensive re-cache; not a substitute for the provider's reported
+//! usage.
+
+use tau_ai::{Content, CostBreakdown, Message, Model, Usage};
+
+use crate::branch::message_blobs;
+use crate::history::HistoryError;
+use crate::log::{CacheBreak, MessagesDiff, load_blob, root_tree, tool_entries};
+use crate::objects::{ObjectHash, Tree, TreeEntry};
+use crate::repository::Repository;
+
+/// Tokens charged for an image, whatever its size.
+const IMAGE_TOKENS: u64 = 1_200;
+
+/// What the request for one commit would reuse from the cache left by
+/// the request for another. Produced by [`Repository::predict_cache`].
+#[derive(Clone, Debug)]
+pub struct CachePrediction {
+    pub shared: SharedPrefix,
+    /// Why the cached prefix stops short of the whole previous prompt.
+    /// `None` if all of it is reused.
+    pub cache_break: Option<CacheBreak>,
+    /// Estimated tokens served from the cache.
+    pub cache_read_tokens: u64,
+    /// Estimated tokens written to the cache: everything after the
+    /// shared prefix.
+    pub cache_write_tokens: u64,
+    /// Estimated tokens of the previous prompt that are no longer
+    /// reused — what a tool change or compaction throws away.
+    pub invalidated_tokens: u64,
+    /// Projected input cost of the next request, in dollars. Output
+    /// isn't predicted.
+    pub cost: CostBreakdown,
+}
+
+/// The part of the prompt two requests have in common, in request
+/// order. Each part counts only if everything before it is shared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SharedPrefix {
+    pub tools: bool,
+    pub system_prompt: bool,
+    /// Leading messages both requests send.
+    pub messages: usize,
+}
+
+impl CachePrediction {
+    /// Dollars spent writing the cache for the next request — "this
+    /// will cost $X to re-cache".
+    pub fn recache_cost(&self) -> f64 {
+        self.cost.cache_write
+    }
+}
+
+/// Per-part token estimates for one commit's prompt.
+struct PromptTokens {
+    tools: u64,
+    system_prompt: u64,
+    messages: Vec<u64>,
+}
+
+impl PromptTokens {
+    fn total(&self) -> u64 {
+        self.tools + self.system_prompt + self.messages.iter().sum::<u64>()
+    }
+
+    fn prefix(&self, shared: &SharedPrefix) -> u64 {
+        let tools = if shared.tools { self.tools } else { 0 };
+        let system_prompt = if shared.system_prompt {
+            self.system_prompt
+        } else {
+            0
+        };
+        tools + system_prompt + self.messages[..shared.messages].iter().sum::<u64>()
+    }
+}
+
+impl Repository {
+    /// Predict cache reuse and input cost when the request for `next`
+    /// follows the request for `prev` on `model`. `prev` and `next`
+    /// needn't be related: unrelated commits simply share less.
+    pub fn predict_cache(
+        &self,
+        prev: &ObjectHash,
+        next: &ObjectHash,
+        model: &Model,
+    ) -> Result<CachePrediction, HistoryError> {
+        let diff = self.diff(prev, next)?;
+        let prev_root = root_tree(self, prev)?;
+        let next_root = root_tree(self, next)?;
+        let prev_tokens = prompt_tokens(self, &prev_root)?;
+        let next_tokens = prompt_tokens(self, &next_root)?;
+
+        let cache_break = diff.cache_break();
+        let shared = match cache_break {
+            Some(CacheBreak::Tools) => SharedPrefix::default(),
+            Some(CacheBreak::SystemPrompt) => SharedPrefix {
+                tools: true,
+                ..SharedPrefix::default()
+            },
+            Some(CacheBreak::Messages { at }) => SharedPrefix {
+                tools: true,
+                system_prompt: true,
+                messages: at,
+            },
+            None => SharedPrefix {
+                tools: true,
+                system_prompt: true,
+                messages: match diff.messages {
+                    MessagesDiff::Appended { .. } => prev_tokens.messages.len(),
+                    _ => next_tokens.messages.len(),
+                },
+            },
+        };
+
+        let cache_read_tokens = next_tokens.prefix(&shared);
+        let cache_write_tokens = next_tokens.total() - cache_read_tokens;
+        let usage = Usage {
+            input: cache_read_tokens + cache_write_tokens,
+            cache_read: cache_read_tokens,
+            cache_write: cache_write_tokens,
+            ..Usage::default()
+        };
+        Ok(CachePrediction {
+            shared,
+            cache_break,
+            cache_read_tokens,
+            cache_write_tokens,
+            invalidated_tokens: prev_tokens.total() - prev_tokens.prefix(&shared),
+            cost: usage.calculate_cost(model),
+        })
+    }
+}
+
+fn prompt_tokens(repo: &Repository, root: &Tree) -> Result<PromptTokens, HistoryError> {
+    let mut tools = 0;
+    for hash in tool_entries(repo, root)?.values() {
+        tools += load_blob(repo, hash)?.bytes.len() as u64 / 4;
+    }
+    let system_prompt = match root.get("system_prompt") {
+        Some(TreeEntry::Blob(h)) => {
+            let content: Vec<Content> = serde_json::from_slice(&load_blob(repo, h)?.bytes)?;
+            content_tokens(&content)
+        }
+        _ => 0,
+    };
+    let mut messages = Vec::new();
+    for hash in message_blobs(repo, root)? {
+        let message: Message = serde_json::from_slice(&load_blob(repo, &hash)?.bytes)?;
+        messages.push(match &message {
+            Message::User { content, .. }
+            | Message::Assistant { content, .. }
+            | Message::ToolResult { content, .. }
+            | Message::SystemInjection { content, .. } => content_tokens(content),
+        });
+    }
+    Ok(PromptTokens {
+        tools,
+        system_prompt,
+        messages,
+    })
+}
+
+fn content_tokens(content: &[Content]) -> u64 {
+    let json_len = |value: &serde_json::Value| serde_json::to_string(value).map_or(0, |s| s.len());
+    let mut chars = 0;
+    let mut images = 0;
+    for c in content {
+        chars += match c {
+            Content::Text { text } => text.len(),
+            Content::Thinking { thinking, .. } => thinking.len(),
+            Content::ToolCall {
+                name, arguments, ..
+            } => name.len() + json_len(arguments),
+            Content::Image { .. } => {
+                images += 1;
+                0
+            }
+            Content::RedactedThinking { data } => data.len(),
+            Content::ServerToolUse { name, input, .. } => name.len() + json_len(input),
+            Content::ServerToolResult { content, .. } => json_len(content),
+        };
+    }
+    chars as u64 / 4 + images * IMAGE_TOKENS
+}
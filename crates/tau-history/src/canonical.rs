@@ -0,0 +1,88 @@
+//! Canonical JSON — the byte form every hashed object and every
+//! JSON blob is written in.
+//!
+//! `serde_json` alone emits struct fields in declaration order and
+//! map keys in whatever order the map iterates, so the same value can
+//! serialize (and hash) differently across versions of this crate or
+//! its dependencies. The canonical form pins all of that down:
+//!
+//! - object keys sorted by their UTF-8 bytes
+//! - no insignificant whitespace
+//! - integral numbers written as integers (`1.0` → `1`, `-0.0` → `0`);
+//!   other floats in shortest round-trip form
+//! - strings escaped exactly as `serde_json` escapes them
+//!
+//! Hash inputs are additionally prefixed with [`HASH_VERSION`], so a
+//! future change to any of these rules produces new hashes rather
+//! than silently colliding with old ones.
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// Version of the hashing scheme. Part of every object's hash input
+/// and recorded by stores, which refuse to open data written under a
+/// different version until it's migrated (see
+/// [`migrate_store`](crate::migrate_store)).
+pub const HASH_VERSION: u32 = 1;
+
+/// Serialize `value` to canonical JSON bytes.
+pub(crate) fn to_vec<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Vec<u8>> {
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    write_value(&mut out, &value);
+    Ok(out)
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Value::Number(n) => write_number(out, n),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_value(out, item);
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            out.push(b'{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_string(out, key);
+                out.push(b':');
+                write_value(out, item);
+            }
+            out.push(b'}');
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, n: &Number) {
+    if n.is_i64() || n.is_u64() {
+        out.extend_from_slice(n.to_string().as_bytes());
+        return;
+    }
+    // JSON numbers can't be NaN or infinite, so `as_f64` always
+    // succeeds here.
+    let f = n.as_f64().unwrap_or_default();
+    // Exactly representable integers (|f| < 2^53) print as integers so
+    // `1.0` and `1` agree.
+    if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 {
+        out.extend_from_slice((f as i64).to_string().as_bytes());
+    } else {
+        out.extend_from_slice(Number::from_f64(f).expect("finite").to_string().as_bytes());
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    serde_json::to_writer(&mut *out, s).expect("writing to a Vec can't fail");
+}
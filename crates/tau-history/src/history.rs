@@ -15,10 +15,13 @@
 //!
 //! The three together describe the full prompt at the current tip.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use tau_ai::{Content, Message};
 
-use crate::objects::ToolDef;
+use crate::log::UserTurn;
+use crate::objects::{CommitMeta, ObjectHash, ToolDef};
 
 /// Error type for [`History`] operations.
 ///
@@ -95,7 +98,7 @@ impl From<HistoryError> for Box<dyn std::error::Error + Send + Sync> {
 /// see what it has said, what it can do, and to record what just
 /// happened.
 ///
-/// Six methods, all stated in conversation terms:
+/// Six required methods, all stated in conversation terms:
 ///
 /// - **Read** the model-visible messages ([`messages`](Self::messages)).
 /// - **Read** the system prompt in effect ([`system_prompt`](Self::system_prompt)).
@@ -106,10 +109,19 @@ impl From<HistoryError> for Box<dyn std::error::Error + Send + Sync> {
 /// - **Compact** an old prefix into a single summary message
 ///   ([`compact_prefix`](Self::compact_prefix)).
 ///
-/// Notably absent: forking, merging, tip-by-hash lookup,
-/// system-prompt / tools mutation. Those are graph-shaped operations
-/// the agent runtime doesn't need. They live on the concrete backend
-/// type — see [`Branch`](crate::Branch) for the git-flavored API.
+/// The rest have defaults. [`append_with`](Self::append_with),
+/// [`compact_prefix_with`](Self::compact_prefix_with) and
+/// [`prune`](Self::prune) are for backends that can record more;
+/// [`head`](Self::head), [`user_turns`](Self::user_turns),
+/// [`rewind`](Self::rewind) and [`fork_at`](Self::fork_at) are for
+/// ones that keep earlier states around, letting the runtime take a
+/// conversation back to before an earlier prompt. The defaults report
+/// no earlier states and refuse to go back.
+///
+/// Notably absent: merging, tip-by-hash lookup, system-prompt / tools
+/// mutation. Those are graph-shaped operations the agent runtime
+/// doesn't need. They live on the concrete backend type — see
+/// [`Branch`](crate::Branch) for the git-flavored API.
 /// `previous_summary` is read-only on the trait — the only writer is
 /// `compact_prefix`.
 ///
@@ -150,6 +162,18 @@ pub trait History: Send + Sync {
     /// becomes a single commit on the branch.
     async fn append(&self, messages: Vec<Message>) -> Result<(), HistoryError>;
 
+    /// [`append`](Self::append), recording `meta` (timestamp, author,
+    /// model, usage…) on the commit. Backends with nowhere to put
+    /// metadata keep the default, which drops it.
+    async fn append_with(
+        &self,
+        messages: Vec<Message>,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        let _ = meta;
+        self.append(messages).await
+    }
+
     /// Replace the first `end` messages with a single
     /// `summary_message`, and record `summary_text` as the new
     /// `previous_summary`. Both updates land in a single commit.
@@ -161,4 +185,60 @@ pub trait History: Send + Sync {
         summary_message: Message,
         summary_text: String,
     ) -> Result<(), HistoryError>;
+
+    /// [`compact_prefix`](Self::compact_prefix), recording `meta` on
+    /// the commit. The default drops it, like
+    /// [`append_with`](Self::append_with)'s.
+    async fn compact_prefix_with(
+        &self,
+        end: usize,
+        summary_message: Message,
+        summary_text: String,
+        meta: CommitMeta,
+    ) -> Result<(), HistoryError> {
+        let _ = meta;
+        self.compact_prefix(end, summary_message, summary_text)
+            .await
+    }
+
+    /// Replace the messages with `messages`, the same conversation
+    /// with old tool outputs trimmed — a compaction that prunes
+    /// instead of summarizing — recording `meta` on the commit.
+    /// `previous_summary` is left alone. Backends that can't rewrite
+    /// keep the default, which fails.
+    async fn prune(&self, messages: Vec<Message>, meta: CommitMeta) -> Result<(), HistoryError> {
+        let _ = (messages, meta);
+        Err(HistoryError::msg("this history can't be pruned"))
+    }
+
+    /// The point the history is at now, for [`rewind`](Self::rewind)
+    /// to come back to. `None` if it's empty or the backend can't go
+    /// back.
+    fn head(&self) -> Option<ObjectHash> {
+        None
+    }
+
+    /// The user prompts in this history, oldest first, each with the
+    /// point just before it.
+    async fn user_turns(&self) -> Result<Vec<UserTurn>, HistoryError> {
+        Ok(Vec::new())
+    }
+
+    /// Go back (or forward) to `to`, a point from
+    /// [`head`](Self::head) or [`user_turns`](Self::user_turns);
+    /// `None` empties the history. Returns the head it left, which
+    /// stays reachable so the abandoned line can be compared against
+    /// or returned to.
+    async fn rewind(&self, to: Option<ObjectHash>) -> Result<Option<ObjectHash>, HistoryError> {
+        let _ = to;
+        Err(HistoryError::msg("this history can't be rewound"))
+    }
+
+    /// A new, independent history starting at `at` (`None`: empty),
+    /// sharing everything before it. This history is left as it is
+    /// and stays reachable after it's dropped.
+    async fn fork_at(&self, at: Option<ObjectHash>) -> Result<Arc<dyn History>, HistoryError> {
+        let _ = at;
+        Err(HistoryError::msg("this history can't be forked"))
+    }
 }
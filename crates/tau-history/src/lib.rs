@@ -24,7 +24,16 @@
 //! `tools` + `previous_summary` — exactly the four pieces that
 //! shape the next API request. Hosts and the fleet work with
 //! [`Branch`] directly, using its git-flavored API (`commit`,
-//! `merge`, `fork`, `tip`) plus [`Repository`] for object access.
+//! `merge`, `fork`, `reset`, `tip`) plus [`Repository`] for object
+//! access and history walks (`log`, `merge_base`, `diff`,
+//! `user_turns`).
+//!
+//! Branches are anonymous until bound to a named ref
+//! (`refs/agents/<id>`, `refs/sessions/<id>`) with
+//! [`Repository::branch_named`]. Refs move only by compare-and-swap
+//! ([`Repository::update_ref`]), so two processes sharing a persisted
+//! repository can't overwrite each other's progress, and each ref
+//! keeps a reflog of its previous values.
 //!
 //! # Cache alignment
 //!
@@ -39,25 +48,63 @@
 //! from the tip commit; the previous request used a different
 //! subtree hash, so the cache invalidates from tools onward. The
 //! graph isn't describing the cache cost — it *is* the cache cost.
+//! [`Repository::predict_cache`] puts a number on it: the prefix two
+//! requests share, estimated cache-read and cache-write tokens, and
+//! the projected cost on a given model.
+//!
+//! # Persistence
+//!
+//! [`Repository::new`] is in-memory. [`Repository::open`] backs the
+//! same API with an [`FsStore`] directory — zstd-compressed loose
+//! objects, optional pack files, a tags file — loaded lazily, so a
+//! graph survives restarts and can be opened by several processes.
+//! Other backends implement [`ObjectStore`].
+//!
+//! To move history between repositories, [`Repository::export_bundle`]
+//! writes a set of heads and everything they reach to a single
+//! self-verifying file, and [`Repository::import_bundle`] reads one
+//! back in.
+//!
+//! # Hashing
+//!
+//! Hashes are SHA-256 over a kind and [`HASH_VERSION`] tag plus the
+//! object's canonical JSON (sorted keys, normalized numbers, no
+//! whitespace). Messages, tools and the system prompt are stored as
+//! canonical JSON blobs too, so equal values hash equally regardless
+//! of key order, struct field order or platform, and hashes are safe
+//! to use as durable identifiers. If the scheme ever changes,
+//! [`HASH_VERSION`] bumps, stores written under the old version are
+//! refused with [`StoreError::Outdated`], and
+//! [`Repository::migrate`] rewrites them.
 //!
 //! # Status
 //!
-//! Pre-1.0, in-process only. Hashing is SHA-256 of `serde_json`
-//! bytes, stable within a process but **not canonical across
-//! versions / platforms** — two semantically-equal messages may
-//! hash differently if their JSON serialization differs in key
-//! order or whitespace, or if struct fields are reordered (since
-//! `serde_json` emits fields in struct declaration order). Don't
-//! use the hashes as cross-process identifiers yet.
+//! Pre-1.0. The API and the on-disk layout may still change; hashes
+//! only change with a [`HASH_VERSION`] bump.
 
 mod branch;
+mod bundle;
+mod cache;
+mod canonical;
 mod history;
+mod log;
+mod migrate;
 mod objects;
+mod refs;
 mod repository;
+mod store;
 
-pub use repository::Repository;
 pub use branch::{Branch, MessagesOp, TreePatch};
+pub use bundle::{BundleImport, BundleManifest, MAX_BUNDLE_OBJECT_SIZE};
+pub use cache::{CachePrediction, SharedPrefix};
+pub use canonical::HASH_VERSION;
 pub use history::{History, HistoryError};
+pub use log::{CacheBreak, CommitDiff, Log, MessagesDiff, UserTurn};
+pub use migrate::migrate_store;
 pub use objects::{
-    Blob, Commit, ObjectHash, ObjectKind, StoreError, ToolDef, Tree, TreeEntry,
+    Blob, Commit, CommitKind, CommitMeta, ObjectHash, ObjectKind, StoreError, TokenUsage,
+    ToolDef, Tree, TreeEntry,
 };
+pub use refs::ReflogEntry;
+pub use repository::{GcReport, Repository};
+pub use store::{FsStore, ObjectStore, STALE_LOCK_AGE};
@@ -0,0 +1,403 @@
+//! Walking and comparing history: [`Log`] iterators, ancestry
+//! queries, [`CommitDiff`], and the [`UserTurn`]s a conversation can
+//! be rewound to.
+//!
+//! Everything here is a read-only view over commits already in a
+//! [`Repository`]; nothing writes. Walks take commit hashes rather
+//! than branches so they work equally on tips, tags and hashes a host
+//! recorded earlier.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use tau_ai::Message;
+
+use crate::branch::message_blobs;
+use crate::history::HistoryError;
+use crate::objects::{
+    Blob, Commit, CommitKind, ObjectHash, ObjectKind, StoreError, Tree, TreeEntry,
+};
+use crate::repository::Repository;
+
+/// Iterator over commits, newest first. Created by
+/// [`Repository::log`] and [`Repository::log_first_parent`].
+///
+/// A commit that can't be loaded is yielded as an error and ends the
+/// walk.
+pub struct Log<'a> {
+    repo: &'a Repository,
+    walk: Walk,
+}
+
+enum Walk {
+    FirstParent(Option<ObjectHash>),
+    /// The order is computed on the first call to `next`.
+    Dag {
+        from: Option<ObjectHash>,
+        order: VecDeque<ObjectHash>,
+    },
+}
+
+impl Iterator for Log<'_> {
+    type Item = Result<(ObjectHash, Arc<Commit>), StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.walk {
+            Walk::FirstParent(next) => {
+                let hash = next.take()?;
+                Some(load_commit(self.repo, &hash).map(|commit| {
+                    *next = commit.parent;
+                    (hash, commit)
+                }))
+            }
+            Walk::Dag { from, order } => {
+                if let Some(from) = from.take() {
+                    match topo_order(self.repo, from) {
+                        Ok(o) => *order = o.into(),
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                let hash = order.pop_front()?;
+                let loaded = load_commit(self.repo, &hash);
+                if loaded.is_err() {
+                    order.clear();
+                }
+                Some(loaded.map(|commit| (hash, commit)))
+            }
+        }
+    }
+}
+
+/// What changed between two commits' trees. Produced by
+/// [`Repository::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitDiff {
+    pub system_prompt_changed: bool,
+    /// Tool names present only in the newer commit.
+    pub tools_added: Vec<String>,
+    /// Tool names present only in the older commit.
+    pub tools_removed: Vec<String>,
+    /// Tool names present in both with a different definition.
+    pub tools_changed: Vec<String>,
+    pub messages: MessagesDiff,
+    pub summary_changed: bool,
+}
+
+/// How the message log changed between two commits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessagesDiff {
+    Unchanged,
+    /// Every old message is still there, in order, followed by
+    /// `count` new ones.
+    Appended {
+        count: usize,
+    },
+    /// The first `kept` messages agree; after that, `removed` old
+    /// messages were replaced by `added` new ones — a compaction, an
+    /// edit, or unrelated branches.
+    Rewritten {
+        kept: usize,
+        removed: usize,
+        added: usize,
+    },
+}
+
+/// A user prompt on a branch's timeline, found by
+/// [`Repository::user_turns`] — a point the conversation can be
+/// rewound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserTurn {
+    /// The commit that recorded the prompt, together with whatever
+    /// the agent added before the turn settled.
+    pub commit: ObjectHash,
+    /// The commit just before the prompt, `None` if the prompt was
+    /// the first thing committed. Going back to before this turn
+    /// means resetting here.
+    pub before: Option<ObjectHash>,
+    /// The prompt's text.
+    pub prompt: String,
+    /// Milliseconds since the Unix epoch, from the prompt message.
+    pub timestamp: i64,
+}
+
+/// Where a prompt cached for the older commit stops matching the
+/// newer one, in API request order: tools, then system prompt, then
+/// messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheBreak {
+    Tools,
+    SystemPrompt,
+    /// Messages from index `at` on differ.
+    Messages {
+        at: usize,
+    },
+}
+
+impl CommitDiff {
+    /// `true` if the two commits describe the same conversation
+    /// state.
+    pub fn is_empty(&self) -> bool {
+        !self.system_prompt_changed
+            && self.tools_added.is_empty()
+            && self.tools_removed.is_empty()
+            && self.tools_changed.is_empty()
+            && self.messages == MessagesDiff::Unchanged
+            && !self.summary_changed
+    }
+
+    /// Why a prompt cached for the older commit wouldn't be reused by
+    /// the newer one. `None` when only messages were appended (the
+    /// cached prefix still matches) or nothing the model sees changed —
+    /// the previous summary isn't part of the prompt.
+    pub fn cache_break(&self) -> Option<CacheBreak> {
+        if !self.tools_added.is_empty()
+            || !self.tools_removed.is_empty()
+            || !self.tools_changed.is_empty()
+        {
+            return Some(CacheBreak::Tools);
+        }
+        if self.system_prompt_changed {
+            return Some(CacheBreak::SystemPrompt);
+        }
+        match self.messages {
+            MessagesDiff::Rewritten { kept, .. } => Some(CacheBreak::Messages { at: kept }),
+            MessagesDiff::Unchanged | MessagesDiff::Appended { .. } => None,
+        }
+    }
+}
+
+impl Repository {
+    /// `git log --topo-order` — every commit reachable from `from`,
+    /// each after all of its descendants. At a merge, the first-parent
+    /// line is listed before the merged-in side.
+    pub fn log(&self, from: ObjectHash) -> Log<'_> {
+        Log {
+            repo: self,
+            walk: Walk::Dag {
+                from: Some(from),
+                order: VecDeque::new(),
+            },
+        }
+    }
+
+    /// `git log --first-parent` — `from`, its parent, its parent's
+    /// parent… ignoring merged-in `extra_parents`. The branch's own
+    /// timeline.
+    pub fn log_first_parent(&self, from: ObjectHash) -> Log<'_> {
+        Log {
+            repo: self,
+            walk: Walk::FirstParent(Some(from)),
+        }
+    }
+
+    /// `git merge-base` — a best common ancestor of `a` and `b` (one
+    /// that isn't an ancestor of another common ancestor), or `None`
+    /// if they share no history. A commit counts as its own ancestor.
+    pub fn merge_base(
+        &self,
+        a: &ObjectHash,
+        b: &ObjectHash,
+    ) -> Result<Option<ObjectHash>, StoreError> {
+        let of_a: HashSet<ObjectHash> = topo_order(self, *a)?.into_iter().collect();
+        // Topological order lists a commit before its ancestors, so
+        // the first common one found isn't behind any other.
+        Ok(topo_order(self, *b)?.into_iter().find(|h| of_a.contains(h)))
+    }
+
+    /// `git merge-base --is-ancestor` — whether `ancestor` is
+    /// reachable from `descendant` (or equal to it).
+    pub fn is_ancestor(
+        &self,
+        ancestor: &ObjectHash,
+        descendant: &ObjectHash,
+    ) -> Result<bool, StoreError> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([*descendant]);
+        while let Some(hash) = queue.pop_front() {
+            if hash == *ancestor {
+                return Ok(true);
+            }
+            if !seen.insert(hash) {
+                continue;
+            }
+            let commit = load_commit(self, &hash)?;
+            queue.extend(commit.parent);
+            queue.extend(commit.extra_parents.iter().copied());
+        }
+        Ok(false)
+    }
+
+    /// Structural diff from commit `a` to commit `b`. Compares
+    /// subtree and blob hashes, so unchanged parts cost nothing to
+    /// check; only the message logs are read when they differ.
+    pub fn diff(&self, a: &ObjectHash, b: &ObjectHash) -> Result<CommitDiff, HistoryError> {
+        let a_root = root_tree(self, a)?;
+        let b_root = root_tree(self, b)?;
+
+        let a_tools = tool_entries(self, &a_root)?;
+        let b_tools = tool_entries(self, &b_root)?;
+        let mut tools_added = Vec::new();
+        let mut tools_changed = Vec::new();
+        for (name, hash) in &b_tools {
+            match a_tools.get(name) {
+                None => tools_added.push(name.clone()),
+                Some(old) if old != hash => tools_changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        let tools_removed = a_tools
+            .keys()
+            .filter(|name| !b_tools.contains_key(*name))
+            .cloned()
+            .collect();
+
+        let messages = if a_root.get("messages") == b_root.get("messages") {
+            MessagesDiff::Unchanged
+        } else {
+            let old = message_blobs(self, &a_root)?;
+            let new = message_blobs(self, &b_root)?;
+            let kept = old.iter().zip(&new).take_while(|(x, y)| x == y).count();
+            if kept == old.len() && kept == new.len() {
+                MessagesDiff::Unchanged
+            } else if kept == old.len() {
+                MessagesDiff::Appended {
+                    count: new.len() - kept,
+                }
+            } else {
+                MessagesDiff::Rewritten {
+                    kept,
+                    removed: old.len() - kept,
+                    added: new.len() - kept,
+                }
+            }
+        };
+
+        Ok(CommitDiff {
+            system_prompt_changed: a_root.get("system_prompt") != b_root.get("system_prompt"),
+            tools_added,
+            tools_removed,
+            tools_changed,
+            messages,
+            summary_changed: a_root.get("previous_summary") != b_root.get("previous_summary"),
+        })
+    }
+
+    /// The user prompts on the first-parent line ending at `tip`,
+    /// oldest first: every commit whose messages extend its parent's
+    /// with a user message first. Compactions rewrite messages rather
+    /// than extend them, so they're never turns, but the turns before
+    /// one stay listed — their commits are still in the graph.
+    /// [`Reprompt`](CommitKind::Reprompt) and
+    /// [`Seed`](CommitKind::Seed) commits aren't the user's, so they
+    /// aren't listed either.
+    pub fn user_turns(&self, tip: ObjectHash) -> Result<Vec<UserTurn>, HistoryError> {
+        let mut line = self.log_first_parent(tip).collect::<Result<Vec<_>, _>>()?;
+        line.reverse();
+
+        let mut turns = Vec::new();
+        let mut parent_blobs = Vec::new();
+        for (hash, commit) in line {
+            let root = load_tree(self, &commit.tree)?;
+            let blobs = message_blobs(self, &root)?;
+            let synthetic = matches!(
+                commit.meta.kind,
+                Some(CommitKind::Reprompt | CommitKind::Seed)
+            );
+            if !synthetic && blobs.len() > parent_blobs.len() && blobs.starts_with(&parent_blobs) {
+                let first = load_blob(self, &blobs[parent_blobs.len()])?;
+                let message: Message = serde_json::from_slice(&first.bytes)?;
+                if let Message::User { timestamp, .. } = &message {
+                    turns.push(UserTurn {
+                        commit: hash,
+                        before: commit.parent,
+                        prompt: message.text(),
+                        timestamp: *timestamp,
+                    });
+                }
+            }
+            parent_blobs = blobs;
+        }
+        Ok(turns)
+    }
+}
+
+fn load_commit(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Commit>, StoreError> {
+    repo.try_get_commit(hash)?.ok_or(StoreError::NotFound {
+        hash: *hash,
+        kind: ObjectKind::Commit,
+    })
+}
+
+pub(crate) fn load_tree(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Tree>, StoreError> {
+    repo.try_get_tree(hash)?.ok_or(StoreError::NotFound {
+        hash: *hash,
+        kind: ObjectKind::Tree,
+    })
+}
+
+pub(crate) fn load_blob(repo: &Repository, hash: &ObjectHash) -> Result<Arc<Blob>, StoreError> {
+    repo.try_get_blob(hash)?.ok_or(StoreError::NotFound {
+        hash: *hash,
+        kind: ObjectKind::Blob,
+    })
+}
+
+pub(crate) fn root_tree(repo: &Repository, commit: &ObjectHash) -> Result<Arc<Tree>, StoreError> {
+    load_tree(repo, &load_commit(repo, commit)?.tree)
+}
+
+/// Tool name → definition blob hash.
+pub(crate) fn tool_entries(
+    repo: &Repository,
+    root: &Tree,
+) -> Result<BTreeMap<String, ObjectHash>, StoreError> {
+    let tools = match root.get("tools") {
+        Some(TreeEntry::Tree(h)) => load_tree(repo, h)?,
+        Some(TreeEntry::Blob(_)) => {
+            return Err(StoreError::TypeMismatch {
+                path: "tools".into(),
+                expected: ObjectKind::Tree,
+                actual: ObjectKind::Blob,
+            });
+        }
+        None => return Ok(BTreeMap::new()),
+    };
+    let mut entries = BTreeMap::new();
+    for (name, entry) in &tools.entries {
+        let TreeEntry::Blob(hash) = entry else {
+            return Err(StoreError::TypeMismatch {
+                path: format!("tools/{name}"),
+                expected: ObjectKind::Blob,
+                actual: ObjectKind::Tree,
+            });
+        };
+        entries.insert(name.clone(), *hash);
+    }
+    Ok(entries)
+}
+
+/// Every commit reachable from `from`, children before parents.
+/// Reversed post-order of a depth-first walk that explores extra
+/// parents before the primary one, so the first-parent line comes out
+/// ahead of merged-in side branches. Iterative so long histories don't
+/// exhaust the stack.
+fn topo_order(repo: &Repository, from: ObjectHash) -> Result<Vec<ObjectHash>, StoreError> {
+    let mut seen = HashSet::new();
+    let mut post = Vec::new();
+    let mut stack = vec![(from, false)];
+    while let Some((hash, expanded)) = stack.pop() {
+        if expanded {
+            post.push(hash);
+            continue;
+        }
+        if !seen.insert(hash) {
+            continue;
+        }
+        let commit = load_commit(repo, &hash)?;
+        stack.push((hash, true));
+        stack.extend(commit.parent.map(|p| (p, false)));
+        stack.extend(commit.extra_parents.iter().map(|p| (*p, false)));
+    }
+    post.reverse();
+    Ok(post)
+}
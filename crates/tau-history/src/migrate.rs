@@ -0,0 +1,301 @@
+//! Rewriting a store written under an older
+//! [`HASH_VERSION`](crate::HASH_VERSION).
+//!
+//! A hash-scheme change gives every object a new hash, and since trees
+//! and commits embed their children's hashes, the whole graph has to
+//! be rebuilt bottom-up: blobs, then the trees that hold them, then
+//! commits in parent-first order. JSON blobs are re-encoded in
+//! canonical form on the way through; the `previous_summary` blob is
+//! plain text and is copied byte-for-byte.
+//!
+//! Every commit in the source store is migrated along with everything
+//! it references. Trees and blobs no commit references are dropped.
+//! Tags, refs and reflogs are carried over with their hashes rewritten.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::canonical::{self, HASH_VERSION};
+use crate::objects::{Blob, Commit, ObjectHash, ObjectKind, StoreError, Tree, TreeEntry};
+use crate::refs::{ReflogEntry, check_ref_name};
+use crate::store::{FsStore, ObjectStore};
+
+/// Copy every commit in `from` into `to` under the current hashing
+/// scheme, rewrite the tags and refs to match, replaying each ref's
+/// reflog, and mark `to` as current.
+/// Returns the old → new hash of every object migrated, so hosts can
+/// rewrite hashes they recorded elsewhere.
+///
+/// `from` is only read. Neither store should be written by anyone else
+/// while this runs.
+pub fn migrate_store(
+    from: &dyn ObjectStore,
+    to: &dyn ObjectStore,
+) -> Result<HashMap<ObjectHash, ObjectHash>, StoreError> {
+    let refs = from.list_refs()?.into_keys().collect();
+    migrate_with_refs(from, to, refs)
+}
+
+/// [`migrate_store`], replaying the reflogs of `refs`, which may
+/// include refs that have since been deleted.
+fn migrate_with_refs(
+    from: &dyn ObjectStore,
+    to: &dyn ObjectStore,
+    refs: BTreeSet<String>,
+) -> Result<HashMap<ObjectHash, ObjectHash>, StoreError> {
+    let mut objects = HashMap::new();
+    for hash in from.list()? {
+        if let Some(object) = from.read(&hash)? {
+            objects.insert(hash, object);
+        }
+    }
+    let mut migrator = Migrator {
+        objects,
+        to,
+        mapped: HashMap::new(),
+    };
+    let mut commits: Vec<ObjectHash> = migrator
+        .objects
+        .iter()
+        .filter(|(_, (kind, _))| *kind == ObjectKind::Commit)
+        .map(|(hash, _)| *hash)
+        .collect();
+    commits.sort_by_key(|h| *h.as_bytes());
+    for hash in commits {
+        migrator.commit(hash)?;
+    }
+
+    let tags = from
+        .read_tags()?
+        .into_iter()
+        .map(|(name, hash)| (name, migrator.remap(hash)))
+        .collect();
+    to.write_tags(&tags)?;
+    for name in refs {
+        migrator.reflog(from, &name)?;
+    }
+    to.write_version(HASH_VERSION)?;
+    Ok(migrator.mapped)
+}
+
+/// In-place migration of the [`FsStore`] directory at `path`: migrate
+/// into a sibling directory, then swap it in. A no-op for a store
+/// that's already current.
+pub(crate) fn migrate_dir(path: &Path) -> Result<HashMap<ObjectHash, ObjectHash>, StoreError> {
+    let from = FsStore::open(path)?;
+    match from.read_version()? {
+        None => {
+            from.write_version(HASH_VERSION)?;
+            return Ok(HashMap::new());
+        }
+        Some(HASH_VERSION) => return Ok(HashMap::new()),
+        Some(_) => {}
+    }
+
+    let staging = sibling(path, "migrating")?;
+    match fs::remove_dir_all(&staging) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    // Reflogs outlive their refs, so list them from `logs/` too.
+    let mut refs: BTreeSet<String> = from.list_refs()?.into_keys().collect();
+    refs.extend(logged_refs(&path.join("logs"))?);
+    let mapped = migrate_with_refs(&from, &FsStore::open(&staging)?, refs)?;
+    drop(from);
+
+    let backup = sibling(path, "pre-migration")?;
+    fs::rename(path, &backup)?;
+    fs::rename(&staging, path)?;
+    fs::remove_dir_all(&backup)?;
+    Ok(mapped)
+}
+
+/// The name of every reflog under `logs`.
+fn logged_refs(logs: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut dirs = vec![(String::new(), logs.to_path_buf())];
+    while let Some((prefix, dir)) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let name = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            if entry.file_type()?.is_dir() {
+                dirs.push((name, entry.path()));
+            } else if check_ref_name(&name).is_ok() {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn sibling(path: &Path, suffix: &str) -> io::Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("can't migrate {} in place", path.display()),
+        )
+    })?;
+    let mut name = name.to_os_string();
+    name.push(format!(".{suffix}"));
+    Ok(path.with_file_name(name))
+}
+
+struct Migrator<'a> {
+    objects: HashMap<ObjectHash, (ObjectKind, Vec<u8>)>,
+    to: &'a dyn ObjectStore,
+    mapped: HashMap<ObjectHash, ObjectHash>,
+}
+
+impl Migrator<'_> {
+    /// New hash for `old`, or `old` itself if it was never migrated
+    /// (a dangling reference in the source store).
+    fn remap(&self, old: ObjectHash) -> ObjectHash {
+        self.mapped.get(&old).copied().unwrap_or(old)
+    }
+
+    fn payload(&self, hash: ObjectHash, kind: ObjectKind) -> Result<&[u8], StoreError> {
+        match self.objects.get(&hash) {
+            Some((k, payload)) if *k == kind => Ok(payload),
+            Some((actual, _)) => Err(StoreError::Corrupt {
+                what: format!("{hash} is a {actual}, referenced as a {kind}"),
+            }),
+            None => Err(StoreError::NotFound { hash, kind }),
+        }
+    }
+
+    /// Replay `name`'s reflog from `from` into `to` with every hash
+    /// rewritten, then point the ref where it points in `from`.
+    fn reflog(&self, from: &dyn ObjectStore, name: &str) -> Result<(), StoreError> {
+        let remap = |hash: Option<ObjectHash>| hash.map(|h| self.remap(h));
+        for entry in from.read_reflog(name)? {
+            let entry = ReflogEntry {
+                old: remap(entry.old),
+                new: remap(entry.new),
+                ..entry
+            };
+            let current = self.to.read_ref(name)?;
+            self.to.update_ref(name, current, entry.new, &entry)?;
+        }
+        // A reflog that doesn't end where the ref points (or a ref
+        // with no reflog) still leaves the ref right.
+        let target = remap(from.read_ref(name)?);
+        let current = self.to.read_ref(name)?;
+        if current != target {
+            let entry = ReflogEntry {
+                old: current,
+                new: target,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as i64),
+                message: "migrate".into(),
+            };
+            self.to.update_ref(name, current, target, &entry)?;
+        }
+        Ok(())
+    }
+
+    /// Migrate a commit and, first, its not-yet-migrated ancestors.
+    /// Iterative so long histories don't exhaust the stack.
+    fn commit(&mut self, root: ObjectHash) -> Result<(), StoreError> {
+        let mut stack = vec![root];
+        while let Some(&hash) = stack.last() {
+            if self.mapped.contains_key(&hash) {
+                stack.pop();
+                continue;
+            }
+            let commit: Commit = decode(self.payload(hash, ObjectKind::Commit)?, hash)?;
+            let pending: Vec<ObjectHash> = commit
+                .parent
+                .iter()
+                .chain(&commit.extra_parents)
+                .filter(|p| !self.mapped.contains_key(p) && self.objects.contains_key(p))
+                .copied()
+                .collect();
+            if !pending.is_empty() {
+                stack.extend(pending);
+                continue;
+            }
+            stack.pop();
+            let migrated = Commit {
+                parent: commit.parent.map(|p| self.remap(p)),
+                extra_parents: commit
+                    .extra_parents
+                    .iter()
+                    .map(|p| self.remap(*p))
+                    .collect(),
+                tree: self.tree(commit.tree, true)?,
+                meta: commit.meta,
+            };
+            let new = migrated.hash();
+            self.to
+                .write(&new, ObjectKind::Commit, &migrated.encode())?;
+            self.mapped.insert(hash, new);
+        }
+        Ok(())
+    }
+
+    fn tree(&mut self, hash: ObjectHash, is_root: bool) -> Result<ObjectHash, StoreError> {
+        if let Some(new) = self.mapped.get(&hash) {
+            return Ok(*new);
+        }
+        // Version-0 stores serialized the whole struct, not just the
+        // entries.
+        let tree: Tree = decode(self.payload(hash, ObjectKind::Tree)?, hash)?;
+        let mut migrated = Tree::new();
+        for (name, entry) in tree.entries {
+            let entry = match entry {
+                TreeEntry::Tree(h) => TreeEntry::Tree(self.tree(h, false)?),
+                TreeEntry::Blob(h) => {
+                    let text = is_root && name == "previous_summary";
+                    TreeEntry::Blob(self.blob(h, !text)?)
+                }
+            };
+            migrated.insert(name, entry);
+        }
+        let new = migrated.hash();
+        self.to.write(&new, ObjectKind::Tree, &migrated.encode())?;
+        self.mapped.insert(hash, new);
+        Ok(new)
+    }
+
+    /// `json` blobs are re-encoded canonically; anything that doesn't
+    /// parse is copied as-is.
+    fn blob(&mut self, hash: ObjectHash, json: bool) -> Result<ObjectHash, StoreError> {
+        if let Some(new) = self.mapped.get(&hash) {
+            return Ok(*new);
+        }
+        let payload = self.payload(hash, ObjectKind::Blob)?;
+        let bytes = json
+            .then(|| serde_json::from_slice::<serde_json::Value>(payload).ok())
+            .flatten()
+            .and_then(|value| canonical::to_vec(&value).ok())
+            .unwrap_or_else(|| payload.to_vec());
+        let blob = Blob::new(bytes);
+        let new = blob.hash();
+        self.to.write(&new, ObjectKind::Blob, &blob.bytes)?;
+        self.mapped.insert(hash, new);
+        Ok(new)
+    }
+}
+
+fn decode<T: serde::de::DeserializeOwned>(
+    payload: &[u8],
+    hash: ObjectHash,
+) -> Result<T, StoreError> {
+    serde_json::from_slice(payload).map_err(|e| StoreError::Corrupt {
+        what: format!("object {hash}: {e}"),
+    })
+}
@@ -1,10 +1,10 @@
 //! The three git object types: [`Blob`], [`Tree`], [`Commit`].
 //!
 //! Everything in the repository is one of these. All three are
-//! content-addressed via [`Hash`] (SHA-256 of a type-tagged
-//! serialization). The type tag in the hash input means a blob's
-//! hash is distinct from a tree's hash even when the underlying
-//! bytes coincide.
+//! content-addressed via [`ObjectHash`] (SHA-256 of a type- and
+//! version-tagged [canonical](crate::canonical) serialization). The
+//! type tag in the hash input means a blob's hash is distinct from a
+//! tree's hash even when the underlying bytes coincide.
 
 use std::collections::BTreeMap;
 use std::fmt;
@@ -12,31 +12,10 @@ use std::io;
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use tau_ai::Usage;
 use thiserror::Error;
 
-/// Adapter that lets `serde_json::to_writer` stream bytes directly
-/// into a `Sha256` hasher, avoiding an intermediate `Vec<u8>`
-/// allocation when hashing trees and commits.
-struct SinkAdapter<'a> {
-    hasher: &'a mut Sha256,
-}
-
-impl<'a> SinkAdapter<'a> {
-    fn new(hasher: &'a mut Sha256) -> Self {
-        Self { hasher }
-    }
-}
-
-impl<'a> io::Write for SinkAdapter<'a> {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.hasher.update(buf);
-        Ok(buf.len())
-    }
-
-    fn flush(&mut self) -> io::Result<()> {
-        Ok(())
-    }
-}
+use crate::canonical::{self, HASH_VERSION};
 
 /// Content hash. One uniform type for all three object kinds (blob,
 /// tree, commit) — the type tag is folded into the hash input so
@@ -59,14 +38,25 @@ impl ObjectHash {
         ObjectHash(bytes)
     }
 
-    pub(crate) fn compute_blob(bytes: &[u8]) -> Self {
-        Self::compute_with_tag(b"blob\0", bytes)
+    /// Parse the 64-character lowercase hex form produced by
+    /// `Display`. Returns `None` for anything else.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 64 || !hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return None;
+        }
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(ObjectHash(bytes))
     }
 
-    fn compute_with_tag(tag: &[u8], bytes: &[u8]) -> Self {
+    /// Hash an object's serialized payload. The input is
+    /// `"<kind> v<HASH_VERSION>\0"` followed by the payload.
+    pub(crate) fn compute(kind: ObjectKind, payload: &[u8]) -> Self {
         let mut h = Sha256::new();
-        h.update(tag);
-        h.update(bytes);
+        h.update(format!("{kind} v{HASH_VERSION}\0").as_bytes());
+        h.update(payload);
         ObjectHash(h.finalize().into())
     }
 }
@@ -101,7 +91,8 @@ impl fmt::Display for ObjectHash {
 /// [`Message`](tau_ai::Message), a tool's parameter schema, a system
 /// prompt's content blocks) by serializing it to bytes and putting
 /// the result in a `Blob`. The repository doesn't know or care what
-/// the bytes mean.
+/// the bytes mean — but the blobs it writes itself hold
+/// [canonical](crate::canonical) JSON, so equal values share a blob.
 #[derive(Clone, Debug)]
 pub struct Blob {
     pub bytes: Vec<u8>,
@@ -115,7 +106,7 @@ impl Blob {
     }
 
     pub fn hash(&self) -> ObjectHash {
-        ObjectHash::compute_blob(&self.bytes)
+        ObjectHash::compute(ObjectKind::Blob, &self.bytes)
     }
 }
 
@@ -141,8 +132,8 @@ impl TreeEntry {
 /// whose contents didn't change between parent and child commits
 /// is referenced by the same hash in both.
 ///
-/// `entries` is a `BTreeMap` so iteration (and thus serialization
-/// for hashing) is deterministic by key order.
+/// `entries` is a `BTreeMap` so iteration is deterministic by key
+/// order; the hash input is the canonical JSON of `entries`.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Tree {
     pub entries: BTreeMap<String, TreeEntry>,
@@ -156,11 +147,19 @@ impl Tree {
     }
 
     pub fn hash(&self) -> ObjectHash {
-        let mut hasher = Sha256::new();
-        hasher.update(b"tree\0");
-        serde_json::to_writer(SinkAdapter::new(&mut hasher), &self.entries)
-            .expect("Tree entries serialize to JSON");
-        ObjectHash(hasher.finalize().into())
+        ObjectHash::compute(ObjectKind::Tree, &self.encode())
+    }
+
+    /// The canonical payload: what's hashed and what stores persist.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        canonical::to_vec(&self.entries).expect("Tree entries serialize to JSON")
+    }
+
+    /// Inverse of [`encode`](Self::encode).
+    pub(crate) fn decode(payload: &[u8]) -> serde_json::Result<Self> {
+        Ok(Self {
+            entries: serde_json::from_slice(payload)?,
+        })
     }
 
     pub fn get(&self, name: &str) -> Option<&TreeEntry> {
@@ -184,6 +183,11 @@ impl Tree {
 /// prompt, tools, messages. Walking back through parents shows how
 /// the state evolved; the tree at any commit is the snapshot at that
 /// moment.
+///
+/// `meta` is part of the commit's hash, so two commits of the same
+/// tree at different times are distinct commits. It's never part of
+/// a tree's hash, so it can't disturb prefix sharing or cache
+/// alignment. Empty metadata is left out of the hash input entirely.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Commit {
     pub parent: Option<ObjectHash>,
@@ -194,15 +198,151 @@ pub struct Commit {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_parents: Vec<ObjectHash>,
     pub tree: ObjectHash,
+    #[serde(default, skip_serializing_if = "CommitMeta::is_empty")]
+    pub meta: CommitMeta,
 }
 
 impl Commit {
     pub fn hash(&self) -> ObjectHash {
-        let mut hasher = Sha256::new();
-        hasher.update(b"commit\0");
-        serde_json::to_writer(SinkAdapter::new(&mut hasher), self)
-            .expect("Commit serializes to JSON");
-        ObjectHash(hasher.finalize().into())
+        ObjectHash::compute(ObjectKind::Commit, &self.encode())
+    }
+
+    /// The canonical payload: what's hashed and what stores persist.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        canonical::to_vec(self).expect("Commit serializes to JSON")
+    }
+}
+
+/// Why a commit was made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitKind {
+    /// One agent turn's messages.
+    Turn,
+    /// A turn started by a prompt the host generated, like a goal
+    /// check's feedback or a restart's resume prompt, rather than one
+    /// the user typed.
+    Reprompt,
+    /// Messages a history was started with, carried over from
+    /// elsewhere rather than produced in it.
+    Seed,
+    /// A compaction replacing an old prefix with a summary.
+    Compaction,
+    /// A host-side edit: system prompt or tools changed, messages
+    /// rewritten by hand.
+    Edit,
+}
+
+/// Optional audit data recorded on a [`Commit`]: when, by whom, with
+/// which model, at what cost, and why. Every field is optional;
+/// unset fields are omitted from the commit's serialization (and so
+/// from its hash).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CommitMeta {
+    /// Milliseconds since the Unix epoch, like message timestamps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// The agent (or user) that produced the commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tokens spent producing the commit's messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CommitKind>,
+    /// Free-form description, like a git commit message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl CommitMeta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timestamp(mut self, millis: i64) -> Self {
+        self.timestamp = Some(millis);
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_usage(mut self, usage: impl Into<TokenUsage>) -> Self {
+        self.usage = Some(usage.into());
+        self
+    }
+
+    pub fn with_kind(mut self, kind: CommitKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// `true` if no field is set.
+    pub fn is_empty(&self) -> bool {
+        self.timestamp.is_none()
+            && self.author.is_none()
+            && self.model.is_none()
+            && self.usage.is_none()
+            && self.kind.is_none()
+            && self.message.is_none()
+    }
+}
+
+/// Token counts recorded in [`CommitMeta::usage`]. A fixed set of
+/// fields, all always serialized, so a field added to
+/// [`tau_ai::Usage`] doesn't change how existing commits hash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_write: u64,
+    pub thinking: u64,
+}
+
+impl From<&Usage> for TokenUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            input: usage.input,
+            output: usage.output,
+            cache_read: usage.cache_read,
+            cache_write: usage.cache_write,
+            thinking: usage.thinking,
+        }
+    }
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self::from(&usage)
+    }
+}
+
+impl From<TokenUsage> for Usage {
+    fn from(usage: TokenUsage) -> Self {
+        Usage {
+            input: usage.input,
+            output: usage.output,
+            cache_read: usage.cache_read,
+            cache_write: usage.cache_write,
+            thinking: usage.thinking,
+            ..Usage::default()
+        }
     }
 }
 
@@ -224,6 +364,16 @@ pub struct ToolDef {
     pub parameters_schema: serde_json::Value,
 }
 
+impl ToolDef {
+    /// Hash of the blob this definition is stored as under
+    /// `/tools/<name>`. Independent of key order in
+    /// `parameters_schema`, so it's stable across hosts that build
+    /// the same schema differently.
+    pub fn hash(&self) -> ObjectHash {
+        Blob::new(canonical::to_vec(self).expect("ToolDef serializes to JSON")).hash()
+    }
+}
+
 /// What kind of object the repository was asked for vs what it
 /// found. Used by [`StoreError`] to disambiguate hash-not-found
 /// cases.
@@ -269,4 +419,47 @@ pub enum StoreError {
         expected: ObjectKind,
         actual: ObjectKind,
     },
+
+    /// The backing [`ObjectStore`](crate::ObjectStore) couldn't be
+    /// read or written.
+    #[error("object store I/O failed: {0}")]
+    Io(#[from] io::Error),
+
+    /// The backing store returned bytes that don't decode, or that
+    /// don't hash to the name they're stored under.
+    #[error("corrupt object store: {what}")]
+    Corrupt { what: String },
+
+    /// The store was written under a different hashing scheme. Run
+    /// [`Repository::migrate`](crate::Repository::migrate) (or
+    /// [`migrate_store`](crate::migrate_store)) to rewrite it.
+    #[error("object store uses hash version {found}, expected {expected}; migrate it first")]
+    Outdated { found: u32, expected: u32 },
+
+    /// A compare-and-swap ref update found the ref somewhere other
+    /// than `expected` — another branch or process moved it first.
+    /// Re-read the ref and decide whether to retry.
+    #[error("ref {name} is at {}, expected {}", show_ref(.actual), show_ref(.expected))]
+    RefConflict {
+        name: String,
+        expected: Option<ObjectHash>,
+        actual: Option<ObjectHash>,
+    },
+
+    /// Another update to the ref is in progress. Transient; retry. A
+    /// process that died mid-update leaves the ref locked until its
+    /// `.lock` file is [`STALE_LOCK_AGE`](crate::STALE_LOCK_AGE)
+    /// old; the next update then removes it.
+    #[error("ref {name} is locked by another update")]
+    RefLocked { name: String },
+
+    /// A ref name wasn't `refs/` followed by one or more non-empty
+    /// path components, like `refs/agents/<id>`, or fell under the
+    /// reserved `refs/tags`.
+    #[error("invalid ref name {name:?}")]
+    InvalidRefName { name: String },
+}
+
+fn show_ref(hash: &Option<ObjectHash>) -> String {
+    hash.map_or_else(|| "nothing".into(), |h| h.to_string())
 }
@@ -0,0 +1,218 @@
+//! Named refs: shared, movable pointers to commits, updated by
+//! compare-and-swap.
+//!
+//! Tags bookmark commits a host wants to find again. Refs track
+//! branches that keep moving — `refs/agents/<id>`,
+//! `refs/sessions/<id>` — possibly from more than one process. Every
+//! update names the value it expects to replace and fails with
+//! [`StoreError::RefConflict`] if the ref has moved since, so two
+//! writers can't silently overwrite each other. Every successful
+//! update is appended to the ref's reflog.
+//!
+//! In memory the refs live on the [`Repository`]; with a backing
+//! store they live only in the store, so every read sees other
+//! processes' updates.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::branch::Branch;
+use crate::objects::{ObjectHash, StoreError};
+use crate::repository::Repository;
+
+/// One update of a ref, oldest first in [`Repository::reflog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflogEntry {
+    /// `None` when the update created the ref.
+    pub old: Option<ObjectHash>,
+    /// `None` when the update deleted the ref.
+    pub new: Option<ObjectHash>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub message: String,
+}
+
+/// Refs and reflogs of a repository without a backing store.
+#[derive(Default)]
+pub(crate) struct MemRefs {
+    refs: BTreeMap<String, ObjectHash>,
+    logs: HashMap<String, Vec<ReflogEntry>>,
+}
+
+impl MemRefs {
+    pub(crate) fn heads(&self) -> impl Iterator<Item = ObjectHash> + '_ {
+        self.refs.values().copied()
+    }
+}
+
+/// Reject anything that isn't `refs/` followed by one or more
+/// path components. Components can't be empty, start with `.`, end
+/// in `.lock`, or contain whitespace, control characters or any of
+/// `\ : ? * [ ~ ^`. `refs/tags` is reserved for the tag set.
+pub(crate) fn check_ref_name(name: &str) -> Result<(), StoreError> {
+    let invalid = || StoreError::InvalidRefName { name: name.into() };
+    let rest = name.strip_prefix("refs/").ok_or_else(invalid)?;
+    if rest == "tags" || rest.starts_with("tags/") {
+        return Err(invalid());
+    }
+    for component in rest.split('/') {
+        let bad_char = |c: char| c.is_whitespace() || c.is_control() || "\\:?*[~^".contains(c);
+        if component.is_empty()
+            || component.starts_with('.')
+            || component.ends_with(".lock")
+            || component.contains(bad_char)
+        {
+            return Err(invalid());
+        }
+    }
+    Ok(())
+}
+
+impl Repository {
+    /// `git rev-parse refs/…` — the commit a ref points at, or `None`
+    /// if it doesn't exist.
+    pub fn resolve_ref(&self, name: &str) -> Result<Option<ObjectHash>, StoreError> {
+        check_ref_name(name)?;
+        match &self.store {
+            Some(store) => store.read_ref(name),
+            None => Ok(self.mem_refs.lock().refs.get(name).copied()),
+        }
+    }
+
+    /// `git update-ref <name> <new> <expected>` — point `name` at
+    /// `new` if it currently points at `expected` (`None`: if it
+    /// doesn't exist yet), recording `message` in the reflog. Fails
+    /// with [`StoreError::RefConflict`] otherwise. Like
+    /// [`set_tag`](Self::set_tag), doesn't check that `new` is in
+    /// this repository.
+    pub fn update_ref(
+        &self,
+        name: &str,
+        expected: Option<ObjectHash>,
+        new: ObjectHash,
+        message: impl Into<String>,
+    ) -> Result<(), StoreError> {
+        self.swap_ref(name, expected, Some(new), message.into())
+    }
+
+    /// `git update-ref -d <name> <expected>` — remove `name` if it
+    /// points at `expected`. Its reflog is kept.
+    pub fn delete_ref(
+        &self,
+        name: &str,
+        expected: ObjectHash,
+        message: impl Into<String>,
+    ) -> Result<(), StoreError> {
+        self.swap_ref(name, Some(expected), None, message.into())
+    }
+
+    fn swap_ref(
+        &self,
+        name: &str,
+        expected: Option<ObjectHash>,
+        new: Option<ObjectHash>,
+        message: String,
+    ) -> Result<(), StoreError> {
+        check_ref_name(name)?;
+        let entry = ReflogEntry {
+            old: expected,
+            new,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as i64),
+            message,
+        };
+        if let Some(store) = &self.store {
+            return store.update_ref(name, expected, new, &entry);
+        }
+        let mut mem = self.mem_refs.lock();
+        let actual = mem.refs.get(name).copied();
+        if actual != expected {
+            return Err(StoreError::RefConflict {
+                name: name.into(),
+                expected,
+                actual,
+            });
+        }
+        match new {
+            Some(hash) => mem.refs.insert(name.into(), hash),
+            None => mem.refs.remove(name),
+        };
+        mem.logs.entry(name.into()).or_default().push(entry);
+        Ok(())
+    }
+
+    /// `git for-each-ref <prefix>` — every ref whose name starts with
+    /// `prefix` (e.g. `"refs/agents/"`), sorted by name.
+    pub fn refs(&self, prefix: &str) -> Result<Vec<(String, ObjectHash)>, StoreError> {
+        let all = match &self.store {
+            Some(store) => store.list_refs()?,
+            None => self.mem_refs.lock().refs.clone(),
+        };
+        Ok(all
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .collect())
+    }
+
+    /// `git reflog <name>` — every update of `name`, oldest first.
+    /// Empty for a ref that was never written.
+    pub fn reflog(&self, name: &str) -> Result<Vec<ReflogEntry>, StoreError> {
+        check_ref_name(name)?;
+        match &self.store {
+            Some(store) => store.read_reflog(name),
+            None => Ok(self
+                .mem_refs
+                .lock()
+                .logs
+                .get(name)
+                .cloned()
+                .unwrap_or_default()),
+        }
+    }
+
+    /// `git reflog expire` for rewinds — delete every
+    /// `refs/abandoned/…` ref set aside at least `max_age` ago, so
+    /// the turns it kept become garbage. A ref with no reflog entry
+    /// for its target counts as expired. Returns how many were
+    /// deleted.
+    pub fn expire_abandoned(&self, max_age: Duration) -> Result<usize, StoreError> {
+        let cutoff = SystemTime::now()
+            .checked_sub(max_age)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as i64);
+        let mut expired = 0;
+        for (name, hash) in self.refs("refs/abandoned/")? {
+            let kept_at = self
+                .reflog(&name)?
+                .iter()
+                .rev()
+                .find(|e| e.new == Some(hash))
+                .map_or(0, |e| e.timestamp);
+            if kept_at > cutoff {
+                continue;
+            }
+            match self.delete_ref(&name, hash, "expire") {
+                Ok(()) => expired += 1,
+                // Someone else expired it first.
+                Err(StoreError::RefConflict { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Resume the branch `name` points at, bound to the ref: every
+    /// commit on the returned branch moves the ref from the branch's
+    /// previous tip to the new commit, and fails with
+    /// [`StoreError::RefConflict`] if the ref was moved in between. If
+    /// the ref doesn't exist yet the branch starts empty and its first
+    /// commit creates it.
+    ///
+    /// [`fork`](Branch::fork)s of the returned branch are anonymous.
+    pub fn branch_named(self: &Arc<Self>, name: &str) -> Result<Arc<Branch>, StoreError> {
+        let tip = self.resolve_ref(name)?;
+        Ok(Branch::named(Arc::clone(self), name.into(), tip))
+    }
+}
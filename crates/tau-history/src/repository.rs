@@ -1,19 +1,79 @@
-//! [`Repository`] — in-memory content-addressed store for the three
-//! object kinds (blobs, trees, commits).
+//! [`Repository`] — content-addressed store for the three object
+//! kinds (blobs, trees, commits), optionally backed by an
+//! [`ObjectStore`].
 //!
 //! Three separate maps keyed by [`ObjectHash`]. Each kind stores
 //! `Arc<T>` so callers reading objects share immutable views without
-//! cloning the underlying bytes.
+//! cloning the underlying bytes. With a backing store the maps act as
+//! a cache: puts write through, and gets that miss fall back to the
+//! store.
 
-use std::collections::{BTreeMap, HashMap};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use crate::branch::Branch;
-use crate::objects::{Blob, Commit, ObjectHash, Tree};
+use crate::canonical::HASH_VERSION;
+use crate::migrate;
+use crate::objects::{Blob, Commit, ObjectHash, ObjectKind, StoreError, Tree, TreeEntry};
+use crate::refs::MemRefs;
+use crate::store::{FsStore, ObjectStore};
 
-/// In-memory content-addressed object store. Issues branches via
+/// The three object types, as the repository stores and persists them.
+trait Object: Sized {
+    const KIND: ObjectKind;
+    fn hash(&self) -> ObjectHash;
+    fn encode(&self) -> Vec<u8>;
+    fn decode(payload: Vec<u8>) -> Result<Self, StoreError>;
+}
+
+impl Object for Blob {
+    const KIND: ObjectKind = ObjectKind::Blob;
+    fn hash(&self) -> ObjectHash {
+        Blob::hash(self)
+    }
+    fn encode(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+    fn decode(payload: Vec<u8>) -> Result<Self, StoreError> {
+        Ok(Blob::new(payload))
+    }
+}
+
+impl Object for Tree {
+    const KIND: ObjectKind = ObjectKind::Tree;
+    fn hash(&self) -> ObjectHash {
+        Tree::hash(self)
+    }
+    fn encode(&self) -> Vec<u8> {
+        Tree::encode(self)
+    }
+    fn decode(payload: Vec<u8>) -> Result<Self, StoreError> {
+        Tree::decode(&payload).map_err(|e| StoreError::Corrupt {
+            what: format!("tree: {e}"),
+        })
+    }
+}
+
+impl Object for Commit {
+    const KIND: ObjectKind = ObjectKind::Commit;
+    fn hash(&self) -> ObjectHash {
+        Commit::hash(self)
+    }
+    fn encode(&self) -> Vec<u8> {
+        Commit::encode(self)
+    }
+    fn decode(payload: Vec<u8>) -> Result<Self, StoreError> {
+        serde_json::from_slice(&payload).map_err(|e| StoreError::Corrupt {
+            what: format!("commit: {e}"),
+        })
+    }
+}
+
+/// Content-addressed object store. Issues branches via
 /// [`new_branch`](Self::new_branch) (empty tip) or
 /// [`branch_at`](Self::branch_at) (resume from a known commit hash).
 ///
@@ -21,6 +81,12 @@ use crate::objects::{Blob, Commit, ObjectHash, Tree};
 /// - blobs: opaque content the host serialized
 /// - trees: named directories of blob and subtree references
 /// - commits: tree pointers with parent/extra_parents
+///
+/// [`new`](Self::new) keeps everything in memory;
+/// [`open`](Self::open) persists to a directory so the graph survives
+/// restarts and can be shared between processes. Branch tips are not
+/// persisted — bookmark the commits you want to find again with a
+/// [tag](Self::set_tag).
 pub struct Repository {
     blobs: RwLock<HashMap<ObjectHash, Arc<Blob>>>,
     trees: RwLock<HashMap<ObjectHash, Arc<Tree>>>,
@@ -42,69 +108,500 @@ pub struct Repository {
     /// immutability check with `resolve_tag` first. (Git's default
     /// is reject-if-exists; we leave that policy to the host.)
     tags: RwLock<BTreeMap<String, ObjectHash>>,
+    /// Named refs when there's no backing store (see
+    /// [`crate::refs`]). With one, refs live only in the store.
+    pub(crate) mem_refs: Mutex<MemRefs>,
+    pub(crate) store: Option<Arc<dyn ObjectStore>>,
+    /// Objects whose write to `store` failed, retried by
+    /// [`flush`](Self::flush). They stay readable from memory.
+    unsaved: Mutex<Vec<(ObjectKind, ObjectHash)>>,
+    /// Tag changes not yet written to `store` (`None`: removed),
+    /// likewise retried by [`flush`](Self::flush).
+    tags_unsaved: Mutex<BTreeMap<String, Option<ObjectHash>>>,
+    /// Every branch opened on this repository, so [`gc`](Self::gc)
+    /// can treat live tips as roots. Dead entries are pruned lazily.
+    branches: Mutex<Vec<Weak<Branch>>>,
+    /// Held shared by [`Branch`] for the whole of a commit, and
+    /// exclusively by `gc` while it snapshots its roots — so a
+    /// snapshot never sees a half-built commit.
+    pub(crate) gc_gate: RwLock<()>,
+    /// While a gc is sweeping: every hash put since its roots were
+    /// snapshotted. Those objects may be referenced by commits the
+    /// snapshot didn't see, so they're never swept.
+    gc_fresh: Mutex<Option<HashSet<ObjectHash>>>,
+    /// Held for the whole of a [`gc`](Self::gc) or
+    /// [`gc_store`](Self::gc_store), so runs don't share `gc_fresh`.
+    gc_lock: Mutex<()>,
+}
+
+/// What a [`Repository::gc`] pass reclaimed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub blobs: usize,
+    pub trees: usize,
+    pub commits: usize,
+    /// Payload bytes of the swept objects (blob contents, encoded
+    /// trees and commits).
+    pub bytes: usize,
+}
+
+impl GcReport {
+    pub fn objects(&self) -> usize {
+        self.blobs + self.trees + self.commits
+    }
 }
 
+/// Objects removed per map write-lock acquisition while sweeping, so
+/// concurrent readers and writers wait at most one batch.
+const GC_BATCH: usize = 256;
+
 impl Repository {
+    /// An in-memory repository. Everything in it dies with the
+    /// process.
     pub fn new() -> Arc<Self> {
-        Arc::new(Self {
+        Arc::new(Self::build(None, BTreeMap::new()))
+    }
+
+    /// `git init` / open: a repository persisted under `path` with
+    /// [`FsStore`], creating the directory if needed. Tags are read
+    /// now; objects are loaded lazily as they're first read.
+    pub fn open(path: impl AsRef<Path>) -> Result<Arc<Self>, StoreError> {
+        Self::with_store(Arc::new(FsStore::open(path.as_ref())?))
+    }
+
+    /// A repository backed by a custom [`ObjectStore`]. Fails with
+    /// [`StoreError::Outdated`] if the store was written under a
+    /// different [`HASH_VERSION`].
+    pub fn with_store(store: Arc<dyn ObjectStore>) -> Result<Arc<Self>, StoreError> {
+        match store.read_version()? {
+            None => store.write_version(HASH_VERSION)?,
+            Some(HASH_VERSION) => {}
+            Some(found) => {
+                return Err(StoreError::Outdated {
+                    found,
+                    expected: HASH_VERSION,
+                });
+            }
+        }
+        let tags = store.read_tags()?;
+        Ok(Arc::new(Self::build(Some(store), tags)))
+    }
+
+    /// Rewrite the store at `path` under the current [`HASH_VERSION`]
+    /// so [`open`](Self::open) accepts it. Returns the old → new hash
+    /// of every migrated object; empty if the store was already
+    /// current. Needs exclusive access to the directory while it runs.
+    pub fn migrate(path: impl AsRef<Path>) -> Result<HashMap<ObjectHash, ObjectHash>, StoreError> {
+        migrate::migrate_dir(path.as_ref())
+    }
+
+    fn build(store: Option<Arc<dyn ObjectStore>>, tags: BTreeMap<String, ObjectHash>) -> Self {
+        Self {
             blobs: RwLock::new(HashMap::new()),
             trees: RwLock::new(HashMap::new()),
             commits: RwLock::new(HashMap::new()),
-            tags: RwLock::new(BTreeMap::new()),
-        })
+            tags: RwLock::new(tags),
+            mem_refs: Mutex::new(MemRefs::default()),
+            store,
+            unsaved: Mutex::new(Vec::new()),
+            tags_unsaved: Mutex::new(BTreeMap::new()),
+            branches: Mutex::new(Vec::new()),
+            gc_gate: RwLock::new(()),
+            gc_fresh: Mutex::new(None),
+            gc_lock: Mutex::new(()),
+        }
     }
 
     // ─── Object store: put ───────────────────────────────────────────
 
     /// Insert a blob, returning its hash. No-op if a blob with the
     /// same hash is already present.
+    ///
+    /// With a backing store the blob is written through. A failed
+    /// write doesn't fail the put — the object is kept in memory and
+    /// the error surfaces from the next [`flush`](Self::flush).
     pub fn put_blob(&self, blob: Blob) -> ObjectHash {
-        let hash = blob.hash();
-        if self.blobs.read().contains_key(&hash) {
-            return hash;
-        }
-        let mut g = self.blobs.write();
-        g.entry(hash).or_insert_with(|| Arc::new(blob));
-        hash
+        self.put(&self.blobs, blob)
     }
 
     /// Insert a tree, returning its hash. Idempotent.
     pub fn put_tree(&self, tree: Tree) -> ObjectHash {
-        let hash = tree.hash();
-        if self.trees.read().contains_key(&hash) {
-            return hash;
-        }
-        let mut g = self.trees.write();
-        g.entry(hash).or_insert_with(|| Arc::new(tree));
-        hash
+        self.put(&self.trees, tree)
     }
 
     /// Insert a commit, returning its hash. Idempotent.
     pub fn put_commit(&self, commit: Commit) -> ObjectHash {
-        let hash = commit.hash();
-        if self.commits.read().contains_key(&hash) {
+        self.put(&self.commits, commit)
+    }
+
+    fn put<T: Object>(&self, map: &RwLock<HashMap<ObjectHash, Arc<T>>>, object: T) -> ObjectHash {
+        let hash = object.hash();
+        // Before touching the map: a sweep that already decided this
+        // object is garbage re-checks `gc_fresh` under the map's write
+        // lock, so the mark is seen either way.
+        if let Some(fresh) = self.gc_fresh.lock().as_mut() {
+            fresh.insert(hash);
+        }
+        if map.read().contains_key(&hash) {
             return hash;
         }
-        let mut g = self.commits.write();
-        g.entry(hash).or_insert_with(|| Arc::new(commit));
+        if let Some(store) = &self.store {
+            if store.write(&hash, T::KIND, &object.encode()).is_err() {
+                self.unsaved.lock().push((T::KIND, hash));
+            }
+        }
+        map.write().entry(hash).or_insert_with(|| Arc::new(object));
         hash
     }
 
+    /// Retry every write to the backing store that has failed so far.
+    /// Returns the first error if any still fails; those objects stay
+    /// queued for the next flush. Always `Ok` without a backing store.
+    ///
+    /// [`Branch`] flushes after each commit, so a commit on a
+    /// persisted repository fails rather than silently going
+    /// unsaved.
+    pub fn flush(&self) -> Result<(), StoreError> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let pending = std::mem::take(&mut *self.unsaved.lock());
+        for (i, (kind, hash)) in pending.iter().enumerate() {
+            let payload = match kind {
+                ObjectKind::Blob => self.blobs.read().get(hash).map(|o| o.encode()),
+                ObjectKind::Tree => self.trees.read().get(hash).map(|o| o.encode()),
+                ObjectKind::Commit => self.commits.read().get(hash).map(|o| o.encode()),
+            };
+            let Some(payload) = payload else {
+                continue;
+            };
+            if let Err(e) = store.write(hash, *kind, &payload) {
+                self.unsaved.lock().extend_from_slice(&pending[i..]);
+                return Err(e);
+            }
+        }
+        let mut tags_unsaved = self.tags_unsaved.lock();
+        if !tags_unsaved.is_empty() {
+            store.update_tags(&tags_unsaved)?;
+            tags_unsaved.clear();
+        }
+        Ok(())
+    }
+
+    /// Ask the backing store to consolidate loose objects (see
+    /// [`ObjectStore::pack`]). Returns how many objects moved; `0`
+    /// without a backing store.
+    pub fn pack(&self) -> Result<usize, StoreError> {
+        match &self.store {
+            Some(store) => store.pack(),
+            None => Ok(0),
+        }
+    }
+
     // ─── Object store: get ───────────────────────────────────────────
 
+    /// Look up a blob. Errors from the backing store read as `None`;
+    /// use [`try_get_blob`](Self::try_get_blob) to see them.
     pub fn get_blob(&self, hash: &ObjectHash) -> Option<Arc<Blob>> {
-        self.blobs.read().get(hash).cloned()
+        self.try_get_blob(hash).ok().flatten()
     }
 
     pub fn get_tree(&self, hash: &ObjectHash) -> Option<Arc<Tree>> {
-        self.trees.read().get(hash).cloned()
+        self.try_get_tree(hash).ok().flatten()
     }
 
     pub fn get_commit(&self, hash: &ObjectHash) -> Option<Arc<Commit>> {
-        self.commits.read().get(hash).cloned()
+        self.try_get_commit(hash).ok().flatten()
+    }
+
+    /// Look up a blob, loading it from the backing store on a miss.
+    /// `Ok(None)` if no blob has this hash.
+    pub fn try_get_blob(&self, hash: &ObjectHash) -> Result<Option<Arc<Blob>>, StoreError> {
+        self.get(&self.blobs, hash)
+    }
+
+    pub fn try_get_tree(&self, hash: &ObjectHash) -> Result<Option<Arc<Tree>>, StoreError> {
+        self.get(&self.trees, hash)
+    }
+
+    pub fn try_get_commit(&self, hash: &ObjectHash) -> Result<Option<Arc<Commit>>, StoreError> {
+        self.get(&self.commits, hash)
+    }
+
+    fn get<T: Object>(
+        &self,
+        map: &RwLock<HashMap<ObjectHash, Arc<T>>>,
+        hash: &ObjectHash,
+    ) -> Result<Option<Arc<T>>, StoreError> {
+        if let Some(object) = map.read().get(hash) {
+            return Ok(Some(Arc::clone(object)));
+        }
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let Some((kind, payload)) = store.read(hash)? else {
+            return Ok(None);
+        };
+        if kind != T::KIND {
+            return Ok(None);
+        }
+        let object = T::decode(payload)?;
+        if object.hash() != *hash {
+            return Err(StoreError::Corrupt {
+                what: format!("{kind} stored as {hash} hashes to {}", object.hash()),
+            });
+        }
+        let object = Arc::clone(map.write().entry(*hash).or_insert_with(|| Arc::new(object)));
+        Ok(Some(object))
+    }
+
+    // ─── Garbage collection ──────────────────────────────────────────
+
+    pub(crate) fn track(&self, branch: &Arc<Branch>) {
+        let mut branches = self.branches.lock();
+        if branches.len() % 64 == 63 {
+            branches.retain(|b| b.strong_count() > 0);
+        }
+        branches.push(Arc::downgrade(branch));
+    }
+
+    /// Drop every in-memory object not reachable from `roots` (commit
+    /// hashes), a tag, a ref, or the tip of a [`Branch`] that's still alive.
+    /// Compactions and abandoned forks leave garbage behind; this is
+    /// how a long-running host keeps memory bounded.
+    ///
+    /// Safe to run from a background thread (e.g. `spawn_blocking`)
+    /// while branches keep committing: roots are snapshotted between
+    /// commits, objects put after that are kept, and the sweep takes
+    /// each map's write lock for one small batch of removals at a
+    /// time. Anything else — say a hash a host set aside without a
+    /// tag — isn't reachable and will be collected; pass it in
+    /// `roots`.
+    ///
+    /// With a backing store this only evicts objects from memory.
+    /// Nothing is deleted from the store — that's
+    /// [`gc_store`](Self::gc_store) — and objects whose write hasn't
+    /// succeeded yet are kept. Overlapping calls run one at a time.
+    ///
+    /// Nothing runs either on its own: when to collect is the host's
+    /// call.
+    pub fn gc(&self, roots: impl IntoIterator<Item = ObjectHash>) -> GcReport {
+        let _serial = self.gc_lock.lock();
+        let mut pending: Vec<ObjectHash> = roots.into_iter().collect();
+        {
+            let _exclusive = self.gc_gate.write();
+            pending.extend(self.tags.read().values().copied());
+            match &self.store {
+                // Objects only leave memory when there's a store to
+                // reload them from, so a failed read costs nothing.
+                Some(store) => pending.extend(store.list_refs().unwrap_or_default().into_values()),
+                None => pending.extend(self.mem_refs.lock().heads()),
+            }
+            let mut branches = self.branches.lock();
+            branches.retain(|b| b.strong_count() > 0);
+            pending.extend(branches.iter().filter_map(|b| b.upgrade()?.tip()));
+            *self.gc_fresh.lock() = Some(HashSet::new());
+        }
+        pending.extend(self.unsaved.lock().iter().map(|(_, h)| *h));
+
+        // Mark. Only resident objects are traversed: with a backing
+        // store, whatever hangs off an evicted object is reloadable.
+        let mut live = HashSet::new();
+        while let Some(hash) = pending.pop() {
+            if !live.insert(hash) {
+                continue;
+            }
+            if let Some(commit) = self.commits.read().get(&hash) {
+                pending.extend(commit.parent);
+                pending.extend(commit.extra_parents.iter().copied());
+                pending.push(commit.tree);
+            } else if let Some(tree) = self.trees.read().get(&hash) {
+                pending.extend(tree.entries.values().map(|e| *e.hash()));
+            }
+        }
+
+        let report = self.sweep_all(&live);
+        *self.gc_fresh.lock() = None;
+        report
+    }
+
+    /// Delete every object in the backing store not reachable from
+    /// `roots`, a tag, a ref, or the tip of a live [`Branch`] — what
+    /// [`gc`](Self::gc) does to memory, done to the store, which
+    /// other processes may share. Reachability is traced through the
+    /// whole store, so this reads every live object once.
+    ///
+    /// Objects written within `grace` are kept: another process may
+    /// have just put them for a commit it hasn't finished, and the
+    /// store freshens an object each time it's written again. Anchor
+    /// anything worth keeping longer with a ref or tag — a commit only
+    /// a reflog mentions is collected. Swept objects leave memory too.
+    /// Returns the number deleted from the store; `0` without one.
+    pub fn gc_store(
+        &self,
+        roots: impl IntoIterator<Item = ObjectHash>,
+        grace: Duration,
+    ) -> Result<usize, StoreError> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let _serial = self.gc_lock.lock();
+        // Before the roots: anything written after this is kept.
+        let cutoff = SystemTime::now()
+            .checked_sub(grace)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut pending: Vec<(ObjectHash, ObjectKind)> =
+            roots.into_iter().map(|h| (h, ObjectKind::Commit)).collect();
+        {
+            let _exclusive = self.gc_gate.write();
+            // Other processes' tags and refs count too.
+            let mut commits: Vec<ObjectHash> = store.read_tags()?.into_values().collect();
+            commits.extend(self.tags.read().values().copied());
+            commits.extend(store.list_refs()?.into_values());
+            let mut branches = self.branches.lock();
+            branches.retain(|b| b.strong_count() > 0);
+            commits.extend(branches.iter().filter_map(|b| b.upgrade()?.tip()));
+            pending.extend(commits.into_iter().map(|h| (h, ObjectKind::Commit)));
+            *self.gc_fresh.lock() = Some(HashSet::new());
+        }
+        pending.extend(self.unsaved.lock().iter().map(|(kind, h)| (*h, *kind)));
+
+        let marked = self.mark_stored(store.as_ref(), pending);
+        let result = marked.and_then(|live| {
+            let deleted = store.prune(&live, cutoff)?;
+            self.sweep_all(&live);
+            Ok((live, deleted))
+        });
+        let fresh = self.gc_fresh.lock().take().unwrap_or_default();
+        let (live, deleted) = result?;
+        // Put since the roots were taken, and so possibly just pruned
+        // from under this process: write them back.
+        for hash in fresh.difference(&live) {
+            let object = self
+                .blobs
+                .read()
+                .get(hash)
+                .map(|o| (ObjectKind::Blob, o.encode()))
+                .or_else(|| {
+                    self.trees
+                        .read()
+                        .get(hash)
+                        .map(|o| (ObjectKind::Tree, o.encode()))
+                })
+                .or_else(|| {
+                    let commits = self.commits.read();
+                    commits.get(hash).map(|o| (ObjectKind::Commit, o.encode()))
+                });
+            if let Some((kind, payload)) = object {
+                store.write(hash, kind, &payload)?;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Every object reachable from `pending`, reading through memory
+    /// and then the store without caching what it reads. Objects
+    /// missing from both are skipped.
+    fn mark_stored(
+        &self,
+        store: &dyn ObjectStore,
+        mut pending: Vec<(ObjectHash, ObjectKind)>,
+    ) -> Result<HashSet<ObjectHash>, StoreError> {
+        let mut live = HashSet::new();
+        while let Some((hash, kind)) = pending.pop() {
+            if !live.insert(hash) {
+                continue;
+            }
+            match kind {
+                ObjectKind::Blob => {}
+                ObjectKind::Commit => {
+                    let resident = self.commits.read().get(&hash).cloned();
+                    let commit = match resident {
+                        Some(commit) => commit,
+                        None => match store.read(&hash)? {
+                            Some((ObjectKind::Commit, payload)) => {
+                                Arc::new(<Commit as Object>::decode(payload)?)
+                            }
+                            _ => continue,
+                        },
+                    };
+                    pending.extend(commit.parent.map(|h| (h, ObjectKind::Commit)));
+                    pending.extend(
+                        commit
+                            .extra_parents
+                            .iter()
+                            .map(|h| (*h, ObjectKind::Commit)),
+                    );
+                    pending.push((commit.tree, ObjectKind::Tree));
+                }
+                ObjectKind::Tree => {
+                    let resident = self.trees.read().get(&hash).cloned();
+                    let tree = match resident {
+                        Some(tree) => tree,
+                        None => match store.read(&hash)? {
+                            Some((ObjectKind::Tree, payload)) => {
+                                Arc::new(<Tree as Object>::decode(payload)?)
+                            }
+                            _ => continue,
+                        },
+                    };
+                    pending.extend(tree.entries.values().map(|e| match e {
+                        TreeEntry::Blob(h) => (*h, ObjectKind::Blob),
+                        TreeEntry::Tree(h) => (*h, ObjectKind::Tree),
+                    }));
+                }
+            }
+        }
+        Ok(live)
+    }
+
+    /// [`sweep`](Self::sweep) each map.
+    fn sweep_all(&self, live: &HashSet<ObjectHash>) -> GcReport {
+        let mut report = GcReport::default();
+        (report.blobs, report.bytes) = self.sweep(&self.blobs, live, |b| b.bytes.len());
+        let (trees, tree_bytes) = self.sweep(&self.trees, live, |t| t.encode().len());
+        let (commits, commit_bytes) = self.sweep(&self.commits, live, |c| c.encode().len());
+        report.trees = trees;
+        report.commits = commits;
+        report.bytes += tree_bytes + commit_bytes;
+        report
+    }
+
+    /// Remove unmarked, non-fresh entries from `map` in batches.
+    /// Returns how many were removed and their total size.
+    fn sweep<T>(
+        &self,
+        map: &RwLock<HashMap<ObjectHash, Arc<T>>>,
+        live: &HashSet<ObjectHash>,
+        size: impl Fn(&T) -> usize,
+    ) -> (usize, usize) {
+        let garbage: Vec<ObjectHash> = map
+            .read()
+            .keys()
+            .filter(|h| !live.contains(*h))
+            .copied()
+            .collect();
+        let (mut count, mut bytes) = (0, 0);
+        for batch in garbage.chunks(GC_BATCH) {
+            let mut map = map.write();
+            let fresh = self.gc_fresh.lock();
+            for hash in batch {
+                if fresh.as_ref().is_some_and(|f| f.contains(hash)) {
+                    continue;
+                }
+                if let Some(object) = map.remove(hash) {
+                    count += 1;
+                    bytes += size(&object);
+                }
+            }
+        }
+        (count, bytes)
     }
 
     // ─── Counts (for tests / observability) ──────────────────────────
+    //
+    // With a backing store these count objects loaded or written by
+    // this process, not everything on disk.
 
     pub fn blob_count(&self) -> usize {
         self.blobs.read().len()
@@ -155,7 +652,9 @@ impl Repository {
     /// "create only if absent" semantics check
     /// [`resolve_tag`](Self::resolve_tag) first.
     pub fn set_tag(&self, name: impl Into<String>, commit: ObjectHash) {
-        self.tags.write().insert(name.into(), commit);
+        let name = name.into();
+        self.tags.write().insert(name.clone(), commit);
+        self.save_tag(name, Some(commit));
     }
 
     /// `git rev-parse <tag>` — look up the commit a tag points at.
@@ -167,7 +666,25 @@ impl Repository {
     /// `git tag -d <name>` — remove a tag. Returns the commit hash
     /// it pointed at, or `None` if no such tag existed.
     pub fn remove_tag(&self, name: &str) -> Option<ObjectHash> {
-        self.tags.write().remove(name)
+        let removed = self.tags.write().remove(name);
+        if removed.is_some() {
+            self.save_tag(name.into(), None);
+        }
+        removed
+    }
+
+    /// Write one tag change through to the backing store, leaving
+    /// tags other processes set alone. Like object writes, a failure
+    /// is deferred to [`flush`](Self::flush).
+    fn save_tag(&self, name: String, commit: Option<ObjectHash>) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let mut tags_unsaved = self.tags_unsaved.lock();
+        tags_unsaved.insert(name, commit);
+        if store.update_tags(&tags_unsaved).is_ok() {
+            tags_unsaved.clear();
+        }
     }
 
     /// Enumerate all tags as `(name, commit)` pairs, sorted by
@@ -0,0 +1,802 @@
+//! Pluggable persistence behind a [`Repository`](crate::Repository).
+//!
+//! The repository keeps every object it has touched in memory; an
+//! [`ObjectStore`] is where objects go to outlive the process and where
+//! the repository looks for ones it hasn't loaded yet. [`FsStore`] is
+//! the filesystem implementation:
+//!
+//! ```text
+//! <root>/
+//!   objects/ab/cdef…      ← loose object: zstd("<kind>\0" + payload)
+//!   packs/<sha256>.pack   ← many loose objects in one file
+//!   refs/tags             ← "<hash> <name>" per line, sorted by name
+//!   refs/agents/<id>      ← named ref: "<hash>\n"
+//!   logs/refs/agents/<id> ← its reflog: "<old> <new> <millis> <message>" per line
+//!   tmp/                  ← staging for atomic writes
+//!   version               ← hash version the objects were written under
+//! ```
+//!
+//! Every write goes to `tmp/` first and is renamed into place, so a
+//! reader — in this process or another — sees either the whole file or
+//! none of it. Objects are content-addressed and immutable, so two
+//! processes writing the same object race harmlessly. A tag update
+//! takes `refs/tags.lock` with an exclusive create and rewrites the
+//! tags file from what it holds now, so processes setting different
+//! tags keep each other's. A named ref update takes `<ref>.lock` the
+//! same way, checks the ref's current value, appends to the reflog
+//! and renames the lock file over the ref, so concurrent updates of
+//! one ref serialize. A lock older than [`STALE_LOCK_AGE`] was left by
+//! a process that died holding it, and the next writer removes it.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
+
+use crate::objects::{ObjectHash, ObjectKind, StoreError};
+use crate::refs::{ReflogEntry, check_ref_name};
+
+/// Backend a [`Repository`](crate::Repository) persists objects and
+/// tags to. Calls are synchronous and may block on I/O.
+pub trait ObjectStore: Send + Sync {
+    /// Read an object, returning its kind and serialized payload, or
+    /// `None` if the store doesn't have it.
+    fn read(&self, hash: &ObjectHash) -> Result<Option<(ObjectKind, Vec<u8>)>, StoreError>;
+
+    /// Store an object. Writing an object that is already present
+    /// only freshens it for [`prune`](Self::prune).
+    fn write(&self, hash: &ObjectHash, kind: ObjectKind, payload: &[u8]) -> Result<(), StoreError>;
+
+    fn read_tags(&self) -> Result<BTreeMap<String, ObjectHash>, StoreError>;
+
+    /// Replace the stored tag set with `tags`.
+    fn write_tags(&self, tags: &BTreeMap<String, ObjectHash>) -> Result<(), StoreError>;
+
+    /// Set (`Some`) or remove (`None`) each tag in `changes`, keeping
+    /// every other stored tag — including ones other writers of the
+    /// store set since this one last read them. The default
+    /// read-modify-writes, which is only safe with a single writer.
+    fn update_tags(
+        &self,
+        changes: &BTreeMap<String, Option<ObjectHash>>,
+    ) -> Result<(), StoreError> {
+        let mut tags = self.read_tags()?;
+        apply_tag_changes(&mut tags, changes);
+        self.write_tags(&tags)
+    }
+
+    /// Every object hash in the store, in no particular order. Used by
+    /// migration, which has to visit objects nothing points at yet.
+    fn list(&self) -> Result<Vec<ObjectHash>, StoreError>;
+
+    /// The [`HASH_VERSION`](crate::HASH_VERSION) the stored objects
+    /// were written under, or `None` for a store that has never been
+    /// written to.
+    fn read_version(&self) -> Result<Option<u32>, StoreError>;
+
+    fn write_version(&self, version: u32) -> Result<(), StoreError>;
+
+    /// The commit ref `name` points at, or `None` if it doesn't
+    /// exist. Names are already validated. Must reflect updates made
+    /// through other handles on the same store.
+    fn read_ref(&self, name: &str) -> Result<Option<ObjectHash>, StoreError>;
+
+    /// Atomically, against every other writer of the store: if `name`
+    /// currently points at `expected`, point it at `new` (`None`
+    /// deletes it), then append `entry` to its reflog. Otherwise fail
+    /// with [`StoreError::RefConflict`], changing nothing.
+    fn update_ref(
+        &self,
+        name: &str,
+        expected: Option<ObjectHash>,
+        new: Option<ObjectHash>,
+        entry: &ReflogEntry,
+    ) -> Result<(), StoreError>;
+
+    /// Every ref, by name.
+    fn list_refs(&self) -> Result<BTreeMap<String, ObjectHash>, StoreError>;
+
+    /// Every update of `name`, oldest first.
+    fn read_reflog(&self, name: &str) -> Result<Vec<ReflogEntry>, StoreError>;
+
+    /// Consolidate small objects into a more compact form. Returns how
+    /// many objects were moved. Stores without such a format keep the
+    /// default, which does nothing.
+    fn pack(&self) -> Result<usize, StoreError> {
+        Ok(0)
+    }
+
+    /// Delete every object not in `live` that was last written before
+    /// `cutoff`. Returns how many were deleted. Driven by
+    /// [`Repository::gc_store`](crate::Repository::gc_store), which
+    /// picks a cutoff that spares objects other writers have only just
+    /// put. Stores that can't delete keep the default, which does
+    /// nothing.
+    fn prune(&self, live: &HashSet<ObjectHash>, cutoff: SystemTime) -> Result<usize, StoreError> {
+        let _ = (live, cutoff);
+        Ok(0)
+    }
+}
+
+const PACK_MAGIC: &[u8; 8] = b"TAUPACK1";
+const ZSTD_LEVEL: i32 = 3;
+
+/// How old a `.lock` file must be for a writer to take it as left by
+/// a dead process and remove it. Locks are held for one small write.
+pub const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// How long a tag update waits for another process's tag lock.
+const TAG_LOCK_WAIT: Duration = Duration::from_secs(2);
+
+/// Where an object lives inside a pack file: offset and length of its
+/// compressed bytes.
+type PackIndex = HashMap<ObjectHash, (u64, u32)>;
+
+/// Filesystem [`ObjectStore`]. See the [module docs](self) for the
+/// layout.
+pub struct FsStore {
+    root: PathBuf,
+    packs: RwLock<Vec<(PathBuf, PackIndex)>>,
+    tmp_seq: AtomicU64,
+}
+
+impl FsStore {
+    /// Open the store at `root`, creating the directory layout if it
+    /// doesn't exist. Reads pack indexes but no objects.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let root = root.into();
+        for dir in ["objects", "packs", "refs", "tmp"] {
+            fs::create_dir_all(root.join(dir))?;
+        }
+        let store = Self {
+            root,
+            packs: RwLock::new(Vec::new()),
+            tmp_seq: AtomicU64::new(0),
+        };
+        store.refresh_packs()?;
+        Ok(store)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn loose_path(&self, hash: &ObjectHash) -> PathBuf {
+        let hex = hash.to_string();
+        self.root.join("objects").join(&hex[..2]).join(&hex[2..])
+    }
+
+    /// Index any pack files not seen yet — e.g. written by another
+    /// process since this store was opened — and forget any that a
+    /// prune removed.
+    fn refresh_packs(&self) -> Result<(), StoreError> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(self.root.join("packs"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "pack") {
+                found.push(path);
+            }
+        }
+        self.packs.write().retain(|(p, _)| found.contains(p));
+        let known: Vec<PathBuf> = self.packs.read().iter().map(|(p, _)| p.clone()).collect();
+        for path in found {
+            if known.contains(&path) {
+                continue;
+            }
+            let index = read_pack_index(&path)?;
+            self.packs.write().push((path, index));
+        }
+        Ok(())
+    }
+
+    fn read_packed(&self, hash: &ObjectHash) -> Result<Option<Vec<u8>>, StoreError> {
+        let location = self
+            .packs
+            .read()
+            .iter()
+            .find_map(|(path, index)| index.get(hash).map(|loc| (path.clone(), *loc)));
+        let Some((path, (offset, len))) = location else {
+            return Ok(None);
+        };
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            // Pruned since it was indexed; the caller re-scans.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(read_pack_entry(&mut file, offset, len)?))
+    }
+
+    /// Write `entries` (hash, compressed object) as a new pack file and
+    /// start reading from it.
+    fn write_pack(&self, entries: &[(ObjectHash, Vec<u8>)]) -> Result<(), StoreError> {
+        let mut bytes = PACK_MAGIC.to_vec();
+        let mut index = PackIndex::new();
+        for (hash, compressed) in entries {
+            bytes.extend_from_slice(hash.as_bytes());
+            bytes.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            index.insert(*hash, (bytes.len() as u64, compressed.len() as u32));
+            bytes.extend_from_slice(compressed);
+        }
+        let name: String = Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let path = self.root.join("packs").join(format!("{name}.pack"));
+        self.write_atomic(&path, &bytes)?;
+        self.packs.write().push((path, index));
+        Ok(())
+    }
+
+    /// Write `bytes` to `dest` via a temp file and rename.
+    fn write_atomic(&self, dest: &Path, bytes: &[u8]) -> io::Result<()> {
+        let tmp = self.root.join("tmp").join(format!(
+            "{}-{}",
+            std::process::id(),
+            self.tmp_seq.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = (|| {
+            let mut file = File::create(&tmp)?;
+            file.write_all(bytes)?;
+            file.sync_all()?;
+            if let Some(dir) = dest.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::rename(&tmp, dest)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+
+    fn version_path(&self) -> PathBuf {
+        self.root.join("version")
+    }
+
+    /// Exclusively create `lock_path`. `None` if another writer holds
+    /// it; a lock older than [`STALE_LOCK_AGE`] is removed first.
+    fn try_lock(&self, lock_path: &Path) -> Result<Option<File>, StoreError> {
+        let create = || {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(lock_path)
+        };
+        match create() {
+            Ok(file) => return Ok(Some(file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        let stale = SystemTime::now()
+            .checked_sub(STALE_LOCK_AGE)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        if !written_before(lock_path, stale)? {
+            return Ok(None);
+        }
+        match fs::remove_file(lock_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        match create() {
+            Ok(file) => Ok(Some(file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The part of [`update_ref`](ObjectStore::update_ref) done while
+    /// holding `lock`, the open `<ref>.lock` file: check `expected` and
+    /// move the ref.
+    fn move_ref_locked(
+        &self,
+        name: &str,
+        mut lock: File,
+        lock_path: &Path,
+        expected: Option<ObjectHash>,
+        new: Option<ObjectHash>,
+    ) -> Result<(), StoreError> {
+        let actual = self.read_ref(name)?;
+        if actual != expected {
+            return Err(StoreError::RefConflict {
+                name: name.into(),
+                expected,
+                actual,
+            });
+        }
+        match new {
+            Some(hash) => {
+                lock.write_all(format!("{hash}\n").as_bytes())?;
+                lock.sync_all()?;
+                drop(lock);
+                fs::rename(lock_path, self.root.join(name))?;
+            }
+            None => match fs::remove_file(self.root.join(name)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    fn loose_objects(&self) -> Result<Vec<(ObjectHash, PathBuf)>, StoreError> {
+        let mut out = Vec::new();
+        for fanout in fs::read_dir(self.root.join("objects"))? {
+            let fanout = fanout?;
+            if !fanout.file_type()?.is_dir() {
+                continue;
+            }
+            let prefix = fanout.file_name().to_string_lossy().into_owned();
+            for entry in fs::read_dir(fanout.path())? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if let Some(hash) = ObjectHash::from_hex(&format!("{prefix}{name}")) {
+                    out.push((hash, entry.path()));
+                }
+            }
+        }
+        out.sort_by_key(|(h, _)| *h.as_bytes());
+        Ok(out)
+    }
+}
+
+impl ObjectStore for FsStore {
+    fn read(&self, hash: &ObjectHash) -> Result<Option<(ObjectKind, Vec<u8>)>, StoreError> {
+        let compressed = match fs::read(self.loose_path(hash)) {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => match self.read_packed(hash)? {
+                Some(bytes) => Some(bytes),
+                None => {
+                    self.refresh_packs()?;
+                    self.read_packed(hash)?
+                }
+            },
+            Err(e) => return Err(e.into()),
+        };
+        compressed.map(|c| decode(hash, &c)).transpose()
+    }
+
+    /// An object already present has its file's mtime bumped — the
+    /// whole pack's, for a packed one — so a concurrent prune keeps
+    /// it. If that fails it's written loose again.
+    fn write(&self, hash: &ObjectHash, kind: ObjectKind, payload: &[u8]) -> Result<(), StoreError> {
+        let path = self.loose_path(hash);
+        let present = if path.exists() {
+            Some(path.clone())
+        } else {
+            self.packs
+                .read()
+                .iter()
+                .find(|(_, i)| i.contains_key(hash))
+                .map(|(p, _)| p.clone())
+        };
+        if present.is_some_and(|p| freshen(&p).is_ok()) {
+            return Ok(());
+        }
+        self.write_atomic(&path, &encode(kind, payload)?)?;
+        Ok(())
+    }
+
+    fn read_tags(&self) -> Result<BTreeMap<String, ObjectHash>, StoreError> {
+        let text = match fs::read_to_string(self.root.join("refs").join("tags")) {
+            Ok(t) => t,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut tags = BTreeMap::new();
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            let parsed = line
+                .split_once(' ')
+                .and_then(|(hex, name)| Some((ObjectHash::from_hex(hex)?, unescape(name))));
+            let Some((hash, name)) = parsed else {
+                return Err(StoreError::Corrupt {
+                    what: format!("malformed tag line {line:?}"),
+                });
+            };
+            tags.insert(name, hash);
+        }
+        Ok(tags)
+    }
+
+    fn write_tags(&self, tags: &BTreeMap<String, ObjectHash>) -> Result<(), StoreError> {
+        let mut text = String::new();
+        for (name, hash) in tags {
+            text.push_str(&format!("{hash} {}\n", escape(name)));
+        }
+        self.write_atomic(&self.root.join("refs").join("tags"), text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Re-reads and rewrites the tags file holding `refs/tags.lock`,
+    /// waiting up to [`TAG_LOCK_WAIT`] for another writer's.
+    fn update_tags(
+        &self,
+        changes: &BTreeMap<String, Option<ObjectHash>>,
+    ) -> Result<(), StoreError> {
+        let lock_path = self.root.join("refs").join("tags.lock");
+        let deadline = std::time::Instant::now() + TAG_LOCK_WAIT;
+        while self.try_lock(&lock_path)?.is_none() {
+            if std::time::Instant::now() >= deadline {
+                return Err(StoreError::RefLocked {
+                    name: "refs/tags".into(),
+                });
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let result = self.read_tags().and_then(|mut tags| {
+            apply_tag_changes(&mut tags, changes);
+            self.write_tags(&tags)
+        });
+        let _ = fs::remove_file(&lock_path);
+        result
+    }
+
+    fn list(&self) -> Result<Vec<ObjectHash>, StoreError> {
+        self.refresh_packs()?;
+        let mut hashes: Vec<ObjectHash> =
+            self.loose_objects()?.into_iter().map(|(h, _)| h).collect();
+        for (_, index) in self.packs.read().iter() {
+            hashes.extend(index.keys().copied());
+        }
+        hashes.sort_by_key(|h| *h.as_bytes());
+        hashes.dedup();
+        Ok(hashes)
+    }
+
+    /// Stores created before the `version` file existed hold
+    /// version-0 objects; a store with no file and no objects is new.
+    fn read_version(&self) -> Result<Option<u32>, StoreError> {
+        match fs::read_to_string(self.version_path()) {
+            Ok(text) => text
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| StoreError::Corrupt {
+                    what: format!("malformed version file {text:?}"),
+                }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Ok((!self.list()?.is_empty()).then_some(0))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_version(&self, version: u32) -> Result<(), StoreError> {
+        self.write_atomic(&self.version_path(), format!("{version}\n").as_bytes())?;
+        Ok(())
+    }
+
+    fn read_ref(&self, name: &str) -> Result<Option<ObjectHash>, StoreError> {
+        let text = match fs::read_to_string(self.root.join(name)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            // Refs nested under this name, like `refs/agents/<id>` under
+            // `refs/agents`.
+            Err(e) if e.kind() == io::ErrorKind::IsADirectory => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        ObjectHash::from_hex(text.trim())
+            .map(Some)
+            .ok_or_else(|| StoreError::Corrupt {
+                what: format!("malformed ref {name}"),
+            })
+    }
+
+    fn update_ref(
+        &self,
+        name: &str,
+        expected: Option<ObjectHash>,
+        new: Option<ObjectHash>,
+        entry: &ReflogEntry,
+    ) -> Result<(), StoreError> {
+        let path = self.root.join(name);
+        let lock_path = self.root.join(format!("{name}.lock"));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let Some(lock) = self.try_lock(&lock_path)? else {
+            return Err(StoreError::RefLocked { name: name.into() });
+        };
+        let moved = self.move_ref_locked(name, lock, &lock_path, expected, new);
+        // A successful move renamed the lock away; removing the path
+        // now could delete the next writer's lock.
+        if moved.is_err() || new.is_none() {
+            let _ = fs::remove_file(&lock_path);
+        }
+        moved?;
+        // Only once the ref has moved, so a failed update leaves no
+        // reflog entry behind.
+        let log_path = self.root.join("logs").join(name);
+        if let Some(dir) = log_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        log.write_all(format_reflog_line(entry).as_bytes())?;
+        Ok(())
+    }
+
+    fn list_refs(&self) -> Result<BTreeMap<String, ObjectHash>, StoreError> {
+        let mut refs = BTreeMap::new();
+        let mut dirs = vec![("refs".to_string(), self.root.join("refs"))];
+        while let Some((prefix, dir)) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+                if entry.file_type()?.is_dir() {
+                    dirs.push((name, entry.path()));
+                } else if check_ref_name(&name).is_ok()
+                    && let Some(hash) = self.read_ref(&name)?
+                {
+                    refs.insert(name, hash);
+                }
+            }
+        }
+        Ok(refs)
+    }
+
+    fn read_reflog(&self, name: &str) -> Result<Vec<ReflogEntry>, StoreError> {
+        let text = match fs::read_to_string(self.root.join("logs").join(name)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        text.lines()
+            .filter(|l| !l.is_empty())
+            .map(|line| {
+                parse_reflog_line(line).ok_or_else(|| StoreError::Corrupt {
+                    what: format!("malformed reflog line {line:?}"),
+                })
+            })
+            .collect()
+    }
+
+    /// Move every loose object into one new pack file, then delete the
+    /// loose copies. Readers that miss a loose object re-scan `packs/`,
+    /// so packing is safe while other processes read the store.
+    fn pack(&self) -> Result<usize, StoreError> {
+        let loose = self.loose_objects()?;
+        if loose.is_empty() {
+            return Ok(0);
+        }
+        let mut entries = Vec::with_capacity(loose.len());
+        for (hash, path) in &loose {
+            entries.push((*hash, fs::read(path)?));
+        }
+        self.write_pack(&entries)?;
+        for (_, path) in &loose {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(loose.len())
+    }
+
+    /// Delete unreachable loose objects, and rewrite each pack holding
+    /// any into a new pack of just its live objects. A pack's age is
+    /// its file's: freshening one object keeps the whole pack.
+    fn prune(&self, live: &HashSet<ObjectHash>, cutoff: SystemTime) -> Result<usize, StoreError> {
+        let mut deleted = 0;
+        for (hash, path) in self.loose_objects()? {
+            if live.contains(&hash) || !written_before(&path, cutoff)? {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => deleted += 1,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        self.refresh_packs()?;
+        let packs = self.packs.read().clone();
+        for (path, index) in packs {
+            let dead = index.keys().filter(|h| !live.contains(*h)).count();
+            if dead == 0 || !written_before(&path, cutoff)? {
+                continue;
+            }
+            let mut file = File::open(&path)?;
+            let mut kept = Vec::new();
+            for (hash, (offset, len)) in &index {
+                if live.contains(hash) {
+                    kept.push((*hash, read_pack_entry(&mut file, *offset, *len)?));
+                }
+            }
+            kept.sort_by_key(|(h, _)| *h.as_bytes());
+            if !kept.is_empty() {
+                self.write_pack(&kept)?;
+            }
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            self.packs.write().retain(|(p, _)| *p != path);
+            deleted += dead;
+        }
+        Ok(deleted)
+    }
+}
+
+fn apply_tag_changes(
+    tags: &mut BTreeMap<String, ObjectHash>,
+    changes: &BTreeMap<String, Option<ObjectHash>>,
+) {
+    for (name, change) in changes {
+        match change {
+            Some(hash) => tags.insert(name.clone(), *hash),
+            None => tags.remove(name),
+        };
+    }
+}
+
+/// Whether `path` was last modified before `cutoff`. A file that's
+/// gone counts as not: there's nothing left to delete.
+fn written_before(path: &Path, cutoff: SystemTime) -> Result<bool, StoreError> {
+    match fs::metadata(path).and_then(|m| m.modified()) {
+        Ok(modified) => Ok(modified < cutoff),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Bump `path`'s mtime to now.
+fn freshen(path: &Path) -> io::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+fn read_pack_entry(file: &mut File, offset: u64, len: u32) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0; len as usize];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Prefix an object with its kind header and compress it — the form
+/// loose objects, pack entries and bundle entries share.
+pub(crate) fn encode(kind: ObjectKind, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let mut raw = Vec::with_capacity(payload.len() + 8);
+    raw.extend_from_slice(kind.to_string().as_bytes());
+    raw.push(0);
+    raw.extend_from_slice(payload);
+    zstd::encode_all(raw.as_slice(), ZSTD_LEVEL)
+}
+
+/// Decompress a stored object and split off its kind header.
+pub(crate) fn decode(
+    hash: &ObjectHash,
+    compressed: &[u8],
+) -> Result<(ObjectKind, Vec<u8>), StoreError> {
+    decode_limited(hash, compressed, u64::MAX)
+}
+
+/// [`decode`], refusing objects that inflate past `limit` bytes.
+pub(crate) fn decode_limited(
+    hash: &ObjectHash,
+    compressed: &[u8],
+    limit: u64,
+) -> Result<(ObjectKind, Vec<u8>), StoreError> {
+    let corrupt = |why: &str| StoreError::Corrupt {
+        what: format!("object {hash}: {why}"),
+    };
+    let mut raw = Vec::new();
+    zstd::stream::read::Decoder::new(compressed)
+        .and_then(|d| d.take(limit.saturating_add(1)).read_to_end(&mut raw))
+        .map_err(|_| corrupt("bad compression"))?;
+    if raw.len() as u64 > limit {
+        return Err(corrupt("too large"));
+    }
+    let split = raw
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(|| corrupt("missing kind header"))?;
+    let kind = match &raw[..split] {
+        b"blob" => ObjectKind::Blob,
+        b"tree" => ObjectKind::Tree,
+        b"commit" => ObjectKind::Commit,
+        _ => return Err(corrupt("unknown kind")),
+    };
+    Ok((kind, raw[split + 1..].to_vec()))
+}
+
+fn read_pack_index(path: &Path) -> Result<PackIndex, StoreError> {
+    let corrupt = |why: &str| StoreError::Corrupt {
+        what: format!("pack {}: {why}", path.display()),
+    };
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| corrupt("truncated header"))?;
+    if &magic != PACK_MAGIC {
+        return Err(corrupt("bad magic"));
+    }
+    let mut index = PackIndex::new();
+    let mut offset = PACK_MAGIC.len() as u64;
+    loop {
+        let mut hash = [0u8; 32];
+        match reader.read_exact(&mut hash) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let mut len = [0u8; 4];
+        reader
+            .read_exact(&mut len)
+            .map_err(|_| corrupt("truncated entry"))?;
+        let len = u32::from_le_bytes(len);
+        offset += 36;
+        if offset + u64::from(len) > file_len {
+            return Err(corrupt("truncated entry"));
+        }
+        index.insert(ObjectHash::from_bytes(hash), (offset, len));
+        reader.seek_relative(i64::from(len))?;
+        offset += u64::from(len);
+    }
+    Ok(index)
+}
+
+/// All zeros stands for "no commit" in a reflog line.
+const NO_COMMIT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn format_reflog_line(entry: &ReflogEntry) -> String {
+    let show = |h: Option<ObjectHash>| h.map_or_else(|| NO_COMMIT.to_string(), |h| h.to_string());
+    format!(
+        "{} {} {} {}\n",
+        show(entry.old),
+        show(entry.new),
+        entry.timestamp,
+        escape(&entry.message)
+    )
+}
+
+fn parse_reflog_line(line: &str) -> Option<ReflogEntry> {
+    let mut fields = line.splitn(4, ' ');
+    let mut hash = || match fields.next()? {
+        NO_COMMIT => Some(None),
+        hex => ObjectHash::from_hex(hex).map(Some),
+    };
+    let old = hash()?;
+    let new = hash()?;
+    Some(ReflogEntry {
+        old,
+        new,
+        timestamp: fields.next()?.parse().ok()?,
+        message: unescape(fields.next().unwrap_or_default()),
+    })
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,135 @@
+//! Moving history between repositories with `export_bundle` /
+//! `import_bundle`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use tau_ai::Message;
+use tau_history::{History, MAX_BUNDLE_OBJECT_SIZE, ObjectHash, Repository, StoreError, TreePatch};
+
+async fn texts(history: &dyn History) -> Vec<String> {
+    let msgs = history.messages().await.expect("messages read");
+    msgs.iter().map(|m| m.text()).collect()
+}
+
+/// A main line and a fork off it. Returns (main tip, fork tip).
+async fn forked(repo: &Arc<Repository>) -> (ObjectHash, ObjectHash) {
+    let main = repo.new_branch();
+    main.commit(TreePatch::new().add_message(Message::user("shared")))
+        .await
+        .unwrap();
+    let fork = main.fork();
+    main.append(vec![Message::user("main only")]).await.unwrap();
+    fork.append(vec![Message::user("found the deadlock")])
+        .await
+        .unwrap();
+    (main.tip().unwrap(), fork.tip().unwrap())
+}
+
+fn export(repo: &Repository, refs: &[(&str, ObjectHash)], commits: &[ObjectHash]) -> Vec<u8> {
+    let refs: BTreeMap<String, ObjectHash> =
+        refs.iter().map(|(n, h)| (n.to_string(), *h)).collect();
+    let mut bytes = Vec::new();
+    repo.export_bundle(&refs, commits, &mut bytes).unwrap();
+    bytes
+}
+
+#[tokio::test]
+async fn bundle_round_trips_between_persisted_repositories() {
+    let src_dir = tempfile::tempdir().unwrap();
+    let dst_dir = tempfile::tempdir().unwrap();
+    let (main_tip, fork_tip) = forked(&Repository::open(src_dir.path()).unwrap()).await;
+
+    // Reopened, so export has to load everything from disk.
+    let src = Repository::open(src_dir.path()).unwrap();
+    let bytes = export(&src, &[("deadlock", fork_tip)], &[main_tip]);
+    assert_eq!(
+        bytes,
+        export(&src, &[("deadlock", fork_tip)], &[main_tip]),
+        "same heads, same file"
+    );
+
+    let dst = Repository::open(dst_dir.path()).unwrap();
+    let report = dst.import_bundle(&mut bytes.as_slice()).unwrap();
+    assert_eq!(report.manifest.commits, [main_tip]);
+    assert_eq!(report.objects_added, report.manifest.objects);
+    assert_eq!(report.tags_added, ["deadlock"]);
+    drop(dst);
+
+    let dst = Repository::open(dst_dir.path()).unwrap();
+    let fork = dst.branch_at_tag("deadlock").expect("ref imported as tag");
+    assert_eq!(texts(fork.as_ref()).await, ["shared", "found the deadlock"]);
+    assert_eq!(
+        texts(dst.branch_at(main_tip).as_ref()).await,
+        ["shared", "main only"]
+    );
+
+    let again = dst.import_bundle(&mut bytes.as_slice()).unwrap();
+    assert_eq!(again.objects_added, 0);
+    assert!(again.tags_added.is_empty() && again.tags_kept.is_empty());
+}
+
+#[tokio::test]
+async fn import_keeps_existing_tags() {
+    let src = Repository::new();
+    let (main_tip, fork_tip) = forked(&src).await;
+    let bytes = export(&src, &[("deadlock", fork_tip), ("main", main_tip)], &[]);
+
+    let dst = Repository::new();
+    let (theirs, _) = forked(&dst).await;
+    dst.set_tag("deadlock", theirs);
+
+    let report = dst.import_bundle(&mut bytes.as_slice()).unwrap();
+    assert_eq!(report.tags_added, ["main"]);
+    assert_eq!(report.tags_kept, ["deadlock"]);
+    assert_eq!(dst.resolve_tag("deadlock"), Some(theirs));
+    assert!(
+        dst.get_commit(&fork_tip).is_some(),
+        "the commit still arrives"
+    );
+}
+
+#[tokio::test]
+async fn damaged_bundles_are_rejected_whole() {
+    let src = Repository::new();
+    let (_, fork_tip) = forked(&src).await;
+    let bytes = export(&src, &[("deadlock", fork_tip)], &[]);
+
+    let mut flipped = bytes.clone();
+    *flipped.last_mut().unwrap() ^= 0xff;
+    let mut truncated = bytes.clone();
+    truncated.truncate(bytes.len() - 10);
+
+    for damaged in [flipped, truncated, b"not a bundle".to_vec()] {
+        let dst = Repository::new();
+        let err = dst.import_bundle(&mut damaged.as_slice()).unwrap_err();
+        assert!(matches!(err, StoreError::Corrupt { .. }), "{err}");
+        assert_eq!(dst.object_count(), 0);
+        assert!(dst.tags().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn oversized_objects_are_refused_on_import() {
+    let src = Repository::new();
+    let branch = src.new_branch();
+    let huge = "x".repeat(MAX_BUNDLE_OBJECT_SIZE as usize);
+    branch.append(vec![Message::user(huge)]).await.unwrap();
+    let bytes = export(&src, &[], &[branch.tip().unwrap()]);
+
+    let dst = Repository::new();
+    let err = dst.import_bundle(&mut bytes.as_slice()).unwrap_err();
+    assert!(matches!(err, StoreError::Corrupt { .. }), "{err}");
+    assert_eq!(dst.object_count(), 0);
+}
+
+#[test]
+fn export_of_unknown_commit_fails() {
+    let repo = Repository::new();
+    let bogus = ObjectHash::from_bytes([0xab; 32]);
+    let mut out = Vec::new();
+    let err = repo
+        .export_bundle(&BTreeMap::new(), &[bogus], &mut out)
+        .unwrap_err();
+    assert!(matches!(err, StoreError::NotFound { .. }));
+}
@@ -0,0 +1,146 @@
+//! Prompt-cache prediction with `Repository::predict_cache`.
+
+use std::sync::Arc;
+
+use tau_ai::{Api, Content, CostInfo, Message, Model, Provider};
+use tau_history::{
+    Branch, CacheBreak, History, ObjectHash, Repository, SharedPrefix, ToolDef, TreePatch,
+};
+
+/// A model priced at $1 per cache-read token and $2 per cache-write
+/// token, so dollar amounts read as token counts.
+fn model() -> Model {
+    Model {
+        id: "test-model".into(),
+        name: "Test".into(),
+        api: Api::AnthropicMessages,
+        provider: Provider::Anthropic,
+        base_url: String::new(),
+        reasoning: false,
+        input_types: Vec::new(),
+        cost: CostInfo {
+            input: 3_000_000.0,
+            output: 0.0,
+            cache_read: 1_000_000.0,
+            cache_write: 2_000_000.0,
+            thinking: 0.0,
+        },
+        context_window: 200_000,
+        max_tokens: 8_192,
+        headers: Default::default(),
+    }
+}
+
+/// A user message of `tokens` estimated tokens.
+fn user(tokens: usize) -> Message {
+    Message::user("x".repeat(tokens * 4))
+}
+
+fn tool(name: &str) -> ToolDef {
+    ToolDef {
+        name: name.into(),
+        description: "a tool".into(),
+        parameters_schema: serde_json::json!({"type": "object"}),
+    }
+}
+
+async fn commit(branch: &Arc<Branch>, patch: TreePatch) -> ObjectHash {
+    branch.commit(patch).await.unwrap();
+    branch.tip().unwrap()
+}
+
+async fn seeded(repo: &Arc<Repository>) -> (Arc<Branch>, ObjectHash) {
+    let branch = repo.new_branch();
+    let tip = commit(
+        &branch,
+        TreePatch::new()
+            .with_system_prompt(vec![Content::text("s".repeat(40))])
+            .with_tools(vec![tool("bash")])
+            .add_messages([user(100), user(50)]),
+    )
+    .await;
+    (branch, tip)
+}
+
+#[tokio::test]
+async fn appending_reads_the_whole_previous_prompt() {
+    let repo = Repository::new();
+    let (branch, before) = seeded(&repo).await;
+    let after = commit(&branch, TreePatch::new().add_message(user(20))).await;
+
+    let full = repo.predict_cache(&before, &before, &model()).unwrap();
+    assert_eq!(full.cache_write_tokens, 0);
+    let tools_and_prompt = full.cache_read_tokens - 150;
+
+    let p = repo.predict_cache(&before, &after, &model()).unwrap();
+    assert_eq!(p.cache_break, None);
+    assert_eq!(
+        p.shared,
+        SharedPrefix {
+            tools: true,
+            system_prompt: true,
+            messages: 2,
+        }
+    );
+    assert_eq!(p.cache_read_tokens, tools_and_prompt + 150);
+    assert_eq!(p.cache_write_tokens, 20);
+    assert_eq!(p.invalidated_tokens, 0);
+    assert_eq!(p.cost.cache_read, p.cache_read_tokens as f64);
+    assert_eq!(p.recache_cost(), 40.0);
+    assert_eq!(p.cost.input, 0.0, "every input token is cached or written");
+}
+
+#[tokio::test]
+async fn tool_change_rewrites_everything() {
+    let repo = Repository::new();
+    let (branch, before) = seeded(&repo).await;
+    let after = commit(
+        &branch,
+        TreePatch::new().with_tools(vec![tool("bash"), tool("read")]),
+    )
+    .await;
+
+    let old_total = repo
+        .predict_cache(&before, &before, &model())
+        .unwrap()
+        .cache_read_tokens;
+    let new_total = repo
+        .predict_cache(&after, &after, &model())
+        .unwrap()
+        .cache_read_tokens;
+
+    let p = repo.predict_cache(&before, &after, &model()).unwrap();
+    assert_eq!(p.cache_break, Some(CacheBreak::Tools));
+    assert_eq!(p.shared, SharedPrefix::default());
+    assert_eq!(p.cache_read_tokens, 0);
+    assert_eq!(p.cache_write_tokens, new_total);
+    assert_eq!(p.invalidated_tokens, old_total);
+    assert_eq!(p.recache_cost(), 2.0 * new_total as f64);
+}
+
+#[tokio::test]
+async fn compaction_keeps_only_tools_and_system_prompt() {
+    let repo = Repository::new();
+    let (branch, before) = seeded(&repo).await;
+    branch
+        .compact_prefix(1, user(10), "summary".into())
+        .await
+        .unwrap();
+    let after = branch.tip().unwrap();
+
+    let old_total = repo
+        .predict_cache(&before, &before, &model())
+        .unwrap()
+        .cache_read_tokens;
+    let tools_and_prompt = old_total - 150;
+
+    let p = repo.predict_cache(&before, &after, &model()).unwrap();
+    assert_eq!(p.cache_break, Some(CacheBreak::Messages { at: 0 }));
+    assert_eq!(p.cache_read_tokens, tools_and_prompt);
+    assert_eq!(
+        p.cache_write_tokens,
+        10 + 50,
+        "summary plus the kept message"
+    );
+    assert_eq!(p.invalidated_tokens, 150);
+}
@@ -0,0 +1,235 @@
+//! Reachability-based garbage collection: what `Repository::gc` keeps,
+//! what it reclaims, and that it's safe alongside concurrent commits;
+//! and what `Repository::gc_store` deletes from disk.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tau_ai::Message;
+use tau_history::{History, Repository, TreePatch};
+
+fn user(text: &str) -> Message {
+    Message::user(text)
+}
+
+async fn texts(history: &dyn History) -> Vec<String> {
+    let msgs = history.messages().await.expect("messages read");
+    msgs.iter().map(|m| m.text()).collect()
+}
+
+#[tokio::test]
+async fn compaction_garbage_is_reclaimed() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    for i in 0..10 {
+        branch
+            .commit(TreePatch::new().add_message(user(&format!("m{i}"))))
+            .await
+            .unwrap();
+    }
+    let before_compaction = branch.tip().unwrap();
+    branch
+        .compact_prefix(9, user("summary"), "summary".into())
+        .await
+        .unwrap();
+
+    // The pre-compaction commits are still the tip's ancestors, so
+    // nothing is garbage yet.
+    assert_eq!(repo.gc([]).objects(), 0);
+
+    // Abandon the history: start a branch from just the compacted
+    // state.
+    let tree = repo.get_commit(&branch.tip().unwrap()).unwrap().tree;
+    drop(branch);
+    let fresh = repo.new_branch();
+    fresh
+        .commit(TreePatch::new().replace_messages(vec![user("summary"), user("m9")]))
+        .await
+        .unwrap();
+
+    let report = repo.gc([]);
+    assert!(report.commits >= 11, "{report:?}");
+    assert!(report.blobs > 0 && report.trees > 0 && report.bytes > 0);
+    assert!(repo.get_commit(&before_compaction).is_none());
+    assert!(repo.get_tree(&tree).is_none(), "old root tree swept");
+    assert_eq!(texts(fresh.as_ref()).await, ["summary", "m9"]);
+    assert_eq!(repo.gc([]).objects(), 0, "second pass finds nothing");
+}
+
+#[tokio::test]
+async fn tags_roots_and_live_branches_are_kept() {
+    let repo = Repository::new();
+    let tagged = repo.new_branch();
+    tagged
+        .commit(TreePatch::new().add_message(user("tagged")))
+        .await
+        .unwrap();
+    repo.set_tag("keep", tagged.tip().unwrap());
+    drop(tagged);
+
+    let rooted = repo.new_branch();
+    rooted
+        .commit(TreePatch::new().add_message(user("rooted")))
+        .await
+        .unwrap();
+    let root = rooted.tip().unwrap();
+    drop(rooted);
+
+    let live = repo.new_branch();
+    live.commit(TreePatch::new().add_message(user("live")))
+        .await
+        .unwrap();
+    let forked = live.fork();
+    drop(live);
+
+    let dropped = repo.new_branch();
+    dropped
+        .commit(TreePatch::new().add_message(user("dropped")))
+        .await
+        .unwrap();
+    let dropped_tip = dropped.tip().unwrap();
+    drop(dropped);
+
+    let report = repo.gc([root]);
+    assert_eq!(report.commits, 1, "{report:?}");
+    assert!(repo.get_commit(&dropped_tip).is_none());
+    assert_eq!(
+        texts(repo.branch_at_tag("keep").unwrap().as_ref()).await,
+        ["tagged"]
+    );
+    assert_eq!(texts(repo.branch_at(root).as_ref()).await, ["rooted"]);
+    assert_eq!(texts(forked.as_ref()).await, ["live"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn gc_runs_alongside_commits() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let stop = Arc::new(AtomicBool::new(false));
+    let collector = {
+        let repo = Arc::clone(&repo);
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let mut passes = 0;
+            while !stop.load(Ordering::Relaxed) {
+                repo.gc([]);
+                passes += 1;
+            }
+            passes
+        })
+    };
+
+    for i in 0..200 {
+        // Scratch branches die immediately and become garbage.
+        let scratch = branch.fork();
+        scratch
+            .commit(TreePatch::new().add_message(user(&format!("scratch {i}"))))
+            .await
+            .unwrap();
+        branch
+            .commit(TreePatch::new().add_message(user(&format!("m{i}"))))
+            .await
+            .unwrap();
+    }
+    stop.store(true, Ordering::Relaxed);
+    assert!(collector.join().unwrap() > 0);
+
+    let expected: Vec<String> = (0..200).map(|i| format!("m{i}")).collect();
+    assert_eq!(texts(branch.as_ref()).await, expected);
+    for entry in repo.log(branch.tip().unwrap()) {
+        entry.expect("every ancestor survived");
+    }
+}
+
+#[tokio::test]
+async fn store_backed_gc_only_evicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::open(dir.path()).unwrap();
+    let branch = repo.new_branch();
+    branch
+        .commit(TreePatch::new().add_message(user("persisted")))
+        .await
+        .unwrap();
+    let tip = branch.tip().unwrap();
+    drop(branch);
+
+    assert!(repo.gc([]).commits > 0);
+    assert_eq!(repo.object_count(), 0);
+    // Still on disk: reloads on demand.
+    assert_eq!(texts(repo.branch_at(tip).as_ref()).await, ["persisted"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn overlapping_gcs_keep_fresh_objects() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let stop = Arc::new(AtomicBool::new(false));
+    let collectors: Vec<_> = (0..2)
+        .map(|_| {
+            let repo = Arc::clone(&repo);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    repo.gc([]);
+                }
+            })
+        })
+        .collect();
+
+    for i in 0..200 {
+        branch
+            .commit(TreePatch::new().add_message(user(&format!("m{i}"))))
+            .await
+            .unwrap();
+    }
+    stop.store(true, Ordering::Relaxed);
+    for collector in collectors {
+        collector.join().unwrap();
+    }
+
+    let expected: Vec<String> = (0..200).map(|i| format!("m{i}")).collect();
+    assert_eq!(texts(branch.as_ref()).await, expected);
+    for entry in repo.log(branch.tip().unwrap()) {
+        entry.expect("every ancestor survived");
+    }
+}
+
+#[tokio::test]
+async fn gc_store_deletes_unreachable_objects() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::open(dir.path()).unwrap();
+    let kept = repo.branch_named("refs/sessions/kept").unwrap();
+    kept.commit(TreePatch::new().add_message(user("kept")))
+        .await
+        .unwrap();
+    let packed = repo.new_branch();
+    packed
+        .commit(TreePatch::new().add_message(user("packed garbage")))
+        .await
+        .unwrap();
+    let packed_tip = packed.tip().unwrap();
+    drop(packed);
+    repo.pack().unwrap();
+    let loose = repo.new_branch();
+    loose
+        .commit(TreePatch::new().add_message(user("loose garbage")))
+        .await
+        .unwrap();
+    let loose_tip = loose.tip().unwrap();
+    drop(loose);
+
+    // Everything is younger than an hour's grace.
+    assert_eq!(repo.gc_store([], Duration::from_secs(3600)).unwrap(), 0);
+
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(repo.gc_store([], Duration::ZERO).unwrap() > 0);
+    assert_eq!(repo.gc_store([], Duration::ZERO).unwrap(), 0);
+    drop(kept);
+
+    let reopened = Repository::open(dir.path()).unwrap();
+    assert!(reopened.try_get_commit(&packed_tip).unwrap().is_none());
+    assert!(reopened.try_get_commit(&loose_tip).unwrap().is_none());
+    let kept = reopened.branch_named("refs/sessions/kept").unwrap();
+    assert_eq!(texts(kept.as_ref()).await, ["kept"]);
+}
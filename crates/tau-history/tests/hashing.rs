@@ -0,0 +1,80 @@
+//! Hash stability. The pinned hex values below are part of the
+//! on-disk format: if one of these tests fails, either the change is a
+//! bug or it needs a `HASH_VERSION` bump and a migration.
+
+use tau_ai::Message;
+use tau_history::{Blob, Commit, History, Repository, ToolDef, Tree, TreeEntry};
+
+fn tool(schema: &str) -> ToolDef {
+    ToolDef {
+        name: "read".into(),
+        description: "Read a file".into(),
+        parameters_schema: serde_json::from_str(schema).unwrap(),
+    }
+}
+
+#[test]
+fn pinned_hashes() {
+    let blob = Blob::new(b"hello".to_vec());
+    let mut tree = Tree::new();
+    tree.insert("greeting", TreeEntry::Blob(blob.hash()));
+    let commit = Commit {
+        parent: None,
+        extra_parents: vec![],
+        meta: Default::default(),
+        tree: tree.hash(),
+    };
+    assert_eq!(
+        blob.hash().to_string(),
+        "7dcb17b493366e501c5562b8fe83fd794c2d4898b98efe28ebd729d6499d984a"
+    );
+    assert_eq!(
+        tree.hash().to_string(),
+        "f04ff194efd9dc5ee9edd385a4e9efc21529c9ce9d24d7e657ac40c77e6d3dd3"
+    );
+    assert_eq!(
+        commit.hash().to_string(),
+        "f39c06e91c13c74f06d1eb24781ba1bb58a399ab6412a4a885fe0fd420cedc24"
+    );
+    assert_eq!(
+        tool(r#"{"type": "object", "properties": {"path": {"type": "string"}}}"#)
+            .hash()
+            .to_string(),
+        "d364b81c9b714b831a577e9da337f3ea605de68e4aac25254df1d30e72ff06a5"
+    );
+}
+
+#[test]
+fn tool_hash_ignores_key_order_and_number_spelling() {
+    let a = tool(r#"{"type": "object", "maxItems": 3, "minimum": 1.0}"#);
+    let b = tool(r#"{"minimum": 1, "maxItems": 3.0, "type":"object"}"#);
+    assert_eq!(a.hash(), b.hash());
+
+    let c = tool(r#"{"type": "object", "maxItems": 3, "minimum": 1.5}"#);
+    assert_ne!(a.hash(), c.hash());
+}
+
+#[tokio::test]
+async fn stored_tool_blob_matches_tool_hash() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let def = tool(r#"{"type": "object"}"#);
+    branch
+        .commit(
+            tau_history::TreePatch::new()
+                .with_tools(vec![def.clone()])
+                .add_message(Message::user("hi")),
+        )
+        .await
+        .unwrap();
+
+    let root = repo
+        .get_tree(&repo.get_commit(&branch.tip().unwrap()).unwrap().tree)
+        .unwrap();
+    let Some(TreeEntry::Tree(tools)) = root.get("tools") else {
+        panic!("no tools subtree");
+    };
+    let tools = repo.get_tree(tools).unwrap();
+    assert_eq!(tools.get("read"), Some(&TreeEntry::Blob(def.hash())));
+    assert_eq!(branch.tools().await.unwrap(), vec![def]);
+}
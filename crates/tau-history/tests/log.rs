@@ -0,0 +1,269 @@
+//! Walking and comparing history: `log`, `log_first_parent`,
+//! `merge_base`, `is_ancestor`, `diff` and `user_turns`.
+
+use std::sync::Arc;
+
+use tau_ai::{Content, Message};
+use tau_history::{
+    Branch, CacheBreak, CommitKind, CommitMeta, History, MessagesDiff, ObjectHash, Repository,
+    ToolDef, TreePatch,
+};
+
+fn user(text: &str) -> Message {
+    Message::user(text)
+}
+
+fn tool(name: &str, description: &str) -> ToolDef {
+    ToolDef {
+        name: name.into(),
+        description: description.into(),
+        parameters_schema: serde_json::json!({"type": "object"}),
+    }
+}
+
+async fn say(branch: &Arc<Branch>, text: &str) -> ObjectHash {
+    branch
+        .commit(TreePatch::new().add_message(user(text)))
+        .await
+        .unwrap();
+    branch.tip().unwrap()
+}
+
+fn hashes(log: tau_history::Log<'_>) -> Vec<ObjectHash> {
+    log.map(|entry| entry.unwrap().0).collect()
+}
+
+// ─── Log ─────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn logs_walk_newest_first() {
+    let repo = Repository::new();
+    let main = repo.new_branch();
+    let base = say(&main, "base").await;
+    let side = main.fork();
+    let m1 = say(&main, "main 1").await;
+    let s1 = say(&side, "side 1").await;
+    main.merge(TreePatch::new().add_message(user("merged")), vec![s1])
+        .await
+        .unwrap();
+    let merge = main.tip().unwrap();
+
+    assert_eq!(
+        hashes(repo.log_first_parent(merge)),
+        [merge, m1, base],
+        "first-parent skips the merged-in side"
+    );
+    assert_eq!(
+        hashes(repo.log(merge)),
+        [merge, m1, s1, base],
+        "full log lists every commit once, after its descendants"
+    );
+}
+
+#[test]
+fn log_of_unknown_commit_errors() {
+    let repo = Repository::new();
+    let bogus = ObjectHash::from_bytes([0xcd; 32]);
+    let mut log = repo.log(bogus);
+    assert!(log.next().unwrap().is_err());
+    assert!(log.next().is_none());
+}
+
+// ─── Ancestry ────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn merge_base_and_ancestry_of_forks() {
+    let repo = Repository::new();
+    let a = repo.new_branch();
+    say(&a, "shared 1").await;
+    let fork_point = say(&a, "shared 2").await;
+    let b = a.fork();
+    let a_tip = say(&a, "only a").await;
+    let b_tip = say(&b, "only b").await;
+
+    assert_eq!(repo.merge_base(&a_tip, &b_tip).unwrap(), Some(fork_point));
+    assert_eq!(
+        repo.merge_base(&a_tip, &fork_point).unwrap(),
+        Some(fork_point)
+    );
+    assert!(repo.is_ancestor(&fork_point, &a_tip).unwrap());
+    assert!(repo.is_ancestor(&a_tip, &a_tip).unwrap());
+    assert!(!repo.is_ancestor(&a_tip, &b_tip).unwrap());
+
+    let unrelated = repo.new_branch();
+    let u_tip = say(&unrelated, "elsewhere").await;
+    assert_eq!(repo.merge_base(&a_tip, &u_tip).unwrap(), None);
+}
+
+#[tokio::test]
+async fn merge_base_follows_extra_parents() {
+    let repo = Repository::new();
+    let parent = repo.new_branch();
+    say(&parent, "p").await;
+    let subagent = repo.new_branch();
+    let s_tip = say(&subagent, "s").await;
+    parent
+        .merge(TreePatch::new().add_message(user("result")), vec![s_tip])
+        .await
+        .unwrap();
+
+    let merge = parent.tip().unwrap();
+    assert!(repo.is_ancestor(&s_tip, &merge).unwrap());
+    assert_eq!(repo.merge_base(&merge, &s_tip).unwrap(), Some(s_tip));
+}
+
+// ─── Diff ────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn diff_of_an_append_keeps_the_cache() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    branch
+        .commit(
+            TreePatch::new()
+                .with_system_prompt(vec![Content::text("sp")])
+                .with_tools(vec![tool("bash", "run")])
+                .add_message(user("q")),
+        )
+        .await
+        .unwrap();
+    let before = branch.tip().unwrap();
+    let after = say(&branch, "q2").await;
+
+    let diff = repo.diff(&before, &after).unwrap();
+    assert_eq!(diff.messages, MessagesDiff::Appended { count: 1 });
+    assert!(!diff.system_prompt_changed && !diff.summary_changed);
+    assert_eq!(diff.cache_break(), None);
+    assert!(repo.diff(&after, &after).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn diff_reports_tool_changes_first() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    branch
+        .commit(
+            TreePatch::new()
+                .with_tools(vec![tool("bash", "run"), tool("read", "read")])
+                .add_message(user("q")),
+        )
+        .await
+        .unwrap();
+    let before = branch.tip().unwrap();
+    branch
+        .commit(
+            TreePatch::new()
+                .with_tools(vec![tool("bash", "run a command"), tool("write", "write")])
+                .with_system_prompt(vec![Content::text("new sp")]),
+        )
+        .await
+        .unwrap();
+    let after = branch.tip().unwrap();
+
+    let diff = repo.diff(&before, &after).unwrap();
+    assert_eq!(diff.tools_added, ["write"]);
+    assert_eq!(diff.tools_removed, ["read"]);
+    assert_eq!(diff.tools_changed, ["bash"]);
+    assert!(diff.system_prompt_changed);
+    assert_eq!(diff.messages, MessagesDiff::Unchanged);
+    assert_eq!(diff.cache_break(), Some(CacheBreak::Tools));
+}
+
+#[tokio::test]
+async fn diff_of_a_compaction_is_a_rewrite() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    branch
+        .commit(TreePatch::new().add_messages([user("a"), user("b"), user("c")]))
+        .await
+        .unwrap();
+    let before = branch.tip().unwrap();
+    branch
+        .compact_prefix(2, user("summary of a, b"), "a, b".into())
+        .await
+        .unwrap();
+    let after = branch.tip().unwrap();
+
+    let diff = repo.diff(&before, &after).unwrap();
+    assert_eq!(
+        diff.messages,
+        MessagesDiff::Rewritten {
+            kept: 0,
+            removed: 3,
+            added: 2,
+        }
+    );
+    assert!(diff.summary_changed);
+    assert_eq!(diff.cache_break(), Some(CacheBreak::Messages { at: 0 }));
+}
+
+// ─── User turns ──────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn user_turns_list_prompts_through_a_compaction() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    branch
+        .commit(TreePatch::new().with_system_prompt(vec![Content::text("be brief")]))
+        .await
+        .unwrap();
+    let root = branch.tip().unwrap();
+    branch
+        .append(vec![user("first"), Message::assistant_empty()])
+        .await
+        .unwrap();
+    let first = branch.tip().unwrap();
+    branch
+        .append(vec![Message::tool_result("t1", "read", vec![], false)])
+        .await
+        .unwrap();
+    branch
+        .compact_prefix(2, user("summary"), "first".into())
+        .await
+        .unwrap();
+    let compacted = branch.tip().unwrap();
+    let second = say(&branch, "second").await;
+
+    let turns = repo.user_turns(second).unwrap();
+    let found: Vec<_> = turns
+        .iter()
+        .map(|t| (t.prompt.as_str(), t.before, t.commit))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("first", Some(root), first),
+            ("second", Some(compacted), second)
+        ],
+        "tool results and the compaction's summary aren't prompts"
+    );
+    assert_eq!(branch.user_turns().await.unwrap(), turns);
+}
+
+#[tokio::test]
+async fn user_turns_skip_seeds_and_reprompts() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let meta = |kind| CommitMeta::new().with_kind(kind);
+    branch
+        .append_with(vec![user("carried over")], meta(CommitKind::Seed))
+        .await
+        .unwrap();
+    let seeded = branch.tip().unwrap();
+    branch
+        .append_with(vec![user("typed")], meta(CommitKind::Turn))
+        .await
+        .unwrap();
+    let typed = branch.tip().unwrap();
+    branch
+        .append_with(vec![user("check failed")], meta(CommitKind::Reprompt))
+        .await
+        .unwrap();
+
+    let turns = branch.user_turns().await.unwrap();
+    let found: Vec<_> = turns
+        .iter()
+        .map(|t| (t.prompt.as_str(), t.before, t.commit))
+        .collect();
+    assert_eq!(found, [("typed", Some(seeded), typed)]);
+}
@@ -0,0 +1,279 @@
+//! Named refs: compare-and-swap updates, reflogs, `branch_named`,
+//! refs shared between two handles on one store directory, and the
+//! `refs/abandoned/` refs that keep a rewound branch's old tip, and
+//! their expiry.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tau_ai::Message;
+use tau_history::{Branch, History, ObjectHash, Repository, StoreError, TreePatch};
+
+async fn texts(history: &dyn History) -> Vec<String> {
+    let msgs = history.messages().await.expect("messages read");
+    msgs.iter().map(|m| m.text()).collect()
+}
+
+async fn say(branch: &Arc<Branch>, text: &str) -> ObjectHash {
+    branch
+        .commit(TreePatch::new().add_message(Message::user(text)))
+        .await
+        .unwrap();
+    branch.tip().unwrap()
+}
+
+fn is_conflict(err: &tau_history::HistoryError) -> bool {
+    matches!(
+        err.as_inner().downcast_ref::<StoreError>(),
+        Some(StoreError::RefConflict { .. })
+    )
+}
+
+#[tokio::test]
+async fn update_ref_is_compare_and_swap() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let a = say(&branch, "a").await;
+    let b = say(&branch, "b").await;
+    let name = "refs/agents/explorer";
+
+    repo.update_ref(name, None, a, "create").unwrap();
+    let err = repo.update_ref(name, None, b, "create again").unwrap_err();
+    assert!(matches!(
+        err,
+        StoreError::RefConflict { actual: Some(h), .. } if h == a
+    ));
+    repo.update_ref(name, Some(a), b, "advance").unwrap();
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(b));
+
+    assert!(repo.delete_ref(name, a, "stale delete").is_err());
+    repo.delete_ref(name, b, "done").unwrap();
+    assert_eq!(repo.resolve_ref(name).unwrap(), None);
+
+    let log = repo.reflog(name).unwrap();
+    let moves: Vec<_> = log.iter().map(|e| (e.old, e.new)).collect();
+    assert_eq!(
+        moves,
+        [(None, Some(a)), (Some(a), Some(b)), (Some(b), None)]
+    );
+    assert_eq!(log[1].message, "advance");
+}
+
+#[tokio::test]
+async fn refs_list_by_prefix_and_reject_bad_names() {
+    let repo = Repository::new();
+    let tip = say(&repo.new_branch(), "x").await;
+    for name in ["refs/agents/a", "refs/agents/b", "refs/sessions/s1"] {
+        repo.update_ref(name, None, tip, "create").unwrap();
+    }
+    let agents: Vec<String> = repo
+        .refs("refs/agents/")
+        .unwrap()
+        .into_iter()
+        .map(|(n, _)| n)
+        .collect();
+    assert_eq!(agents, ["refs/agents/a", "refs/agents/b"]);
+    assert_eq!(repo.refs("refs/").unwrap().len(), 3);
+
+    for bad in [
+        "agents/a",
+        "refs/",
+        "refs/tags/v1",
+        "refs/a//b",
+        "refs/a/../b",
+        "refs/a.lock",
+        "refs/a b",
+    ] {
+        assert!(
+            matches!(
+                repo.update_ref(bad, None, tip, "bad"),
+                Err(StoreError::InvalidRefName { .. })
+            ),
+            "{bad:?} should be rejected"
+        );
+    }
+}
+
+#[tokio::test]
+async fn named_branch_commits_move_the_ref() {
+    let repo = Repository::new();
+    let name = "refs/sessions/s1";
+    let first = repo.branch_named(name).unwrap();
+    assert_eq!(first.ref_name(), Some(name));
+    assert_eq!(first.tip(), None);
+    let t1 = say(&first, "one").await;
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(t1));
+
+    // A second handle on the same ref, like a second process.
+    let second = repo.branch_named(name).unwrap();
+    let t2 = say(&second, "two").await;
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(t2));
+
+    // The first handle is now stale: its commit fails and nothing moves.
+    let err = first
+        .commit(TreePatch::new().add_message(Message::user("lost")))
+        .await
+        .unwrap_err();
+    assert!(is_conflict(&err), "{err}");
+    assert_eq!(first.tip(), Some(t1));
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(t2));
+
+    let resumed = repo.branch_named(name).unwrap();
+    assert_eq!(texts(resumed.as_ref()).await, ["one", "two"]);
+    assert_eq!(resumed.fork().ref_name(), None);
+
+    let messages: Vec<String> = repo
+        .reflog(name)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.message)
+        .collect();
+    assert_eq!(messages, ["commit", "commit"]);
+}
+
+#[tokio::test]
+async fn gc_keeps_ref_targets() {
+    let repo = Repository::new();
+    {
+        let branch = repo.branch_named("refs/agents/a").unwrap();
+        say(&branch, "kept").await;
+    }
+    repo.gc([]);
+    let branch = repo.branch_named("refs/agents/a").unwrap();
+    assert_eq!(texts(branch.as_ref()).await, ["kept"]);
+}
+
+#[tokio::test]
+async fn refs_are_shared_through_the_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let desk = Repository::open(dir.path()).unwrap();
+    let session = Repository::open(dir.path()).unwrap();
+    let name = "refs/sessions/s1";
+
+    let on_desk = desk.branch_named(name).unwrap();
+    let t1 = say(&on_desk, "from desk").await;
+    desk.set_tag("bookmark", t1);
+
+    let in_session = session.branch_named(name).unwrap();
+    assert_eq!(in_session.tip(), Some(t1));
+    let t2 = say(&in_session, "from session").await;
+
+    let err = on_desk
+        .commit(TreePatch::new().add_message(Message::user("stale")))
+        .await
+        .unwrap_err();
+    assert!(is_conflict(&err), "{err}");
+
+    // A leftover lock file blocks updates until it's removed.
+    let lock = dir.path().join("refs/sessions/s1.lock");
+    std::fs::write(&lock, "").unwrap();
+    assert!(matches!(
+        desk.update_ref(name, Some(t2), t1, "rewind"),
+        Err(StoreError::RefLocked { .. })
+    ));
+    std::fs::remove_file(&lock).unwrap();
+
+    let reopened = Repository::open(dir.path()).unwrap();
+    assert_eq!(
+        reopened.refs("").unwrap(),
+        [(name.to_string(), t2)],
+        "tags and lock files aren't refs"
+    );
+    let log = reopened.reflog(name).unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!((log[1].old, log[1].new), (Some(t1), Some(t2)));
+    assert!(log[1].timestamp >= log[0].timestamp);
+}
+
+#[tokio::test]
+async fn stale_lock_is_removed() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::open(dir.path()).unwrap();
+    let name = "refs/sessions/s1";
+    let branch = repo.branch_named(name).unwrap();
+    say(&branch, "before").await;
+
+    // Left by a process that died mid-update.
+    let lock = dir.path().join("refs/sessions/s1.lock");
+    let file = std::fs::File::create(&lock).unwrap();
+    let long_ago = std::time::SystemTime::now() - tau_history::STALE_LOCK_AGE * 2;
+    file.set_modified(long_ago).unwrap();
+    drop(file);
+
+    let after = say(&branch, "after").await;
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(after));
+    assert!(!lock.exists());
+}
+
+#[tokio::test]
+async fn reset_moves_the_ref_and_keeps_the_old_tip() {
+    let repo = Repository::new();
+    let name = "refs/sessions/s1";
+    let branch = repo.branch_named(name).unwrap();
+    let a = say(&branch, "a").await;
+    let b = say(&branch, "b").await;
+
+    assert_eq!(branch.reset(Some(a)).unwrap(), Some(b));
+    assert_eq!(repo.resolve_ref(name).unwrap(), Some(a));
+    let last = repo.reflog(name).unwrap().pop().unwrap();
+    assert_eq!((last.old, last.new), (Some(b), Some(a)));
+    assert!(last.message.starts_with("reset"), "{}", last.message);
+    assert_eq!(
+        repo.refs("refs/abandoned/").unwrap(),
+        [(format!("refs/abandoned/{b}"), b)]
+    );
+
+    let c = say(&branch, "c").await;
+    assert_eq!(repo.get_commit(&c).unwrap().parent, Some(a));
+    assert_eq!(texts(branch.as_ref()).await, ["a", "c"]);
+
+    drop(branch);
+    repo.gc([]);
+    let abandoned = repo.branch_at(b);
+    assert_eq!(texts(abandoned.as_ref()).await, ["a", "b"]);
+
+    let branch = repo.branch_named(name).unwrap();
+    assert_eq!(branch.reset(None).unwrap(), Some(c));
+    assert_eq!(repo.resolve_ref(name).unwrap(), None);
+    assert!(texts(branch.as_ref()).await.is_empty());
+}
+
+#[tokio::test]
+async fn fork_at_leaves_the_history_and_keeps_its_tip() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let a = say(&branch, "a").await;
+    let b = say(&branch, "b").await;
+
+    let fork = branch.fork_at(Some(a)).await.unwrap();
+    assert_eq!(fork.head(), Some(a));
+    fork.append(vec![Message::user("a2")]).await.unwrap();
+    assert_eq!(texts(fork.as_ref()).await, ["a", "a2"]);
+    assert_eq!(texts(branch.as_ref()).await, ["a", "b"]);
+    assert_eq!(
+        repo.resolve_ref(&format!("refs/abandoned/{b}")).unwrap(),
+        Some(b)
+    );
+
+    let bogus = ObjectHash::from_bytes([7; 32]);
+    assert!(branch.fork_at(Some(bogus)).await.is_err());
+    assert!(branch.reset(Some(bogus)).is_err());
+    assert_eq!(branch.tip(), Some(b));
+}
+
+#[tokio::test]
+async fn expire_abandoned_deletes_old_kept_tips() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let a = say(&branch, "a").await;
+    let b = say(&branch, "b").await;
+    branch.reset(Some(a)).unwrap();
+    let kept = format!("refs/abandoned/{b}");
+
+    assert_eq!(repo.expire_abandoned(Duration::from_secs(3600)).unwrap(), 0);
+    assert_eq!(repo.resolve_ref(&kept).unwrap(), Some(b));
+
+    assert_eq!(repo.expire_abandoned(Duration::ZERO).unwrap(), 1);
+    assert_eq!(repo.resolve_ref(&kept).unwrap(), None);
+    assert!(repo.refs("refs/abandoned/").unwrap().is_empty());
+}
@@ -11,7 +11,9 @@
 use std::sync::Arc;
 
 use tau_ai::{Content, Message};
-use tau_history::{Branch, History, ObjectHash, Repository, ToolDef, TreePatch};
+use tau_history::{
+    Branch, CommitKind, CommitMeta, History, ObjectHash, Repository, ToolDef, TreePatch,
+};
 
 fn user(text: &str) -> Message {
     Message::user(text)
@@ -500,6 +502,75 @@ async fn branch_at_unknown_tip_errors_on_first_read() {
     );
 }
 
+// ─── Commit metadata ─────────────────────────────────────────────────
+
+#[tokio::test]
+async fn commit_with_records_metadata_in_the_commit_hash_only() {
+    let repo = Repository::new();
+    let plain = repo.new_branch();
+    let stamped = repo.new_branch();
+    // One message, cloned: `Message::user` stamps the current time.
+    let message = user("same");
+    plain
+        .commit(TreePatch::new().add_message(message.clone()))
+        .await
+        .unwrap();
+    stamped
+        .commit_with(
+            TreePatch::new().add_message(message),
+            CommitMeta::new()
+                .with_timestamp(1_700_000_000_000)
+                .with_author("agent-1")
+                .with_model("claude-test")
+                .with_usage(tau_ai::Usage {
+                    input: 10,
+                    output: 5,
+                    ..Default::default()
+                })
+                .with_kind(CommitKind::Turn)
+                .with_message("first turn"),
+        )
+        .await
+        .unwrap();
+
+    let plain_commit = repo.get_commit(&plain.tip().unwrap()).unwrap();
+    let stamped_commit = repo.get_commit(&stamped.tip().unwrap()).unwrap();
+    assert!(plain_commit.meta.is_empty());
+    assert_eq!(stamped_commit.meta.author.as_deref(), Some("agent-1"));
+    assert_eq!(stamped_commit.meta.usage.as_ref().unwrap().output, 5);
+    assert_ne!(
+        plain.tip(),
+        stamped.tip(),
+        "metadata is part of the commit hash"
+    );
+    assert_eq!(
+        plain_commit.tree, stamped_commit.tree,
+        "metadata never touches the tree"
+    );
+}
+
+#[tokio::test]
+async fn history_trait_records_kind() {
+    let repo = Repository::new();
+    let branch = repo.new_branch();
+    let history: &dyn History = branch.as_ref();
+    history
+        .append_with(
+            vec![user("a"), user("b"), user("c")],
+            CommitMeta::new().with_kind(CommitKind::Turn),
+        )
+        .await
+        .unwrap();
+    let kind = |h: ObjectHash| repo.get_commit(&h).unwrap().meta.kind;
+    assert_eq!(kind(branch.tip().unwrap()), Some(CommitKind::Turn));
+
+    history
+        .compact_prefix(2, user("summary"), "summary".into())
+        .await
+        .unwrap();
+    assert_eq!(kind(branch.tip().unwrap()), Some(CommitKind::Compaction));
+}
+
 // ─── Dedup at the blob layer ─────────────────────────────────────────
 
 #[tokio::test]
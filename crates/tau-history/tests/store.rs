@@ -0,0 +1,263 @@
+//! Integration tests for the filesystem-backed repository.
+//!
+//! Everything goes through `Repository::open` on a temp directory:
+//! persistence across reopen, lazy loading, packing, two handles on
+//! one directory, corruption detection, and migrating a store written
+//! under an older hash version.
+
+use tau_ai::Message;
+use tau_history::{
+    Commit, FsStore, HASH_VERSION, History, ObjectHash, ObjectKind, ObjectStore, ReflogEntry,
+    Repository, StoreError, Tree, TreeEntry, TreePatch,
+};
+
+async fn texts(history: &dyn History) -> Vec<String> {
+    let msgs = history.messages().await.expect("messages read");
+    msgs.iter().map(|m| m.text()).collect()
+}
+
+async fn commit_conversation(repo: &std::sync::Arc<Repository>) -> ObjectHash {
+    let branch = repo.new_branch();
+    branch
+        .commit(
+            TreePatch::new()
+                .add_message(Message::user("first"))
+                .add_message(Message::user("second")),
+        )
+        .await
+        .unwrap();
+    branch
+        .commit(TreePatch::new().add_message(Message::user("third")))
+        .await
+        .unwrap();
+    branch.tip().unwrap()
+}
+
+#[tokio::test]
+async fn conversation_survives_reopen_via_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let repo = Repository::open(dir.path()).unwrap();
+        let tip = commit_conversation(&repo).await;
+        repo.set_tag("main", tip);
+    }
+
+    let repo = Repository::open(dir.path()).unwrap();
+    // Objects load lazily: nothing is in memory until read.
+    assert_eq!(repo.commit_count(), 0);
+    let branch = repo.branch_at_tag("main").expect("tag persisted");
+    assert_eq!(texts(branch.as_ref()).await, ["first", "second", "third"]);
+    assert!(repo.commit_count() > 0);
+
+    // Appending after reopen continues the same chain.
+    branch.append(vec![Message::user("fourth")]).await.unwrap();
+    let reopened = Repository::open(dir.path()).unwrap();
+    let resumed = reopened.branch_at(branch.tip().unwrap());
+    assert_eq!(
+        texts(resumed.as_ref()).await,
+        ["first", "second", "third", "fourth"]
+    );
+}
+
+#[tokio::test]
+async fn packed_objects_still_resolve() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::open(dir.path()).unwrap();
+    let tip = commit_conversation(&repo).await;
+    let packed = repo.pack().unwrap();
+    assert!(packed > 0);
+    assert_eq!(repo.pack().unwrap(), 0, "nothing loose left to pack");
+
+    let reopened = Repository::open(dir.path()).unwrap();
+    let branch = reopened.branch_at(tip);
+    assert_eq!(texts(branch.as_ref()).await, ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn two_handles_share_one_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = Repository::open(dir.path()).unwrap();
+    let reader = Repository::open(dir.path()).unwrap();
+
+    let tip = commit_conversation(&writer).await;
+    // Packed after the reader opened: the reader must discover the
+    // new pack on a miss.
+    writer.pack().unwrap();
+    let branch = reader.branch_at(tip);
+    assert_eq!(texts(branch.as_ref()).await, ["first", "second", "third"]);
+
+    // Tags are read at open, so a later tag shows up on a fresh handle.
+    writer.set_tag("shared", tip);
+    assert_eq!(
+        Repository::open(dir.path()).unwrap().resolve_tag("shared"),
+        Some(tip)
+    );
+
+    // Neither handle has seen the other's tag; both are kept.
+    reader.set_tag("mine", tip);
+    writer.set_tag("yours", tip);
+    writer.remove_tag("shared");
+    let tags = Repository::open(dir.path()).unwrap().tags();
+    assert_eq!(tags, [("mine".into(), tip), ("yours".into(), tip)]);
+}
+
+#[tokio::test]
+async fn corrupt_object_is_detected() {
+    let dir = tempfile::tempdir().unwrap();
+    let tip = commit_conversation(&Repository::open(dir.path()).unwrap()).await;
+
+    let hex = tip.to_string();
+    let path = dir.path().join("objects").join(&hex[..2]).join(&hex[2..]);
+    std::fs::write(&path, b"not zstd").unwrap();
+
+    let repo = Repository::open(dir.path()).unwrap();
+    assert!(matches!(
+        repo.try_get_commit(&tip),
+        Err(StoreError::Corrupt { .. })
+    ));
+    assert!(repo.branch_at(tip).messages().await.is_err());
+}
+
+// ─── Migration ───────────────────────────────────────────────────────
+
+/// Write a pre-versioning store by hand: plain `serde_json` payloads,
+/// arbitrary (old-scheme) hashes, no version file.
+fn write_legacy_store(root: &std::path::Path) -> ObjectHash {
+    let store = FsStore::open(root).unwrap();
+    let old = |n: u8| ObjectHash::from_bytes([n; 32]);
+    let json = |v: &serde_json::Value| serde_json::to_vec_pretty(v).unwrap();
+
+    let message = serde_json::to_value(Message::user("from an old build")).unwrap();
+    store
+        .write(&old(1), ObjectKind::Blob, &json(&message))
+        .unwrap();
+    store
+        .write(&old(2), ObjectKind::Blob, b"{\"b\": 1, \"a\": 2}")
+        .unwrap();
+    let tree = |entries: &[(&str, TreeEntry)]| {
+        let mut tree = Tree::new();
+        for (name, entry) in entries {
+            tree.insert(*name, entry.clone());
+        }
+        serde_json::to_vec(&tree).unwrap()
+    };
+    let bucket = tree(&[("0000000000-0000", TreeEntry::Blob(old(1)))]);
+    store.write(&old(3), ObjectKind::Tree, &bucket).unwrap();
+    let user = tree(&[("0000000", TreeEntry::Tree(old(3)))]);
+    store.write(&old(4), ObjectKind::Tree, &user).unwrap();
+    let messages = tree(&[("user", TreeEntry::Tree(old(4)))]);
+    store.write(&old(5), ObjectKind::Tree, &messages).unwrap();
+    let root_tree = tree(&[
+        ("messages", TreeEntry::Tree(old(5))),
+        ("previous_summary", TreeEntry::Blob(old(2))),
+    ]);
+    store.write(&old(6), ObjectKind::Tree, &root_tree).unwrap();
+    let commit = Commit {
+        parent: None,
+        extra_parents: vec![],
+        meta: Default::default(),
+        tree: old(6),
+    };
+    store
+        .write(
+            &old(7),
+            ObjectKind::Commit,
+            &serde_json::to_vec(&commit).unwrap(),
+        )
+        .unwrap();
+    store
+        .write_tags(&[("main".to_string(), old(7))].into_iter().collect())
+        .unwrap();
+    old(7)
+}
+
+#[tokio::test]
+async fn legacy_store_is_refused_until_migrated() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("history");
+    let old_tip = write_legacy_store(&root);
+
+    assert!(matches!(
+        Repository::open(&root),
+        Err(StoreError::Outdated {
+            found: 0,
+            expected: HASH_VERSION
+        })
+    ));
+
+    let mapped = Repository::migrate(&root).unwrap();
+    let new_tip = mapped[&old_tip];
+    assert!(
+        Repository::migrate(&root).unwrap().is_empty(),
+        "already current"
+    );
+
+    let repo = Repository::open(&root).unwrap();
+    assert_eq!(repo.resolve_tag("main"), Some(new_tip));
+    let branch = repo.branch_at_tag("main").unwrap();
+    assert_eq!(texts(branch.as_ref()).await, ["from an old build"]);
+    // The summary is text, not JSON: carried over byte-for-byte.
+    assert_eq!(
+        branch.previous_summary().await.unwrap().as_deref(),
+        Some("{\"b\": 1, \"a\": 2}")
+    );
+    // Every migrated object verifies under the new scheme.
+    assert!(repo.try_get_commit(&new_tip).unwrap().is_some());
+}
+
+#[tokio::test]
+async fn migration_carries_refs_and_reflogs() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("history");
+    let old_tip = write_legacy_store(&root);
+    let store = FsStore::open(&root).unwrap();
+    let entry = |old, new, message: &str| ReflogEntry {
+        old,
+        new,
+        timestamp: 1,
+        message: message.into(),
+    };
+    store
+        .update_ref(
+            "refs/sessions/a",
+            None,
+            Some(old_tip),
+            &entry(None, Some(old_tip), "start"),
+        )
+        .unwrap();
+    store
+        .update_ref(
+            "refs/sessions/gone",
+            None,
+            Some(old_tip),
+            &entry(None, Some(old_tip), "start"),
+        )
+        .unwrap();
+    store
+        .update_ref(
+            "refs/sessions/gone",
+            Some(old_tip),
+            None,
+            &entry(Some(old_tip), None, "drop"),
+        )
+        .unwrap();
+    drop(store);
+
+    let new_tip = Repository::migrate(&root).unwrap()[&old_tip];
+
+    let repo = Repository::open(&root).unwrap();
+    assert_eq!(repo.resolve_ref("refs/sessions/a").unwrap(), Some(new_tip));
+    assert_eq!(
+        repo.reflog("refs/sessions/a").unwrap(),
+        [entry(None, Some(new_tip), "start")]
+    );
+    // A deleted ref's reflog survives the migration too.
+    assert_eq!(repo.resolve_ref("refs/sessions/gone").unwrap(), None);
+    assert_eq!(
+        repo.reflog("refs/sessions/gone").unwrap(),
+        [
+            entry(None, Some(new_tip), "start"),
+            entry(Some(new_tip), None, "drop")
+        ]
+    );
+}
@@ -19,9 +19,12 @@
 //!   (debounced) so a crash mid-session loses at most a few seconds of
 //!   activity.
 //!
-//! Subagents are *not* persisted — hibernation aborts in-flight subagents.
-//! On activation the parent's conversation contains their textual results
-//! (gap #2's transcript and `inherit_history_from`).
+//! Subagents are persisted by the host's `tau_agent::AgentManager`, not by
+//! this crate: point it at [`SessionManager::fleet_dir`] with
+//! `AgentManager::set_checkpoint_dir` and call `AgentManager::restore`
+//! after `activate` to bring idle subagents back (and optionally resume
+//! interrupted background ones). Hibernation still aborts in-flight
+//! subagents; their checkpoints are what `restore` resumes.
 
 pub mod info;
 pub mod manager;
@@ -318,6 +318,14 @@ impl SessionManager {
         })
     }
 
+    /// Where the session's subagents should be checkpointed — pass it
+    /// to `AgentManager::set_checkpoint_dir`. `None` when the storage
+    /// backend has no local directory. Removed with the session on
+    /// [`Self::delete`].
+    pub fn fleet_dir(&self, id: &SessionId) -> Option<PathBuf> {
+        self.storage.fleet_dir(id)
+    }
+
     /// Live handle for an active session.
     pub async fn handle(&self, id: &SessionId) -> Option<AgentHandle> {
         self.active.lock().await.get(id).map(|r| r.handle.clone())
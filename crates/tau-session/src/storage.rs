@@ -8,6 +8,7 @@
 //!     messages.jsonl     // appended on every MessageEnd
 //!     snapshot.json      // full snapshot, written on hibernate + debounced
 //!     ui_state.json      // last UI state from save_ui_state
+//!     fleet/             // subagent checkpoints (see `fleet_dir`)
 //! ```
 //!
 //! v1 is single-process. Per-session file locking (e.g. via `fd-lock`)
@@ -66,6 +67,13 @@ pub trait SessionStorage: Send + Sync {
 
     /// Hard-delete every artefact for `id`.
     async fn delete(&self, id: &SessionId) -> Result<()>;
+
+    /// Directory the host's `AgentManager` checkpoints subagents into,
+    /// for backends that live on the local filesystem. `None` (the
+    /// default) means subagents aren't persisted for this backend.
+    fn fleet_dir(&self, _id: &SessionId) -> Option<PathBuf> {
+        None
+    }
 }
 
 /// Default filesystem-backed storage.
@@ -234,6 +242,10 @@ impl SessionStorage for FsStorage {
         Ok(Some(serde_json::from_slice(&bytes)?))
     }
 
+    fn fleet_dir(&self, id: &SessionId) -> Option<PathBuf> {
+        Some(self.dir(id).join("fleet"))
+    }
+
     async fn delete(&self, id: &SessionId) -> Result<()> {
         let dir = self.dir(id);
         match tokio::fs::remove_dir_all(&dir).await {
@@ -383,3 +383,50 @@ async fn cannot_delete_active_session() {
     let err = manager.delete(&active.id).await.expect_err("should fail");
     assert!(matches!(err, Error::Running(_)));
 }
+
+#[tokio::test]
+async fn subagents_checkpointed_in_fleet_dir_survive_hibernate() {
+    let (manager, dir) = setup();
+    let transport: Arc<dyn Transport> = TextTransport::create("done");
+    let active = manager
+        .create(fresh_request(transport.clone(), None))
+        .await
+        .expect("create");
+    let id = active.id.clone();
+    let fleet_dir = manager.fleet_dir(&id).expect("fs storage has a fleet dir");
+    assert!(fleet_dir.starts_with(dir.path().join(&id)));
+
+    let fleet = tau_agent::AgentManager::new(test_config(), transport.clone(), 4);
+    fleet.set_checkpoint_dir(&fleet_dir);
+    let spec = || tau_agent::AgentSpec {
+        system_prompt: String::new(),
+        tools: Vec::new(),
+        max_turns: 2,
+    };
+    let spawned = fleet
+        .spawn(
+            spec(),
+            "look around".into(),
+            tau_agent::SpawnOpts {
+                description: "scout".into(),
+                spec_name: Some("explore".into()),
+                ..Default::default()
+            },
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .expect("spawn");
+    manager.hibernate(&id).await.expect("hibernate");
+
+    // Next activation: a new fleet over the same directory.
+    let fleet = tau_agent::AgentManager::new(test_config(), transport, 4);
+    fleet.set_checkpoint_dir(&fleet_dir);
+    let report = fleet
+        .restore(&|_: &str, _: u32| Some(spec()), None)
+        .await
+        .expect("restore");
+    assert_eq!(report.restored, vec![spawned.agent_id]);
+
+    manager.delete(&id).await.expect("delete");
+    assert!(!fleet_dir.exists(), "delete removes subagent checkpoints");
+}
@@ -292,6 +292,7 @@ impl Tool for AgentTool {
             spec_name: Some(args.subagent_type.clone()),
             // Stamp the child's depth — one beyond ours.
             subagent_depth: depth + 1,
+            merge_branch: None,
         };
 
         if run_in_background {
@@ -333,7 +334,7 @@ impl Tool for AgentTool {
     }
 }
 
-fn format_result(result: &tau_agent::SubagentResult) -> String {
+pub(crate) fn format_result(result: &tau_agent::SubagentResult) -> String {
     let mut output = result.text.clone();
     let mut meta = format!(
         "\n[Agent {} | {} in + {} out tokens | {} tool calls | {}ms",
@@ -0,0 +1,282 @@
+//! Agent batch tool — fan one subagent out per input, collect the results
+use crate::cached_schema;
+
+use std::sync::{Arc, Weak};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tau_agent::ApprovalPolicy;
+use tau_agent::{AgentManager, BatchInput, BatchOpts, BatchResult, Isolation, SpawnOpts};
+use tau_agent::{ExecutionContext, Tool, ToolResult};
+
+use crate::agent::{SpecResolver, format_result};
+
+/// Upper bound on `concurrency`, whatever the model asks for.
+const MAX_CONCURRENCY: usize = 16;
+/// Upper bound on `retries`.
+const MAX_RETRIES: u32 = 3;
+/// Placeholder replaced with each input in the prompt template.
+const INPUT_PLACEHOLDER: &str = "{input}";
+
+#[derive(Deserialize, JsonSchema)]
+struct AgentBatchArgs {
+    /// Short (3-5 word) description of the whole batch
+    description: String,
+    /// Task instructions for every subagent. `{input}` is replaced with
+    /// the item; without it, the item is appended to the prompt.
+    prompt: String,
+    /// One subagent is spawned per input (e.g. crate names, file paths)
+    inputs: Vec<String>,
+    /// Type of agent for every item. Host-defined; see the system prompt for the valid set.
+    subagent_type: String,
+    /// Maximum subagents running at once (default 4, max 16)
+    concurrency: Option<usize>,
+    /// Extra attempts for an item whose subagent fails (default 1, max 3)
+    retries: Option<u32>,
+    /// Override model for every subagent
+    model: Option<String>,
+    /// Run each item in its own isolated git worktree
+    isolation: Option<Isolation>,
+}
+
+/// Tool for map-reduce style jobs: one subagent per input, bounded
+/// concurrency, one aggregated result.
+///
+/// Holds [`Weak<AgentManager>`] for the same reason as
+/// [`AgentTool`](crate::AgentTool): the manager's specs own this tool.
+pub struct AgentBatchTool {
+    manager: Weak<AgentManager>,
+    /// If set, only these spec names are allowed. None means no restriction.
+    allowed_specs: Option<Vec<String>>,
+    spec_resolver: Option<SpecResolver>,
+    /// Effective approval policy of the owning agent; every spawned
+    /// item inherits it.
+    inherited_policy: Option<Arc<dyn ApprovalPolicy>>,
+    /// Canonical (lowercase) spec names that may run in a git
+    /// worktree. `None` leaves worktree use unrestricted. See
+    /// [`AgentTool::with_worktree_specs`](crate::AgentTool::with_worktree_specs).
+    worktree_specs: Option<Vec<String>>,
+}
+
+impl AgentBatchTool {
+    pub fn new(manager: Arc<AgentManager>) -> Self {
+        Self {
+            manager: Arc::downgrade(&manager),
+            allowed_specs: None,
+            spec_resolver: None,
+            inherited_policy: None,
+            worktree_specs: None,
+        }
+    }
+
+    pub fn with_allowed_specs(mut self, names: Vec<String>) -> Self {
+        self.allowed_specs = Some(names);
+        self
+    }
+
+    pub fn with_spec_resolver(mut self, resolver: SpecResolver) -> Self {
+        self.spec_resolver = Some(resolver);
+        self
+    }
+
+    pub fn with_inherited_policy(mut self, policy: Arc<dyn ApprovalPolicy>) -> Self {
+        self.inherited_policy = Some(policy);
+        self
+    }
+
+    pub fn with_worktree_specs(mut self, names: Vec<String>) -> Self {
+        self.worktree_specs = Some(names.into_iter().map(|n| n.to_ascii_lowercase()).collect());
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for AgentBatchTool {
+    fn name(&self) -> &str {
+        "agent_batch"
+    }
+
+    fn activity_description(&self, arguments: &serde_json::Value) -> String {
+        let count = arguments
+            .get("inputs")
+            .and_then(|v| v.as_array())
+            .map_or(0, Vec::len);
+        format!("Spawning {count} agents")
+    }
+
+    fn description(&self) -> &str {
+        "Run the same task over many inputs in one call: spawns one subagent \
+         per input (at most `concurrency` at a time), retries failed items, \
+         and returns every subagent's result in input order. Use it instead \
+         of calling `agent` repeatedly when the work splits into independent \
+         items — one investigation per crate, one fix per file. Each \
+         subagent starts with a fresh context and sees only its own prompt."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        cached_schema!(AgentBatchArgs)
+    }
+
+    async fn execute(&self, arguments: serde_json::Value, ctx: ExecutionContext) -> ToolResult {
+        let manager = match self.manager.upgrade() {
+            Some(m) => m,
+            None => {
+                return ToolResult::error(
+                    "AgentBatchTool: parent AgentManager has been dropped; cannot spawn",
+                );
+            }
+        };
+
+        let args: AgentBatchArgs = match serde_json::from_value(arguments) {
+            Ok(a) => a,
+            Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+        };
+        if args.inputs.is_empty() {
+            return ToolResult::error("inputs is empty; nothing to spawn");
+        }
+
+        let subagent_type = args.subagent_type.to_ascii_lowercase();
+        if let Some(ref allowed) = self.allowed_specs
+            && !allowed
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&subagent_type))
+        {
+            return ToolResult::error(format!(
+                "subagent_type '{}' not allowed here. Allowed: {}.",
+                args.subagent_type,
+                allowed.join(", ")
+            ));
+        }
+        if args.isolation == Some(Isolation::Worktree)
+            && let Some(ref worktree_specs) = self.worktree_specs
+            && !worktree_specs.iter().any(|s| s == &subagent_type)
+        {
+            return ToolResult::error(format!(
+                "isolation: worktree is not available for subagent_type '{}'. \
+                 Worktree isolation is an execution capability; drop the field \
+                 or omit it.",
+                args.subagent_type
+            ));
+        }
+
+        let Some(resolver) = self.spec_resolver.as_ref() else {
+            return ToolResult::error(
+                "AgentBatchTool has no spec resolver installed; cannot spawn subagents",
+            );
+        };
+        let depth = ctx.subagent_depth;
+        let Some(spec) = resolver(&subagent_type, depth) else {
+            return ToolResult::error(format!(
+                "Unknown subagent_type '{}' (or recursion depth limit reached)",
+                args.subagent_type
+            ));
+        };
+
+        let inputs = args
+            .inputs
+            .iter()
+            .map(|input| BatchInput {
+                description: crate::truncate_chars(input, 40),
+                prompt: expand_prompt(&args.prompt, input),
+            })
+            .collect();
+        let opts = BatchOpts {
+            description: args.description.clone(),
+            concurrency: args.concurrency.unwrap_or(4).clamp(1, MAX_CONCURRENCY),
+            retries: args.retries.unwrap_or(1).min(MAX_RETRIES),
+            template: SpawnOpts {
+                model: args
+                    .model
+                    .as_deref()
+                    .and_then(tau_ai::models::get_model_by_id),
+                isolation: args.isolation,
+                approval_policy: self.inherited_policy.clone(),
+                spec_name: Some(args.subagent_type.clone()),
+                subagent_depth: depth + 1,
+                ..Default::default()
+            },
+        };
+
+        let result = manager.spawn_batch(spec, inputs, opts, ctx.cancel).await;
+        let text = format_batch(&args.description, &result);
+        if result.succeeded() == 0 {
+            ToolResult::error(text)
+        } else {
+            ToolResult::text(text)
+        }
+    }
+}
+
+fn expand_prompt(template: &str, input: &str) -> String {
+    if template.contains(INPUT_PLACEHOLDER) {
+        template.replace(INPUT_PLACEHOLDER, input)
+    } else {
+        format!("{template}\n\nInput: {input}")
+    }
+}
+
+fn format_batch(description: &str, result: &BatchResult) -> String {
+    let mut out = format!(
+        "Batch \"{}\": {}/{} succeeded, {} failed.\n",
+        description,
+        result.succeeded(),
+        result.items.len(),
+        result.failed()
+    );
+    for item in &result.items {
+        match &item.result {
+            Ok(r) => {
+                out.push_str(&format!("\n### {}. {}\n", item.index + 1, item.description));
+                out.push_str(&format_result(r));
+                out.push('\n');
+            }
+            Err(e) => {
+                out.push_str(&format!(
+                    "\n### {}. {} — FAILED after {} attempt(s)\n{}\n",
+                    item.index + 1,
+                    item.description,
+                    item.attempts,
+                    e
+                ));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tau_agent::test_utils::{MockTransport, make_test_config};
+
+    #[test]
+    fn prompt_template_substitutes_or_appends_input() {
+        assert_eq!(
+            expand_prompt("Audit {input} for panics", "tau-ai"),
+            "Audit tau-ai for panics"
+        );
+        assert_eq!(
+            expand_prompt("Audit for panics", "tau-ai"),
+            "Audit for panics\n\nInput: tau-ai"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_inputs() {
+        let transport = Arc::new(MockTransport::new()) as Arc<dyn tau_agent::Transport>;
+        let manager = Arc::new(AgentManager::new(make_test_config(), transport, 4));
+        let tool = AgentBatchTool::new(Arc::clone(&manager));
+        let args = serde_json::json!({
+            "description": "survey",
+            "prompt": "look at {input}",
+            "inputs": [],
+            "subagent_type": "explore",
+        });
+        let result = tool
+            .execute(args, tau_agent::test_utils::make_execution_context())
+            .await;
+        assert!(result.is_error);
+        assert!(result.text_content().contains("inputs is empty"));
+    }
+}
@@ -1,6 +1,10 @@
 //! Bash command execution tool
 use crate::cached_schema;
+use crate::shell::{self, CommandRisk};
 
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 use std::{collections::VecDeque, process::Stdio};
 
 use async_trait::async_trait;
@@ -17,6 +21,12 @@ use tokio::{
 const MAX_OUTPUT_SIZE: usize = 30_000; // 30KB
 /// Maximum number of lines before truncation
 const MAX_OUTPUT_LINES: usize = 500;
+/// Bytes of one stream kept in memory when the runtime spills results.
+/// Past this the stream is written to a file instead.
+const MAX_SPILL_MEMORY: usize = 1024 * 1024; // 1MB
+/// Bytes of a stream written to a file that stay in the result, split
+/// between head and tail.
+const SPILLED_PREVIEW_SIZE: usize = 4_000;
 
 #[derive(Deserialize, JsonSchema)]
 struct BashArgs {
@@ -26,6 +36,18 @@ struct BashArgs {
     timeout: Option<u64>,
 }
 
+/// Rate the `command` argument. Commands run through `cmd` on Windows,
+/// which the shell analysis doesn't cover, so they stay `Elevated`.
+fn classify_args(arguments: &serde_json::Value) -> CommandRisk {
+    match arguments.get("command").and_then(|v| v.as_str()) {
+        Some(cmd) if !cfg!(target_os = "windows") => shell::classify(cmd),
+        _ => CommandRisk {
+            risk: ToolRisk::Elevated,
+            reasons: Vec::new(),
+        },
+    }
+}
+
 /// Tool for executing bash commands
 pub struct BashTool;
 
@@ -64,6 +86,10 @@ struct OutputCollector {
     max_tail_bytes: usize,
     /// Max lines for tail portion
     max_tail_lines: usize,
+    /// Where the full output goes once it outgrows `max_head_bytes`
+    spill_path: Option<PathBuf>,
+    /// Open once the output is being written to `spill_path`
+    spill_file: Option<BufWriter<File>>,
 }
 
 impl OutputCollector {
@@ -81,6 +107,21 @@ impl OutputCollector {
             max_head_lines: half_lines,
             max_tail_bytes: half_bytes,
             max_tail_lines: half_lines,
+            spill_path: None,
+            spill_file: None,
+        }
+    }
+
+    /// Keep every line in memory up to [`MAX_SPILL_MEMORY`], then write
+    /// the whole stream to `path` and keep only a preview. Used when
+    /// the runtime spills oversized results to disk, so the full output
+    /// stays recoverable.
+    fn spilling(path: PathBuf) -> Self {
+        Self {
+            max_head_bytes: MAX_SPILL_MEMORY,
+            max_head_lines: usize::MAX,
+            spill_path: Some(path),
+            ..Self::new()
         }
     }
 
@@ -88,6 +129,11 @@ impl OutputCollector {
         self.total_lines += 1;
         let line_len = line.len() + 1; // +1 for newline
 
+        if let Some(file) = &mut self.spill_file {
+            // A failed write leaves the file short; head and tail are
+            // still in the result.
+            let _ = writeln!(file, "{line}");
+        }
         if !self.head_full {
             if self.head.len() < self.max_head_lines
                 && self.head_bytes + line_len <= self.max_head_bytes
@@ -97,6 +143,10 @@ impl OutputCollector {
                 return;
             }
             self.head_full = true;
+            self.start_spill();
+            if let Some(file) = &mut self.spill_file {
+                let _ = writeln!(file, "{line}");
+            }
         }
 
         self.tail_bytes += line_len;
@@ -110,7 +160,47 @@ impl OutputCollector {
         }
     }
 
-    fn into_string(self) -> String {
+    /// Move what's been kept so far into the spill file and shrink the
+    /// in-memory head to a preview. Without a spill path, or if the
+    /// file can't be written, the head shrinks to the ordinary cap.
+    fn start_spill(&mut self) {
+        let opened = self.spill_path.as_ref().map(|path| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut file = BufWriter::new(File::create(path)?);
+            for line in &self.head {
+                writeln!(file, "{line}")?;
+            }
+            Ok::<_, std::io::Error>(file)
+        });
+        let keep_bytes = match opened {
+            Some(Ok(file)) => {
+                self.spill_file = Some(file);
+                SPILLED_PREVIEW_SIZE / 2
+            }
+            Some(Err(_)) => {
+                self.spill_path = None;
+                MAX_OUTPUT_SIZE / 2
+            }
+            None => return,
+        };
+        let mut kept_bytes = 0;
+        let kept = self
+            .head
+            .iter()
+            .take(MAX_OUTPUT_LINES / 2)
+            .take_while(|line| {
+                kept_bytes += line.len() + 1;
+                kept_bytes <= keep_bytes
+            })
+            .count();
+        self.head.truncate(kept);
+        self.head_bytes = self.head.iter().map(|l| l.len() + 1).sum();
+        self.max_tail_bytes = keep_bytes;
+    }
+
+    fn into_string(mut self) -> String {
         if !self.head_full {
             return self.head.join("\n");
         }
@@ -119,10 +209,19 @@ impl OutputCollector {
         let tail_count = self.tail.len();
         let tail_text: String = self.tail.into_iter().collect::<Vec<_>>().join("\n");
         let omitted = self.total_lines - self.head.len() - tail_count;
-        format!(
-            "{}\n\n... [{} lines truncated] ...\n\n{}",
-            head_text, omitted, tail_text
-        )
+        let spilled = self
+            .spill_file
+            .take()
+            .is_some_and(|mut f| f.flush().is_ok());
+        let marker = match &self.spill_path {
+            Some(path) if spilled => format!(
+                "[{omitted} lines truncated; all {} lines are in {} — page through it with the read tool]",
+                self.total_lines,
+                path.display()
+            ),
+            _ => format!("[{omitted} lines truncated]"),
+        };
+        format!("{}\n\n... {} ...\n\n{}", head_text, marker, tail_text)
     }
 }
 
@@ -149,8 +248,12 @@ impl Tool for BashTool {
         Concurrency::Sequential
     }
 
-    fn risk(&self, _arguments: &serde_json::Value) -> ToolRisk {
-        ToolRisk::Elevated
+    fn risk(&self, arguments: &serde_json::Value) -> ToolRisk {
+        classify_args(arguments).risk
+    }
+
+    fn risk_reasons(&self, arguments: &serde_json::Value) -> Vec<String> {
+        classify_args(arguments).reasons
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -198,8 +301,15 @@ impl Tool for BashTool {
         let mut stdout_reader = BufReader::new(stdout).lines();
         let mut stderr_reader = BufReader::new(stderr).lines();
 
-        let mut stdout_collector = OutputCollector::new();
-        let mut stderr_collector = OutputCollector::new();
+        let new_collector = |stream: &str| match &ctx.spill {
+            Some(spill) => OutputCollector::spilling(tau_agent::spill_path(
+                &spill.dir,
+                &format!("{}-{stream}", ctx.progress.tool_call_id()),
+            )),
+            None => OutputCollector::new(),
+        };
+        let mut stdout_collector = new_collector("stdout");
+        let mut stderr_collector = new_collector("stderr");
         let mut stdout_done = false;
         let mut stderr_done = false;
 
@@ -300,3 +410,37 @@ impl Tool for BashTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spilling_collector_moves_to_disk_past_the_memory_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spill").join("call-stdout.txt");
+        let mut collector = OutputCollector::spilling(path.clone());
+        let line = "x".repeat(999);
+        let lines = 2 * MAX_SPILL_MEMORY / 1000;
+        for _ in 0..lines {
+            collector.push_line(line.clone());
+        }
+        assert!(collector.head_bytes + collector.tail_bytes <= SPILLED_PREVIEW_SIZE);
+
+        let text = collector.into_string();
+        assert!(text.len() < SPILLED_PREVIEW_SIZE + 200);
+        assert!(text.contains(&path.display().to_string()));
+        let spilled = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(spilled.lines().count(), lines);
+    }
+
+    #[test]
+    fn small_output_stays_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("call-stdout.txt");
+        let mut collector = OutputCollector::spilling(path.clone());
+        collector.push_line("hello".into());
+        assert_eq!(collector.into_string(), "hello");
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,259 @@
+//! Blackboard tools — share findings with sibling agents through the
+//! fleet's named topics.
+//!
+//! `blackboard_post` appends to a topic as the calling agent;
+//! `blackboard_read` lists topics or reads one, optionally subscribing
+//! so later posts are steered into the caller while it runs. Both are
+//! thin wrappers over
+//! [`AgentManager::post_to_blackboard`](tau_agent::AgentManager::post_to_blackboard)
+//! and friends.
+use crate::cached_schema;
+
+use std::sync::{Arc, Weak};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tau_agent::{AgentManager, BlackboardEntry};
+use tau_agent::{Concurrency, ExecutionContext, Tool, ToolResult, ToolRisk};
+
+/// Poster id for agents the fleet doesn't track (a root agent that
+/// was never adopted).
+const UNTRACKED_AGENT: &str = "root";
+
+#[derive(Deserialize, JsonSchema)]
+struct BlackboardPostArgs {
+    /// Topic to post to (e.g. "findings", "todo"). Created if missing.
+    topic: String,
+    /// The finding to share. Keep it short and self-contained.
+    text: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct BlackboardReadArgs {
+    /// Topic to read. Omit to list every topic with its entry count.
+    #[serde(default)]
+    topic: Option<String>,
+    /// Only return entries with a sequence number greater than this
+    /// (the last `#n` you saw). Defaults to 0, i.e. everything retained.
+    #[serde(default)]
+    since: Option<u64>,
+    /// true: have new posts to `topic` injected into your context while
+    /// you run. false: stop receiving them.
+    #[serde(default)]
+    subscribe: Option<bool>,
+}
+
+/// Holds [`Weak<AgentManager>`] for the same reason as
+/// [`SendMessageTool`](crate::SendMessageTool).
+pub struct BlackboardPostTool {
+    manager: Weak<AgentManager>,
+}
+
+impl BlackboardPostTool {
+    pub fn new(manager: Arc<AgentManager>) -> Self {
+        Self {
+            manager: Arc::downgrade(&manager),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for BlackboardPostTool {
+    fn name(&self) -> &str {
+        "blackboard_post"
+    }
+
+    fn description(&self) -> &str {
+        "Post a finding to a shared blackboard topic that every agent in the \
+         session can read. Use it to tell sibling agents what you learned or \
+         already covered so they don't repeat the work. Subscribers that are \
+         running see the post immediately."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        cached_schema!(BlackboardPostArgs)
+    }
+
+    fn concurrency(&self) -> Concurrency {
+        Concurrency::Sequential
+    }
+
+    fn risk(&self, _arguments: &serde_json::Value) -> ToolRisk {
+        ToolRisk::Safe
+    }
+
+    fn activity_description(&self, arguments: &serde_json::Value) -> String {
+        let topic = arguments
+            .get("topic")
+            .and_then(|v| v.as_str())
+            .unwrap_or("blackboard");
+        format!("Posting to #{topic}")
+    }
+
+    async fn execute(&self, arguments: serde_json::Value, ctx: ExecutionContext) -> ToolResult {
+        let Some(manager) = self.manager.upgrade() else {
+            return ToolResult::error("BlackboardPostTool: parent AgentManager has been dropped");
+        };
+        let args: BlackboardPostArgs = match serde_json::from_value(arguments) {
+            Ok(a) => a,
+            Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+        };
+        if args.topic.trim().is_empty() {
+            return ToolResult::error("topic is empty");
+        }
+        let agent_id = ctx.agent_id.as_deref().unwrap_or(UNTRACKED_AGENT);
+        let entry = manager
+            .post_to_blackboard(agent_id, &args.topic, args.text)
+            .await;
+        ToolResult::text(format!("Posted #{} to {}.", entry.seq, entry.topic))
+    }
+}
+
+/// Holds [`Weak<AgentManager>`] for the same reason as
+/// [`SendMessageTool`](crate::SendMessageTool).
+pub struct BlackboardReadTool {
+    manager: Weak<AgentManager>,
+}
+
+impl BlackboardReadTool {
+    pub fn new(manager: Arc<AgentManager>) -> Self {
+        Self {
+            manager: Arc::downgrade(&manager),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for BlackboardReadTool {
+    fn name(&self) -> &str {
+        "blackboard_read"
+    }
+
+    fn description(&self) -> &str {
+        "Read the shared blackboard other agents post findings to. Without a \
+         `topic`, lists the topics. With one, returns its entries (pass `since` \
+         to get only entries newer than the last one you saw). Set \
+         `subscribe: true` to have new posts to the topic injected while you work. \
+         Check the blackboard before starting work a sibling may have done."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        cached_schema!(BlackboardReadArgs)
+    }
+
+    fn risk(&self, _arguments: &serde_json::Value) -> ToolRisk {
+        ToolRisk::Safe
+    }
+
+    fn activity_description(&self, arguments: &serde_json::Value) -> String {
+        match arguments.get("topic").and_then(|v| v.as_str()) {
+            Some(topic) => format!("Reading #{topic}"),
+            None => "Listing blackboard topics".to_string(),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value, ctx: ExecutionContext) -> ToolResult {
+        let Some(manager) = self.manager.upgrade() else {
+            return ToolResult::error("BlackboardReadTool: parent AgentManager has been dropped");
+        };
+        let args: BlackboardReadArgs = match serde_json::from_value(arguments) {
+            Ok(a) => a,
+            Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+        };
+
+        let Some(topic) = args.topic else {
+            let topics = manager.snapshot().blackboard;
+            if topics.is_empty() {
+                return ToolResult::text("The blackboard is empty.");
+            }
+            let mut out = String::from("Blackboard topics:\n");
+            for t in topics {
+                let latest = t.entries.last().map_or(0, |e| e.seq);
+                out.push_str(&format!(
+                    "- {}: {} entries (latest #{}), {} subscriber(s)\n",
+                    t.name,
+                    t.entries.len(),
+                    latest,
+                    t.subscribers.len()
+                ));
+            }
+            return ToolResult::text(out);
+        };
+
+        let agent_id = ctx.agent_id.as_deref().unwrap_or(UNTRACKED_AGENT);
+        let mut out = String::new();
+        match args.subscribe {
+            Some(true) => {
+                manager.subscribe_blackboard(agent_id, &topic);
+                out.push_str(&format!("Subscribed to {}.\n", topic.trim()));
+            }
+            Some(false) => {
+                manager.unsubscribe_blackboard(agent_id, &topic);
+                out.push_str(&format!("Unsubscribed from {}.\n", topic.trim()));
+            }
+            None => {}
+        }
+        let entries = manager.read_blackboard(&topic, args.since.unwrap_or(0));
+        out.push_str(&format_entries(&manager, topic.trim(), &entries));
+        ToolResult::text(out)
+    }
+}
+
+fn format_entries(manager: &AgentManager, topic: &str, entries: &[BlackboardEntry]) -> String {
+    if entries.is_empty() {
+        return format!("No new entries in {topic}.");
+    }
+    let mut out = format!("{} entries in {topic}:\n", entries.len());
+    for e in entries {
+        let who = manager
+            .find_agent(&e.agent_id)
+            .map_or_else(|| e.agent_id.clone(), |l| l.description);
+        out.push_str(&format!(
+            "#{} {} ({}): {}\n",
+            e.seq, who, e.agent_id, e.text
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tau_agent::test_utils::{MockTransport, make_execution_context, make_test_config};
+
+    #[tokio::test]
+    async fn post_then_read_since_and_list_topics() {
+        let transport = Arc::new(MockTransport::new()) as Arc<dyn tau_agent::Transport>;
+        let manager = Arc::new(AgentManager::new(make_test_config(), transport, 4));
+        let post = BlackboardPostTool::new(Arc::clone(&manager));
+        let read = BlackboardReadTool::new(Arc::clone(&manager));
+
+        for text in ["tau-ai: no unsafe", "tau-agent: one unsafe"] {
+            let result = post
+                .execute(
+                    serde_json::json!({ "topic": "findings", "text": text }),
+                    make_execution_context(),
+                )
+                .await;
+            assert!(!result.is_error);
+        }
+
+        let listed = read
+            .execute(serde_json::json!({}), make_execution_context())
+            .await;
+        assert!(listed.text_content().contains("findings: 2 entries"));
+
+        let newer = read
+            .execute(
+                serde_json::json!({ "topic": "findings", "since": 1, "subscribe": true }),
+                make_execution_context(),
+            )
+            .await;
+        let text = newer.text_content();
+        assert!(text.contains("Subscribed to findings"));
+        assert!(text.contains("#2 root (root): tau-agent: one unsafe"));
+        assert!(!text.contains("tau-ai: no unsafe"));
+        assert_eq!(manager.snapshot().blackboard[0].subscribers, ["root"]);
+    }
+}
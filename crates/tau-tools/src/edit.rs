@@ -122,6 +122,7 @@ impl Tool for EditTool {
 
         match fs::write(&path, &new_content).await {
             Ok(()) => {
+                ctx.mark_written(&path);
                 ctx.progress.emit(AgentEvent::FileChanged {
                     path: path.clone(),
                     before: Some(content.clone()),
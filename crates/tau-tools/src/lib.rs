@@ -1,8 +1,10 @@
 //! Built-in tools for the tau coding agent
 
 mod agent;
+mod agent_batch;
 mod ask;
 mod bash;
+mod blackboard;
 pub mod console;
 pub mod diff;
 pub mod diff_validation;
@@ -16,20 +18,25 @@ pub mod lsp;
 pub mod mcp;
 pub mod plan;
 mod read;
+mod schedule;
 mod send_message;
+pub mod shell;
 mod subagent_report;
 mod web_fetch;
 mod write;
 
 pub use agent::{AgentTool, SpecResolver};
+pub use agent_batch::AgentBatchTool;
 pub use ask::AskTool;
 pub use bash::BashTool;
+pub use blackboard::{BlackboardPostTool, BlackboardReadTool};
 pub use edit::EditTool;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
 pub use list::ListTool;
 pub use plan::{Plan, PlanFile, PlanFileOp, PlanFlag, PlanFlagSeverity, PlanStep, SubmitPlanTool};
 pub use read::ReadTool;
+pub use schedule::ScheduleTool;
 pub use send_message::SendMessageTool;
 pub use subagent_report::SubagentReportTool;
 pub use web_fetch::WebFetchTool;
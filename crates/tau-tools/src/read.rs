@@ -1,14 +1,20 @@
 //! File reading tool
 use crate::cached_schema;
 
+use std::io::SeekFrom;
+use std::path::Path;
+
 use async_trait::async_trait;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use tau_agent::{ExecutionContext, Tool, ToolCategory, ToolResult};
 use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
 const MAX_LINES: usize = 2000;
 const MAX_LINE_LENGTH: usize = 2000;
+/// Page size for `byte_offset` reads outside the spill directory.
+const MAX_PAGE_BYTES: usize = 30_000;
 
 #[derive(Deserialize, JsonSchema)]
 struct ReadArgs {
@@ -16,8 +22,12 @@ struct ReadArgs {
     path: String,
     /// Line number to start reading from (1-indexed)
     offset: Option<u64>,
-    /// Maximum number of lines to read
+    /// Maximum number of lines to read (bytes, with byte_offset)
     limit: Option<u64>,
+    /// Byte to start reading from (0-indexed), instead of a line. Pages
+    /// raw text without cutting long lines; use it for saved tool
+    /// output.
+    byte_offset: Option<u64>,
 }
 
 /// Tool for reading file contents
@@ -70,6 +80,19 @@ impl Tool for ReadTool {
             return ToolResult::error("Operation cancelled");
         }
 
+        if let Some(byte_offset) = args.byte_offset {
+            let page = match &ctx.spill {
+                Some(spill) if path.starts_with(&spill.dir) => spill.page_bytes(),
+                _ => MAX_PAGE_BYTES,
+            };
+            let page = args.limit.map_or(page, |l| page.min(l as usize));
+            let result = read_bytes(&path, byte_offset, page).await;
+            if !result.is_error {
+                ctx.mark_read(&path);
+            }
+            return result;
+        }
+
         let content = match fs::read_to_string(&path).await {
             Ok(c) => c,
             Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
@@ -85,7 +108,14 @@ impl Tool for ReadTool {
             .map(|o| (o as usize).saturating_sub(1)) // 1-indexed to 0-indexed
             .unwrap_or(0);
 
-        let limit = args.limit.map(|l| l as usize).unwrap_or(MAX_LINES);
+        let mut limit = args.limit.map(|l| l as usize).unwrap_or(MAX_LINES);
+        // Pages of a spilled tool result stay small enough that reading
+        // them back doesn't spill again.
+        if let Some(spill) = &ctx.spill
+            && path.starts_with(&spill.dir)
+        {
+            limit = limit.min(spill.page_lines(&content));
+        }
 
         if offset >= total_lines {
             return ToolResult::error(format!(
@@ -144,3 +174,94 @@ impl Tool for ReadTool {
         ToolResult::text(output)
     }
 }
+
+/// Up to `page` bytes of `path` from `offset`, trimmed to whole UTF-8
+/// characters, with a notice naming the next offset.
+async fn read_bytes(path: &Path, offset: u64, page: usize) -> ToolResult {
+    let mut file = match fs::File::open(path).await {
+        Ok(f) => f,
+        Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
+    };
+    let total = match file.metadata().await {
+        Ok(m) => m.len(),
+        Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
+    };
+    if offset >= total {
+        return ToolResult::error(format!(
+            "Byte offset {} is beyond end of file ({} bytes total)",
+            offset, total
+        ));
+    }
+    // A character is at most 4 bytes; a smaller page could never end
+    // on one.
+    let mut buf = Vec::with_capacity(page.max(4));
+    let read = match file.seek(SeekFrom::Start(offset)).await {
+        Ok(_) => {
+            (&mut file)
+                .take(page.max(4) as u64)
+                .read_to_end(&mut buf)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = read {
+        return ToolResult::error(format!("Failed to read file: {}", e));
+    }
+
+    // An offset inside a character starts at the next one.
+    let skip = buf.iter().take_while(|b| *b & 0xC0 == 0x80).count();
+    let bytes = &buf[skip..];
+    let (text, used) = match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), bytes.len()),
+        // Cut mid-character: leave it for the next page.
+        Err(e) if e.error_len().is_none() => (
+            String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned(),
+            e.valid_up_to(),
+        ),
+        Err(_) => (String::from_utf8_lossy(bytes).into_owned(), bytes.len()),
+    };
+
+    let mut output = text;
+    let end = offset + (skip + used) as u64;
+    if end < total {
+        output.push_str(&format!(
+            "\n\n... ({} more bytes not shown. Use byte_offset={} to continue reading)",
+            total - end,
+            end
+        ));
+    }
+    ToolResult::text(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(path: &Path, byte_offset: u64, limit: u64) -> String {
+        let args = serde_json::json!({ "path": path, "byte_offset": byte_offset, "limit": limit });
+        let result = ReadTool::new()
+            .execute(args, tau_agent::test_utils::make_execution_context())
+            .await;
+        assert!(!result.is_error, "{}", result.text_content());
+        result.text_content()
+    }
+
+    #[tokio::test]
+    async fn byte_offset_pages_through_one_long_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.json");
+        let line = "é".repeat(MAX_LINE_LENGTH) + "end";
+        std::fs::write(&path, &line).unwrap();
+
+        // 5 bytes: two "é" and half of a third, which waits for the
+        // next page.
+        let page = read(&path, 0, 5).await;
+        assert!(page.starts_with("éé\n\n... ("));
+        assert!(page.ends_with("Use byte_offset=4 to continue reading)"));
+
+        let last = read(&path, (line.len() - 5) as u64, 100).await;
+        assert_eq!(last, "éend");
+        let mid = read(&path, 1, 4).await;
+        assert!(mid.starts_with("é\n"), "starts at the next character");
+    }
+}
@@ -0,0 +1,284 @@
+//! Schedule tool — lets an agent set timers that prompt it later.
+//!
+//! Thin wrapper over
+//! [`AgentManager::schedule_timer`](tau_agent::AgentManager::schedule_timer)
+//! and friends. Timers are always scheduled for the calling agent, and
+//! an agent can only list or cancel its own.
+use crate::cached_schema;
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tau_agent::{AgentManager, MAX_TIMER_DELAY, ScheduledTimer};
+use tau_agent::{Concurrency, ExecutionContext, Tool, ToolResult, ToolRisk};
+
+/// Timer owner for agents the fleet doesn't track (a root agent that
+/// was never adopted). The host receives these when they fire.
+const UNTRACKED_AGENT: &str = "root";
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum ScheduleAction {
+    Create,
+    List,
+    Cancel,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ScheduleArgs {
+    /// "create" a timer, "list" your pending timers, or "cancel" one.
+    action: ScheduleAction,
+    /// create: what to tell yourself when the timer fires.
+    #[serde(default)]
+    prompt: Option<String>,
+    /// create: how long until the first firing, e.g. "90s", "10m",
+    /// "1h30m" (at most "30d"). Defaults to `every` for recurring timers.
+    #[serde(default)]
+    delay: Option<String>,
+    /// create: repeat at this interval (at least "1m"). Omit for a
+    /// one-shot timer.
+    #[serde(default)]
+    every: Option<String>,
+    /// cancel: the timer id returned by create (e.g. "t3").
+    #[serde(default)]
+    timer_id: Option<String>,
+}
+
+/// Holds [`Weak<AgentManager>`] for the same reason as
+/// [`SendMessageTool`](crate::SendMessageTool).
+pub struct ScheduleTool {
+    manager: Weak<AgentManager>,
+}
+
+impl ScheduleTool {
+    pub fn new(manager: Arc<AgentManager>) -> Self {
+        Self {
+            manager: Arc::downgrade(&manager),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleTool {
+    fn name(&self) -> &str {
+        "schedule"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a prompt to yourself for later: \"check the CI run again in \
+         10m\" (delay: \"10m\") or \"re-run the flaky test every hour\" (every: \
+         \"1h\"). When the timer fires you receive the prompt as a new message, \
+         even after your current task has finished. Use `list` to see your \
+         pending timers and `cancel` to stop one you no longer need."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        cached_schema!(ScheduleArgs)
+    }
+
+    fn concurrency(&self) -> Concurrency {
+        Concurrency::Sequential
+    }
+
+    fn risk(&self, _arguments: &serde_json::Value) -> ToolRisk {
+        ToolRisk::Safe
+    }
+
+    fn activity_description(&self, arguments: &serde_json::Value) -> String {
+        match arguments.get("action").and_then(|v| v.as_str()) {
+            Some("list") => "Listing timers".to_string(),
+            Some("cancel") => "Cancelling timer".to_string(),
+            _ => "Scheduling timer".to_string(),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value, ctx: ExecutionContext) -> ToolResult {
+        let Some(manager) = self.manager.upgrade() else {
+            return ToolResult::error("ScheduleTool: parent AgentManager has been dropped");
+        };
+        let args: ScheduleArgs = match serde_json::from_value(arguments) {
+            Ok(a) => a,
+            Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+        };
+        let agent_id = ctx.agent_id.as_deref().unwrap_or(UNTRACKED_AGENT);
+
+        match args.action {
+            ScheduleAction::Create => {
+                let Some(prompt) = args.prompt.filter(|p| !p.trim().is_empty()) else {
+                    return ToolResult::error("create needs a `prompt`");
+                };
+                let every = match args.every.as_deref().map(parse_duration).transpose() {
+                    Ok(e) => e,
+                    Err(e) => return ToolResult::error(e),
+                };
+                let delay = match args.delay.as_deref().map(parse_duration).transpose() {
+                    Ok(Some(d)) => d,
+                    Ok(None) => match every {
+                        Some(e) => e,
+                        None => return ToolResult::error("create needs a `delay` or `every`"),
+                    },
+                    Err(e) => return ToolResult::error(e),
+                };
+                match manager.schedule_timer(agent_id, prompt, delay, every) {
+                    Ok(t) => ToolResult::text(format!("Scheduled {}.", describe(&t))),
+                    Err(e) => ToolResult::error(e.to_string()),
+                }
+            }
+            ScheduleAction::List => {
+                let timers: Vec<_> = manager
+                    .timers()
+                    .into_iter()
+                    .filter(|t| t.agent_id == agent_id)
+                    .collect();
+                if timers.is_empty() {
+                    return ToolResult::text("You have no pending timers.");
+                }
+                let mut out = format!("{} pending timer(s):\n", timers.len());
+                for t in &timers {
+                    out.push_str(&format!("- {}: {}\n", describe(t), t.prompt));
+                }
+                ToolResult::text(out)
+            }
+            ScheduleAction::Cancel => {
+                let Some(id) = args.timer_id else {
+                    return ToolResult::error("cancel needs a `timer_id`");
+                };
+                let owned = manager
+                    .timers()
+                    .iter()
+                    .any(|t| t.id == id && t.agent_id == agent_id);
+                if !owned {
+                    return ToolResult::error(format!("You have no timer '{id}'"));
+                }
+                match manager.cancel_timer(&id) {
+                    Some(_) => ToolResult::text(format!("Cancelled {id}.")),
+                    None => ToolResult::error(format!("Timer '{id}' already fired")),
+                }
+            }
+        }
+    }
+}
+
+fn describe(t: &ScheduledTimer) -> String {
+    let at = t.next_fire.format("%H:%M:%S UTC");
+    match t.every_secs {
+        Some(every) => format!("{} (next at {at}, every {})", t.id, format_secs(every)),
+        None => format!("{} (at {at})", t.id),
+    }
+}
+
+/// Parse "90", "90s", "10m", "2h", "1d" or combinations like "1h30m".
+/// A bare number is seconds. Longer than [`MAX_TIMER_DELAY`] is an error.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let bounded = |secs: u64| {
+        if secs > MAX_TIMER_DELAY.as_secs() {
+            Err(format!(
+                "Duration '{s}' is too long (at most {})",
+                format_secs(MAX_TIMER_DELAY.as_secs())
+            ))
+        } else {
+            Ok(Duration::from_secs(secs))
+        }
+    };
+    if let Ok(secs) = s.parse::<u64>() {
+        return bounded(secs);
+    }
+    let invalid = || format!("Invalid duration '{s}' (use e.g. \"90s\", \"10m\", \"1h30m\")");
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            _ => return Err(invalid()),
+        };
+        let n: u64 = digits.parse().map_err(|_| invalid())?;
+        total = n
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || s.is_empty() {
+        return Err(invalid());
+    }
+    bounded(total)
+}
+
+fn format_secs(secs: u64) -> String {
+    match secs {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tau_agent::test_utils::{MockTransport, make_execution_context, make_test_config};
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5m3").is_err());
+        assert_eq!(parse_duration("30d").unwrap(), MAX_TIMER_DELAY);
+        assert!(parse_duration("31d").is_err());
+        assert!(parse_duration("18446744073709551615").is_err());
+    }
+
+    #[tokio::test]
+    async fn create_list_and_cancel_own_timer() {
+        let transport = Arc::new(MockTransport::new()) as Arc<dyn tau_agent::Transport>;
+        let manager = Arc::new(AgentManager::new(make_test_config(), transport, 4));
+        let tool = ScheduleTool::new(Arc::clone(&manager));
+
+        let too_fast = tool
+            .execute(
+                serde_json::json!({ "action": "create", "prompt": "poll", "every": "10s" }),
+                make_execution_context(),
+            )
+            .await;
+        assert!(too_fast.is_error);
+
+        let created = tool
+            .execute(
+                serde_json::json!({ "action": "create", "prompt": "check CI", "delay": "10m" }),
+                make_execution_context(),
+            )
+            .await;
+        assert!(created.text_content().starts_with("Scheduled t1"));
+
+        let listed = tool
+            .execute(
+                serde_json::json!({ "action": "list" }),
+                make_execution_context(),
+            )
+            .await;
+        assert!(listed.text_content().contains("check CI"));
+
+        let cancelled = tool
+            .execute(
+                serde_json::json!({ "action": "cancel", "timer_id": "t1" }),
+                make_execution_context(),
+            )
+            .await;
+        assert!(!cancelled.is_error);
+        assert!(manager.timers().is_empty());
+    }
+}
@@ -116,6 +116,7 @@ impl Tool for WriteTool {
 
         match fs::write(&path, &args.content).await {
             Ok(()) => {
+                ctx.mark_written(&path);
                 if emit_changed {
                     ctx.progress.emit(AgentEvent::FileChanged {
                         path: path.clone(),